@@ -0,0 +1,350 @@
+//! Portable insight bundles for sharing between teammates and machines.
+//!
+//! A bundle is a JSON Lines file: a manifest on the first line followed by one
+//! insight per line. Imports go through `insight::save`, so imported insights
+//! are stored, indexed and versioned exactly like ones added by hand.
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::insight::{self, Insight};
+#[cfg(feature = "neural")]
+use crate::model_registry;
+use crate::provenance::InsightSource;
+use crate::scope::Scope;
+
+const BUNDLE_FORMAT: &str = "insights-bundle";
+const BUNDLE_VERSION: u32 = 1;
+
+/// First line of a bundle
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Manifest {
+  pub format: String,
+  pub version: u32,
+  pub exported: DateTime<Utc>,
+  pub insights: usize,
+  pub embeddings: bool,
+}
+
+/// A single insight as stored in a bundle
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BundledInsight {
+  pub topic: String,
+  pub name: String,
+  pub overview: String,
+  pub details: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub created: Option<DateTime<Utc>>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub updated: Option<DateTime<Utc>>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub author: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub source: Option<InsightSource>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub origin: Option<String>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub tags: Vec<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub embedding_version: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub embedding: Option<Vec<f32>>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub embedding_hash: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub embedding_computed: Option<DateTime<Utc>>,
+}
+
+/// What to do when an imported insight already exists in the target store
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ConflictStrategy {
+  /// Keep the existing insight
+  #[default]
+  Skip,
+  /// Replace the existing insight; the old version is kept in its history
+  Overwrite,
+  /// Import under the next free name, e.g. `name-2`
+  Rename,
+  /// Keep the existing overview, append new details and combine tags
+  Merge,
+}
+
+/// Outcome of importing a single bundle line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportAction {
+  Added,
+  Skipped,
+  Overwritten,
+  Renamed,
+  Merged,
+  Unchanged,
+  Failed,
+}
+
+/// Per-insight import report
+#[derive(Debug, Clone)]
+pub struct ImportResult {
+  /// Line of the bundle the insight was read from
+  pub line: usize,
+  pub topic: String,
+  pub name: String,
+  pub action: ImportAction,
+  /// New name for renamed insights, or the reason an import failed
+  pub detail: Option<String>,
+}
+
+/// Outcome of an export
+#[derive(Debug)]
+pub struct ExportSummary {
+  pub insights: usize,
+  pub embeddings: bool,
+}
+
+impl From<&Insight> for BundledInsight {
+  fn from(insight: &Insight) -> Self {
+    Self {
+      topic: insight.topic.clone(),
+      name: insight.name.clone(),
+      overview: insight.overview.clone(),
+      details: insight.details.clone(),
+      created: insight.created,
+      updated: insight.updated,
+      author: insight.author.clone(),
+      source: insight.source.clone(),
+      origin: insight.origin.clone(),
+      tags: insight.tags.clone(),
+      embedding_version: insight.embedding_version.clone(),
+      embedding: insight.embedding.clone(),
+      embedding_hash: insight.embedding_hash.clone(),
+      embedding_computed: insight.embedding_computed,
+    }
+  }
+}
+
+impl BundledInsight {
+  fn into_insight(self, scope: Scope) -> Insight {
+    let mut insight = Insight::new(self.topic, self.name, self.overview, self.details);
+    insight.created = self.created;
+    insight.updated = self.updated;
+    insight.author = self.author;
+    insight.source = self.source;
+    insight.origin = self.origin;
+    insight.tags = self.tags;
+    insight.scope = scope;
+    insight.embedding_version = self.embedding_version;
+    insight.embedding = self.embedding;
+    insight.embedding_hash = self.embedding_hash;
+    insight.embedding_computed = self.embedding_computed;
+    insight
+  }
+
+  fn strip_embedding(&mut self) {
+    self.embedding_version = None;
+    self.embedding = None;
+    self.embedding_hash = None;
+    self.embedding_computed = None;
+  }
+}
+
+/// Write the visible insights, optionally limited to one topic, to a bundle at `out`
+pub fn export(topic: Option<&str>, strip_embeddings: bool, out: &Path) -> Result<ExportSummary> {
+  let insights = insight::get_insights(topic)?;
+
+  let manifest = Manifest {
+    format: BUNDLE_FORMAT.to_string(),
+    version: BUNDLE_VERSION,
+    exported: Utc::now(),
+    insights: insights.len(),
+    embeddings: !strip_embeddings,
+  };
+
+  let mut lines = vec![serde_json::to_string(&manifest)?];
+  for insight in &insights {
+    let mut bundled = BundledInsight::from(insight);
+    if strip_embeddings {
+      bundled.strip_embedding();
+    }
+    lines.push(serde_json::to_string(&bundled)?);
+  }
+
+  if let Some(parent) = out.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+    fs::create_dir_all(parent)?;
+  }
+  fs::write(out, lines.join("\n") + "\n")
+    .with_context(|| format!("Could not write bundle {}", out.display()))?;
+
+  Ok(ExportSummary { insights: insights.len(), embeddings: !strip_embeddings })
+}
+
+/// Import every insight in the bundle at `path` into the `scope` store.
+///
+/// A malformed manifest aborts the import; problems with individual insights
+/// are reported as failed results and the rest of the bundle is still imported.
+pub fn import(path: &Path, strategy: ConflictStrategy, scope: Scope) -> Result<Vec<ImportResult>> {
+  let content = fs::read_to_string(path)
+    .with_context(|| format!("Could not read bundle {}", path.display()))?;
+  let mut lines = content
+    .lines()
+    .enumerate()
+    .map(|(index, line)| (index + 1, line))
+    .filter(|(_, line)| !line.trim().is_empty());
+
+  let (_, manifest_line) = lines.next().ok_or_else(|| anyhow!("Bundle is empty"))?;
+  let manifest = parse_manifest(manifest_line)?;
+
+  let entries: Vec<(usize, &str)> = lines.collect();
+  if entries.len() != manifest.insights {
+    return Err(anyhow!(
+      "Bundle is incomplete: manifest lists {} insights but {} were found",
+      manifest.insights,
+      entries.len()
+    ));
+  }
+
+  Ok(entries.into_iter().map(|(line, entry)| import_line(line, entry, strategy, scope)).collect())
+}
+
+fn parse_manifest(line: &str) -> Result<Manifest> {
+  let manifest: Manifest =
+    serde_json::from_str(line).map_err(|e| anyhow!("Invalid bundle manifest: {}", e))?;
+
+  if manifest.format != BUNDLE_FORMAT {
+    return Err(anyhow!("Not an insights bundle (format '{}')", manifest.format));
+  }
+  if manifest.version > BUNDLE_VERSION {
+    return Err(anyhow!(
+      "Bundle version {} is newer than the supported version {}",
+      manifest.version,
+      BUNDLE_VERSION
+    ));
+  }
+  Ok(manifest)
+}
+
+fn import_line(line: usize, entry: &str, strategy: ConflictStrategy, scope: Scope) -> ImportResult {
+  let bundled = match serde_json::from_str::<BundledInsight>(entry) {
+    Ok(bundled) => bundled,
+    Err(e) => {
+      return ImportResult {
+        line,
+        topic: String::new(),
+        name: String::new(),
+        action: ImportAction::Failed,
+        detail: Some(format!("Invalid insight: {e}")),
+      }
+    }
+  };
+
+  let (topic, name) = (bundled.topic.clone(), bundled.name.clone());
+  let (action, detail) =
+    match validate(&bundled).and_then(|_| import_insight(into_current(bundled, scope), strategy)) {
+      Ok((action, detail)) => (action, detail),
+      Err(e) => (ImportAction::Failed, Some(e.to_string())),
+    };
+
+  ImportResult { line, topic, name, action, detail }
+}
+
+/// Reject entries that could not have come from a valid insight file
+fn validate(bundled: &BundledInsight) -> Result<()> {
+  insight::check_id(&bundled.topic, &bundled.name)?;
+
+  if let Some(embedding) = &bundled.embedding {
+    if embedding.is_empty() || bundled.embedding_version.is_none() {
+      return Err(anyhow!("Embedding is missing its vector or version"));
+    }
+  }
+  Ok(())
+}
+
+/// The insight a bundle entry describes. Embeddings from another model than
+/// the active one can't be compared with local ones, so they are dropped.
+fn into_current(bundled: BundledInsight, scope: Scope) -> Insight {
+  #[allow(unused_mut)] // Only neural builds know the active model
+  let mut imported = bundled.into_insight(scope);
+  #[cfg(feature = "neural")]
+  {
+    let active = model_registry::active_model().ok().map(|model| model.name);
+    if imported.embedding_version.is_some() && imported.embedding_version != active {
+      insight::clear_embedding(&mut imported);
+    }
+  }
+  imported
+}
+
+fn import_insight(
+  imported: Insight,
+  strategy: ConflictStrategy,
+) -> Result<(ImportAction, Option<String>)> {
+  let existing_path = insight::file_path(&imported)?;
+  if !existing_path.exists() {
+    insight::save(&imported)?;
+    return Ok((ImportAction::Added, None));
+  }
+
+  match strategy {
+    ConflictStrategy::Skip => Ok((ImportAction::Skipped, None)),
+    ConflictStrategy::Overwrite => {
+      insight::delete(&insight::load_from_path(&existing_path)?)?;
+      insight::save(&imported)?;
+      Ok((ImportAction::Overwritten, None))
+    }
+    ConflictStrategy::Rename => {
+      let renamed = next_free_name(&imported)?;
+      let detail = format!("{}/{}", renamed.topic, renamed.name);
+      insight::save(&renamed)?;
+      Ok((ImportAction::Renamed, Some(detail)))
+    }
+    ConflictStrategy::Merge => merge(insight::load_from_path(&existing_path)?, imported),
+  }
+}
+
+/// The imported insight under the first `name-N` not yet taken
+fn next_free_name(imported: &Insight) -> Result<Insight> {
+  let mut renamed = imported.clone();
+  // The embedded text includes the name
+  insight::clear_embedding(&mut renamed);
+
+  for suffix in 2.. {
+    renamed.name = format!("{}-{}", imported.name, suffix);
+    if !insight::file_path(&renamed)?.exists() {
+      break;
+    }
+  }
+  Ok(renamed)
+}
+
+fn merge(mut existing: Insight, imported: Insight) -> Result<(ImportAction, Option<String>)> {
+  let mut tags = existing.tags.clone();
+  tags.extend(imported.tags);
+  let tags = insight::normalize_tags(&tags);
+  let tags_changed = tags != existing.tags;
+
+  let details =
+    if imported.details.trim().is_empty() || existing.details.contains(&imported.details) {
+      None
+    } else if existing.details.trim().is_empty() {
+      Some(imported.details)
+    } else {
+      Some(format!("{}\n\n{}", existing.details, imported.details))
+    };
+
+  if !tags_changed && details.is_none() {
+    return Ok((ImportAction::Unchanged, None));
+  }
+
+  // Write once, so the merge leaves a single revision behind
+  match details {
+    Some(details) => {
+      existing.tags = tags;
+      insight::update(&mut existing, None, Some(&details))?;
+    }
+    None => insight::set_tags(&mut existing, &tags)?,
+  }
+  Ok((ImportAction::Merged, None))
+}
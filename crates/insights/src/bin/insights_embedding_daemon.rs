@@ -1,250 +1,81 @@
 use anyhow::Result;
-use serde::{Deserialize, Serialize};
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::time::{timeout, Duration};
-
-// Platform-specific imports
-#[cfg(windows)]
-use tokio::net::{TcpListener, TcpStream};
-#[cfg(unix)]
-use tokio::net::{UnixListener, UnixStream};
+use clap::Parser;
 
+use insights::daemon::{self, EmbeddingService, DEFAULT_IDLE_TIMEOUT_SECS, IDLE_TIMEOUT_ENV};
 #[cfg(feature = "neural")]
-use insights::embedding_model::{create_production_model, EmbeddingModel};
+use insights::embedding_model::create_model;
+use insights::model_registry::{self, ModelSpec};
+use insights::query_cache::{self, QueryCache};
 
 #[cfg(not(feature = "neural"))]
 use insights::embedding_model::MockEmbeddingModel;
 
-// Platform-specific constants
-#[cfg(unix)]
-const SOCKET_PATH: &str = "/tmp/insights_embeddings.sock";
-#[cfg(windows)]
-const TCP_ADDRESS: &str = "127.0.0.1:47291";
-
-const INACTIVITY_TIMEOUT_SECS: u64 = 300;
-
-// Cross-platform listener abstraction
-pub enum InsightsListener {
-  #[cfg(unix)]
-  Unix(UnixListener),
-  #[cfg(windows)]
-  Tcp(TcpListener),
-}
-
-// Cross-platform stream abstraction
-pub enum BlizzStream {
-  #[cfg(unix)]
-  Unix(UnixStream),
-  #[cfg(windows)]
-  Tcp(TcpStream),
-}
-
-impl InsightsListener {
-  async fn accept(&self) -> std::io::Result<(BlizzStream, String)> {
-    match self {
-      #[cfg(unix)]
-      InsightsListener::Unix(listener) => {
-        let (stream, addr) = listener.accept().await?;
-        Ok((BlizzStream::Unix(stream), format!("{addr:?}")))
-      }
-      #[cfg(windows)]
-      InsightsListener::Tcp(listener) => {
-        let (stream, addr) = listener.accept().await?;
-        Ok((BlizzStream::Tcp(stream), addr.to_string()))
-      }
-    }
-  }
-}
-
-impl BlizzStream {
-  async fn write_all(&mut self, buf: &[u8]) -> Result<(), std::io::Error> {
-    match self {
-      #[cfg(unix)]
-      BlizzStream::Unix(stream) => stream.write_all(buf).await,
-      #[cfg(windows)]
-      BlizzStream::Tcp(stream) => stream.write_all(buf).await,
-    }
-  }
-}
-
-/// Embedding service that keeps model loaded in memory
-pub struct EmbeddingService<M: EmbeddingModel> {
-  model: M,
-}
-
-/// Request to compute embeddings (supports batching)
-#[derive(Serialize, Deserialize)]
-pub struct EmbeddingRequest {
-  pub texts: Vec<String>,
-  pub id: String,
-}
-
-/// Response with computed embeddings (supports batching)
-#[derive(Serialize, Deserialize)]
-pub struct EmbeddingResponse {
-  pub embeddings: Vec<Vec<f32>>,
-  pub id: String,
-  pub error: Option<String>,
-}
-
-impl<M: EmbeddingModel> EmbeddingService<M> {
-  pub fn new(model: M) -> Self {
-    Self { model }
-  }
-
-  pub async fn handle_request(&mut self, request: EmbeddingRequest) -> EmbeddingResponse {
-    match self.model.compute_embeddings(&request.texts) {
-      Ok(embeddings) => EmbeddingResponse { embeddings, id: request.id, error: None },
-      Err(e) => {
-        EmbeddingResponse { embeddings: vec![], id: request.id, error: Some(e.to_string()) }
-      }
+/// Keeps the embedding model loaded and serves embeddings to insights
+#[derive(Parser)]
+#[command(name = "insights_embedding_daemon")]
+struct Args {
+  /// Seconds without a connection before exiting; 0 keeps running
+  #[arg(long, env = IDLE_TIMEOUT_ENV, default_value_t = DEFAULT_IDLE_TIMEOUT_SECS)]
+  idle_timeout: u64,
+  /// Search queries kept in the query cache; 0 disables it
+  #[arg(long, env = query_cache::CAPACITY_ENV, default_value_t = query_cache::DEFAULT_CAPACITY)]
+  query_cache_size: usize,
+  /// Save the query cache next to the model on exit and load it on start
+  #[arg(long, env = query_cache::PERSIST_ENV)]
+  persist_query_cache: bool,
+}
+
+impl Args {
+  fn query_cache(&self, spec: Option<&ModelSpec>) -> Result<QueryCache> {
+    match spec {
+      Some(spec) if self.persist_query_cache => Ok(QueryCache::persistent(
+        self.query_cache_size,
+        query_cache::cache_path(&spec.name)?,
+        &model_registry::embedding_version(spec)?,
+      )),
+      _ => Ok(QueryCache::new(self.query_cache_size)),
     }
   }
 }
 
 #[cfg(feature = "neural")]
 async fn create_embedding_service(
+  args: &Args,
 ) -> Result<EmbeddingService<insights::embedding_model::OnnxEmbeddingModel>> {
-  let model = create_production_model().await?;
-  Ok(EmbeddingService::new(model))
+  let spec = model_registry::active_model()?;
+  let model = create_model(spec.clone())?;
+  let query_cache = args.query_cache(Some(&spec))?;
+  Ok(EmbeddingService::with_query_cache(model, args.idle_timeout, query_cache))
 }
 
 #[cfg(not(feature = "neural"))]
-async fn create_embedding_service() -> Result<EmbeddingService<MockEmbeddingModel>> {
+async fn create_embedding_service(args: &Args) -> Result<EmbeddingService<MockEmbeddingModel>> {
   let model = MockEmbeddingModel::new();
-  Ok(EmbeddingService::new(model))
+  let query_cache = args.query_cache(None)?;
+  Ok(EmbeddingService::with_query_cache(model, args.idle_timeout, query_cache))
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-  cleanup_existing_socket();
-
-  let service = create_embedding_service().await?;
-  let listener = setup_listener().await?;
+  let args = Args::parse();
 
-  run_server_loop(listener, service).await?;
-
-  cleanup_existing_socket();
-  Ok(())
-}
-
-async fn run_server_loop<M: EmbeddingModel>(
-  listener: InsightsListener,
-  mut service: EmbeddingService<M>,
-) -> Result<()> {
-  let timeout = Duration::from_secs(INACTIVITY_TIMEOUT_SECS);
-
-  loop {
-    let should_continue =
-      handle_single_connection(&listener, &mut service, timeout).await.unwrap_or(false);
-    if !should_continue {
-      break;
-    }
+  // Never take the socket over from a daemon that is still answering
+  if daemon::try_connect().await?.is_some() {
+    println!("Blizz daemon is already running");
+    return Ok(());
   }
 
-  Ok(())
-}
-
-async fn handle_single_connection<M: insights::embedding_model::EmbeddingModel>(
-  listener: &InsightsListener,
-  service: &mut EmbeddingService<M>,
-  timeout_duration: Duration,
-) -> Result<bool, ()> {
-  match wait_for_connections(listener, timeout_duration).await {
-    Ok(connection_result) => {
-      let should_continue = handle_connection_result(Ok(connection_result), service).await;
-      Ok(should_continue)
-    }
-    Err(error_msg) => {
-      if error_msg == "Timeout" {
-        println!("💤 Blizz daemon shutting down due to inactivity");
-      }
-      Ok(false)
-    }
-  }
-}
-
-async fn wait_for_connections(
-  listener: &InsightsListener,
-  timeout_duration: Duration,
-) -> Result<(BlizzStream, String), String> {
-  match timeout(timeout_duration, listener.accept()).await {
-    Ok(connection_result) => connection_result.map_err(|e| format!("Connection error: {e}")),
-    Err(_) => Err("Timeout".to_string()),
-  }
-}
-
-async fn handle_connection_result<M: EmbeddingModel>(
-  connection_result: Result<(BlizzStream, String), std::io::Error>,
-  service: &mut EmbeddingService<M>,
-) -> bool {
-  match connection_result {
-    Ok((stream, _)) => {
-      if let Err(e) = handle_client(stream, service).await {
-        eprintln!("Error handling client: {e}");
-      }
-      true // Continue running
-    }
-    Err(e) => {
-      eprintln!("Error accepting connection: {e}");
-      false // Stop running
-    }
-  }
-}
-
-pub async fn handle_client<M: EmbeddingModel>(
-  mut stream: BlizzStream,
-  service: &mut EmbeddingService<M>,
-) -> Result<()> {
-  let reader = match &mut stream {
-    #[cfg(unix)]
-    BlizzStream::Unix(stream) => BufReader::new(stream),
-    #[cfg(windows)]
-    BlizzStream::Tcp(stream) => BufReader::new(stream),
+  // Only one of several daemons started at once goes on to load the model
+  let Some(_pid_lock) = daemon::lock_pid_file()? else {
+    println!("Blizz daemon is already starting");
+    return Ok(());
   };
 
-  let mut line = String::new();
-  let mut reader = reader;
-
-  // Read request line
-  reader.read_line(&mut line).await?;
-
-  // Parse request
-  let request: EmbeddingRequest = serde_json::from_str(line.trim())?;
-
-  // Compute embedding
-  let response = service.handle_request(request).await;
-
-  // Send response
-  let response_json = serde_json::to_string(&response)?;
-  stream.write_all(response_json.as_bytes()).await?;
-  stream.write_all(b"\n").await?;
-
-  Ok(())
-}
-
-// Shared utility functions
-
-#[cfg(unix)]
-fn cleanup_existing_socket() {
-  let _ = std::fs::remove_file(SOCKET_PATH);
-}
-
-#[cfg(windows)]
-fn cleanup_existing_socket() {
-  // TCP sockets don't require cleanup
-}
-
-#[cfg(unix)]
-async fn setup_listener() -> Result<InsightsListener> {
-  let listener = UnixListener::bind(SOCKET_PATH)?;
-  println!("🚀 Blizz daemon listening on {SOCKET_PATH}");
-  Ok(InsightsListener::Unix(listener))
-}
+  // The model loads before the socket is bound, so clients that can connect
+  // know the daemon is ready
+  let service = create_embedding_service(&args).await?;
+  let listener = daemon::bind().await?;
 
-#[cfg(windows)]
-async fn setup_listener() -> Result<InsightsListener> {
-  let listener = TcpListener::bind(TCP_ADDRESS).await?;
-  println!("🚀 Blizz daemon listening on {TCP_ADDRESS}");
-  Ok(InsightsListener::Tcp(listener))
+  // Dropping the lock on the way out removes the socket and pid file
+  daemon::serve(listener, service).await
 }
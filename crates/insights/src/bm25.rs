@@ -0,0 +1,149 @@
+//! BM25 lexical scoring over an in-memory inverted index.
+
+use std::collections::HashMap;
+
+// Standard Okapi BM25 tuning parameters
+const K1: f32 = 1.2;
+const B: f32 = 0.75;
+
+/// Token postings for a corpus of documents
+#[derive(Debug, Default)]
+pub struct InvertedIndex {
+  /// Token -> (document index, term frequency)
+  postings: HashMap<String, Vec<(usize, usize)>>,
+  doc_lengths: Vec<usize>,
+  avg_doc_length: f32,
+}
+
+/// Build an inverted index over the given documents. Document indices in
+/// scores match the order of `documents`.
+pub fn build<S: AsRef<str>>(documents: &[S]) -> InvertedIndex {
+  let mut postings: HashMap<String, Vec<(usize, usize)>> = HashMap::new();
+  let mut doc_lengths = Vec::with_capacity(documents.len());
+
+  for (doc, text) in documents.iter().enumerate() {
+    let mut frequencies: HashMap<&str, usize> = HashMap::new();
+    let mut length = 0;
+    for token in tokenize(text.as_ref()) {
+      *frequencies.entry(token).or_default() += 1;
+      length += 1;
+    }
+
+    for (token, frequency) in frequencies {
+      postings.entry(token.to_string()).or_default().push((doc, frequency));
+    }
+    doc_lengths.push(length);
+  }
+
+  let total_length: usize = doc_lengths.iter().sum();
+  let avg_doc_length = total_length as f32 / doc_lengths.len().max(1) as f32;
+
+  InvertedIndex { postings, doc_lengths, avg_doc_length }
+}
+
+/// Split text into index tokens. Underscores are kept so identifiers such as
+/// `search_topic` stay whole.
+pub fn tokenize(text: &str) -> impl Iterator<Item = &str> {
+  text.split(|c: char| !(c.is_alphanumeric() || c == '_')).filter(|token| !token.is_empty())
+}
+
+/// Score every document in the index against the query terms.
+///
+/// A query term matches any indexed token containing it, preserving the
+/// substring semantics exact search has always had.
+#[allow(dead_code)] // Library entry point; search scores parsed queries with `score_by`
+pub fn score(index: &InvertedIndex, terms: &[String]) -> Vec<f32> {
+  let terms: Vec<&str> = terms.iter().flat_map(|term| tokenize(term)).collect();
+  score_by(index, terms.iter().map(|term| move |token: &str| token.contains(term)))
+}
+
+/// Score every document against query terms that each decide which indexed
+/// tokens they match, e.g. wildcards
+pub fn score_by<F: Fn(&str) -> bool>(
+  index: &InvertedIndex,
+  terms: impl IntoIterator<Item = F>,
+) -> Vec<f32> {
+  let mut scores = vec![0.0; index.doc_lengths.len()];
+
+  for term in terms {
+    for (_, postings) in index.postings.iter().filter(|(token, _)| term(token)) {
+      let idf = inverse_document_frequency(index, postings.len());
+      for &(doc, frequency) in postings {
+        scores[doc] += idf * term_weight(index, doc, frequency);
+      }
+    }
+  }
+
+  scores
+}
+
+fn inverse_document_frequency(index: &InvertedIndex, doc_frequency: usize) -> f32 {
+  let documents = index.doc_lengths.len() as f32;
+  let doc_frequency = doc_frequency as f32;
+  // The +1 inside the log keeps common terms from scoring negatively
+  ((documents - doc_frequency + 0.5) / (doc_frequency + 0.5)).ln_1p()
+}
+
+fn term_weight(index: &InvertedIndex, doc: usize, frequency: usize) -> f32 {
+  let frequency = frequency as f32;
+  let length_ratio = index.doc_lengths[doc] as f32 / index.avg_doc_length.max(1.0);
+  (frequency * (K1 + 1.0)) / (frequency + K1 * (1.0 - B + B * length_ratio))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn terms(words: &[&str]) -> Vec<String> {
+    words.iter().map(|word| word.to_string()).collect()
+  }
+
+  #[test]
+  fn test_tokenize_keeps_identifiers() {
+    let tokens: Vec<&str> = tokenize("search_topic, (rust) & c++").collect();
+    assert_eq!(tokens, vec!["search_topic", "rust", "c"]);
+  }
+
+  #[test]
+  fn test_score_only_matching_documents() {
+    let index = build(&["rust is fast", "python is dynamic"]);
+    let scores = score(&index, &terms(&["rust"]));
+
+    assert!(scores[0] > 0.0);
+    assert_eq!(scores[1], 0.0);
+  }
+
+  #[test]
+  fn test_rare_terms_outweigh_common_terms() {
+    let index = build(&["shared rare", "shared", "shared"]);
+    let common = score(&index, &terms(&["shared"]));
+    let rare = score(&index, &terms(&["rare"]));
+
+    assert!(rare[0] > common[0]);
+  }
+
+  #[test]
+  fn test_term_frequency_saturates() {
+    let index = build(&["cache", "cache cache cache cache cache cache cache cache", "other"]);
+    let scores = score(&index, &terms(&["cache"]));
+
+    assert!(scores[1] > scores[0]);
+    // Eight occurrences should score well under eight times a single occurrence
+    assert!(scores[1] < scores[0] * 3.0);
+  }
+
+  #[test]
+  fn test_substring_terms_match_tokens() {
+    let index = build(&["programming languages", "cooking"]);
+    let scores = score(&index, &terms(&["program"]));
+
+    assert!(scores[0] > 0.0);
+    assert_eq!(scores[1], 0.0);
+  }
+
+  #[test]
+  fn test_empty_corpus() {
+    let index = build::<&str>(&[]);
+    assert!(score(&index, &terms(&["anything"])).is_empty());
+  }
+}
@@ -1,100 +1,240 @@
 use anyhow::Result;
-use colored::*;
+use std::path::Path;
 
+use crate::archive::{self, ConflictStrategy, ExportSummary, ImportResult};
+#[cfg(feature = "neural")]
+use crate::daemon::{self, DaemonStart, DaemonStatus};
 #[cfg(feature = "neural")]
 use crate::embedding_client::{self, EmbeddingClient};
+use crate::eval::{self, EvalOptions, EvalReport};
+use crate::history::{self, Revision};
+#[cfg(feature = "neural")]
+use crate::index;
+use crate::ingest::{self, IngestResult};
 #[cfg(feature = "neural")]
-use crate::insight::InsightMetaData;
+use crate::insight::EmbeddingStatus;
 use crate::insight::{self, Insight};
+use crate::links::{self, InsightLink};
+#[cfg(feature = "neural")]
+use crate::model_registry::{self, InstalledModel, ModelStatus};
+#[cfg(feature = "neural")]
+use crate::related::{self, DuplicateGroup, Neighbour};
+use crate::scope::Scope;
 
-/// Add a new insight to the knowledge base (testable version with dependency injection)
+/// Number of insights `index` sends to the embedding service per request
 #[cfg(feature = "neural")]
-pub fn add_insight_with_client(
-  topic: &str,
-  name: &str,
-  overview: &str,
-  details: &str,
-  client: &EmbeddingClient,
-) -> Result<()> {
-  let mut insight =
-    Insight::new(topic.to_string(), name.to_string(), overview.to_string(), details.to_string());
+pub const DEFAULT_INDEX_BATCH_SIZE: usize = 32;
+
+/// Outcome of recomputing embeddings across the knowledge base
+#[cfg(feature = "neural")]
+#[derive(Debug)]
+pub struct IndexSummary {
+  /// Number of insights examined
+  pub processed: usize,
+  /// Number of vectors in the rebuilt index
+  pub indexed: usize,
+  /// Insights whose embeddings were recomputed
+  pub updated: Vec<Insight>,
+  /// Insights the embedding service returned no embedding for; they are left unchanged
+  pub failed: Vec<Insight>,
+}
+
+/// An insight whose embedding no longer matches its content or the current model
+#[cfg(feature = "neural")]
+#[derive(Debug)]
+pub struct EmbeddingDrift {
+  pub insight: Insight,
+  pub status: EmbeddingStatus,
+}
+
+/// A newly added insight and the existing insights it closely resembles
+#[derive(Debug)]
+pub struct AddedInsight {
+  pub insight: Insight,
+  /// Existing insights at least `related::DEFAULT_DUPLICATE_THRESHOLD` similar, most similar first
+  #[cfg(feature = "neural")]
+  pub similar: Vec<Neighbour>,
+}
+
+/// Line diff between two versions of an insight
+#[derive(Debug)]
+pub struct InsightDiff {
+  pub from: String,
+  pub to: String,
+  pub unified: String,
+}
+
+/// A link from an insight and whether its target exists
+#[derive(Debug)]
+pub struct ResolvedLink {
+  pub link: InsightLink,
+  pub exists: bool,
+}
+
+/// Links from and to a single insight
+#[derive(Debug)]
+pub struct InsightLinks {
+  pub outgoing: Vec<ResolvedLink>,
+  pub backlinks: Vec<Insight>,
+}
+
+/// Content and placement of an insight to add
+#[derive(Debug, Clone, Copy)]
+pub struct NewInsight<'a> {
+  pub topic: &'a str,
+  pub name: &'a str,
+  pub overview: &'a str,
+  pub details: &'a str,
+  pub tags: &'a [String],
+  pub scope: Scope,
+  /// Add the insight even if it nearly duplicates an existing one
+  #[cfg_attr(not(feature = "neural"), allow(dead_code))]
+  pub force: bool,
+}
+
+impl<'a> NewInsight<'a> {
+  /// An untagged insight for the global store
+  pub fn new(topic: &'a str, name: &'a str, overview: &'a str, details: &'a str) -> Self {
+    Self { topic, name, overview, details, tags: &[], scope: Scope::Global, force: false }
+  }
+
+  fn to_insight(self) -> Insight {
+    let mut insight = Insight::new(
+      self.topic.to_string(),
+      self.name.to_string(),
+      self.overview.to_string(),
+      self.details.to_string(),
+    );
+    insight.tags = self.tags.to_vec();
+    insight.scope = self.scope;
+    insight
+  }
+}
+
+/// Add a new insight. One that nearly duplicates existing insights is refused
+/// with a list of them unless `force` is set, and added with them otherwise
+/// (testable version with dependency injection)
+#[cfg(feature = "neural")]
+pub fn add_insight_with_client(new: &NewInsight, client: &EmbeddingClient) -> Result<AddedInsight> {
+  let mut insight = new.to_insight();
+  check_not_shadowed(&insight)?;
 
   // Compute embedding before saving
   let embedding = embedding_client::embed_insight(client, &mut insight);
   insight::set_embedding(&mut insight, embedding);
-  insight::save(&insight)?;
 
-  println!("{} Added insight {}/{}", "✓".green(), topic.cyan(), name.yellow());
-  Ok(())
+  // Compare against the existing insights before the new one joins them. The
+  // check is only a safeguard, so the insight is added even if it can't be made.
+  let similar = index::embedded_insights()
+    .map(|candidates| related::similar(&insight, candidates, related::DEFAULT_DUPLICATE_THRESHOLD))
+    .and_then(load_neighbours)
+    .unwrap_or_default();
+  if !similar.is_empty() && !new.force {
+    let duplicates: Vec<String> = similar
+      .iter()
+      .map(|neighbour| {
+        format!(
+          "{}/{} ({:.2})",
+          neighbour.insight.topic, neighbour.insight.name, neighbour.similarity
+        )
+      })
+      .collect();
+    return Err(anyhow::anyhow!(
+      "{}/{} is very similar to {}; add it anyway with --force",
+      insight.topic,
+      insight.name,
+      duplicates.join(", ")
+    ));
+  }
+  Ok(AddedInsight { insight: save_new(&insight)?, similar })
 }
 
 /// Add a new insight to the knowledge base (production version)
-pub fn add_insight(topic: &str, name: &str, overview: &str, details: &str) -> Result<()> {
+pub fn add_insight(new: &NewInsight) -> Result<AddedInsight> {
   #[cfg(feature = "neural")]
   {
     let client = embedding_client::create();
-    add_insight_with_client(topic, name, overview, details, &client)
+    add_insight_with_client(new, &client)
   }
   #[cfg(not(feature = "neural"))]
   {
-    let insight =
-      Insight::new(topic.to_string(), name.to_string(), overview.to_string(), details.to_string());
-    insight::save(&insight)?;
-    println!("{} Added insight {}/{}", "✓".green(), topic.cyan(), name.yellow());
-    Ok(())
+    let insight = new.to_insight();
+    check_not_shadowed(&insight)?;
+    Ok(AddedInsight { insight: save_new(&insight)? })
   }
 }
 
-/// Get content of a specific insight
-pub fn get_insight(topic: &str, name: &str, overview_only: bool) -> Result<()> {
-  let insight = insight::load(topic, name)?;
+/// Refuse to add a global insight that a project insight of the same topic
+/// and name would hide
+fn check_not_shadowed(insight: &Insight) -> Result<()> {
+  if insight.scope != Scope::Global {
+    return Ok(());
+  }
 
-  if overview_only {
-    println!("{}", insight.overview);
-  } else {
-    println!("---\n{}\n---\n\n{}", insight.overview, insight.details);
+  let project = Insight { scope: Scope::Project, ..insight.clone() };
+  match insight::file_path(&project) {
+    Ok(path) if path.exists() => Err(anyhow::anyhow!(
+      "Insight {}/{} exists in the project store and would hide the global one; choose another name",
+      insight.topic,
+      insight.name
+    )),
+    _ => Ok(()),
   }
+}
 
-  Ok(())
+/// Save a new insight and reload it from its own store to pick up the
+/// creation metadata stamped on save
+fn save_new(insight: &Insight) -> Result<Insight> {
+  insight::save(insight)?;
+  insight::load_from_path(&insight::file_path(insight)?)
 }
 
-pub fn list_insights(filter: Option<&str>, verbose: bool) -> Result<()> {
-  let insights = insight::get_insights(filter)?;
+/// Get content of a specific insight
+pub fn get_insight(topic: &str, name: &str) -> Result<Insight> {
+  insight::load(topic, name)
+}
 
-  if insights.is_empty() {
-    if let Some(topic) = filter {
-      println!("No insights found in topic: {}", topic.yellow());
-    } else {
-      println!("No insights found.");
-    }
-    return Ok(());
-  }
+/// Load the existing insights an insight links to, in link order
+pub fn linked_insights(insight: &Insight) -> Vec<Insight> {
+  links::outgoing(insight)
+    .iter()
+    .filter_map(|link| insight::load(&link.topic, &link.name).ok())
+    .collect()
+}
 
-  for insight in insights {
-    let formatted_name = if verbose {
-      format!("{}/{} - {}", insight.topic.cyan(), insight.name.yellow(), insight.overview)
-    } else {
-      format!("{}/{}", insight.topic.cyan(), insight.name.yellow())
-    };
-    println!("{formatted_name}");
-  }
+/// Outgoing links of an insight, with broken ones flagged, and the insights linking back to it
+pub fn insight_links(topic: &str, name: &str) -> Result<InsightLinks> {
+  let insight = insight::load(topic, name)?;
 
-  Ok(())
-}
+  let outgoing = links::outgoing(&insight)
+    .into_iter()
+    .map(|link| {
+      let exists = links::resolves(&link);
+      ResolvedLink { link, exists }
+    })
+    .collect();
 
-pub fn list_topics() -> Result<()> {
-  let topics = insight::get_topics()?;
+  Ok(InsightLinks { outgoing, backlinks: links::backlinks(topic, name)? })
+}
 
-  if topics.is_empty() {
-    println!("No topics found.");
-    return Ok(());
+/// Insights whose links to `topic/name` no longer resolve
+pub fn broken_backlinks(topic: &str, name: &str) -> Result<Vec<Insight>> {
+  if insight::load(topic, name).is_ok() {
+    return Ok(Vec::new());
   }
+  links::backlinks(topic, name)
+}
 
-  for topic in topics {
-    println!("{}", topic.cyan());
-  }
+/// List insights in a topic or all topics, keeping only those with every given tag
+pub fn list_insights(filter: Option<&str>, tags: &[String]) -> Result<Vec<Insight>> {
+  let mut insights = insight::get_insights(filter)?;
+  insights.retain(|insight| insight::has_tags(insight, tags));
+  Ok(insights)
+}
 
-  Ok(())
+/// List all available topics
+pub fn list_topics() -> Result<Vec<String>> {
+  insight::get_topics()
 }
 
 /// Update an existing insight's overview and/or details
@@ -104,9 +244,14 @@ pub fn update_insight_with_client(
   name: &str,
   new_overview: Option<&str>,
   new_details: Option<&str>,
+  new_tags: Option<&[String]>,
   client: &EmbeddingClient,
-) -> Result<()> {
-  let mut insight = insight::load(topic, name)?;
+) -> Result<Insight> {
+  let (mut insight, content_changed) =
+    load_and_retag(topic, name, new_overview, new_details, new_tags)?;
+  if !content_changed {
+    return Ok(insight);
+  }
 
   insight::update(&mut insight, new_overview, new_details)?;
 
@@ -114,24 +259,9 @@ pub fn update_insight_with_client(
   let embedding = embedding_client::embed_insight(client, &mut insight);
   insight::set_embedding(&mut insight, embedding);
 
-  let file_path = insight::file_path(&insight)?;
-  let metadata = InsightMetaData {
-    topic: insight.topic.clone(),
-    name: insight.name.clone(),
-    overview: insight.overview.clone(),
-    embedding_version: insight.embedding_version.clone(),
-    embedding: insight.embedding.clone(),
-    embedding_text: insight.embedding_text.clone(),
-    embedding_computed: insight.embedding_computed,
-  };
-
-  let yaml_content = serde_yaml::to_string(&metadata)?;
-  let content = format!("---\n{}---\n\n{}", yaml_content, insight.details);
-  std::fs::write(&file_path, content)?;
-
-  println!("{} Updated insight {}/{}", "✓".green(), topic.cyan(), name.yellow());
+  insight::save_existing(&insight)?;
 
-  Ok(())
+  Ok(insight)
 }
 
 /// Update an existing insight's overview and/or details
@@ -140,122 +270,395 @@ pub fn update_insight(
   name: &str,
   new_overview: Option<&str>,
   new_details: Option<&str>,
-) -> Result<()> {
+  new_tags: Option<&[String]>,
+) -> Result<Insight> {
   #[cfg(feature = "neural")]
   {
     let client = embedding_client::create();
-    update_insight_with_client(topic, name, new_overview, new_details, &client)
+    update_insight_with_client(topic, name, new_overview, new_details, new_tags, &client)
   }
   #[cfg(not(feature = "neural"))]
   {
-    let mut insight = insight::load(topic, name)?;
-    insight::update(&mut insight, new_overview, new_details)?;
-    println!("{} Updated insight {}/{}", "✓".green(), topic.cyan(), name.yellow());
-    Ok(())
+    let (mut insight, content_changed) =
+      load_and_retag(topic, name, new_overview, new_details, new_tags)?;
+    if content_changed {
+      insight::update(&mut insight, new_overview, new_details)?;
+    }
+    Ok(insight)
   }
 }
 
-/// Delete an insight
-pub fn delete_insight(topic: &str, name: &str, force: bool) -> Result<()> {
-  if !force {
-    return Err(anyhow::anyhow!("Delete operation requires --force flag"));
+/// Load an insight for updating and apply any tag change. Returns whether the
+/// overview or details still need to be updated. Tags that come with a
+/// content change are only set on the insight, so the update writes it once.
+fn load_and_retag(
+  topic: &str,
+  name: &str,
+  new_overview: Option<&str>,
+  new_details: Option<&str>,
+  new_tags: Option<&[String]>,
+) -> Result<(Insight, bool)> {
+  let mut insight = insight::load(topic, name)?;
+  let content_changed = new_overview.is_some() || new_details.is_some();
+
+  match new_tags {
+    Some(tags) if content_changed => insight.tags = insight::normalize_tags(tags),
+    Some(tags) => insight::set_tags(&mut insight, tags)?,
+    None => {}
   }
 
-  let insight = insight::load(topic, name)?;
-  insight::delete(&insight)?;
+  // Without any change, fall through so `insight::update` reports the error
+  Ok((insight, content_changed || new_tags.is_none()))
+}
 
-  println!("{} Deleted insight {}/{}", "✓".green(), topic.cyan(), name.yellow());
+/// List the stored revisions of an insight, oldest first
+pub fn insight_history(topic: &str, name: &str) -> Result<Vec<Revision>> {
+  let revisions = history::list(topic, name)?;
+  if revisions.is_empty() && insight::load(topic, name).is_err() {
+    return Err(anyhow::anyhow!("Insight {}/{} not found", topic, name));
+  }
+  Ok(revisions)
+}
 
-  Ok(())
+/// Diff two revisions of an insight. `from` defaults to the latest revision
+/// and `to` defaults to the current insight.
+pub fn diff_insight(
+  topic: &str,
+  name: &str,
+  from: Option<u32>,
+  to: Option<u32>,
+) -> Result<InsightDiff> {
+  let from = match from {
+    Some(number) => number,
+    None => history::list(topic, name)?
+      .last()
+      .map(|revision| revision.number)
+      .ok_or_else(|| anyhow::anyhow!("Insight {}/{} has no revisions", topic, name))?,
+  };
+  let old = history::load(topic, name, from)?.insight;
+  let from_label = format!("{topic}/{name}@{from}");
+
+  let (new, to_label) = match to {
+    Some(number) => {
+      (history::load(topic, name, number)?.insight, format!("{topic}/{name}@{number}"))
+    }
+    None => (insight::load(topic, name)?, format!("{topic}/{name}")),
+  };
+
+  let unified = history::diff(&old, &new, &from_label, &to_label);
+  Ok(InsightDiff { from: from_label, to: to_label, unified })
 }
 
+/// Restore an insight's content and tags from a revision (testable version with dependency injection)
 #[cfg(feature = "neural")]
-fn index_insight(insight: &mut Insight, force: bool, client: &EmbeddingClient) -> Result<bool> {
-  let should_update = if force { true } else { !insight::has_embedding(insight) };
+pub fn revert_insight_with_client(
+  topic: &str,
+  name: &str,
+  revision: u32,
+  client: &EmbeddingClient,
+) -> Result<Insight> {
+  let mut insight = restore_revision(topic, name, revision)?;
 
-  if !should_update {
-    return Ok(false);
-  }
+  // Recompute and set embedding after content change
+  let embedding = embedding_client::embed_insight(client, &mut insight);
+  insight::set_embedding(&mut insight, embedding);
 
-  let embedding = embedding_client::embed_insight(client, insight);
-  insight::set_embedding(insight, embedding);
-
-  let file_path = insight::file_path(insight)?;
-  let metadata = InsightMetaData {
-    topic: insight.topic.clone(),
-    name: insight.name.clone(),
-    overview: insight.overview.clone(),
-    embedding_version: insight.embedding_version.clone(),
-    embedding: insight.embedding.clone(),
-    embedding_text: insight.embedding_text.clone(),
-    embedding_computed: insight.embedding_computed,
-  };
+  insight::save_existing(&insight)?;
 
-  let yaml = serde_yaml::to_string(&metadata)?;
-  let content = format!("---\n{}---\n\n# Details\n{}", yaml, insight.details);
-  std::fs::write(&file_path, content)?;
+  Ok(insight)
+}
 
-  println!(
-    "  {} Updated embeddings for {}/{}",
-    "✓".green(),
-    insight.topic.cyan(),
-    insight.name.yellow()
-  );
+/// Restore an insight's content and tags from a revision
+pub fn revert_insight(topic: &str, name: &str, revision: u32) -> Result<Insight> {
+  #[cfg(feature = "neural")]
+  {
+    let client = embedding_client::create();
+    revert_insight_with_client(topic, name, revision, &client)
+  }
+  #[cfg(not(feature = "neural"))]
+  {
+    restore_revision(topic, name, revision)
+  }
+}
 
-  Ok(true)
+/// Write a revision's content over the current insight. The current version is
+/// itself kept as a new revision, so a revert can be reverted.
+fn restore_revision(topic: &str, name: &str, revision: u32) -> Result<Insight> {
+  let restored = history::load(topic, name, revision)?.insight;
+  let mut insight = insight::load(topic, name)?;
+
+  insight.tags = restored.tags;
+  insight::update(&mut insight, Some(&restored.overview), Some(&restored.details))?;
+
+  Ok(insight)
 }
 
+/// Move an insight to another topic and/or name (testable version with dependency injection)
 #[cfg(feature = "neural")]
-fn index_topics_with_client(
+pub fn move_insight_with_client(
   topic: &str,
-  force: bool,
+  name: &str,
+  new_topic: &str,
+  new_name: Option<&str>,
   client: &EmbeddingClient,
-) -> Result<(usize, usize)> {
-  let insights = insight::get_insights(Some(topic))?;
-  let total = insights.len();
-  let mut updated = 0;
-
-  for mut insight in insights {
-    if index_insight(&mut insight, force, client)? {
-      updated += 1;
-    }
+) -> Result<Insight> {
+  let mut insight = relocate_insight(topic, name, new_topic, new_name)?;
+
+  // The embedded text includes the topic and name, so the move cleared it
+  let embedding = embedding_client::embed_insight(client, &mut insight);
+  insight::set_embedding(&mut insight, embedding);
+  insight::save_existing(&insight)?;
+
+  Ok(insight)
+}
+
+/// Move an insight to another topic and/or name
+pub fn move_insight(
+  topic: &str,
+  name: &str,
+  new_topic: &str,
+  new_name: Option<&str>,
+) -> Result<Insight> {
+  #[cfg(feature = "neural")]
+  {
+    let client = embedding_client::create();
+    move_insight_with_client(topic, name, new_topic, new_name, &client)
+  }
+  #[cfg(not(feature = "neural"))]
+  {
+    relocate_insight(topic, name, new_topic, new_name)
   }
+}
 
-  Ok((updated, total))
+fn relocate_insight(
+  topic: &str,
+  name: &str,
+  new_topic: &str,
+  new_name: Option<&str>,
+) -> Result<Insight> {
+  let insight = insight::load(topic, name)?;
+  let new_name = new_name.unwrap_or(&insight.name).to_string();
+  insight::rename(&insight, new_topic, &new_name)
 }
 
-/// Recompute embeddings for insights (testable version with dependency injection)
-#[cfg(feature = "neural")]
-pub fn index_insights_with_client(force: bool, client: &EmbeddingClient) -> Result<()> {
-  let topics = insight::get_topics()?;
+/// Write insights to a portable bundle file
+pub fn export_insights(
+  topic: Option<&str>,
+  strip_embeddings: bool,
+  out: &Path,
+) -> Result<ExportSummary> {
+  archive::export(topic, strip_embeddings, out)
+}
 
-  if topics.is_empty() {
-    println!("No topics found to index.");
-    return Ok(());
+/// Import insights from a bundle file, resolving conflicts with `strategy`
+pub fn import_insights(
+  path: &Path,
+  strategy: ConflictStrategy,
+  scope: Scope,
+) -> Result<Vec<ImportResult>> {
+  archive::import(path, strategy, scope)
+}
+
+/// Ingest a directory of Markdown notes. Ingested insights are not embedded
+/// until `insights index` runs.
+pub fn ingest_directory(dir: &Path, scope: Scope) -> Result<Vec<IngestResult>> {
+  ingest::ingest(dir, scope)
+}
+
+/// Evaluate search quality against a fixture file of queries and relevant insights
+pub fn evaluate_search(fixtures: &Path, options: &EvalOptions) -> Result<EvalReport> {
+  eval::run(&eval::load(fixtures)?, options)
+}
+
+/// Insights whose text still refers to `topic/name`, e.g. after it was moved
+pub fn stale_references(topic: &str, name: &str) -> Result<Vec<Insight>> {
+  links::mentions(topic, name)
+}
+
+/// Delete an insight
+pub fn delete_insight(topic: &str, name: &str, force: bool) -> Result<Insight> {
+  if !force {
+    return Err(anyhow::anyhow!("Delete operation requires --force flag"));
   }
 
-  let mut total_updated = 0;
-  let mut total_processed = 0;
+  let insight = insight::load(topic, name)?;
+  insight::delete(&insight)?;
+
+  Ok(insight)
+}
+
+/// Recompute embeddings for insights (testable version with dependency injection).
+/// Insights are sent to the embedding service `batch_size` at a time, and
+/// `progress` is called with the number processed so far and the number pending.
+/// Insights the service returns no embedding for are reported as failed and
+/// the run carries on. The vector index is rebuilt once at the end.
+#[cfg(feature = "neural")]
+pub fn index_insights_with_client(
+  force: bool,
+  batch_size: usize,
+  client: &EmbeddingClient,
+  mut progress: impl FnMut(usize, usize),
+) -> Result<IndexSummary> {
+  let insights = insight::get_insights(None)?;
+  let processed = insights.len();
+  if processed == 0 {
+    return Ok(IndexSummary { processed, indexed: 0, updated: Vec::new(), failed: Vec::new() });
+  }
+  if let Some(reason) = embedding_client::unavailable(client) {
+    return Err(anyhow::anyhow!("Cannot compute embeddings: {}", reason));
+  }
 
-  for topic in topics {
-    let (updated, processed) = index_topics_with_client(&topic, force, client)?;
-    total_updated += updated;
-    total_processed += processed;
+  let model_version = embedding_client::model_version(client);
+  let pending: Vec<Insight> = insights
+    .into_iter()
+    .filter(|insight| {
+      force
+        || insight::embedding_status(insight, model_version.as_deref()) != EmbeddingStatus::Current
+    })
+    .collect();
+
+  let mut updated = Vec::with_capacity(pending.len());
+  let mut failed = Vec::new();
+  progress(0, pending.len());
+  for batch in pending.chunks(batch_size.max(1)) {
+    let mut embeddings = embedding_client::embed_insights(client, batch).into_iter();
+    for mut insight in batch.iter().cloned() {
+      // Placeholders stand in for embeddings the service failed to compute
+      match embeddings.next().filter(|embedding| !embedding.embedding.is_empty()) {
+        Some(embedding) => {
+          insight::set_embedding(&mut insight, embedding);
+          insight::save_existing_unindexed(&insight)?;
+          updated.push(insight);
+        }
+        None => failed.push(insight),
+      }
+    }
+    progress(updated.len() + failed.len(), pending.len());
   }
 
-  println!(
-    "{} Indexed {} of {} insights",
-    "✓".green(),
-    total_updated.to_string().yellow(),
-    total_processed.to_string().cyan()
-  );
+  let indexed = index::rebuild()?.entries.len();
+  Ok(IndexSummary { processed, indexed, updated, failed })
+}
+
+/// Insights whose embeddings are missing, stale or from another model, without
+/// changing anything (testable version with dependency injection)
+#[cfg(feature = "neural")]
+pub fn check_embeddings_with_client(client: &EmbeddingClient) -> Result<Vec<EmbeddingDrift>> {
+  let model_version = embedding_client::model_version(client);
+
+  let mut drift = Vec::new();
+  for insight in insight::get_insights(None)? {
+    let status = insight::embedding_status(&insight, model_version.as_deref());
+    if status != EmbeddingStatus::Current {
+      drift.push(EmbeddingDrift { insight, status });
+    }
+  }
+  Ok(drift)
+}
 
-  Ok(())
+/// Insights whose embeddings need recomputing, without changing anything
+#[cfg(feature = "neural")]
+pub fn check_embeddings() -> Result<Vec<EmbeddingDrift>> {
+  let client = embedding_client::create();
+  check_embeddings_with_client(&client)
 }
 
 /// Recompute embeddings for insights
 #[cfg(feature = "neural")]
-pub fn index_insights(force: bool) -> Result<()> {
+pub fn index_insights(
+  force: bool,
+  batch_size: usize,
+  progress: impl FnMut(usize, usize),
+) -> Result<IndexSummary> {
   let client = embedding_client::create();
-  index_insights_with_client(force, &client)
+  index_insights_with_client(force, batch_size, &client, progress)
+}
+
+/// The insights whose stored embeddings are closest to `topic/name`
+#[cfg(feature = "neural")]
+pub fn related_insights(topic: &str, name: &str, limit: usize) -> Result<Vec<Neighbour>> {
+  let insight = insight::load(topic, name)?;
+  if insight::embedding_status(&insight, None) == EmbeddingStatus::Missing {
+    return Err(anyhow::anyhow!(
+      "Insight {}/{} has no embedding; run `insights index` first",
+      topic,
+      name
+    ));
+  }
+  load_neighbours(related::nearest(&insight, index::embedded_insights()?, limit))
+}
+
+/// Groups of near-duplicate insights across all topics
+#[cfg(feature = "neural")]
+pub fn find_duplicates(threshold: f32) -> Result<Vec<DuplicateGroup>> {
+  if !(0.0..=1.0).contains(&threshold) {
+    return Err(anyhow::anyhow!("Threshold must be between 0 and 1, got {}", threshold));
+  }
+  related::duplicates(index::embedded_insights()?, threshold)
+    .into_iter()
+    .map(|group| {
+      let insights = group.insights.iter().map(load_embedded).collect::<Result<_>>()?;
+      Ok(DuplicateGroup { insights, ..group })
+    })
+    .collect()
+}
+
+/// Replace the index stand-ins among neighbours with the insights they stand for
+#[cfg(feature = "neural")]
+fn load_neighbours(neighbours: Vec<Neighbour>) -> Result<Vec<Neighbour>> {
+  neighbours
+    .into_iter()
+    .map(|neighbour| Ok(Neighbour { insight: load_embedded(&neighbour.insight)?, ..neighbour }))
+    .collect()
+}
+
+/// Read an insight returned by `index::embedded_insights` in full
+#[cfg(feature = "neural")]
+fn load_embedded(insight: &Insight) -> Result<Insight> {
+  index::load_indexed(&insight.topic, &insight.name, insight.scope)
+}
+
+/// Registered embedding models and whether they are installed
+#[cfg(feature = "neural")]
+pub fn list_models() -> Result<Vec<ModelStatus>> {
+  model_registry::list()
+}
+
+/// Install a model file into the model cache, verifying its checksum
+#[cfg(feature = "neural")]
+pub fn install_model(
+  name: Option<&str>,
+  from: &Path,
+  sha256: Option<&str>,
+) -> Result<InstalledModel> {
+  model_registry::install(name, from, sha256)
+}
+
+/// Status of the embedding daemon, or None if it isn't running
+#[cfg(feature = "neural")]
+pub fn daemon_status() -> Result<Option<DaemonStatus>> {
+  block_on(daemon::status())
+}
+
+/// Start the embedding daemon in the background and wait until it is ready
+#[cfg(feature = "neural")]
+pub fn start_daemon(idle_timeout_secs: Option<u64>) -> Result<DaemonStart> {
+  block_on(daemon::start(idle_timeout_secs))
+}
+
+/// Stop the embedding daemon, returning its last status if it was running
+#[cfg(feature = "neural")]
+pub fn stop_daemon() -> Result<Option<DaemonStatus>> {
+  block_on(daemon::stop())
+}
+
+/// Replace the running embedding daemon with a fresh one
+#[cfg(feature = "neural")]
+pub fn restart_daemon(idle_timeout_secs: Option<u64>) -> Result<DaemonStart> {
+  block_on(daemon::restart(idle_timeout_secs))
+}
+
+#[cfg(feature = "neural")]
+fn block_on<T>(future: impl std::future::Future<Output = Result<T>>) -> Result<T> {
+  tokio::runtime::Runtime::new()?.block_on(future)
 }
@@ -0,0 +1,957 @@
+//! Embedding daemon: the JSON-lines protocol it speaks, the request handler
+//! that keeps the model loaded, and the lifecycle used by the CLI and the
+//! embedding client to start, stop and query it.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, OpenOptionsExt, PermissionsExt};
+#[cfg(unix)]
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::{mpsc, Notify};
+use tokio::time::{sleep, Duration};
+
+// Platform-specific imports
+#[cfg(windows)]
+use tokio::net::{TcpListener, TcpStream};
+#[cfg(unix)]
+use tokio::net::{UnixListener, UnixStream};
+
+use crate::embedding_model::EmbeddingModel;
+use crate::model_registry;
+use crate::query_cache::{self, QueryCache};
+
+// Platform-specific constants
+#[cfg(windows)]
+pub const TCP_ADDRESS: &str = "127.0.0.1:47291";
+
+/// Overrides the directory holding the daemon's socket, pid file and log
+pub const RUNTIME_DIR_ENV: &str = "INSIGHTS_RUNTIME_DIR";
+
+#[cfg(unix)]
+const SOCKET_FILE: &str = "embeddings.sock";
+const PID_FILE: &str = "daemon.pid";
+
+/// Seconds without a connection before the daemon exits
+#[allow(dead_code)] // used by the daemon binary
+pub const DEFAULT_IDLE_TIMEOUT_SECS: u64 = 300;
+/// Overrides the idle timeout of auto-started daemons; 0 disables it
+#[allow(dead_code)] // used by the daemon binary
+pub const IDLE_TIMEOUT_ENV: &str = "INSIGHTS_DAEMON_IDLE_TIMEOUT";
+
+/// How long to wait for a starting daemon to load its model and answer
+const READY_TIMEOUT: Duration = Duration::from_secs(30);
+/// How long to wait for a stopping daemon to release its socket
+const STOP_TIMEOUT: Duration = Duration::from_secs(5);
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// Most texts coalesced into a single model call
+const MAX_BATCH_TEXTS: usize = 256;
+
+/// What a request asks the daemon to do
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RequestKind {
+  /// Compute embeddings for the request's texts
+  #[default]
+  Embed,
+  /// Check that the daemon is up; answered with its status
+  Ping,
+  /// Report the daemon's status
+  Status,
+  /// Answer with the status, then exit
+  Shutdown,
+}
+
+/// One request line. Requests without a `type` are embedding requests, as
+/// sent by clients that predate the other request types.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonRequest {
+  pub id: String,
+  #[serde(rename = "type", default)]
+  pub kind: RequestKind,
+  #[serde(default)]
+  pub texts: Vec<String>,
+  /// Answer from, and remember in, the daemon's query cache. Set for search
+  /// queries, which repeat; insight texts rarely do.
+  #[serde(default)]
+  pub cache: bool,
+}
+
+/// One response line, matched to its request by `id`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DaemonResponse {
+  pub id: String,
+  #[serde(default)]
+  pub embeddings: Vec<Vec<f32>>,
+  #[serde(default)]
+  pub error: Option<String>,
+  /// Name of the model that computed the embeddings
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub model: Option<String>,
+  /// Daemon status, for ping, status and shutdown requests
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub status: Option<DaemonStatus>,
+}
+
+/// What a running daemon reports about itself
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DaemonStatus {
+  pub pid: u32,
+  pub model: Option<String>,
+  pub uptime_secs: u64,
+  /// Embedding requests answered since the daemon started
+  pub requests_served: u64,
+  /// Seconds without a connection before the daemon exits; 0 means never
+  pub idle_timeout_secs: u64,
+  /// Queries held in the query cache
+  #[serde(default)]
+  pub cached_queries: usize,
+  /// Queries answered from the cache instead of the model
+  #[serde(default)]
+  pub cache_hits: u64,
+}
+
+impl DaemonRequest {
+  pub fn new(kind: RequestKind, texts: Vec<String>) -> Self {
+    Self { id: uuid::Uuid::new_v4().to_string(), kind, texts, cache: false }
+  }
+
+  /// An embedding request for search queries, answered from the query cache
+  /// where possible
+  pub fn query(texts: Vec<String>) -> Self {
+    Self { cache: true, ..Self::new(RequestKind::Embed, texts) }
+  }
+}
+
+/// State shared by the model thread and the connections, so status requests
+/// and cached queries are answered without waiting for the model
+#[allow(dead_code)] // run by the daemon binary
+struct ServiceState {
+  model: Option<String>,
+  started: Instant,
+  requests_served: AtomicU64,
+  idle_timeout_secs: u64,
+  query_cache: Mutex<QueryCache>,
+}
+
+#[allow(dead_code)] // run by the daemon binary
+impl ServiceState {
+  fn status(&self) -> DaemonStatus {
+    let query_cache = self.query_cache.lock().unwrap();
+    DaemonStatus {
+      pid: std::process::id(),
+      model: self.model.clone(),
+      uptime_secs: self.started.elapsed().as_secs(),
+      requests_served: self.requests_served.load(Ordering::Relaxed),
+      idle_timeout_secs: self.idle_timeout_secs,
+      cached_queries: query_cache.len(),
+      cache_hits: query_cache.hits(),
+    }
+  }
+
+  fn status_response(&self, id: String) -> DaemonResponse {
+    DaemonResponse {
+      id,
+      model: self.model.clone(),
+      status: Some(self.status()),
+      ..Default::default()
+    }
+  }
+
+  /// Answer a query request entirely from the query cache, if every text is cached
+  fn cached_response(&self, request: &DaemonRequest) -> Option<DaemonResponse> {
+    if !request.cache || request.kind != RequestKind::Embed {
+      return None;
+    }
+    let mut query_cache = self.query_cache.lock().unwrap();
+    let embeddings =
+      request.texts.iter().map(|text| query_cache.get(text)).collect::<Option<Vec<_>>>()?;
+
+    self.requests_served.fetch_add(1, Ordering::Relaxed);
+    Some(DaemonResponse {
+      id: request.id.clone(),
+      embeddings,
+      model: self.model.clone(),
+      ..Default::default()
+    })
+  }
+
+  fn remember(&self, request: &DaemonRequest, response: &DaemonResponse) {
+    if !request.cache || response.error.is_some() {
+      return;
+    }
+    let mut query_cache = self.query_cache.lock().unwrap();
+    for (text, embedding) in request.texts.iter().zip(&response.embeddings) {
+      query_cache.insert(text.clone(), embedding.clone());
+    }
+  }
+}
+
+/// Embedding service that keeps model loaded in memory
+#[allow(dead_code)] // run by the daemon binary
+pub struct EmbeddingService<M: EmbeddingModel> {
+  model: M,
+  state: Arc<ServiceState>,
+}
+
+#[allow(dead_code)] // run by the daemon binary
+impl<M: EmbeddingModel> EmbeddingService<M> {
+  pub fn new(model: M, idle_timeout_secs: u64) -> Self {
+    Self::with_query_cache(model, idle_timeout_secs, QueryCache::new(query_cache::DEFAULT_CAPACITY))
+  }
+
+  pub fn with_query_cache(model: M, idle_timeout_secs: u64, query_cache: QueryCache) -> Self {
+    let state = ServiceState {
+      model: model.version().map(str::to_string),
+      started: Instant::now(),
+      requests_served: AtomicU64::new(0),
+      idle_timeout_secs,
+      query_cache: Mutex::new(query_cache),
+    };
+    Self { model, state: Arc::new(state) }
+  }
+
+  pub fn handle_request(&mut self, request: DaemonRequest) -> DaemonResponse {
+    match request.kind {
+      RequestKind::Embed => self.handle_batch(vec![request]).remove(0),
+      RequestKind::Ping | RequestKind::Status | RequestKind::Shutdown => {
+        self.state.status_response(request.id)
+      }
+    }
+  }
+
+  /// Answer several embedding requests, in order. Cached queries are answered
+  /// from the cache and the rest share a single model call.
+  pub fn handle_batch(&mut self, requests: Vec<DaemonRequest>) -> Vec<DaemonResponse> {
+    let mut responses: Vec<Option<DaemonResponse>> = Vec::with_capacity(requests.len());
+    let mut uncached = Vec::new();
+    for request in requests {
+      let response = self.state.cached_response(&request);
+      if response.is_none() {
+        uncached.push((responses.len(), request));
+      }
+      responses.push(response);
+    }
+
+    let (positions, requests): (Vec<_>, Vec<_>) = uncached.into_iter().unzip();
+    let computed = self.compute_batch(&requests);
+    for ((position, request), response) in positions.into_iter().zip(&requests).zip(computed) {
+      self.state.remember(request, &response);
+      responses[position] = Some(response);
+    }
+    responses.into_iter().flatten().collect()
+  }
+
+  /// Compute embeddings for several requests with a single model call. If the
+  /// call fails each request is retried alone, so a bad text only fails its
+  /// own request.
+  fn compute_batch(&mut self, requests: &[DaemonRequest]) -> Vec<DaemonResponse> {
+    if requests.len() < 2 {
+      return requests.iter().map(|request| self.compute(request)).collect();
+    }
+
+    let texts: Vec<String> =
+      requests.iter().flat_map(|request| request.texts.iter().cloned()).collect();
+    let embeddings = match self.model.compute_embeddings(&texts) {
+      Ok(embeddings) if embeddings.len() == texts.len() => embeddings,
+      _ => return requests.iter().map(|request| self.compute(request)).collect(),
+    };
+
+    self.state.requests_served.fetch_add(requests.len() as u64, Ordering::Relaxed);
+    let mut embeddings = embeddings.into_iter();
+    requests
+      .iter()
+      .map(|request| DaemonResponse {
+        id: request.id.clone(),
+        embeddings: embeddings.by_ref().take(request.texts.len()).collect(),
+        model: self.state.model.clone(),
+        ..Default::default()
+      })
+      .collect()
+  }
+
+  fn compute(&mut self, request: &DaemonRequest) -> DaemonResponse {
+    self.state.requests_served.fetch_add(1, Ordering::Relaxed);
+    let (embeddings, error) = match self.model.compute_embeddings(&request.texts) {
+      Ok(embeddings) => (embeddings, None),
+      Err(e) => (vec![], Some(e.to_string())),
+    };
+    DaemonResponse {
+      id: request.id.clone(),
+      embeddings,
+      error,
+      model: self.state.model.clone(),
+      ..Default::default()
+    }
+  }
+
+  pub fn status(&self) -> DaemonStatus {
+    self.state.status()
+  }
+}
+
+// Cross-platform listener abstraction
+#[allow(dead_code)] // run by the daemon binary
+pub enum InsightsListener {
+  #[cfg(unix)]
+  Unix(UnixListener),
+  #[cfg(windows)]
+  Tcp(TcpListener),
+}
+
+type StreamReader = Box<dyn AsyncRead + Unpin + Send>;
+type StreamWriter = Box<dyn AsyncWrite + Unpin + Send>;
+
+#[allow(dead_code)] // run by the daemon binary
+impl InsightsListener {
+  async fn accept(&self) -> std::io::Result<(StreamReader, StreamWriter)> {
+    match self {
+      #[cfg(unix)]
+      InsightsListener::Unix(listener) => loop {
+        let (stream, _) = listener.accept().await?;
+        // The socket's permissions already keep other users out; checking the
+        // peer also covers sockets bound somewhere less private
+        match stream.peer_cred() {
+          Ok(peer) if peer.uid() == current_uid() => {
+            let (reader, writer) = stream.into_split();
+            return Ok((Box::new(reader), Box::new(writer)));
+          }
+          Ok(peer) => eprintln!("Rejected connection from uid {}", peer.uid()),
+          Err(e) => eprintln!("Rejected connection without peer credentials: {e}"),
+        }
+      },
+      #[cfg(windows)]
+      InsightsListener::Tcp(listener) => {
+        let (reader, writer) = listener.accept().await?.0.into_split();
+        Ok((Box::new(reader), Box::new(writer)))
+      }
+    }
+  }
+}
+
+/// An embedding request waiting for the model, and the connection its
+/// response goes back to
+struct Job {
+  request: DaemonRequest,
+  reply: mpsc::UnboundedSender<DaemonResponse>,
+}
+
+/// What every connection handler shares with the server
+#[derive(Clone)]
+struct Shared {
+  jobs: mpsc::UnboundedSender<Job>,
+  state: Arc<ServiceState>,
+  shutdown: Arc<Notify>,
+  connections: Arc<AtomicUsize>,
+}
+
+/// Serve connections concurrently until a shutdown request, or until no
+/// client has been connected for the idle timeout. The model runs on its own
+/// thread; embedding requests that arrive while it is busy are coalesced into
+/// the next model call. A persistent query cache is saved on the way out.
+#[allow(dead_code)] // run by the daemon binary
+pub async fn serve<M: EmbeddingModel + Send + 'static>(
+  listener: InsightsListener,
+  service: EmbeddingService<M>,
+) -> Result<()> {
+  let idle_timeout_secs = service.state.idle_timeout_secs;
+  let (jobs, queue) = mpsc::unbounded_channel();
+  let shared = Shared {
+    jobs,
+    state: service.state.clone(),
+    shutdown: Arc::new(Notify::new()),
+    connections: Arc::new(AtomicUsize::new(0)),
+  };
+  std::thread::spawn(move || run_model(service, queue));
+
+  loop {
+    tokio::select! {
+      accepted = listener.accept() => {
+        let (reader, writer) = match accepted {
+          Ok(stream) => stream,
+          Err(e) => {
+            eprintln!("Error accepting connection: {e}");
+            break;
+          }
+        };
+        let shared = shared.clone();
+        tokio::spawn(async move {
+          if let Err(e) = handle_client(reader, writer, shared).await {
+            eprintln!("Error handling client: {e}");
+          }
+        });
+      }
+      _ = shared.shutdown.notified() => {
+        println!("🛑 Blizz daemon shutting down on request");
+        break;
+      }
+      _ = sleep(Duration::from_secs(idle_timeout_secs)), if idle_timeout_secs > 0 => {
+        if shared.connections.load(Ordering::SeqCst) == 0 {
+          println!("💤 Blizz daemon shutting down due to inactivity");
+          break;
+        }
+      }
+    }
+  }
+
+  let saved = shared.state.query_cache.lock().unwrap().save();
+  saved
+}
+
+/// Run queued embedding requests through the model, batching whatever has
+/// queued up while the previous call ran
+fn run_model<M: EmbeddingModel>(
+  mut service: EmbeddingService<M>,
+  mut queue: mpsc::UnboundedReceiver<Job>,
+) {
+  while let Some(job) = queue.blocking_recv() {
+    let mut texts = job.request.texts.len();
+    let mut batch = vec![job];
+    while texts < MAX_BATCH_TEXTS {
+      let Ok(job) = queue.try_recv() else { break };
+      texts += job.request.texts.len();
+      batch.push(job);
+    }
+
+    let (requests, replies): (Vec<_>, Vec<_>) =
+      batch.into_iter().map(|job| (job.request, job.reply)).unzip();
+    for (response, reply) in service.handle_batch(requests).into_iter().zip(replies) {
+      // The client may have disconnected while waiting
+      let _ = reply.send(response);
+    }
+  }
+}
+
+/// Counts a connection as active while it is alive
+struct ActiveConnection(Arc<AtomicUsize>);
+
+impl ActiveConnection {
+  fn new(connections: &Arc<AtomicUsize>) -> Self {
+    connections.fetch_add(1, Ordering::SeqCst);
+    Self(connections.clone())
+  }
+}
+
+impl Drop for ActiveConnection {
+  fn drop(&mut self) {
+    self.0.fetch_sub(1, Ordering::SeqCst);
+  }
+}
+
+/// Serve requests on a connection until the client closes it. Requests are
+/// read as they arrive, without waiting for earlier responses, and responses
+/// are written as they complete; clients match them up by `id`.
+async fn handle_client(reader: StreamReader, writer: StreamWriter, shared: Shared) -> Result<()> {
+  let _active = ActiveConnection::new(&shared.connections);
+  let (replies, responses) = mpsc::unbounded_channel();
+  let writing = tokio::spawn(write_responses(writer, responses));
+
+  let mut lines = BufReader::new(reader).lines();
+  while let Some(line) = lines.next_line().await? {
+    let request: DaemonRequest = match serde_json::from_str(line.trim()) {
+      Ok(request) => request,
+      Err(e) => {
+        let error = Some(format!("Invalid request: {e}"));
+        let _ = replies.send(DaemonResponse { error, ..Default::default() });
+        continue;
+      }
+    };
+
+    match request.kind {
+      RequestKind::Embed => {
+        // Cached queries don't wait behind requests queued for the model
+        if let Some(response) = shared.state.cached_response(&request) {
+          let _ = replies.send(response);
+          continue;
+        }
+        let job = Job { request, reply: replies.clone() };
+        if shared.jobs.send(job).is_err() {
+          return Err(anyhow!("Embedding model stopped"));
+        }
+      }
+      RequestKind::Ping | RequestKind::Status => {
+        let _ = replies.send(shared.state.status_response(request.id));
+      }
+      RequestKind::Shutdown => {
+        let _ = replies.send(shared.state.status_response(request.id));
+        drop(replies);
+        let _ = writing.await;
+        shared.shutdown.notify_one();
+        return Ok(());
+      }
+    }
+  }
+
+  // Finish writing responses to requests still with the model
+  drop(replies);
+  writing.await?
+}
+
+async fn write_responses(
+  mut writer: StreamWriter,
+  mut responses: mpsc::UnboundedReceiver<DaemonResponse>,
+) -> Result<()> {
+  while let Some(response) = responses.recv().await {
+    let json = serde_json::to_string(&response)?;
+    writer.write_all(json.as_bytes()).await?;
+    writer.write_all(b"\n").await?;
+  }
+  Ok(())
+}
+
+/// Result of `start`: the daemon's status, and whether it was already running
+#[derive(Debug, Clone)]
+pub struct DaemonStart {
+  pub status: DaemonStatus,
+  pub already_running: bool,
+}
+
+/// Open connection to the daemon, reused across requests
+pub struct DaemonConnection {
+  reader: BufReader<Box<dyn AsyncRead + Unpin + Send>>,
+  writer: Box<dyn AsyncWrite + Unpin + Send>,
+}
+
+impl DaemonConnection {
+  /// Connect to a daemon listening on the given socket
+  #[cfg(unix)]
+  pub async fn connect_at(socket: &Path) -> Result<Self> {
+    let stream = UnixStream::connect(socket).await.map_err(|_| anyhow!("Daemon not running"))?;
+    let (reader, writer) = stream.into_split();
+    Ok(Self { reader: BufReader::new(Box::new(reader)), writer: Box::new(writer) })
+  }
+
+  #[cfg(windows)]
+  async fn connect_tcp() -> Result<Self> {
+    let stream =
+      TcpStream::connect(TCP_ADDRESS).await.map_err(|_| anyhow!("Daemon not running"))?;
+    let (reader, writer) = stream.into_split();
+    Ok(Self { reader: BufReader::new(Box::new(reader)), writer: Box::new(writer) })
+  }
+
+  /// Send a request and wait for its response
+  pub async fn send(&mut self, request: &DaemonRequest) -> Result<DaemonResponse> {
+    let json = serde_json::to_string(request)?;
+    self.writer.write_all(json.as_bytes()).await?;
+    self.writer.write_all(b"\n").await?;
+
+    let mut line = String::new();
+    if self.reader.read_line(&mut line).await? == 0 {
+      return Err(anyhow!("Daemon closed the connection"));
+    }
+    serde_json::from_str(line.trim()).map_err(|e| anyhow!("Invalid response: {}", e))
+  }
+
+  /// Ask the daemon for its status
+  pub async fn status(&mut self, kind: RequestKind) -> Result<DaemonStatus> {
+    let response = self.send(&DaemonRequest::new(kind, vec![])).await?;
+    if let Some(error) = response.error {
+      return Err(anyhow!("Daemon error: {}", error));
+    }
+    response.status.ok_or_else(|| {
+      anyhow!("Daemon does not report its status; it was started by an older version")
+    })
+  }
+}
+
+/// Connect to this user's daemon. None if no daemon is listening; an error if
+/// the socket belongs to someone else.
+#[cfg(unix)]
+pub async fn try_connect() -> Result<Option<DaemonConnection>> {
+  try_connect_at(&socket_path()?).await
+}
+
+/// Connect to the daemon listening on `socket`, as `try_connect` does
+#[cfg(unix)]
+pub async fn try_connect_at(socket: &Path) -> Result<Option<DaemonConnection>> {
+  if let Ok(metadata) = fs::symlink_metadata(socket) {
+    if metadata.uid() != current_uid() {
+      return Err(anyhow!(
+        "{} is owned by another user; refusing to send it requests",
+        socket.display()
+      ));
+    }
+  }
+  Ok(DaemonConnection::connect_at(socket).await.ok())
+}
+
+/// Connect to the daemon. None if no daemon is listening.
+#[cfg(windows)]
+pub async fn try_connect() -> Result<Option<DaemonConnection>> {
+  Ok(DaemonConnection::connect_tcp().await.ok())
+}
+
+/// Connect to the daemon whose runtime directory is `dir`
+async fn try_connect_in(dir: &Path) -> Result<Option<DaemonConnection>> {
+  #[cfg(unix)]
+  {
+    try_connect_at(&dir.join(SOCKET_FILE)).await
+  }
+
+  #[cfg(windows)]
+  {
+    let _ = dir;
+    try_connect().await
+  }
+}
+
+/// Connect to the running daemon, starting one if none is running. A daemon
+/// running another model than the active one is restarted, so embeddings are
+/// labelled with the model they are checked against.
+pub async fn connect_or_start() -> Result<DaemonConnection> {
+  match try_connect().await? {
+    Some(mut connection) => {
+      if runs_active_model(&mut connection).await? {
+        return Ok(connection);
+      }
+      drop(connection);
+      restart(None).await?;
+    }
+    None => {
+      start(None).await?;
+    }
+  }
+  try_connect().await?.ok_or_else(|| anyhow!("Daemon not running"))
+}
+
+/// Whether the daemon on the other end runs the active model, as it is on
+/// disk now. Daemons that don't report a model are restarted, and so is any
+/// daemon when the model file can't be read, so starting it reports why.
+async fn runs_active_model(connection: &mut DaemonConnection) -> Result<bool> {
+  let active = model_registry::embedding_version(&model_registry::active_model()?).ok();
+  let model = match connection.status(RequestKind::Status).await {
+    Ok(status) => status.model,
+    Err(_) => None,
+  };
+  Ok(active.is_some() && model == active)
+}
+
+/// Status of the running daemon, or None if none is running
+pub async fn status() -> Result<Option<DaemonStatus>> {
+  status_at(&runtime_dir()?).await
+}
+
+/// Status of the daemon whose runtime directory is `dir`
+pub async fn status_at(dir: &Path) -> Result<Option<DaemonStatus>> {
+  match try_connect_in(dir).await? {
+    Some(mut connection) => Ok(Some(connection.status(RequestKind::Status).await?)),
+    None => {
+      remove_stale_pid_file(dir);
+      Ok(None)
+    }
+  }
+}
+
+/// Start the daemon and wait until it answers. `idle_timeout_secs` overrides
+/// the idle timeout; otherwise the daemon reads it from the environment.
+pub async fn start(idle_timeout_secs: Option<u64>) -> Result<DaemonStart> {
+  if let Some(status) = status().await? {
+    return Ok(DaemonStart { status, already_running: true });
+  }
+
+  // Report a missing model here rather than as an opaque startup failure
+  model_registry::verify(&model_registry::active_model()?)?;
+
+  let child = spawn(idle_timeout_secs)?;
+  let status = wait_until_ready(child).await?;
+  Ok(DaemonStart { status, already_running: false })
+}
+
+/// Ask the daemon to exit and wait until it has. Returns its status from just
+/// before it stopped, or None if it wasn't running.
+pub async fn stop() -> Result<Option<DaemonStatus>> {
+  stop_at(&runtime_dir()?).await
+}
+
+/// Stop the daemon whose runtime directory is `dir`
+pub async fn stop_at(dir: &Path) -> Result<Option<DaemonStatus>> {
+  let Some(mut connection) = try_connect_in(dir).await? else {
+    remove_stale_pid_file(dir);
+    return Ok(None);
+  };
+  let status = connection.status(RequestKind::Shutdown).await?;
+  drop(connection);
+
+  let deadline = Instant::now() + STOP_TIMEOUT;
+  while try_connect_in(dir).await?.is_some() {
+    if Instant::now() >= deadline {
+      return Err(anyhow!("Daemon (pid {}) did not stop within {:?}", status.pid, STOP_TIMEOUT));
+    }
+    sleep(POLL_INTERVAL).await;
+  }
+  Ok(Some(status))
+}
+
+/// Stop the daemon if it is running, then start a fresh one
+pub async fn restart(idle_timeout_secs: Option<u64>) -> Result<DaemonStart> {
+  stop().await?;
+  start(idle_timeout_secs).await
+}
+
+/// Directory holding this user's daemon socket, pid file and log:
+/// `$INSIGHTS_RUNTIME_DIR`, else `$XDG_RUNTIME_DIR/insights`, else
+/// `~/.kernelle/run`
+pub fn runtime_dir() -> Result<PathBuf> {
+  if let Ok(custom_dir) = env::var(RUNTIME_DIR_ENV) {
+    return Ok(PathBuf::from(custom_dir));
+  }
+  if let Some(runtime_dir) = dirs::runtime_dir() {
+    return Ok(runtime_dir.join("insights"));
+  }
+
+  let home = dirs::home_dir().ok_or_else(|| anyhow!("Could not find home directory"))?;
+  Ok(home.join(".kernelle").join("run"))
+}
+
+/// Socket the daemon listens on
+#[cfg(unix)]
+pub fn socket_path() -> Result<PathBuf> {
+  Ok(runtime_dir()?.join(SOCKET_FILE))
+}
+
+/// File the running daemon records its process id in
+#[allow(dead_code)] // Library entry point; the daemon goes through `lock_pid_file`
+pub fn pid_file() -> Result<PathBuf> {
+  Ok(runtime_dir()?.join(PID_FILE))
+}
+
+/// File the daemon's output goes to when it is started in the background
+pub fn log_file() -> Result<PathBuf> {
+  Ok(runtime_dir()?.join("daemon.log"))
+}
+
+fn prepare_runtime_dir() -> Result<PathBuf> {
+  let dir = runtime_dir()?;
+  prepare_runtime_dir_at(&dir)?;
+  Ok(dir)
+}
+
+/// Create the runtime directory, or check an existing one, so that only this
+/// user can reach the socket inside it. A directory other users can get into
+/// is refused rather than fixed, since they may already have planted files.
+#[cfg(unix)]
+fn prepare_runtime_dir_at(dir: &Path) -> Result<()> {
+  fs::DirBuilder::new().recursive(true).mode(0o700).create(dir)?;
+
+  let metadata = fs::metadata(dir)?;
+  if metadata.uid() != current_uid() {
+    return Err(anyhow!("{} is owned by another user; refusing to use it", dir.display()));
+  }
+  if metadata.permissions().mode() & 0o077 != 0 {
+    return Err(anyhow!(
+      "{} is accessible to other users; refusing to use it (run `chmod 700 {}`)",
+      dir.display(),
+      dir.display()
+    ));
+  }
+  Ok(())
+}
+
+#[cfg(windows)]
+fn prepare_runtime_dir_at(dir: &Path) -> Result<()> {
+  fs::create_dir_all(dir)?;
+  Ok(())
+}
+
+/// Bind the daemon's socket, readable and writable by this user only. A
+/// socket left by an earlier daemon is replaced, but only once nothing answers
+/// on it.
+#[cfg(unix)]
+#[allow(dead_code)] // used by the daemon binary
+pub async fn bind() -> Result<InsightsListener> {
+  bind_at(&runtime_dir()?).await
+}
+
+/// Bind a daemon socket in the runtime directory `dir`, as `bind` does
+#[cfg(unix)]
+pub async fn bind_at(dir: &Path) -> Result<InsightsListener> {
+  prepare_runtime_dir_at(dir)?;
+  let socket = dir.join(SOCKET_FILE);
+  if try_connect_at(&socket).await?.is_some() {
+    return Err(anyhow!("Another daemon is already listening on {}", socket.display()));
+  }
+  remove_socket(dir);
+
+  let listener = UnixListener::bind(&socket)?;
+  fs::set_permissions(&socket, fs::Permissions::from_mode(0o600))?;
+  println!("🚀 Blizz daemon listening on {}", socket.display());
+  Ok(InsightsListener::Unix(listener))
+}
+
+/// Bind the daemon's port. Loopback TCP has no peer credentials, so on
+/// Windows any local user can reach the daemon.
+#[cfg(windows)]
+#[allow(dead_code)] // used by the daemon binary
+pub async fn bind() -> Result<InsightsListener> {
+  prepare_runtime_dir()?;
+  let listener = TcpListener::bind(TCP_ADDRESS).await?;
+  println!("🚀 Blizz daemon listening on {TCP_ADDRESS}");
+  Ok(InsightsListener::Tcp(listener))
+}
+
+/// Remove the socket left by this or an earlier daemon
+fn remove_socket(dir: &Path) {
+  #[cfg(unix)]
+  let _ = fs::remove_file(dir.join(SOCKET_FILE));
+  #[cfg(windows)]
+  let _ = dir;
+}
+
+/// Process id recorded in the pid file, if there is one
+#[allow(dead_code)] // Library entry point; the CLI asks the daemon instead
+pub fn read_pid_file() -> Option<u32> {
+  read_pid(&runtime_dir().ok()?)
+}
+
+fn read_pid(dir: &Path) -> Option<u32> {
+  fs::read_to_string(dir.join(PID_FILE)).ok()?.trim().parse().ok()
+}
+
+/// Exclusive lock on the pid file, held by a daemon from before it loads its
+/// model until it exits. Of several daemons started at once only one gets it.
+///
+/// Dropping the lock removes the socket and pid file, as long as the pid file
+/// still names this process.
+pub struct PidLock {
+  dir: PathBuf,
+  _file: fs::File,
+}
+
+impl Drop for PidLock {
+  fn drop(&mut self) {
+    if read_pid(&self.dir) == Some(std::process::id()) {
+      remove_socket(&self.dir);
+      let _ = fs::remove_file(self.dir.join(PID_FILE));
+    }
+  }
+}
+
+/// Lock the pid file and record this process in it. None if another daemon
+/// holds the lock, i.e. is running or still loading its model.
+#[allow(dead_code)] // used by the daemon binary
+pub fn lock_pid_file() -> Result<Option<PidLock>> {
+  let dir = prepare_runtime_dir()?;
+  let mut options = fs::OpenOptions::new();
+  options.read(true).write(true).create(true).truncate(false);
+  #[cfg(unix)]
+  options.mode(0o600);
+  let mut file = options.open(dir.join(PID_FILE))?;
+  if !try_lock(&file)? {
+    return Ok(None);
+  }
+
+  file.set_len(0)?;
+  write!(file, "{}", std::process::id())?;
+  Ok(Some(PidLock { dir, _file: file }))
+}
+
+/// Remove a pid file left behind by a daemon that is no longer running. A
+/// daemon still loading its model doesn't answer yet but holds the lock.
+fn remove_stale_pid_file(dir: &Path) {
+  let pid_file = dir.join(PID_FILE);
+  if let Ok(file) = fs::File::open(&pid_file) {
+    if let Ok(true) = try_lock(&file) {
+      let _ = fs::remove_file(pid_file);
+    }
+  }
+}
+
+/// Take an exclusive lock on `file` without waiting; false if it is held
+#[cfg(unix)]
+fn try_lock(file: &fs::File) -> Result<bool> {
+  // SAFETY: the descriptor stays open for the duration of the call
+  if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } == 0 {
+    return Ok(true);
+  }
+  let error = std::io::Error::last_os_error();
+  match error.raw_os_error() {
+    Some(libc::EWOULDBLOCK) => Ok(false),
+    _ => Err(error.into()),
+  }
+}
+
+/// Not locked on Windows, where only one daemon can bind the TCP port anyway
+#[cfg(windows)]
+fn try_lock(_file: &fs::File) -> Result<bool> {
+  Ok(true)
+}
+
+#[cfg(unix)]
+fn current_uid() -> u32 {
+  // SAFETY: geteuid has no preconditions and cannot fail
+  unsafe { libc::geteuid() }
+}
+
+fn spawn(idle_timeout_secs: Option<u64>) -> Result<Child> {
+  let daemon_path = get_daemon_executable_path()?;
+  prepare_runtime_dir()?;
+  let log = fs::File::create(log_file()?)?;
+
+  let mut command = Command::new(daemon_path);
+  if let Some(secs) = idle_timeout_secs {
+    command.arg("--idle-timeout").arg(secs.to_string());
+  }
+  command
+    .stdout(log.try_clone()?)
+    .stderr(log)
+    .stdin(Stdio::null())
+    .spawn()
+    .map_err(|e| anyhow!("Failed to start daemon: {}", e))
+}
+
+/// Poll the new daemon until it answers a ping. The daemon only listens once
+/// its model is loaded, so an answer means it is ready to embed.
+///
+/// A child that exits cleanly found another daemon starting, so polling goes
+/// on for that one instead.
+async fn wait_until_ready(mut child: Child) -> Result<DaemonStatus> {
+  let deadline = Instant::now() + READY_TIMEOUT;
+  let log = log_file()?;
+  loop {
+    if let Some(mut connection) = try_connect().await? {
+      if let Ok(status) = connection.status(RequestKind::Ping).await {
+        return Ok(status);
+      }
+    }
+    if let Some(exit) = child.try_wait()? {
+      if !exit.success() {
+        return Err(anyhow!("Daemon exited during startup ({}); see {}", exit, log.display()));
+      }
+    }
+    if Instant::now() >= deadline {
+      return Err(anyhow!(
+        "Daemon did not become ready within {:?}; see {}",
+        READY_TIMEOUT,
+        log.display()
+      ));
+    }
+    sleep(POLL_INTERVAL).await;
+  }
+}
+
+#[cfg(windows)]
+fn get_daemon_executable_path() -> Result<PathBuf> {
+  let current_exe = std::env::current_exe()?;
+  let exe_dir =
+    current_exe.parent().ok_or_else(|| anyhow!("Could not find executable directory"))?;
+  Ok(exe_dir.join("insights_embedding_daemon.exe"))
+}
+
+#[cfg(unix)]
+fn get_daemon_executable_path() -> Result<PathBuf> {
+  let current_exe = std::env::current_exe()?;
+  let exe_dir =
+    current_exe.parent().ok_or_else(|| anyhow!("Could not find executable directory"))?;
+  Ok(exe_dir.join("insights_embedding_daemon"))
+}
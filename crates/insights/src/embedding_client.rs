@@ -1,49 +1,16 @@
 use anyhow::{anyhow, Result};
 use chrono::{DateTime, Utc};
 use colored::*;
-use serde::{Deserialize, Serialize};
-use std::process::Stdio;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::process::Command;
-use tokio::time::{sleep, Duration};
-
-// Platform-specific imports
-#[cfg(windows)]
-use tokio::net::TcpStream;
-#[cfg(unix)]
-use tokio::net::UnixStream;
-
-use crate::insight::{self, Insight};
-
-// Platform-specific constants
-#[cfg(all(feature = "neural", unix))]
-const SOCKET_PATH: &str = "/tmp/insights_embeddings.sock";
-#[cfg(all(feature = "neural", windows))]
-const TCP_ADDRESS: &str = "127.0.0.1:47291";
+use std::sync::{Mutex, OnceLock};
+use tokio::runtime::Runtime;
 
 #[cfg(feature = "neural")]
-const STARTUP_DELAY_MS: u64 = 500;
-
-// Cross-platform stream abstraction
+use crate::daemon::{self, DaemonConnection, DaemonRequest, DaemonResponse, RequestKind};
+use crate::insight::{self, Insight};
 #[cfg(feature = "neural")]
-enum InsightsStream {
-  #[cfg(unix)]
-  Unix(UnixStream),
-  #[cfg(windows)]
-  Tcp(TcpStream),
-}
-
-#[cfg(feature = "neural")]
-impl InsightsStream {
-  async fn write_all(&mut self, buf: &[u8]) -> Result<(), std::io::Error> {
-    match self {
-      #[cfg(unix)]
-      InsightsStream::Unix(stream) => stream.write_all(buf).await,
-      #[cfg(windows)]
-      InsightsStream::Tcp(stream) => stream.write_all(buf).await,
-    }
-  }
-}
+use crate::model_registry;
+use crate::similarity;
+use crate::stem;
 
 // Core data structures
 #[derive(Debug, Clone)]
@@ -53,24 +20,56 @@ pub struct Embedding {
   pub embedding: Vec<f32>,
 }
 
+/// Embeddings returned by the daemon, with the model that produced them
 #[cfg(feature = "neural")]
-#[derive(Serialize, Deserialize)]
-struct EmbeddingRequest {
-  texts: Vec<String>,
-  id: String,
-}
-
-#[cfg(feature = "neural")]
-#[derive(Serialize, Deserialize)]
-struct EmbeddingResponse {
-  embeddings: Vec<Vec<f32>>,
-  id: String,
-  error: Option<String>,
+struct DaemonEmbeddings {
+  vectors: Vec<Vec<f32>>,
+  model: Option<String>,
 }
 
 // Service trait for dependency injection
 pub trait EmbeddingService {
   fn embed_insight(&self, insight: &mut Insight) -> Embedding;
+
+  /// Embed several insights, in the same order. Services that cannot batch
+  /// embed them one at a time.
+  fn embed_insights(&self, insights: &[Insight]) -> Vec<Embedding> {
+    insights.iter().map(|insight| self.embed_insight(&mut insight.clone())).collect()
+  }
+
+  /// Embed a search query. Services may answer repeated queries from a cache.
+  fn embed_query(&self, query: &mut Insight) -> Embedding {
+    self.embed_insight(query)
+  }
+
+  /// Embed plain texts, in the same order, without an insight's topic and
+  /// name around them. Services that only embed insights embed each text as
+  /// an overview.
+  fn embed_texts(&self, texts: &[String]) -> Vec<Embedding> {
+    let insights: Vec<Insight> = texts
+      .iter()
+      .map(|text| Insight::new(String::new(), String::new(), text.clone(), String::new()))
+      .collect();
+    self.embed_insights(&insights)
+  }
+
+  /// Version recorded on the embeddings this service produces, if it is known
+  /// up front. Embeddings from any other version are treated as outdated.
+  fn model_version(&self) -> Option<String> {
+    None
+  }
+
+  /// Why embeddings can't be computed, if they can't. Services return
+  /// placeholder embeddings while unavailable.
+  fn unavailable(&self) -> Option<String> {
+    None
+  }
+
+  /// Why the last request failed, if it did. Unlike `unavailable`, this does
+  /// not stop the next request from being tried.
+  fn last_error(&self) -> Option<String> {
+    None
+  }
 }
 
 // Main EmbeddingClient struct (the "class")
@@ -81,7 +80,7 @@ pub struct EmbeddingClient {
 // Constructor functions
 /// Create a new embedding client with production service (default)
 pub fn create() -> EmbeddingClient {
-  EmbeddingClient { service: Box::new(ProductionEmbeddingService) }
+  EmbeddingClient { service: Box::new(ProductionEmbeddingService::default()) }
 }
 
 /// Create a new embedding client with injected service (for testing)
@@ -95,12 +94,92 @@ pub fn embed_insight(client: &EmbeddingClient, insight: &mut Insight) -> Embeddi
   client.service.embed_insight(insight)
 }
 
+pub fn model_version(client: &EmbeddingClient) -> Option<String> {
+  client.service.model_version()
+}
+
+/// Why neural embeddings are unavailable, e.g. because the model is not installed
+pub fn unavailable(client: &EmbeddingClient) -> Option<String> {
+  client.service.unavailable()
+}
+
+/// Why the last embedding request failed, if it did
+pub fn last_error(client: &EmbeddingClient) -> Option<String> {
+  client.service.last_error()
+}
+
+/// Embed a search query, reusing the embedding of an identical earlier query where possible
+pub fn embed_query(client: &EmbeddingClient, query: &mut Insight) -> Embedding {
+  client.service.embed_query(query)
+}
+
+/// Embed a batch of insights with a single request where the service supports it
+pub fn embed_insights(client: &EmbeddingClient, insights: &[Insight]) -> Vec<Embedding> {
+  client.service.embed_insights(insights)
+}
+
+/// Embed a batch of plain texts, such as passages of an insight
+pub fn embed_texts(client: &EmbeddingClient, texts: &[String]) -> Vec<Embedding> {
+  client.service.embed_texts(texts)
+}
+
 // Service implementations
-pub struct ProductionEmbeddingService;
+
+/// Talks to the embedding daemon. The runtime and the daemon connection are
+/// created on first use and kept for the lifetime of the service.
+#[derive(Default)]
+pub struct ProductionEmbeddingService {
+  runtime: OnceLock<Runtime>,
+  #[cfg(feature = "neural")]
+  connection: Mutex<Option<DaemonConnection>>,
+  /// Error from the last failed request to the daemon
+  last_error: Mutex<Option<String>>,
+}
 
 impl EmbeddingService for ProductionEmbeddingService {
   fn embed_insight(&self, insight: &mut Insight) -> Embedding {
-    blocking_embed(insight)
+    self.embed_insights(std::slice::from_ref(insight)).pop().unwrap_or_else(placeholder)
+  }
+
+  fn embed_insights(&self, insights: &[Insight]) -> Vec<Embedding> {
+    self.embed(insights.iter().map(insight::get_embedding_text).collect(), false)
+  }
+
+  fn embed_query(&self, query: &mut Insight) -> Embedding {
+    self.embed(vec![insight::get_embedding_text(query)], true).pop().unwrap_or_else(placeholder)
+  }
+
+  fn embed_texts(&self, texts: &[String]) -> Vec<Embedding> {
+    self.embed(texts.to_vec(), false)
+  }
+
+  fn model_version(&self) -> Option<String> {
+    model_registry::active_model().and_then(|model| model_registry::embedding_version(&model)).ok()
+  }
+
+  fn unavailable(&self) -> Option<String> {
+    let installed = model_registry::active_model().and_then(|model| {
+      model_registry::ensure_installed(&model)?;
+      Ok(model)
+    });
+    installed.err().map(|e| e.to_string())
+  }
+
+  fn last_error(&self) -> Option<String> {
+    self.last_error.lock().unwrap().clone()
+  }
+}
+
+impl ProductionEmbeddingService {
+  /// Embed texts through the daemon, through its query cache if `cache` is set
+  fn embed(&self, texts: Vec<String>, cache: bool) -> Vec<Embedding> {
+    // Don't start a daemon that can't load its model
+    if let Some(reason) = self.unavailable() {
+      eprintln!("  {} Warning: Neural embeddings unavailable: {}", "⚠".yellow(), reason);
+      return texts.iter().map(|_| placeholder()).collect();
+    }
+
+    blocking_embed(self, texts, cache)
   }
 }
 
@@ -115,153 +194,164 @@ impl EmbeddingService for MockEmbeddingService {
       embedding: vec![0.1; 384], // Mock 384-dimensional embedding
     }
   }
+
+  fn model_version(&self) -> Option<String> {
+    Some("test-mock".to_string())
+  }
+}
+
+/// Dimensions `HashingEmbeddingService` hashes words into
+pub const HASHING_DIMENSION: usize = 256;
+
+/// Deterministic bag-of-words embeddings: every stemmed word of the overview
+/// and details is hashed into one dimension. No model is needed, so
+/// evaluations and tests can rank by embedding similarity offline.
+pub struct HashingEmbeddingService;
+
+impl EmbeddingService for HashingEmbeddingService {
+  fn embed_insight(&self, insight: &mut Insight) -> Embedding {
+    let mut embedding = vec![0.0; HASHING_DIMENSION];
+    let text = format!("{} {}", insight.overview, insight.details);
+    for word in similarity::extract_words(&text) {
+      embedding[fnv1a(&stem::stem(&word)) as usize % HASHING_DIMENSION] += 1.0;
+    }
+
+    let magnitude = embedding.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if magnitude > 0.0 {
+      embedding.iter_mut().for_each(|x| *x /= magnitude);
+    }
+    Embedding { version: "hashing-v1".to_string(), created_at: Utc::now(), embedding }
+  }
+
+  fn model_version(&self) -> Option<String> {
+    Some("hashing-v1".to_string())
+  }
+}
+
+/// 64-bit FNV-1a, which unlike the standard hasher is stable across releases
+fn fnv1a(text: &str) -> u64 {
+  text
+    .bytes()
+    .fold(0xcbf29ce484222325, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3))
 }
 
 // Private implementation functions
-fn blocking_embed(insight: &mut Insight) -> Embedding {
+fn blocking_embed(
+  service: &ProductionEmbeddingService,
+  texts: Vec<String>,
+  cache: bool,
+) -> Vec<Embedding> {
   #[cfg(feature = "neural")]
   {
-    real_blocking_embed(insight)
+    real_blocking_embed(service, texts, cache)
   }
 
   #[cfg(not(feature = "neural"))]
   {
-    let _ = insight;
-    Embedding { version: "mock".to_string(), created_at: Utc::now(), embedding: vec![0.0; 384] }
+    let _ = (service, cache);
+    texts
+      .iter()
+      .map(|_| Embedding {
+        version: "mock".to_string(),
+        created_at: Utc::now(),
+        embedding: vec![0.0; 384],
+      })
+      .collect()
   }
 }
 
-fn real_blocking_embed(insight: &mut Insight) -> Embedding {
-  let rt = tokio::runtime::Runtime::new().unwrap();
-  match rt.block_on(async { compute_insight_embedding(insight).await }) {
-    Ok(embedding) => embedding,
+fn real_blocking_embed(
+  service: &ProductionEmbeddingService,
+  texts: Vec<String>,
+  cache: bool,
+) -> Vec<Embedding> {
+  let count = texts.len();
+  let rt = service.runtime.get_or_init(|| Runtime::new().unwrap());
+  match rt.block_on(compute_embeddings(service, texts, cache)) {
+    Ok(embeddings) => {
+      *service.last_error.lock().unwrap() = None;
+      embeddings
+    }
     Err(e) => {
+      *service.last_error.lock().unwrap() = Some(e.to_string());
       eprintln!("  {} Warning: Failed to compute embedding: {}", "⚠".yellow(), e);
       eprintln!("  {} Insight saved without embedding", "ℹ".blue());
 
-      // Return a placeholder embedding instead of panicking
-      Embedding { version: "placeholder".to_string(), created_at: Utc::now(), embedding: vec![] }
+      // Return placeholder embeddings instead of panicking
+      (0..count).map(|_| placeholder()).collect()
     }
   }
 }
 
-#[cfg(feature = "neural")]
-async fn compute_insight_embedding(insight: &Insight) -> Result<Embedding> {
-  let embedding_text = insight::get_embedding_text(insight);
-  let result = request(&embedding_text).await;
-  let version = "all-MiniLM-L6-v2".to_string();
-
-  match result {
-    Ok(embedding) => Ok(Embedding { version, created_at: Utc::now(), embedding }),
-    Err(e) => Err(e),
-  }
-}
-
-#[cfg(feature = "neural")]
-async fn request(text: &str) -> Result<Vec<f32>> {
-  if let Ok(embedding) = request_embedding_from_daemon(text).await {
-    return Ok(embedding);
-  }
-
-  request_embedding_with_retry(text).await
-}
-
-#[cfg(feature = "neural")]
-async fn request_embedding_from_daemon(text: &str) -> Result<Vec<f32>> {
-  let request = create_request(text);
-  let response = send(&request).await?;
-  parse_response(response)
+fn placeholder() -> Embedding {
+  Embedding { version: "placeholder".to_string(), created_at: Utc::now(), embedding: vec![] }
 }
 
 #[cfg(feature = "neural")]
-fn create_request(text: &str) -> EmbeddingRequest {
-  EmbeddingRequest { texts: vec![text.to_string()], id: uuid::Uuid::new_v4().to_string() }
+async fn compute_embeddings(
+  service: &ProductionEmbeddingService,
+  texts: Vec<String>,
+  cache: bool,
+) -> Result<Vec<Embedding>> {
+  let request = match cache {
+    true => DaemonRequest::query(texts),
+    false => DaemonRequest::new(RequestKind::Embed, texts),
+  };
+  let response = send_request(service, request).await?;
+  let created_at = Utc::now();
+
+  // Older daemons don't report their model; they only ever ran the built-in one
+  let version = response.model.unwrap_or_else(|| model_registry::BUILTIN_MODEL.to_string());
+  Ok(
+    response
+      .vectors
+      .into_iter()
+      .map(|embedding| Embedding { version: version.clone(), created_at, embedding })
+      .collect(),
+  )
 }
 
+/// Send a request over the cached connection, falling back to a fresh
+/// connection (and starting the daemon) if it was closed
 #[cfg(feature = "neural")]
-async fn send(request: &EmbeddingRequest) -> Result<EmbeddingResponse> {
-  let mut stream = connect_to_daemon().await?;
-  stream_to(&mut stream, request).await?;
-  stream_from(&mut stream).await
-}
-
-#[cfg(all(feature = "neural", unix))]
-async fn connect_to_daemon() -> Result<InsightsStream> {
-  let stream = UnixStream::connect(SOCKET_PATH).await.map_err(|_| anyhow!("Daemon not running"))?;
-  Ok(InsightsStream::Unix(stream))
-}
+async fn send_request(
+  service: &ProductionEmbeddingService,
+  request: DaemonRequest,
+) -> Result<DaemonEmbeddings> {
+  let cached = service.connection.lock().unwrap().take();
+  if let Some(mut connection) = cached {
+    if let Ok(embeddings) = request_embeddings(&mut connection, &request).await {
+      *service.connection.lock().unwrap() = Some(connection);
+      return Ok(embeddings);
+    }
+  }
 
-#[cfg(all(feature = "neural", windows))]
-async fn connect_to_daemon() -> Result<InsightsStream> {
-  let stream = TcpStream::connect(TCP_ADDRESS).await.map_err(|_| anyhow!("Daemon not running"))?;
-  Ok(InsightsStream::Tcp(stream))
+  let mut connection = daemon::connect_or_start().await?;
+  let embeddings = request_embeddings(&mut connection, &request).await?;
+  *service.connection.lock().unwrap() = Some(connection);
+  Ok(embeddings)
 }
 
 #[cfg(feature = "neural")]
-async fn stream_to(stream: &mut InsightsStream, request: &EmbeddingRequest) -> Result<()> {
-  let json = serde_json::to_string(request)?;
-  stream.write_all(json.as_bytes()).await?;
-  stream.write_all(b"\n").await?;
-  Ok(())
-}
-
-#[cfg(feature = "neural")]
-async fn stream_from(stream: &mut InsightsStream) -> Result<EmbeddingResponse> {
-  let reader = match stream {
-    #[cfg(unix)]
-    InsightsStream::Unix(stream) => BufReader::new(stream),
-    #[cfg(windows)]
-    InsightsStream::Tcp(stream) => BufReader::new(stream),
-  };
-
-  let mut line = String::new();
-  let mut reader = reader;
-  reader.read_line(&mut line).await?;
-
-  serde_json::from_str(line.trim()).map_err(|e| anyhow!("Invalid response: {}", e))
+async fn request_embeddings(
+  connection: &mut DaemonConnection,
+  request: &DaemonRequest,
+) -> Result<DaemonEmbeddings> {
+  let response = connection.send(request).await?;
+  parse_response(response, request.texts.len())
 }
 
 #[cfg(feature = "neural")]
-fn parse_response(response: EmbeddingResponse) -> Result<Vec<f32>> {
+fn parse_response(response: DaemonResponse, expected: usize) -> Result<DaemonEmbeddings> {
   if let Some(error) = response.error {
     return Err(anyhow!("Daemon error: {}", error));
   }
-  Ok(response.embeddings.into_iter().next().unwrap_or_default())
-}
-
-#[cfg(feature = "neural")]
-async fn request_embedding_with_retry(text: &str) -> Result<Vec<f32>> {
-  start_daemon().await?;
-  sleep(Duration::from_millis(STARTUP_DELAY_MS)).await;
-  request_embedding_from_daemon(text).await
-}
-
-#[cfg(feature = "neural")]
-async fn start_daemon() -> Result<()> {
-  let daemon_path = get_daemon_executable_path()?;
-
-  Command::new(daemon_path)
-    .stdout(Stdio::null())
-    .stderr(Stdio::null())
-    .stdin(Stdio::null())
-    .spawn()
-    .map_err(|e| anyhow!("Failed to start daemon: {}", e))?;
-
-  Ok(())
-}
-
-#[cfg(all(feature = "neural", windows))]
-fn get_daemon_executable_path() -> Result<std::path::PathBuf> {
-  let current_exe = std::env::current_exe()?;
-  let exe_dir =
-    current_exe.parent().ok_or_else(|| anyhow!("Could not find executable directory"))?;
-  Ok(exe_dir.join("insights_embedding_daemon.exe"))
-}
-
-#[cfg(all(feature = "neural", unix))]
-fn get_daemon_executable_path() -> Result<std::path::PathBuf> {
-  let current_exe = std::env::current_exe()?;
-  let exe_dir =
-    current_exe.parent().ok_or_else(|| anyhow!("Could not find executable directory"))?;
-  Ok(exe_dir.join("insights_embedding_daemon"))
+  if response.embeddings.len() != expected {
+    return Err(anyhow!(
+      "Daemon returned {} embeddings for {} texts",
+      response.embeddings.len(),
+      expected
+    ));
+  }
+  Ok(DaemonEmbeddings { vectors: response.embeddings, model: response.model })
 }
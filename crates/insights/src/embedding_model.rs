@@ -1,19 +1,41 @@
 use anyhow::{anyhow, Result};
-use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 #[cfg(feature = "neural")]
 use ort::session::{builder::GraphOptimizationLevel, Session};
-use ort::{session::SessionOutputs, value::Tensor};
+use ort::{
+  session::{SessionInputValue, SessionOutputs},
+  value::Tensor,
+};
+
+use crate::model_registry::{self, ModelSpec, Pooling};
+
+/// Most windows a long text is split into; anything beyond is not embedded
+const MAX_CHUNKS_PER_TEXT: usize = 16;
+
+/// Most windows run through the model at once, bounding the size of the
+/// input tensors however many texts a client sends
+const MAX_SESSION_BATCH: usize = 32;
 
 pub trait EmbeddingModel {
   #[allow(dead_code)]
   fn compute_embeddings(&mut self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
+
+  /// Version reported back to clients and recorded on their embeddings,
+  /// see `model_registry::embedding_version`
+  #[allow(dead_code)]
+  fn version(&self) -> Option<&str> {
+    None
+  }
 }
 
 #[cfg(feature = "neural")]
 pub struct OnnxEmbeddingModel {
   session: Session,
   tokenizer: tokenizers::Tokenizer,
+  spec: ModelSpec,
+  version: String,
 }
 
 #[cfg(feature = "neural")]
@@ -21,12 +43,23 @@ impl EmbeddingModel for OnnxEmbeddingModel {
   fn compute_embeddings(&mut self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
     compute_onnx_embeddings(self, texts)
   }
+
+  fn version(&self) -> Option<&str> {
+    Some(&self.version)
+  }
 }
 
 #[allow(dead_code)]
 pub struct MockEmbeddingModel {
   pub fail_on_texts: Vec<String>,
   pub response_embeddings: Vec<Vec<f32>>,
+  /// Number of texts in each call, shared so tests can inspect it after
+  /// handing the model to a daemon
+  pub batch_sizes: Arc<Mutex<Vec<usize>>>,
+  /// Time each call takes, standing in for inference
+  pub latency: Duration,
+  /// Version to report, standing in for a loaded model's
+  pub version: Option<String>,
 }
 
 impl MockEmbeddingModel {
@@ -35,6 +68,9 @@ impl MockEmbeddingModel {
     Self {
       fail_on_texts: vec![],
       response_embeddings: vec![vec![0.1, 0.2, 0.3]; 10], // Default mock embeddings
+      batch_sizes: Arc::default(),
+      latency: Duration::ZERO,
+      version: None,
     }
   }
 }
@@ -47,6 +83,9 @@ impl Default for MockEmbeddingModel {
 
 impl EmbeddingModel for MockEmbeddingModel {
   fn compute_embeddings(&mut self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+    self.batch_sizes.lock().unwrap().push(texts.len());
+    std::thread::sleep(self.latency);
+
     // Check if we should fail for any of these texts
     for text in texts {
       if self.fail_on_texts.contains(text) {
@@ -63,16 +102,29 @@ impl EmbeddingModel for MockEmbeddingModel {
 
     Ok(result)
   }
+
+  fn version(&self) -> Option<&str> {
+    self.version.as_deref()
+  }
 }
 
+/// Load the active model from the model registry
 #[cfg(feature = "neural")]
 #[allow(dead_code)]
 pub async fn create_production_model() -> Result<OnnxEmbeddingModel> {
+  let spec = model_registry::active_model()?;
+  create_model(spec)
+}
+
+#[cfg(feature = "neural")]
+#[allow(dead_code)] // Used by daemon binary
+pub fn create_model(spec: ModelSpec) -> Result<OnnxEmbeddingModel> {
   initialize_onnx_runtime()?;
-  let session = create_model_session()?;
-  let tokenizer = load_tokenizer()?;
+  let session = create_model_session(&spec)?;
+  let tokenizer = load_tokenizer(&spec)?;
+  let version = model_registry::embedding_version(&spec)?;
 
-  Ok(OnnxEmbeddingModel { session, tokenizer })
+  Ok(OnnxEmbeddingModel { session, tokenizer, spec, version })
 }
 
 #[cfg(feature = "neural")]
@@ -87,33 +139,40 @@ fn initialize_onnx_runtime() -> Result<()> {
 
 #[cfg(feature = "neural")]
 #[allow(dead_code)] // Used by daemon binary
-fn create_model_session() -> Result<Session> {
-  // Try to load from URL if local file doesn't exist
-  let local_model_path = Path::new("all-MiniLM-L6-v2.onnx");
-
-  let session = if local_model_path.exists() {
-    Session::builder()?
-      .with_optimization_level(GraphOptimizationLevel::Level1)?
-      .commit_from_file(local_model_path)
-      .map_err(|e| anyhow!("Failed to load local ONNX model: {}", e))?
-  } else {
-    // Load from remote URL
-    Session::builder()?
-      .with_optimization_level(GraphOptimizationLevel::Level1)?
-      .commit_from_url("https://huggingface.co/sentence-transformers/all-MiniLM-L6-v2/resolve/main/onnx/model.onnx")
-      .map_err(|e| anyhow!("Failed to load ONNX model from URL: {}", e))?
-  };
+fn create_model_session(spec: &ModelSpec) -> Result<Session> {
+  // Models are only ever loaded from disk, so the daemon works offline
+  model_registry::verify(spec)?;
+
+  let session = Session::builder()?
+    .with_optimization_level(GraphOptimizationLevel::Level1)?
+    .commit_from_file(&spec.model)
+    .map_err(|e| anyhow!("Failed to load ONNX model {}: {}", spec.model.display(), e))?;
 
   Ok(session)
 }
 
 #[cfg(feature = "neural")]
 #[allow(dead_code)] // Used by daemon binary
-fn load_tokenizer() -> Result<tokenizers::Tokenizer> {
-  // Load tokenizer from embedded data - no files, no downloads, no dependencies!
-  let tokenizer_bytes = include_bytes!("../data/tokenizer.json");
-  tokenizers::Tokenizer::from_bytes(tokenizer_bytes)
-    .map_err(|e| anyhow!("Failed to load embedded tokenizer: {}", e))
+fn load_tokenizer(spec: &ModelSpec) -> Result<tokenizers::Tokenizer> {
+  let mut tokenizer = match &spec.tokenizer {
+    Some(path) => tokenizers::Tokenizer::from_file(path)
+      .map_err(|e| anyhow!("Failed to load tokenizer {}: {}", path.display(), e))?,
+    // The built-in model's tokenizer is embedded - no files, no downloads, no dependencies!
+    None => tokenizers::Tokenizer::from_bytes(include_bytes!("../data/tokenizer.json"))
+      .map_err(|e| anyhow!("Failed to load embedded tokenizer: {}", e))?,
+  };
+
+  // Longer inputs are split into overlapping windows the model can take;
+  // batches are padded to their longest window when they are run
+  tokenizer
+    .with_truncation(Some(tokenizers::TruncationParams {
+      max_length: spec.max_tokens,
+      stride: spec.max_tokens / 8,
+      ..Default::default()
+    }))
+    .map_err(|e| anyhow!("Invalid max_tokens for '{}': {}", spec.name, e))?;
+  tokenizer.with_padding(None);
+  Ok(tokenizer)
 }
 
 #[cfg(feature = "neural")]
@@ -126,30 +185,59 @@ pub fn compute_onnx_embeddings(
     return Ok(vec![]);
   }
 
-  validate_inputs(texts)?;
-
   let encodings = tokenize_texts(&mut model.tokenizer, texts)?;
-  let (ids, mask, token_type_ids, batch, length) = batch_tokens(&encodings);
+  let (chunks, owners) = split_chunks(encodings);
 
-  // Create input tensors
-  let ids = Tensor::from_array(([batch, length], ids.into_boxed_slice()))?;
-  let mask = Tensor::from_array(([batch, length], mask.into_boxed_slice()))?;
-  let token_type_ids = Tensor::from_array(([batch, length], token_type_ids.into_boxed_slice()))?;
+  let mut pooled = Vec::with_capacity(chunks.len());
+  for batch in chunks.chunks(MAX_SESSION_BATCH) {
+    pooled.extend(run_session(model, batch)?);
+  }
 
-  // Run inference with all required inputs
-  let outputs = model.session.run(ort::inputs![
-    "input_ids" => ids,
-    "attention_mask" => mask,
-    "token_type_ids" => token_type_ids
-  ])?;
+  let weights = chunks.iter().map(|chunk| chunk.get_attention_mask().iter().sum::<u32>() as f32);
+  Ok(combine_chunks(pooled, &owners, weights, texts.len()))
+}
+
+/// Run one batch of windows through the model and pool each to a vector
+#[cfg(feature = "neural")]
+#[allow(dead_code)] // Used by daemon binary
+fn run_session(
+  model: &mut OnnxEmbeddingModel,
+  chunks: &[tokenizers::Encoding],
+) -> Result<Vec<Vec<f32>>> {
+  let (ids, mask, token_type_ids, batch, length) = batch_tokens(chunks);
+
+  // Only feed the inputs the model declares; not every model takes token type ids
+  let input_names: Vec<String> =
+    model.session.inputs.iter().map(|input| input.name.clone()).collect();
+  let mut inputs: Vec<(String, SessionInputValue)> = Vec::new();
+  for name in input_names {
+    let values = match name.as_str() {
+      "input_ids" => ids.clone(),
+      "attention_mask" => mask.clone(),
+      "token_type_ids" => token_type_ids.clone(),
+      other => return Err(anyhow!("Unsupported model input '{}'", other)),
+    };
+    let tensor = Tensor::from_array(([batch, length], values.into_boxed_slice()))?;
+    inputs.push((name, tensor.into()));
+  }
+
+  let outputs = model.session.run(inputs)?;
 
   // Extract embeddings from the output
   let output = get_session_outputs(&outputs)?;
+  let (shape, data) = output.try_extract_tensor::<f32>()?;
+  let pooled = pool(data, shape, &mask, model.spec.pooling)?;
+
+  if let Some(vector) = pooled.iter().find(|vector| vector.len() != model.spec.dimension) {
+    return Err(anyhow!(
+      "Model '{}' produced {}-dimensional embeddings, expected {}",
+      model.spec.name,
+      vector.len(),
+      model.spec.dimension
+    ));
+  }
 
-  let (_shape, data) = output.try_extract_tensor::<f32>()?;
-
-  let results = extract_embeddings(data, texts.len());
-  Ok(results)
+  Ok(pooled)
 }
 
 fn get_session_outputs<'a>(outputs: &'a SessionOutputs<'_>) -> Result<&'a ort::value::Value> {
@@ -177,6 +265,33 @@ fn tokenize_texts(
   tokenizer.encode_batch(text_refs, true).map_err(|e| anyhow!("Failed to encode texts: {}", e))
 }
 
+/// Flatten truncated encodings and their overflowing windows into the chunks
+/// to run through the model, along with the index of the text each came from
+#[cfg(feature = "neural")]
+#[allow(dead_code)] // Used by daemon binary
+fn split_chunks(encodings: Vec<tokenizers::Encoding>) -> (Vec<tokenizers::Encoding>, Vec<usize>) {
+  let mut chunks = Vec::new();
+  let mut owners = Vec::new();
+
+  for (owner, mut encoding) in encodings.into_iter().enumerate() {
+    let overflowing = encoding.take_overflowing();
+    if overflowing.len() >= MAX_CHUNKS_PER_TEXT {
+      eprintln!(
+        "Warning: text {} spans {} windows; only the first {} are embedded",
+        owner + 1,
+        overflowing.len() + 1,
+        MAX_CHUNKS_PER_TEXT
+      );
+    }
+    for chunk in std::iter::once(encoding).chain(overflowing).take(MAX_CHUNKS_PER_TEXT) {
+      chunks.push(chunk);
+      owners.push(owner);
+    }
+  }
+
+  (chunks, owners)
+}
+
 #[cfg(feature = "neural")]
 #[allow(dead_code)] // Used by daemon binary
 fn batch_tokens(
@@ -210,33 +325,81 @@ fn batch_tokens(
   (ids, mask, token_type_ids, batch, length)
 }
 
-#[cfg(feature = "neural")]
-#[allow(dead_code)] // Used by daemon binary
-fn extract_embeddings(data: &[f32], batch_size: usize) -> Vec<Vec<f32>> {
-  let mut results = Vec::new();
-
-  // For now, assume the data is already in [batch, features] format (384-dim embeddings)
-  // This works for most sentence transformer models that output pooled embeddings
-  let features = data.len() / batch_size.max(1);
-
-  for i in 0..batch_size {
-    let start = i * features;
-    let end = start + features;
-    if end <= data.len() {
-      let vector: Vec<f32> = data[start..end].to_vec();
-      let normalized = normalize_vector(vector);
-      results.push(normalized);
-    } else {
-      // Fallback: create a zero vector if dimensions don't match
-      results.push(vec![0.0; 384]);
+/// Reduce the model output to one vector per sequence.
+///
+/// Sentence-transformer exports return token embeddings shaped
+/// `[batch, tokens, hidden]`; padding is excluded using the attention mask.
+/// Outputs that are already pooled (`[batch, hidden]`) are used as they are.
+fn pool(data: &[f32], shape: &[i64], mask: &[i64], pooling: Pooling) -> Result<Vec<Vec<f32>>> {
+  match *shape {
+    [batch, hidden] if data.len() == (batch * hidden) as usize => {
+      Ok(data.chunks(hidden.max(1) as usize).map(<[f32]>::to_vec).collect())
+    }
+    [batch, tokens, hidden] => {
+      let (batch, tokens, hidden) = (batch as usize, tokens as usize, hidden as usize);
+      if mask.len() != batch * tokens || data.len() != batch * tokens * hidden {
+        return Err(anyhow!(
+          "Model output {:?} does not match the {} input tokens",
+          shape,
+          mask.len()
+        ));
+      }
+
+      let pooled = (0..batch)
+        .map(|sequence| {
+          let token_embedding = |token: usize| {
+            let start = (sequence * tokens + token) * hidden;
+            &data[start..start + hidden]
+          };
+
+          match pooling {
+            Pooling::Cls => token_embedding(0).to_vec(),
+            Pooling::Mean => {
+              let mut sum = vec![0.0; hidden];
+              let mut count = 0.0;
+              for token in 0..tokens {
+                if mask[sequence * tokens + token] == 0 {
+                  continue;
+                }
+                for (total, value) in sum.iter_mut().zip(token_embedding(token)) {
+                  *total += value;
+                }
+                count += 1.0;
+              }
+              sum.into_iter().map(|total| total / f32::max(count, 1.0)).collect()
+            }
+          }
+        })
+        .collect();
+      Ok(pooled)
     }
+    _ => Err(anyhow!("Unexpected model output shape {:?}", shape)),
   }
+}
 
-  results
+/// Average the chunk embeddings of each text, weighted by their token counts
+fn combine_chunks(
+  pooled: Vec<Vec<f32>>,
+  owners: &[usize],
+  weights: impl IntoIterator<Item = f32>,
+  texts: usize,
+) -> Vec<Vec<f32>> {
+  let mut combined: Vec<Vec<f32>> = vec![Vec::new(); texts];
+
+  for ((vector, &owner), weight) in pooled.into_iter().zip(owners).zip(weights) {
+    let vector = normalize_vector(vector);
+    let total = &mut combined[owner];
+    if total.is_empty() {
+      *total = vec![0.0; vector.len()];
+    }
+    for (sum, value) in total.iter_mut().zip(vector) {
+      *sum += value * weight;
+    }
+  }
+
+  combined.into_iter().map(normalize_vector).collect()
 }
 
-#[cfg(feature = "neural")]
-#[allow(dead_code)] // Used by daemon binary
 fn normalize_vector(vector: Vec<f32>) -> Vec<f32> {
   let magnitude: f32 = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
   if magnitude > 0.0 {
@@ -246,16 +409,45 @@ fn normalize_vector(vector: Vec<f32>) -> Vec<f32> {
   }
 }
 
-fn validate_inputs(texts: &[String]) -> Result<()> {
-  if texts.is_empty() {
-    return Err(anyhow!("Input texts cannot be empty"));
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Two sequences of three tokens with two-dimensional embeddings; the
+  /// second sequence has one padding token
+  const HIDDEN: [f32; 12] = [1.0, 0.0, 0.0, 1.0, 1.0, 1.0, 2.0, 0.0, 0.0, 2.0, 9.0, 9.0];
+  const MASK: [i64; 6] = [1, 1, 1, 1, 1, 0];
+
+  #[test]
+  fn test_mean_pooling_ignores_padding() {
+    let pooled = pool(&HIDDEN, &[2, 3, 2], &MASK, Pooling::Mean).unwrap();
+    assert_eq!(pooled, vec![vec![2.0 / 3.0, 2.0 / 3.0], vec![1.0, 1.0]]);
   }
 
-  for text in texts {
-    if text.len() > 8192 {
-      return Err(anyhow!("Text too long: {} characters (max 8192)", text.len()));
-    }
+  #[test]
+  fn test_cls_pooling_uses_first_token() {
+    let pooled = pool(&HIDDEN, &[2, 3, 2], &MASK, Pooling::Cls).unwrap();
+    assert_eq!(pooled, vec![vec![1.0, 0.0], vec![2.0, 0.0]]);
+  }
+
+  #[test]
+  fn test_pooled_outputs_are_used_as_is() {
+    let pooled = pool(&[1.0, 2.0, 3.0, 4.0], &[2, 2], &[1, 1], Pooling::Mean).unwrap();
+    assert_eq!(pooled, vec![vec![1.0, 2.0], vec![3.0, 4.0]]);
   }
 
-  Ok(())
+  #[test]
+  fn test_pooling_rejects_mismatched_shapes() {
+    assert!(pool(&HIDDEN, &[2, 2, 2], &MASK, Pooling::Mean).is_err());
+    assert!(pool(&HIDDEN, &[12], &MASK, Pooling::Mean).is_err());
+  }
+
+  #[test]
+  fn test_chunks_are_averaged_per_text() {
+    let pooled = vec![vec![2.0, 0.0], vec![0.0, 3.0], vec![0.0, 0.0, 5.0]];
+    let combined = combine_chunks(pooled, &[0, 0, 1], [3.0, 1.0, 2.0], 2);
+
+    let expected = normalize_vector(vec![3.0, 1.0]);
+    assert_eq!(combined, vec![expected, vec![0.0, 0.0, 1.0]]);
+  }
 }
@@ -0,0 +1,341 @@
+//! Evaluation of search quality against relevance fixtures.
+//!
+//! A fixture file lists insights and queries, each with the insights that are
+//! relevant to it:
+//!
+//! ```yaml
+//! insights:
+//!   - topic: perf
+//!     name: invalidation
+//!     overview: Cache rules
+//!     details: Call clearCache on every deploy
+//!     tags: [cache]
+//! queries:
+//!   - query: caching
+//!     relevant: [perf/invalidation]
+//! ```
+//!
+//! The insights are written to a scratch store, every search mode runs every
+//! query against it, and the top `k` results are scored with precision@k,
+//! recall@k, mean reciprocal rank and nDCG@k. Relevance is binary.
+
+use anyhow::{anyhow, Context, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "neural")]
+use crate::embedding_client::{self, EmbeddingClient, HashingEmbeddingService};
+use crate::insight::{self, Insight};
+use crate::scope;
+use crate::search::{self, SearchOptions};
+
+/// Results per query that are scored when no cutoff is given
+pub const DEFAULT_K: usize = 5;
+
+/// Insights to search and queries with their relevant insights
+#[derive(Debug, Clone, Deserialize)]
+pub struct Fixture {
+  pub insights: Vec<FixtureInsight>,
+  pub queries: Vec<FixtureQuery>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FixtureInsight {
+  pub topic: String,
+  pub name: String,
+  pub overview: String,
+  #[serde(default)]
+  pub details: String,
+  #[serde(default)]
+  pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FixtureQuery {
+  pub query: String,
+  /// Relevant insights as `topic/name`
+  pub relevant: Vec<String>,
+}
+
+/// A way of running searches, matching the search command's flags
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+  /// Lexical matching only, as with `--exact`
+  Exact,
+  /// Semantic and lexical ranking, as with `--semantic`
+  #[cfg(feature = "semantic")]
+  Semantic,
+  /// Every strategy fused, as searches run by default
+  #[cfg(feature = "neural")]
+  Hybrid,
+}
+
+impl Mode {
+  /// Every mode this build supports
+  pub fn all() -> Vec<Mode> {
+    vec![
+      Mode::Exact,
+      #[cfg(feature = "semantic")]
+      Mode::Semantic,
+      #[cfg(feature = "neural")]
+      Mode::Hybrid,
+    ]
+  }
+}
+
+impl std::fmt::Display for Mode {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Mode::Exact => write!(f, "exact"),
+      #[cfg(feature = "semantic")]
+      Mode::Semantic => write!(f, "semantic"),
+      #[cfg(feature = "neural")]
+      Mode::Hybrid => write!(f, "hybrid"),
+    }
+  }
+}
+
+pub struct EvalOptions {
+  /// Number of top results scored per query
+  pub k: usize,
+  /// Modes to evaluate; empty evaluates every mode
+  pub modes: Vec<Mode>,
+  /// Rank with deterministic hashed word embeddings instead of the model
+  #[cfg(feature = "neural")]
+  pub hashing_embeddings: bool,
+}
+
+/// Retrieval quality at a cutoff. For a single query `mrr` is its
+/// reciprocal rank; for a mode every metric is averaged over its queries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Metrics {
+  pub precision: f32,
+  pub recall: f32,
+  pub mrr: f32,
+  pub ndcg: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct QueryReport {
+  pub query: String,
+  /// Top results as `topic/name`, best first
+  pub found: Vec<String>,
+  pub metrics: Metrics,
+}
+
+#[derive(Debug, Clone)]
+pub struct ModeReport {
+  pub mode: Mode,
+  pub metrics: Metrics,
+  pub queries: Vec<QueryReport>,
+  /// Why neural ranking was left out, if it had to be
+  pub fallback: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct EvalReport {
+  pub k: usize,
+  pub modes: Vec<ModeReport>,
+}
+
+/// Read a fixture file
+pub fn load(path: &Path) -> Result<Fixture> {
+  let contents = fs::read_to_string(path)
+    .with_context(|| format!("Could not read fixtures from {}", path.display()))?;
+  serde_yaml::from_str(&contents).with_context(|| format!("Invalid fixtures in {}", path.display()))
+}
+
+/// Write the fixture's insights to a scratch store and evaluate it. The
+/// scratch store is removed afterwards; no other store is touched.
+pub fn run(fixture: &Fixture, options: &EvalOptions) -> Result<EvalReport> {
+  validate(fixture, options)?;
+  let store = ScratchStore::create()?;
+  scope::with_root(&store.dir, || {
+    for entry in &fixture.insights {
+      let mut insight = Insight::new(
+        entry.topic.clone(),
+        entry.name.clone(),
+        entry.overview.clone(),
+        entry.details.clone(),
+      );
+      insight.tags = entry.tags.clone();
+      insight::save(&insight)?;
+    }
+
+    evaluate(fixture, options)
+  })
+}
+
+/// Run the fixture's queries against the scratch store and score the results
+fn evaluate(fixture: &Fixture, options: &EvalOptions) -> Result<EvalReport> {
+  let modes = if options.modes.is_empty() { Mode::all() } else { options.modes.clone() };
+
+  let mut reports = Vec::new();
+  for mode in modes {
+    let search_options = search_options(mode, options);
+    let mut queries = Vec::new();
+    let mut fallback = None;
+
+    for query in &fixture.queries {
+      let outcome =
+        search::search_with_outcome(std::slice::from_ref(&query.query), &search_options)
+          .with_context(|| format!("Query '{}' failed", query.query))?;
+      fallback = fallback.or(outcome.neural_unavailable);
+
+      let found: Vec<String> =
+        outcome.results.iter().map(|result| format!("{}/{}", result.topic, result.name)).collect();
+      let relevant: HashSet<&str> = query.relevant.iter().map(String::as_str).collect();
+      queries.push(QueryReport {
+        query: query.query.clone(),
+        metrics: score(&found, &relevant, options.k),
+        found,
+      });
+    }
+
+    reports.push(ModeReport { mode, metrics: mean(&queries), queries, fallback });
+  }
+
+  Ok(EvalReport { k: options.k, modes: reports })
+}
+
+fn validate(fixture: &Fixture, options: &EvalOptions) -> Result<()> {
+  if options.k == 0 {
+    return Err(anyhow!("k must be at least 1"));
+  }
+  if fixture.insights.is_empty() {
+    return Err(anyhow!("Fixtures have no insights"));
+  }
+  if fixture.queries.is_empty() {
+    return Err(anyhow!("Fixtures have no queries"));
+  }
+
+  let known: HashSet<String> =
+    fixture.insights.iter().map(|insight| format!("{}/{}", insight.topic, insight.name)).collect();
+  for query in &fixture.queries {
+    if query.relevant.is_empty() {
+      return Err(anyhow!("Query '{}' has no relevant insights", query.query));
+    }
+    if let Some(unknown) = query.relevant.iter().find(|id| !known.contains(id.as_str())) {
+      return Err(anyhow!("Query '{}' expects unknown insight {}", query.query, unknown));
+    }
+  }
+  Ok(())
+}
+
+fn search_options(mode: Mode, options: &EvalOptions) -> SearchOptions {
+  SearchOptions {
+    #[cfg(feature = "semantic")]
+    semantic: mode == Mode::Semantic,
+    exact: mode == Mode::Exact,
+    limit: Some(options.k),
+    full: true,
+    #[cfg(feature = "neural")]
+    embedding_client: embedding_client(options),
+    ..Default::default()
+  }
+}
+
+#[cfg(feature = "neural")]
+fn embedding_client(options: &EvalOptions) -> EmbeddingClient {
+  if options.hashing_embeddings {
+    embedding_client::with_service(Box::new(HashingEmbeddingService))
+  } else {
+    embedding_client::create()
+  }
+}
+
+/// Score a ranking against the relevant ids, looking at the top `k` results
+pub fn score(found: &[String], relevant: &HashSet<&str>, k: usize) -> Metrics {
+  let hits: Vec<bool> = found.iter().take(k).map(|id| relevant.contains(id.as_str())).collect();
+  let hit_count = hits.iter().filter(|&&hit| hit).count();
+
+  let gain = |position: usize| 1.0 / (position as f32 + 2.0).log2();
+  // Folding from 0.0 rather than summing keeps an empty ranking at 0.0 instead of -0.0
+  let dcg = hits.iter().enumerate().filter(|(_, &hit)| hit).fold(0.0, |dcg, (i, _)| dcg + gain(i));
+  let ideal: f32 = (0..relevant.len().min(k)).map(gain).sum();
+
+  Metrics {
+    precision: hit_count as f32 / k as f32,
+    recall: if relevant.is_empty() { 0.0 } else { hit_count as f32 / relevant.len() as f32 },
+    mrr: hits.iter().position(|&hit| hit).map_or(0.0, |i| 1.0 / (i as f32 + 1.0)),
+    ndcg: if ideal > 0.0 { dcg / ideal } else { 0.0 },
+  }
+}
+
+fn mean(queries: &[QueryReport]) -> Metrics {
+  if queries.is_empty() {
+    return Metrics::default();
+  }
+
+  let count = queries.len() as f32;
+  let sum =
+    |metric: fn(&Metrics) -> f32| queries.iter().map(|q| metric(&q.metrics)).sum::<f32>() / count;
+  Metrics {
+    precision: sum(|m| m.precision),
+    recall: sum(|m| m.recall),
+    mrr: sum(|m| m.mrr),
+    ndcg: sum(|m| m.ndcg),
+  }
+}
+
+/// A temporary store directory, removed when dropped
+struct ScratchStore {
+  dir: PathBuf,
+}
+
+impl ScratchStore {
+  fn create() -> Result<Self> {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos();
+    let dir = env::temp_dir().join(format!("insights-eval-{}-{}", std::process::id(), nanos));
+    fs::create_dir_all(&dir)
+      .with_context(|| format!("Could not create scratch store {}", dir.display()))?;
+    Ok(ScratchStore { dir })
+  }
+}
+
+impl Drop for ScratchStore {
+  fn drop(&mut self) {
+    let _ = fs::remove_dir_all(&self.dir);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn ids(ids: &[&str]) -> Vec<String> {
+    ids.iter().map(|id| id.to_string()).collect()
+  }
+
+  #[test]
+  fn test_perfect_ranking() {
+    let relevant: HashSet<&str> = ["a/1", "a/2"].into();
+    let metrics = score(&ids(&["a/1", "a/2", "b/1"]), &relevant, 2);
+    assert_eq!(metrics, Metrics { precision: 1.0, recall: 1.0, mrr: 1.0, ndcg: 1.0 });
+  }
+
+  #[test]
+  fn test_late_and_missing_hits() {
+    let relevant: HashSet<&str> = ["a/1", "a/2"].into();
+    let metrics = score(&ids(&["b/1", "a/1", "b/2"]), &relevant, 3);
+    assert!((metrics.precision - 1.0 / 3.0).abs() < 1e-6);
+    assert_eq!(metrics.recall, 0.5);
+    assert_eq!(metrics.mrr, 0.5);
+    // One hit at rank 2 against an ideal of hits at ranks 1 and 2
+    let expected = (1.0 / 3f32.log2()) / (1.0 + 1.0 / 3f32.log2());
+    assert!((metrics.ndcg - expected).abs() < 1e-6);
+  }
+
+  #[test]
+  fn test_hits_beyond_k_do_not_count() {
+    let relevant: HashSet<&str> = ["a/1"].into();
+    assert_eq!(score(&ids(&["b/1", "b/2", "a/1"]), &relevant, 2), Metrics::default());
+    assert_eq!(score(&[], &relevant, 5), Metrics::default());
+  }
+}
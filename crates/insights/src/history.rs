@@ -0,0 +1,152 @@
+//! Revision history for insights.
+//!
+//! Before an insight file is overwritten or deleted, its current contents are
+//! copied to `<store root>/.history/<topic>/<name>/<revision>.insight.md` in
+//! the store the insight lives in. Revisions are numbered from 1 and never
+//! rewritten.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use similar::TextDiff;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::insight::{self, Insight};
+use crate::scope::{self, Scope};
+
+const HISTORY_DIR: &str = ".history";
+const REVISION_SUFFIX: &str = ".insight.md";
+
+/// A stored prior version of an insight
+#[derive(Debug, Clone)]
+pub struct Revision {
+  pub number: u32,
+  pub path: PathBuf,
+  pub insight: Insight,
+}
+
+impl Revision {
+  /// When this version of the insight was written, if known
+  pub fn written(&self) -> Option<DateTime<Utc>> {
+    self.insight.updated.or(self.insight.created)
+  }
+}
+
+/// Directory holding the revisions of a single insight, in the first store by
+/// precedence that has any
+pub fn history_dir(topic: &str, name: &str) -> Result<PathBuf> {
+  for (_, root) in scope::stores()? {
+    let dir = history_dir_in(&root, topic, name);
+    if dir.exists() {
+      return Ok(dir);
+    }
+  }
+  Ok(history_dir_in(&insight::get_insights_root()?, topic, name))
+}
+
+fn history_dir_in(root: &Path, topic: &str, name: &str) -> PathBuf {
+  root.join(HISTORY_DIR).join(topic.to_lowercase()).join(name.to_lowercase())
+}
+
+/// Copy the file at `current_path` into the insight's history as a new revision
+pub fn record(current_path: &Path, topic: &str, name: &str) -> Result<u32> {
+  let root = scope::root(scope::of_path(current_path))?;
+  let dir = history_dir_in(&root, topic, name);
+  fs::create_dir_all(&dir)?;
+
+  let number = revision_numbers(&dir)?.last().map_or(1, |last| last + 1);
+  fs::copy(current_path, dir.join(format!("{number}{REVISION_SUFFIX}")))?;
+  Ok(number)
+}
+
+/// Move an insight's revisions to its new topic and name. Revisions left by an
+/// earlier insight of the new name are kept, and the moved ones follow them.
+pub fn relocate(
+  scope: Scope,
+  topic: &str,
+  name: &str,
+  new_topic: &str,
+  new_name: &str,
+) -> Result<()> {
+  let root = scope::root(scope)?;
+  let from = history_dir_in(&root, topic, name);
+  let to = history_dir_in(&root, new_topic, new_name);
+  if from == to || !from.exists() {
+    return Ok(());
+  }
+
+  if to.exists() {
+    for number in revision_numbers(&from)? {
+      record(&from.join(format!("{number}{REVISION_SUFFIX}")), new_topic, new_name)?;
+    }
+    fs::remove_dir_all(&from)?;
+  } else {
+    if let Some(parent) = to.parent() {
+      fs::create_dir_all(parent)?;
+    }
+    fs::rename(&from, &to)?;
+  }
+
+  // Drop the old topic's history directory if this was its last insight
+  if let Some(parent) = from.parent() {
+    let _ = fs::remove_dir(parent);
+  }
+  Ok(())
+}
+
+/// All stored revisions of an insight, oldest first
+pub fn list(topic: &str, name: &str) -> Result<Vec<Revision>> {
+  let dir = history_dir(topic, name)?;
+  revision_numbers(&dir)?.into_iter().map(|number| load(topic, name, number)).collect()
+}
+
+/// Load a specific revision of an insight
+pub fn load(topic: &str, name: &str, number: u32) -> Result<Revision> {
+  let path = history_dir(topic, name)?.join(format!("{number}{REVISION_SUFFIX}"));
+  if !path.exists() {
+    return Err(anyhow!("Revision {} of {}/{} not found", number, topic, name));
+  }
+
+  let content = fs::read_to_string(&path)?;
+  let insight = insight::parse_insight_from_content(topic, name, &content)?;
+  Ok(Revision { number, path, insight })
+}
+
+/// Unified line diff between two versions of an insight
+pub fn diff(old: &Insight, new: &Insight, old_label: &str, new_label: &str) -> String {
+  let old_text = diffable_text(old);
+  let new_text = diffable_text(new);
+
+  TextDiff::from_lines(&old_text, &new_text).unified_diff().header(old_label, new_label).to_string()
+}
+
+fn diffable_text(insight: &Insight) -> String {
+  format!(
+    "tags: {}\n---\n{}\n---\n\n{}\n",
+    insight.tags.join(", "),
+    insight.overview,
+    insight.details
+  )
+}
+
+fn revision_numbers(dir: &Path) -> Result<Vec<u32>> {
+  if !dir.exists() {
+    return Ok(Vec::new());
+  }
+
+  let mut numbers = Vec::new();
+  for entry in fs::read_dir(dir)? {
+    let file_name = entry?.file_name();
+    let number = file_name
+      .to_str()
+      .and_then(|file_name| file_name.strip_suffix(REVISION_SUFFIX))
+      .and_then(|number| number.parse::<u32>().ok());
+
+    if let Some(number) = number {
+      numbers.push(number);
+    }
+  }
+
+  numbers.sort_unstable();
+  Ok(numbers)
+}
@@ -0,0 +1,275 @@
+//! Persistent vector index for neural search.
+//!
+//! Keeps every insight's embedding in a single file under each store's root so
+//! searches can score a query against all insights in memory instead of
+//! parsing each `.insight.md` file.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+
+use crate::insight::{self, Insight};
+use crate::scope::{self, Scope};
+
+const INDEX_DIR: &str = ".index";
+const INDEX_FILE: &str = "embeddings.json";
+/// Extension a corrupt index is renamed to, so it can be inspected
+const CORRUPT_EXTENSION: &str = "json.corrupt";
+/// Extension of the file writers lock while they update an index
+const LOCK_EXTENSION: &str = "lock";
+
+/// A single indexed insight embedding
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IndexEntry {
+  pub topic: String,
+  pub name: String,
+  /// Hash of the content the embedding was computed from, see `insight::content_hash`
+  pub content_hash: String,
+  /// The insight's tags, so searches can filter without reading the file.
+  /// Missing from indexes written before tags were recorded.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub tags: Option<Vec<String>>,
+  /// Modification time of the insight file when it was indexed. A file
+  /// modified since, e.g. by hand, has to be read to tell whether the entry
+  /// is still current.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub modified: Option<DateTime<Utc>>,
+  pub embedding_version: Option<String>,
+  pub embedding: Vec<f32>,
+}
+
+/// On-disk collection of insight embeddings keyed by normalized topic/name
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct VectorIndex {
+  pub entries: BTreeMap<String, IndexEntry>,
+}
+
+pub fn index_path(scope: Scope) -> Result<PathBuf> {
+  Ok(scope::root(scope)?.join(INDEX_DIR).join(INDEX_FILE))
+}
+
+/// Normalized lookup key for an insight, matching the on-disk path normalization
+pub fn key(topic: &str, name: &str) -> String {
+  format!("{}/{}", topic.to_lowercase(), name.to_lowercase())
+}
+
+/// Load the index from disk. A missing index is empty. A corrupt one is
+/// renamed aside with a warning and treated as empty, since it can always be
+/// rebuilt from the insight files.
+pub fn load(scope: Scope) -> Result<VectorIndex> {
+  let path = index_path(scope)?;
+  if !path.exists() {
+    return Ok(VectorIndex::default());
+  }
+
+  let content = fs::read_to_string(&path)?;
+  match serde_json::from_str(&content) {
+    Ok(index) => Ok(index),
+    Err(e) => {
+      let corrupt_path = path.with_extension(CORRUPT_EXTENSION);
+      fs::rename(&path, &corrupt_path)?;
+      eprintln!(
+        "  {} Warning: Vector index {} is corrupt ({}); moved it to {}",
+        "⚠".yellow(),
+        path.display(),
+        e,
+        corrupt_path.display()
+      );
+      eprintln!("  {} Run `insights index` to rebuild it", "ℹ".blue());
+      Ok(VectorIndex::default())
+    }
+  }
+}
+
+pub fn save(scope: Scope, index: &VectorIndex) -> Result<()> {
+  let path = index_path(scope)?;
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent)?;
+  }
+
+  // Write to a temporary file of our own first so readers never see a
+  // partial index and concurrent writers never share one
+  let mut tmp = tempfile::NamedTempFile::new_in(path.parent().unwrap_or(Path::new(".")))?;
+  tmp.write_all(serde_json::to_string(index)?.as_bytes())?;
+  tmp.persist(&path)?;
+  Ok(())
+}
+
+/// Take the lock on a store's index, waiting for other writers. It is held
+/// until the returned file is dropped, so a read-modify-write of the index
+/// doesn't lose another process's changes.
+fn lock(scope: Scope) -> Result<fs::File> {
+  let path = index_path(scope)?.with_extension(LOCK_EXTENSION);
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent)?;
+  }
+  let file = fs::OpenOptions::new().create(true).truncate(false).write(true).open(&path)?;
+  wait_for_lock(&file)?;
+  Ok(file)
+}
+
+#[cfg(unix)]
+fn wait_for_lock(file: &fs::File) -> Result<()> {
+  // SAFETY: the descriptor stays open for the duration of the call
+  if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } != 0 {
+    return Err(std::io::Error::last_os_error().into());
+  }
+  Ok(())
+}
+
+/// Not locked on Windows; the atomic rename still keeps the index whole
+#[cfg(windows)]
+fn wait_for_lock(_file: &fs::File) -> Result<()> {
+  Ok(())
+}
+
+#[cfg(feature = "neural")]
+pub fn get<'a>(index: &'a VectorIndex, topic: &str, name: &str) -> Option<&'a IndexEntry> {
+  index.entries.get(&key(topic, name))
+}
+
+/// Insert or refresh the entry for an insight. Insights without an embedding,
+/// or whose embedding was computed from other content, are dropped from the index.
+pub fn upsert(index: &mut VectorIndex, insight: &Insight) {
+  let entry_key = key(&insight.topic, &insight.name);
+  let current_hash = insight::content_hash(insight);
+
+  let embedding = insight.embedding.as_ref().filter(|embedding| !embedding.is_empty());
+  match (embedding, &insight.embedding_hash) {
+    (Some(embedding), Some(embedding_hash)) if *embedding_hash == current_hash => {
+      index.entries.insert(
+        entry_key,
+        IndexEntry {
+          topic: insight.topic.clone(),
+          name: insight.name.clone(),
+          content_hash: embedding_hash.clone(),
+          tags: Some(insight.tags.clone()),
+          modified: insight::file_path(insight).ok().and_then(|path| modified(&path)),
+          embedding_version: insight.embedding_version.clone(),
+          embedding: embedding.clone(),
+        },
+      );
+    }
+    _ => {
+      index.entries.remove(&entry_key);
+    }
+  }
+}
+
+/// Modification time of an insight file
+pub fn modified(path: &Path) -> Option<DateTime<Utc>> {
+  fs::metadata(path).and_then(|metadata| metadata.modified()).ok().map(DateTime::from)
+}
+
+/// Whether an entry still describes the insight file at `path`, judged by
+/// the file's modification time alone
+#[cfg(feature = "neural")]
+pub fn is_fresh(entry: &IndexEntry, path: &Path) -> bool {
+  entry.modified.is_some() && entry.modified == modified(path)
+}
+
+/// Topic and name of an insight file, as they appear on disk
+#[cfg(feature = "neural")]
+pub fn path_id(path: &Path) -> Option<(&str, &str)> {
+  let topic = path.parent()?.file_name()?.to_str()?;
+  let name = path.file_stem()?.to_str()?.trim_end_matches(".insight");
+  Some((topic, name))
+}
+
+/// Load an insight from the store its index entry belongs to, even when an
+/// insight in another store shadows it
+#[cfg(feature = "neural")]
+pub fn load_indexed(topic: &str, name: &str, scope: Scope) -> Result<Insight> {
+  let mut located = Insight::new(topic.to_string(), name.to_string(), String::new(), String::new());
+  located.scope = scope;
+  insight::load_from_path(&insight::file_path(&located)?)
+}
+
+/// Every visible insight with the embedding to compare it by. Current index
+/// entries stand in for their insights and carry only the topic, name, scope
+/// and embedding; `load_indexed` reads the rest. Insights missing from the
+/// index or modified since they were indexed are read from their files.
+#[cfg(feature = "neural")]
+pub fn embedded_insights() -> Result<Vec<Insight>> {
+  let indexes = scope::stores()?
+    .into_iter()
+    .map(|(scope, _)| Ok((scope, load(scope)?)))
+    .collect::<Result<Vec<_>>>()?;
+
+  let mut insights = Vec::new();
+  for path in insight::get_insight_paths(None)? {
+    let scope = scope::of_path(&path);
+    let entry = path_id(&path).and_then(|(topic, name)| {
+      let (_, index) = indexes.iter().find(|(store, _)| *store == scope)?;
+      get(index, topic, name)
+    });
+    match entry {
+      Some(entry) if is_fresh(entry, &path) => {
+        let mut stub =
+          Insight::new(entry.topic.clone(), entry.name.clone(), String::new(), String::new());
+        stub.scope = scope;
+        stub.embedding_version = entry.embedding_version.clone();
+        stub.embedding = Some(entry.embedding.clone());
+        insights.push(stub);
+      }
+      _ => insights.push(insight::load_from_path(&path)?),
+    }
+  }
+  Ok(insights)
+}
+
+pub fn remove(index: &mut VectorIndex, topic: &str, name: &str) {
+  index.entries.remove(&key(topic, name));
+}
+
+/// Record an insight's current embedding in the on-disk index
+pub fn record(insight: &Insight) -> Result<()> {
+  if insight.embedding.is_none() && !index_path(insight.scope)?.exists() {
+    return Ok(());
+  }
+
+  let _lock = lock(insight.scope)?;
+  let mut index = load(insight.scope)?;
+  upsert(&mut index, insight);
+  save(insight.scope, &index)
+}
+
+/// Drop an insight from the on-disk index of a store
+pub fn forget(scope: Scope, topic: &str, name: &str) -> Result<()> {
+  let path = index_path(scope)?;
+  if !path.exists() {
+    return Ok(());
+  }
+
+  let _lock = lock(scope)?;
+  let mut index = load(scope)?;
+  remove(&mut index, topic, name);
+  save(scope, &index)
+}
+
+/// Rebuild every store's on-disk index from the visible insight files.
+/// Returns the combined entries.
+#[cfg(feature = "neural")]
+pub fn rebuild() -> Result<VectorIndex> {
+  let insights = insight::get_insights(None)?;
+  let mut combined = VectorIndex::default();
+
+  for (scope, _) in scope::stores()? {
+    let mut index = VectorIndex::default();
+    for insight in insights.iter().filter(|insight| insight.scope == scope) {
+      upsert(&mut index, insight);
+    }
+    let _lock = lock(scope)?;
+    save(scope, &index)?;
+    combined.entries.append(&mut index.entries);
+  }
+
+  Ok(combined)
+}
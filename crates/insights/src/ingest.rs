@@ -0,0 +1,494 @@
+//! Ingestion of plain Markdown note trees, such as Obsidian vaults.
+//!
+//! Folders map to topics and file names to insight names. Long documents are
+//! split into one insight per section. Each ingest is remembered per store in
+//! `.index/ingest.json`, so files that have not changed since the previous
+//! ingest are skipped and insights for removed sections and deleted notes are
+//! cleaned up. Ingested insights are not embedded; `insights index` computes
+//! their embeddings in one batch.
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::insight::{self, Insight};
+use crate::scope::{self, Scope};
+
+const STATE_DIR: &str = ".index";
+const STATE_FILE: &str = "ingest.json";
+
+/// Documents longer than this are split into one insight per section
+const SPLIT_THRESHOLD_CHARS: usize = 2000;
+const MAX_OVERVIEW_CHARS: usize = 200;
+
+/// Outcome of ingesting a single insight
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IngestAction {
+  Added,
+  Updated,
+  Unchanged,
+  Removed,
+  Failed,
+}
+
+/// Per-insight ingest report
+#[derive(Debug, Clone)]
+pub struct IngestResult {
+  /// Markdown file the insight was read from, relative to the ingested directory
+  pub file: PathBuf,
+  pub topic: String,
+  pub name: String,
+  pub action: IngestAction,
+  /// Reason an insight could not be ingested
+  pub detail: Option<String>,
+}
+
+/// What was written for each ingested file, keyed by its canonical path
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct IngestState {
+  files: BTreeMap<String, IngestedFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IngestedFile {
+  content_hash: String,
+  insights: Vec<(String, String)>,
+}
+
+/// A note section that becomes one insight
+#[derive(Debug, PartialEq)]
+struct Section {
+  name: String,
+  overview: String,
+  details: String,
+}
+
+/// Ingest every Markdown file below `dir` into the `scope` store
+pub fn ingest(dir: &Path, scope: Scope) -> Result<Vec<IngestResult>> {
+  let dir = dir.canonicalize().with_context(|| format!("Could not read {}", dir.display()))?;
+  if !dir.is_dir() {
+    return Err(anyhow!("{} is not a directory", dir.display()));
+  }
+
+  let mut files = Vec::new();
+  collect_markdown_files(&dir, &mut files)?;
+  files.sort();
+
+  let mut state = load_state(scope)?;
+  let mut results = Vec::new();
+
+  for file in &files {
+    let relative = file.strip_prefix(&dir).unwrap_or(file).to_path_buf();
+    match ingest_file(&dir, file, &relative, scope, &mut state) {
+      Ok(mut file_results) => results.append(&mut file_results),
+      Err(e) => results.push(failed(relative, e)),
+    }
+  }
+
+  // Notes below `dir` that were ingested before but have since been deleted
+  let present: HashSet<String> =
+    files.iter().map(|file| file.to_string_lossy().to_string()).collect();
+  let deleted: Vec<String> = state
+    .files
+    .keys()
+    .filter(|origin| Path::new(origin).starts_with(&dir) && !present.contains(*origin))
+    .cloned()
+    .collect();
+  for origin in deleted {
+    let relative =
+      Path::new(&origin).strip_prefix(&dir).unwrap_or(Path::new(&origin)).to_path_buf();
+    let ingested = state.files.remove(&origin).map(|file| file.insights).unwrap_or_default();
+    match remove_stale(ingested, &origin, &relative, scope) {
+      Ok(mut removed) => results.append(&mut removed),
+      Err(e) => results.push(failed(relative, e)),
+    }
+  }
+
+  save_state(scope, &state)?;
+  Ok(results)
+}
+
+fn ingest_file(
+  dir: &Path,
+  file: &Path,
+  relative: &Path,
+  scope: Scope,
+  state: &mut IngestState,
+) -> Result<Vec<IngestResult>> {
+  let content = fs::read_to_string(file)?;
+  let origin = file.to_string_lossy().to_string();
+  let content_hash = hash(&content);
+  let topic = topic_for(dir, relative);
+
+  let result = |name: &str, action: IngestAction, detail: Option<String>| IngestResult {
+    file: relative.to_path_buf(),
+    topic: topic.clone(),
+    name: name.to_string(),
+    action,
+    detail,
+  };
+
+  let previous = state.files.get(&origin).cloned();
+  if let Some(previous) = &previous {
+    let all_present = previous.insights.iter().all(|(topic, name)| exists(topic, name, scope));
+    if previous.content_hash == content_hash && all_present {
+      return Ok(
+        previous
+          .insights
+          .iter()
+          .map(|(_, name)| result(name, IngestAction::Unchanged, None))
+          .collect(),
+      );
+    }
+  }
+
+  let (tags, body) = split_frontmatter(&content);
+  let stem = file.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+  let sections = split_sections(stem, body);
+
+  let mut results = Vec::new();
+  let mut written = Vec::new();
+  for section in &sections {
+    let mut ingested = Insight::new(
+      topic.clone(),
+      section.name.clone(),
+      section.overview.clone(),
+      section.details.clone(),
+    );
+    ingested.tags = insight::normalize_tags(&tags);
+    ingested.origin = Some(origin.clone());
+    ingested.scope = scope;
+
+    match write_section(ingested, &origin) {
+      Ok(action) => {
+        written.push((topic.clone(), section.name.clone()));
+        results.push(result(&section.name, action, None));
+      }
+      Err(e) => results.push(result(&section.name, IngestAction::Failed, Some(e.to_string()))),
+    }
+  }
+
+  // Sections that disappeared from the file since the previous ingest
+  let disappeared = previous
+    .map(|previous| previous.insights)
+    .unwrap_or_default()
+    .into_iter()
+    .filter(|insight| !written.contains(insight))
+    .collect();
+  results.append(&mut remove_stale(disappeared, &origin, relative, scope)?);
+
+  state.files.insert(origin, IngestedFile { content_hash, insights: written });
+  Ok(results)
+}
+
+/// Delete insights an earlier ingest wrote for `origin`, unless they have
+/// since been replaced by insights from elsewhere
+fn remove_stale(
+  insights: Vec<(String, String)>,
+  origin: &str,
+  relative: &Path,
+  scope: Scope,
+) -> Result<Vec<IngestResult>> {
+  let mut results = Vec::new();
+  for (topic, name) in insights {
+    if let Some(stale) = load_in(&topic, &name, scope) {
+      if stale.origin.as_deref() == Some(origin) {
+        insight::delete(&stale)?;
+        results.push(IngestResult {
+          file: relative.to_path_buf(),
+          topic,
+          name,
+          action: IngestAction::Removed,
+          detail: None,
+        });
+      }
+    }
+  }
+  Ok(results)
+}
+
+fn failed(file: PathBuf, error: anyhow::Error) -> IngestResult {
+  IngestResult {
+    file,
+    topic: String::new(),
+    name: String::new(),
+    action: IngestAction::Failed,
+    detail: Some(error.to_string()),
+  }
+}
+
+/// Save a new insight or refresh one written by an earlier ingest of the same file.
+/// Insights from anywhere else are never overwritten.
+fn write_section(ingested: Insight, origin: &str) -> Result<IngestAction> {
+  let Some(mut existing) = load_in(&ingested.topic, &ingested.name, ingested.scope) else {
+    insight::save(&ingested)?;
+    return Ok(IngestAction::Added);
+  };
+
+  if existing.origin.as_deref() != Some(origin) {
+    return Err(anyhow!("Insight {}/{} already exists", ingested.topic, ingested.name));
+  }
+
+  let content_changed =
+    existing.overview != ingested.overview || existing.details != ingested.details;
+  if content_changed {
+    existing.tags = ingested.tags;
+    insight::update(&mut existing, Some(&ingested.overview), Some(&ingested.details))?;
+  } else if existing.tags != ingested.tags {
+    insight::set_tags(&mut existing, &ingested.tags)?;
+  } else {
+    return Ok(IngestAction::Unchanged);
+  }
+  Ok(IngestAction::Updated)
+}
+
+/// Load an insight from one store only, ignoring copies in other stores
+fn load_in(topic: &str, name: &str, scope: Scope) -> Option<Insight> {
+  let mut probe = Insight::new(topic.to_string(), name.to_string(), String::new(), String::new());
+  probe.scope = scope;
+  let path = insight::file_path(&probe).ok().filter(|path| path.exists())?;
+  insight::load_from_path(&path).ok()
+}
+
+fn exists(topic: &str, name: &str, scope: Scope) -> bool {
+  load_in(topic, name, scope).is_some()
+}
+
+fn collect_markdown_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+  for entry in fs::read_dir(dir)? {
+    let path = entry?.path();
+    let hidden =
+      path.file_name().and_then(|name| name.to_str()).is_some_and(|name| name.starts_with('.'));
+    if hidden {
+      // Editor settings such as `.obsidian` and version control
+      continue;
+    }
+
+    if path.is_dir() {
+      collect_markdown_files(&path, files)?;
+    } else if path.extension().and_then(|ext| ext.to_str()) == Some("md")
+      && !insight::is_insight_file(&path)
+    {
+      files.push(path);
+    }
+  }
+  Ok(())
+}
+
+/// Topic for a file: its folders below the ingested directory joined with `-`,
+/// or the directory's own name for files at the top level
+fn topic_for(dir: &Path, relative: &Path) -> String {
+  let folders: Vec<String> = relative
+    .parent()
+    .into_iter()
+    .flat_map(|parent| parent.components())
+    .map(|component| slug(&component.as_os_str().to_string_lossy()))
+    .filter(|folder| !folder.is_empty())
+    .collect();
+
+  if folders.is_empty() {
+    dir.file_name().map(|name| slug(&name.to_string_lossy())).unwrap_or_else(|| "notes".into())
+  } else {
+    folders.join("-")
+  }
+}
+
+/// Lowercase, hyphen-separated form of a file, folder or heading name
+fn slug(text: &str) -> String {
+  let mut slug = String::new();
+  for c in text.chars() {
+    if c.is_alphanumeric() || c == '_' {
+      slug.extend(c.to_lowercase());
+    } else if !slug.is_empty() && !slug.ends_with('-') {
+      slug.push('-');
+    }
+  }
+  slug.trim_end_matches('-').to_string()
+}
+
+/// Separate YAML frontmatter from the note body, returning the tags it declares.
+/// Notes saved with Windows line endings are handled too.
+fn split_frontmatter(content: &str) -> (Vec<String>, &str) {
+  let Some(rest) = content.strip_prefix("---\n").or_else(|| content.strip_prefix("---\r\n")) else {
+    return (Vec::new(), content);
+  };
+  let Some(end) = rest.find("\n---") else {
+    return (Vec::new(), content);
+  };
+
+  let body = rest[end + 4..].trim_start_matches(['\r', '\n']);
+  let tags = match serde_yaml::from_str::<serde_yaml::Value>(&rest[..end]) {
+    Ok(frontmatter) => match frontmatter.get("tags") {
+      Some(serde_yaml::Value::Sequence(tags)) => {
+        tags.iter().filter_map(|tag| tag.as_str()).map(|tag| tag.to_string()).collect()
+      }
+      Some(serde_yaml::Value::String(tags)) => tags
+        .split([',', ' '])
+        .filter(|tag| !tag.is_empty())
+        .map(|tag| tag.trim_start_matches('#').to_string())
+        .collect(),
+      _ => Vec::new(),
+    },
+    Err(_) => Vec::new(),
+  };
+  (tags, body)
+}
+
+/// Markdown heading level and text, if the line is an ATX heading
+fn heading(line: &str) -> Option<(usize, &str)> {
+  let level = line.chars().take_while(|&c| c == '#').count();
+  if !(1..=6).contains(&level) {
+    return None;
+  }
+  let rest = &line[level..];
+  (rest.is_empty() || rest.starts_with(' ')).then(|| (level, rest.trim()))
+}
+
+/// Indices and levels of headings outside of fenced code blocks
+fn headings(lines: &[&str]) -> Vec<(usize, usize)> {
+  let mut in_fence = false;
+  let mut found = Vec::new();
+  for (index, line) in lines.iter().enumerate() {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+      in_fence = !in_fence;
+    } else if !in_fence {
+      if let Some((level, _)) = heading(line) {
+        found.push((index, level));
+      }
+    }
+  }
+  found
+}
+
+/// Split a note into insights. Short notes become a single insight named after
+/// the file; long ones get an insight per top-level section, plus one for any
+/// introduction before the first section.
+fn split_sections(stem: &str, body: &str) -> Vec<Section> {
+  let lines: Vec<&str> = body.lines().collect();
+  let mut found = headings(&lines);
+  let file_name = slug(stem);
+
+  // A single leading H1 is the note's title rather than a section
+  let mut title = None;
+  let mut start = 0;
+  if let Some(&(index, 1)) = found.first() {
+    if found.iter().filter(|(_, level)| *level == 1).count() == 1 {
+      title = heading(lines[index]).map(|(_, text)| text.to_string());
+      start = index + 1;
+      found.remove(0);
+    }
+  }
+  let fallback = title.unwrap_or_else(|| stem.to_string());
+
+  let split_level = found.iter().map(|(_, level)| *level).min();
+  let boundaries: Vec<usize> = found
+    .iter()
+    .filter(|(_, level)| Some(*level) == split_level)
+    .map(|(index, _)| *index)
+    .collect();
+
+  if body.chars().count() <= SPLIT_THRESHOLD_CHARS || boundaries.len() < 2 {
+    return vec![section(file_name, &fallback, &lines[start..])];
+  }
+
+  let mut sections = Vec::new();
+  let intro = &lines[start..boundaries[0]];
+  if intro.iter().any(|line| !line.trim().is_empty()) {
+    sections.push(section(file_name.clone(), &fallback, intro));
+  }
+
+  let mut used: HashSet<String> = sections.iter().map(|section| section.name.clone()).collect();
+  for (position, &index) in boundaries.iter().enumerate() {
+    let end = boundaries.get(position + 1).copied().unwrap_or(lines.len());
+    let heading_text = heading(lines[index]).map(|(_, text)| text).unwrap_or_default();
+
+    let base = format!("{}-{}", file_name, slug(heading_text)).trim_end_matches('-').to_string();
+    let mut name = base.clone();
+    for suffix in 2.. {
+      if used.insert(name.clone()) {
+        break;
+      }
+      name = format!("{base}-{suffix}");
+    }
+    sections.push(section(name, heading_text, &lines[index + 1..end]));
+  }
+  sections
+}
+
+/// An insight for the lines under a heading. The heading itself is the
+/// title or section name; every line below it is kept.
+fn section(name: String, fallback_overview: &str, lines: &[&str]) -> Section {
+  let details = lines
+    .iter()
+    .skip_while(|line| line.trim().is_empty())
+    .copied()
+    .collect::<Vec<_>>()
+    .join("\n")
+    .trim()
+    .to_string();
+
+  let overview = first_paragraph(lines).unwrap_or_else(|| fallback_overview.to_string());
+  Section { name, overview, details }
+}
+
+/// The first paragraph of prose, flattened to one line and shortened to fit an overview
+fn first_paragraph(lines: &[&str]) -> Option<String> {
+  let paragraph: Vec<&str> = lines
+    .iter()
+    .map(|line| line.trim())
+    .skip_while(|line| line.is_empty() || heading(line).is_some() || is_tag_line(line))
+    .take_while(|line| !line.is_empty() && heading(line).is_none())
+    .collect();
+  if paragraph.is_empty() || paragraph[0].starts_with("```") || paragraph[0].starts_with("~~~") {
+    return None;
+  }
+
+  let text = paragraph.join(" ");
+  if text.chars().count() <= MAX_OVERVIEW_CHARS {
+    return Some(text);
+  }
+
+  let cut: String = text.chars().take(MAX_OVERVIEW_CHARS).collect();
+  let cut = cut.rsplit_once(' ').map(|(head, _)| head).unwrap_or(&cut);
+  Some(format!("{}...", cut.trim_end()))
+}
+
+/// A line of nothing but `#tags`, as Obsidian notes often have under the title
+fn is_tag_line(line: &str) -> bool {
+  line
+    .split_whitespace()
+    .all(|word| word.len() > 1 && word.starts_with('#') && heading(word).is_none())
+}
+
+fn hash(content: &str) -> String {
+  Sha256::digest(content.as_bytes()).iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn state_path(scope: Scope) -> Result<PathBuf> {
+  Ok(scope::root(scope)?.join(STATE_DIR).join(STATE_FILE))
+}
+
+/// A missing or unreadable state only means every file is ingested again
+fn load_state(scope: Scope) -> Result<IngestState> {
+  let path = state_path(scope)?;
+  if !path.exists() {
+    return Ok(IngestState::default());
+  }
+
+  let content = fs::read_to_string(&path)?;
+  Ok(serde_json::from_str(&content).unwrap_or_default())
+}
+
+fn save_state(scope: Scope, state: &IngestState) -> Result<()> {
+  let path = state_path(scope)?;
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent)?;
+  }
+  fs::write(path, serde_json::to_string_pretty(state)?)?;
+  Ok(())
+}
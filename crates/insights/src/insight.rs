@@ -1,9 +1,16 @@
 #[cfg(feature = "neural")]
 use crate::embedding_client::Embedding;
+use crate::history;
+use crate::index;
+use crate::links::{self, InsightLink};
+use crate::provenance::{self, InsightSource};
+use crate::scope::{self, Scope};
 use anyhow::{anyhow, Result};
 use chrono::{DateTime, Utc};
 use dirs::home_dir;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
 use std::fs;
 use std::path::PathBuf;
 
@@ -12,6 +19,8 @@ const FRONTMATTER_START: &str = "---\n";
 const FRONTMATTER_END: &str = "\n---\n";
 const FRONTMATTER_START_LEN: usize = 4; // Length of "---\n"
 const FRONTMATTER_END_LEN: usize = 5; // Length of "\n---\n"
+/// Heading that separates the frontmatter from an insight's details
+const DETAILS_MARKER: &str = "# Details";
 
 /// YAML frontmatter structure for insight files
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,14 +30,33 @@ pub struct InsightMetaData {
   #[serde(default)]
   pub name: String,
   pub overview: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub created: Option<DateTime<Utc>>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub updated: Option<DateTime<Utc>>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub author: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub source: Option<InsightSource>,
+  /// File the insight was ingested from
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub origin: Option<String>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub tags: Vec<String>,
   #[serde(skip_serializing_if = "Option::is_none")]
   pub embedding_version: Option<String>,
   #[serde(skip_serializing_if = "Option::is_none")]
   pub embedding: Option<Vec<f32>>,
-  #[serde(skip_serializing_if = "Option::is_none")]
-  pub embedding_text: Option<String>,
+  /// Hash of the content the embedding was computed from, see `content_hash`.
+  /// It replaces the `embedding_text` older files carry, which is ignored on
+  /// read and dropped on the next write.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub embedding_hash: Option<String>,
   #[serde(skip_serializing_if = "Option::is_none")]
   pub embedding_computed: Option<DateTime<Utc>>,
+  /// `[[topic/name]]` links found in the overview and body, filled in on parse
+  #[serde(skip)]
+  pub links: Vec<InsightLink>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -38,10 +66,22 @@ pub struct Insight {
   pub overview: String,
   pub details: String,
 
+  // Authorship metadata (None for legacy insights)
+  pub created: Option<DateTime<Utc>>,
+  pub updated: Option<DateTime<Utc>>,
+  pub author: Option<String>,
+  pub source: Option<InsightSource>,
+  pub origin: Option<String>,
+  pub tags: Vec<String>,
+
+  // Store the insight lives in, derived from its location rather than frontmatter
+  #[serde(default)]
+  pub scope: Scope,
+
   // Embedding metadata (None if not computed yet)
   pub embedding_version: Option<String>,
   pub embedding: Option<Vec<f32>>,
-  pub embedding_text: Option<String>, // The exact text that was embedded
+  pub embedding_hash: Option<String>,
   pub embedding_computed: Option<DateTime<Utc>>,
 }
 
@@ -52,16 +92,23 @@ impl Insight {
       name,
       overview,
       details,
+      created: None,
+      updated: None,
+      author: None,
+      source: None,
+      origin: None,
+      tags: Vec::new(),
+      scope: Scope::Global,
       embedding_version: None,
       embedding: None,
-      embedding_text: None,
+      embedding_hash: None,
       embedding_computed: None,
     }
   }
 }
 
 pub fn file_path(insight: &Insight) -> Result<PathBuf> {
-  let insights_root = get_insights_root()?;
+  let insights_root = scope::root(insight.scope)?;
   // Normalize file paths for x-platform compatibility.
   // Original case is preserved in insight metadata.
   let normalized_topic = insight.topic.to_lowercase();
@@ -71,25 +118,108 @@ pub fn file_path(insight: &Insight) -> Result<PathBuf> {
 
 #[cfg(feature = "neural")]
 pub fn set_embedding(insight: &mut Insight, embedding: Embedding) {
+  insight.embedding_hash = Some(content_hash(insight));
   insight.embedding_version = Some(embedding.version);
   insight.embedding = Some(embedding.embedding);
   insight.embedding_computed = Some(embedding.created_at);
 }
 
 #[cfg(feature = "neural")]
+#[allow(dead_code)] // Library entry point; the CLI checks `embedding_status`
 pub fn has_embedding(insight: &Insight) -> bool {
   insight.embedding.is_some()
 }
 
+/// Whether an insight's stored embedding can still be used
+#[cfg(feature = "neural")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbeddingStatus {
+  Current,
+  /// No embedding has been computed, or computing it failed
+  Missing,
+  /// The content changed since the embedding was computed, e.g. by editing the file by hand
+  Stale,
+  /// The embedding was computed by a different model than the current one
+  ModelMismatch,
+}
+
+/// Check an insight's embedding against its content and, if known, the current model.
+/// Embeddings written before content hashes were recorded count as stale.
 #[cfg(feature = "neural")]
+pub fn embedding_status(insight: &Insight, model_version: Option<&str>) -> EmbeddingStatus {
+  if insight.embedding.as_ref().is_none_or(|embedding| embedding.is_empty()) {
+    return EmbeddingStatus::Missing;
+  }
+  if model_version.is_some_and(|version| insight.embedding_version.as_deref() != Some(version)) {
+    return EmbeddingStatus::ModelMismatch;
+  }
+  if insight.embedding_hash.as_deref() != Some(content_hash(insight).as_str()) {
+    return EmbeddingStatus::Stale;
+  }
+  EmbeddingStatus::Current
+}
+
 pub fn get_embedding_text(insight: &Insight) -> String {
   format!("{} {} {} {}", insight.topic, insight.name, insight.overview, insight.details)
 }
 
+/// Hex-encoded SHA-256 of the text an insight's embedding is computed from
+pub fn content_hash(insight: &Insight) -> String {
+  let digest = Sha256::digest(get_embedding_text(insight).as_bytes());
+  digest.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
 pub fn save(insight: &Insight) -> Result<()> {
+  check_id(&insight.topic, &insight.name)?;
   let file_path = file_path(insight)?;
   ensure_parent_dir_exists(&file_path)?;
   check_insight_is_new(&file_path, &insight.topic, &insight.name)?;
+
+  let mut insight = insight.clone();
+  stamp_new(&mut insight);
+  write_to_file(&insight, &file_path)
+}
+
+/// Fill in creation metadata that the caller has not already provided
+fn stamp_new(insight: &mut Insight) {
+  let now = Utc::now();
+  insight.created = insight.created.or(Some(now));
+  insight.updated = insight.updated.or(insight.created);
+  insight.author = insight.author.take().or_else(provenance::current_author);
+  insight.source = insight.source.take().or_else(provenance::current_source);
+  insight.tags = normalize_tags(&insight.tags);
+}
+
+/// Lowercase, trim, sort and deduplicate tags
+pub fn normalize_tags(tags: &[String]) -> Vec<String> {
+  let mut normalized: Vec<String> =
+    tags.iter().map(|tag| normalize_tag(tag)).filter(|tag| !tag.is_empty()).collect();
+  normalized.sort();
+  normalized.dedup();
+  normalized
+}
+
+/// Form a tag is stored and compared in
+pub fn normalize_tag(tag: &str) -> String {
+  tag.trim().to_lowercase()
+}
+
+/// Check whether an insight carries every one of the given tags. The
+/// insight's own tags are normalized too, in case its file was edited by hand.
+pub fn has_tags(insight: &Insight, tags: &[String]) -> bool {
+  let own = normalize_tags(&insight.tags);
+  normalize_tags(tags).iter().all(|tag| own.contains(tag))
+}
+
+/// Replace an insight's tags. Tags are not embedded, so the embedding is kept.
+pub fn set_tags(insight: &mut Insight, tags: &[String]) -> Result<()> {
+  let file_path = existing_insight_path(insight)?;
+  check_insight_exists(&file_path, &insight.topic, &insight.name)?;
+
+  history::record(&file_path, &insight.topic, &insight.name)?;
+  insight.tags = normalize_tags(tags);
+  insight.updated = Some(Utc::now());
   write_to_file(insight, &file_path)
 }
 
@@ -100,23 +230,41 @@ pub fn save_existing(insight: &Insight) -> Result<()> {
   write_to_file(insight, &file_path)
 }
 
+/// Like `save_existing`, but leaves the vector index alone so a batch of
+/// saves can rebuild it once at the end
+#[cfg(feature = "neural")]
+pub fn save_existing_unindexed(insight: &Insight) -> Result<()> {
+  write_file(insight, &file_path(insight)?)
+}
+
 fn write_to_file(insight: &Insight, file_path: &PathBuf) -> Result<()> {
+  write_file(insight, file_path)?;
+  index::record(insight)
+}
+
+fn write_file(insight: &Insight, file_path: &PathBuf) -> Result<()> {
   ensure_parent_dir_exists(file_path)?;
 
   let frontmatter = InsightMetaData {
     topic: insight.topic.clone(),
     name: insight.name.clone(),
     overview: insight.overview.clone(),
+    created: insight.created,
+    updated: insight.updated,
+    author: insight.author.clone(),
+    source: insight.source.clone(),
+    origin: insight.origin.clone(),
+    tags: insight.tags.clone(),
     embedding_version: insight.embedding_version.clone(),
     embedding: insight.embedding.clone(),
-    embedding_text: insight.embedding_text.clone(),
+    embedding_hash: insight.embedding_hash.clone(),
     embedding_computed: insight.embedding_computed,
+    links: Vec::new(),
   };
 
   let yaml_content = serde_yaml::to_string(&frontmatter)?;
-  let content = format!("---\n{}---\n\n# Details\n{}", yaml_content, insight.details);
+  let content = format!("---\n{}---\n\n{}\n{}", yaml_content, DETAILS_MARKER, insight.details);
   fs::write(file_path, content)?;
-
   Ok(())
 }
 
@@ -128,16 +276,20 @@ pub fn load(topic: &str, name: &str) -> Result<Insight> {
   }
 
   let content = fs::read_to_string(&file_path)?;
-  parse_insight_from_content(topic, name, &content)
+  let mut insight = parse_insight_from_content(topic, name, &content)?;
+  insight.scope = scope::of_path(&file_path);
+  Ok(insight)
 }
 
 pub fn load_from_path(path: &std::path::Path) -> Result<Insight> {
   let content = fs::read_to_string(path)?;
-  parse_insight_from_content(
+  let mut insight = parse_insight_from_content(
     path.parent().unwrap().file_name().unwrap().to_str().unwrap(),
     path.file_stem().unwrap().to_str().unwrap(),
     &content,
-  )
+  )?;
+  insight.scope = scope::of_path(path);
+  Ok(insight)
 }
 
 pub fn update(
@@ -156,15 +308,28 @@ pub fn update(
     return Err(anyhow!("At least one of overview or details must be provided"));
   }
 
-  let existing_file_path = make_insight_path(&insight.topic, &insight.name)?;
+  let existing_file_path = existing_insight_path(insight)?;
   if !existing_file_path.exists() {
     return Err(anyhow!("Insight {}/{} not found", insight.topic, insight.name));
   }
 
+  // Stay in the store the insight was found in
+  insight.scope = scope::of_path(&existing_file_path);
   let new_file_path = file_path(insight)?;
 
+  // Keep creation metadata the caller's copy may not carry
+  let existing = load_from_path(&existing_file_path)?;
+  insight.created = insight.created.or(existing.created);
+  insight.author = insight.author.take().or(existing.author);
+  insight.source = insight.source.take().or(existing.source);
+  insight.origin = insight.origin.take().or(existing.origin);
+
   // Gets recomputed lazily on next search.
   clear_embedding(insight);
+  insight.updated = Some(Utc::now());
+
+  // Keep the previous version so the edit can be reverted
+  history::record(&existing_file_path, &insight.topic, &insight.name)?;
 
   // Delete the existing file FIRST to ensure cross-platform compatibility.
   // Prevents issues on case-insensitive filesystems
@@ -181,24 +346,63 @@ pub fn update(
   Ok(())
 }
 
+/// Move an insight to a new topic and/or name, taking its revision history
+/// along. The embedded text includes the topic and name, so the embedding is cleared.
+pub fn rename(insight: &Insight, new_topic: &str, new_name: &str) -> Result<Insight> {
+  check_id(new_topic, new_name)?;
+  let existing_file_path = existing_insight_path(insight)?;
+  check_insight_exists(&existing_file_path, &insight.topic, &insight.name)?;
+
+  let mut moved = insight.clone();
+  moved.topic = new_topic.to_string();
+  moved.name = new_name.to_string();
+  moved.scope = scope::of_path(&existing_file_path);
+
+  // A case-only rename resolves to the same file, which is not a conflict
+  let new_file_path = file_path(&moved)?;
+  let same_file = new_file_path.to_string_lossy().to_lowercase()
+    == existing_file_path.to_string_lossy().to_lowercase();
+  if !same_file {
+    check_insight_is_new(&new_file_path, new_topic, new_name)?;
+  }
+
+  clear_embedding(&mut moved);
+  moved.updated = Some(Utc::now());
+
+  fs::remove_file(&existing_file_path)?;
+  cleanup_empty_dir(&existing_file_path)?;
+  index::forget(moved.scope, &insight.topic, &insight.name)?;
+
+  write_to_file(&moved, &new_file_path)?;
+
+  // Only move the history once the insight itself has moved
+  history::relocate(moved.scope, &insight.topic, &insight.name, new_topic, new_name)?;
+
+  Ok(moved)
+}
+
 pub fn clear_embedding(insight: &mut Insight) {
   insight.embedding_version = None;
   insight.embedding = None;
-  insight.embedding_text = None;
+  insight.embedding_hash = None;
   insight.embedding_computed = None;
 }
 
 pub fn delete(insight: &Insight) -> Result<()> {
   let file_path = file_path(insight)?;
   check_insight_exists(&file_path, &insight.topic, &insight.name)?;
+  history::record(&file_path, &insight.topic, &insight.name)?;
   fs::remove_file(&file_path)?;
   cleanup_empty_dir(&file_path)?;
-  Ok(())
+  index::forget(insight.scope, &insight.topic, &insight.name)
 }
 
 pub fn get_insights_root() -> Result<PathBuf> {
+  if let Some(root) = scope::root_override() {
+    return Ok(root);
+  }
   // Allow tests or callers to override the root directory via env var
-  if let Ok(custom_root) = std::env::var("INSIGHTS_ROOT") {
+  if let Ok(custom_root) = std::env::var(scope::GLOBAL_ROOT_ENV) {
     return Ok(PathBuf::from(custom_root));
   }
 
@@ -206,25 +410,28 @@ pub fn get_insights_root() -> Result<PathBuf> {
   Ok(home.join(".kernelle").join("persistent").join("insights"))
 }
 
-pub fn get_valid_insights_dir() -> Result<std::path::PathBuf> {
-  let insights_dir = get_insights_root()?;
-  if !insights_dir.exists() {
-    println!("No insights found. Create some insights first!");
-    return Err(anyhow!("No insights directory found"));
+/// Fail with a hint when no store has been created yet
+pub fn check_insights_exist() -> Result<()> {
+  if !scope::stores()?.iter().any(|(_, root)| root.exists()) {
+    return Err(anyhow!("No insights found. Create some insights first!"));
   }
-  Ok(insights_dir)
+  Ok(())
 }
 
 pub fn parse_insight_with_metadata(content: &str) -> Result<(InsightMetaData, String)> {
-  if let Ok((frontmatter_section, body)) = split_frontmatter_content(content) {
-    if let Ok(result) = parse_yaml_format(frontmatter_section, body) {
-      Ok(result)
+  let (mut frontmatter, details) =
+    if let Ok((frontmatter_section, body)) = split_frontmatter_content(content) {
+      if let Ok(result) = parse_yaml_format(frontmatter_section, body) {
+        result
+      } else {
+        parse_legacy_format(frontmatter_section, body)
+      }
     } else {
-      Ok(parse_legacy_format(frontmatter_section, body))
-    }
-  } else {
-    Ok(parse_legacy_format_no_frontmatter(content))
-  }
+      parse_legacy_format_no_frontmatter(content)
+    };
+
+  frontmatter.links = links::parse(&format!("{}\n{}", frontmatter.overview, details));
+  Ok((frontmatter, details))
 }
 
 fn split_frontmatter_content(content: &str) -> Result<(&str, &str)> {
@@ -264,10 +471,17 @@ fn parse_legacy_format_no_frontmatter(content: &str) -> (InsightMetaData, String
     topic: "".to_string(),
     name: "".to_string(),
     overview,
+    created: None,
+    updated: None,
+    author: None,
+    source: None,
+    origin: None,
+    tags: Vec::new(),
     embedding_version: None,
     embedding: None,
-    embedding_text: None,
+    embedding_hash: None,
     embedding_computed: None,
+    links: Vec::new(),
   };
 
   (frontmatter, details)
@@ -281,92 +495,134 @@ fn parse_legacy_format(frontmatter_section: &str, body: &str) -> (InsightMetaDat
     topic: "".to_string(),
     name: "".to_string(),
     overview,
+    created: None,
+    updated: None,
+    author: None,
+    source: None,
+    origin: None,
+    tags: Vec::new(),
     embedding_version: None,
     embedding: None,
-    embedding_text: None,
+    embedding_hash: None,
     embedding_computed: None,
+    links: Vec::new(),
   };
 
   (frontmatter, details)
 }
 
+/// The details under the `# Details` marker. Headings after it are content.
 fn clean_body_content(body: &str) -> String {
-  body
-    .lines()
-    .skip_while(|line| line.trim().is_empty() || line.starts_with('#'))
-    .collect::<Vec<_>>()
-    .join("\n")
-    .trim()
-    .to_string()
+  let mut lines = body.lines().skip_while(|line| line.trim().is_empty()).peekable();
+  if lines.peek().is_some_and(|line| line.trim() == DETAILS_MARKER) {
+    lines.next();
+  }
+  lines.collect::<Vec<_>>().join("\n").trim().to_string()
 }
 
+/// Topics across all stores
 pub fn get_topics() -> Result<Vec<String>> {
-  let insights_root = get_insights_root()?;
+  let mut topics = Vec::new();
 
+  for (_, root) in scope::stores()? {
+    for topic in get_topics_in(&root)? {
+      if !topics.contains(&topic) {
+        topics.push(topic);
+      }
+    }
+  }
+
+  topics.sort();
+  Ok(topics)
+}
+
+fn get_topics_in(insights_root: &std::path::Path) -> Result<Vec<String>> {
   if !insights_root.exists() {
     return Ok(vec![]);
   }
 
   let mut topics = Vec::new();
 
-  for entry in fs::read_dir(&insights_root)? {
+  for entry in fs::read_dir(insights_root)? {
     let entry = entry?;
     if entry.file_type()?.is_dir() {
-      if let Some(name) = entry.file_name().to_str() {
+      // Hidden directories hold bookkeeping such as the vector index
+      if let Some(name) = entry.file_name().to_str().filter(|name| !name.starts_with('.')) {
         topics.push(name.to_string());
       }
     }
   }
 
-  topics.sort();
   Ok(topics)
 }
 
 pub fn get_insights(topic_filter: Option<&str>) -> Result<Vec<Insight>> {
-  let search_paths = get_search_paths(topic_filter)?;
-  let mut all_insights = Vec::new();
-
-  for topic_path in search_paths {
-    let mut topic_insights = collect_insights_from_topic(&topic_path)?;
-    all_insights.append(&mut topic_insights);
-  }
+  let mut all_insights = get_insight_paths(topic_filter)?
+    .iter()
+    .map(|path| load_from_path(path))
+    .collect::<Result<Vec<_>>>()?;
 
   all_insights.sort_by_key(|insight| insight.name.clone());
   Ok(all_insights)
 }
 
-fn get_search_paths(topic_filter: Option<&str>) -> Result<Vec<std::path::PathBuf>> {
-  let insights_root = get_insights_root()?;
+/// Files of every visible insight. An insight in a higher-precedence store
+/// hides one with the same topic/name in a lower one.
+pub fn get_insight_paths(topic_filter: Option<&str>) -> Result<Vec<PathBuf>> {
+  let mut seen = HashSet::new();
+  let mut paths = Vec::new();
+
+  for (_, root) in scope::stores()? {
+    for topic_path in get_search_paths(&root, topic_filter)? {
+      for path in collect_insight_paths_from_topic(&topic_path)? {
+        let Some(insight_name) = extract_insight_name(&path) else {
+          continue;
+        };
+
+        let key = format!(
+          "{}/{}",
+          extract_topic_name(&topic_path).to_lowercase(),
+          insight_name.to_lowercase()
+        );
+        if seen.insert(key) {
+          paths.push(path);
+        }
+      }
+    }
+  }
+
+  Ok(paths)
+}
 
+fn get_search_paths(
+  insights_root: &std::path::Path,
+  topic_filter: Option<&str>,
+) -> Result<Vec<std::path::PathBuf>> {
   if let Some(topic) = topic_filter {
     return Ok(vec![insights_root.join(topic)]);
   }
 
-  let paths = get_topics()?.into_iter().map(|topic| insights_root.join(topic)).collect();
+  let paths =
+    get_topics_in(insights_root)?.into_iter().map(|topic| insights_root.join(topic)).collect();
   Ok(paths)
 }
 
-fn collect_insights_from_topic(topic_path: &std::path::Path) -> Result<Vec<Insight>> {
+fn collect_insight_paths_from_topic(topic_path: &std::path::Path) -> Result<Vec<PathBuf>> {
   if !topic_path.exists() {
     return Ok(Vec::new());
   }
 
-  let topic_name = extract_topic_name(topic_path);
-  let mut insights = Vec::new();
+  let mut paths = Vec::new();
 
   for entry in fs::read_dir(topic_path)? {
     let path = entry?.path();
 
-    if !is_insight_file(&path) {
-      continue;
-    }
-
-    if let Some(insight_name) = extract_insight_name(&path) {
-      insights.push(load(topic_name, &insight_name)?);
+    if is_insight_file(&path) {
+      paths.push(path);
     }
   }
 
-  Ok(insights)
+  Ok(paths)
 }
 
 pub fn is_insight_file(path: &std::path::Path) -> bool {
@@ -383,27 +639,38 @@ pub fn is_insight_file(path: &std::path::Path) -> bool {
 
 // Shared helper functions used by multiple public functions
 
-fn make_insight_path(topic: &str, name: &str) -> Result<std::path::PathBuf> {
-  let root = get_insights_root()?;
+/// File of an insight that is already stored: in its own store if it is there,
+/// otherwise wherever `topic/name` resolves to
+fn existing_insight_path(insight: &Insight) -> Result<std::path::PathBuf> {
+  let own_path = file_path(insight)?;
+  if own_path.exists() {
+    return Ok(own_path);
+  }
+  make_insight_path(&insight.topic, &insight.name)
+}
 
-  // Try normalized case first.
+fn make_insight_path(topic: &str, name: &str) -> Result<std::path::PathBuf> {
   let normalized_topic = topic.to_lowercase();
   let normalized_name = name.to_lowercase();
-  let normalized_path = root.join(&normalized_topic).join(format!("{normalized_name}.insight.md"));
 
-  // If normalized path exists, use it
-  if normalized_path.exists() {
-    return Ok(normalized_path);
-  }
+  // Stores are checked in precedence order, so project insights shadow global ones
+  for (_, root) in scope::stores()? {
+    // Try normalized case first.
+    let normalized_path =
+      root.join(&normalized_topic).join(format!("{normalized_name}.insight.md"));
+    if normalized_path.exists() {
+      return Ok(normalized_path);
+    }
 
-  // Fallback to original case for backwards compatibility with legacy insights
-  let legacy_path = root.join(topic).join(format!("{name}.insight.md"));
-  if legacy_path.exists() {
-    return Ok(legacy_path);
+    // Fallback to original case for backwards compatibility with legacy insights
+    let legacy_path = root.join(topic).join(format!("{name}.insight.md"));
+    if legacy_path.exists() {
+      return Ok(legacy_path);
+    }
   }
 
-  // If neither exists, return the normalized path (for error messages and new file creation)
-  Ok(normalized_path)
+  // If neither exists, return the normalized global path (for error messages and new file creation)
+  Ok(get_insights_root()?.join(&normalized_topic).join(format!("{normalized_name}.insight.md")))
 }
 
 fn ensure_parent_dir_exists(path: &std::path::Path) -> Result<()> {
@@ -413,6 +680,17 @@ fn ensure_parent_dir_exists(path: &std::path::Path) -> Result<()> {
   Ok(())
 }
 
+/// Reject a topic or name that would leave its store or clash with the
+/// store's hidden directories. Empty topics and names are allowed.
+pub fn check_id(topic: &str, name: &str) -> Result<()> {
+  for (field, value) in [("topic", topic), ("name", name)] {
+    if value.contains(['/', '\\']) || value.starts_with('.') {
+      return Err(anyhow!("Invalid {} '{}'", field, value));
+    }
+  }
+  Ok(())
+}
+
 fn check_insight_is_new(path: &std::path::Path, topic: &str, name: &str) -> Result<()> {
   if path.exists() {
     return Err(anyhow!("Insight {}/{} already exists", topic, name));
@@ -427,7 +705,7 @@ fn check_insight_exists(path: &std::path::Path, topic: &str, name: &str) -> Resu
   Ok(())
 }
 
-fn parse_insight_from_content(topic: &str, name: &str, content: &str) -> Result<Insight> {
+pub fn parse_insight_from_content(topic: &str, name: &str, content: &str) -> Result<Insight> {
   let (fm, details) = parse_insight_with_metadata(content)?;
   Ok(Insight {
     // Use topic and name from frontmatter to preserve original case.
@@ -436,9 +714,16 @@ fn parse_insight_from_content(topic: &str, name: &str, content: &str) -> Result<
     name: if !fm.name.is_empty() { fm.name } else { name.to_string() },
     overview: fm.overview,
     details,
+    created: fm.created,
+    updated: fm.updated,
+    author: fm.author,
+    source: fm.source,
+    origin: fm.origin,
+    tags: fm.tags,
+    scope: Scope::Global,
     embedding_version: fm.embedding_version,
     embedding: fm.embedding,
-    embedding_text: fm.embedding_text,
+    embedding_hash: fm.embedding_hash,
     embedding_computed: fm.embedding_computed,
   })
 }
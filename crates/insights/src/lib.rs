@@ -3,13 +3,35 @@
 //! A high-performance knowledge management system providing structured insight
 //! storage and retrieval for development workflows and team collaboration.
 
+pub mod archive;
+pub mod bm25;
 pub mod commands;
 #[cfg(feature = "neural")]
+pub mod daemon;
+#[cfg(feature = "neural")]
 pub mod embedding_client;
 #[cfg(feature = "neural")]
 pub mod embedding_model;
+pub mod eval;
+pub mod history;
+pub mod index;
+pub mod ingest;
 pub mod insight;
+pub mod links;
+#[cfg(feature = "neural")]
+pub mod model_registry;
+pub mod output;
+pub mod provenance;
+pub mod query;
+#[cfg(feature = "neural")]
+pub mod query_cache;
+pub mod ranking;
+#[cfg(feature = "neural")]
+pub mod related;
+pub mod scope;
 pub mod search;
 #[cfg(feature = "semantic")]
 pub mod semantic;
 pub mod similarity;
+pub mod snippet;
+pub mod stem;
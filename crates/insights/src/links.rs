@@ -0,0 +1,117 @@
+//! Cross-links between insights.
+//!
+//! Insight text can reference other insights with `[[topic/name]]`, optionally
+//! followed by a display label: `[[topic/name|label]]`. Links are matched
+//! case-insensitively, the same way insight files are stored.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::insight::{self, Insight};
+
+const LINK_START: &str = "[[";
+const LINK_END: &str = "]]";
+const LABEL_SEPARATOR: char = '|';
+
+/// A `[[topic/name]]` reference to another insight
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InsightLink {
+  pub topic: String,
+  pub name: String,
+}
+
+impl InsightLink {
+  /// Whether this link refers to the insight `topic/name`
+  pub fn targets(&self, topic: &str, name: &str) -> bool {
+    self.topic.eq_ignore_ascii_case(topic) && self.name.eq_ignore_ascii_case(name)
+  }
+}
+
+impl std::fmt::Display for InsightLink {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}/{}", self.topic, self.name)
+  }
+}
+
+/// Extract the links in `text`, in order of first appearance and without duplicates
+pub fn parse(text: &str) -> Vec<InsightLink> {
+  let mut links: Vec<InsightLink> = Vec::new();
+  let mut rest = text;
+
+  while let Some(start) = rest.find(LINK_START) {
+    let after_start = &rest[start + LINK_START.len()..];
+    let Some(end) = after_start.find(LINK_END) else {
+      break;
+    };
+
+    if let Some(link) = parse_target(&after_start[..end]) {
+      if !links.iter().any(|existing| existing.targets(&link.topic, &link.name)) {
+        links.push(link);
+      }
+    }
+    rest = &after_start[end + LINK_END.len()..];
+  }
+
+  links
+}
+
+fn parse_target(inner: &str) -> Option<InsightLink> {
+  let target = inner.split(LABEL_SEPARATOR).next()?.trim();
+  let (topic, name) = target.split_once('/')?;
+  let (topic, name) = (topic.trim(), name.trim());
+
+  if topic.is_empty() || name.is_empty() || name.contains('/') || target.contains('\n') {
+    return None;
+  }
+
+  Some(InsightLink { topic: topic.to_string(), name: name.to_string() })
+}
+
+/// Links from an insight's overview and details
+pub fn outgoing(insight: &Insight) -> Vec<InsightLink> {
+  parse(&format!("{}\n{}", insight.overview, insight.details))
+}
+
+/// Whether the insight a link points to exists
+pub fn resolves(link: &InsightLink) -> bool {
+  insight::load(&link.topic, &link.name).is_ok()
+}
+
+/// Insights that link to `topic/name`
+pub fn backlinks(topic: &str, name: &str) -> Result<Vec<Insight>> {
+  let mut sources = insight::get_insights(None)?;
+  sources.retain(|source| {
+    !(source.topic.eq_ignore_ascii_case(topic) && source.name.eq_ignore_ascii_case(name))
+      && outgoing(source).iter().any(|link| link.targets(topic, name))
+  });
+  Ok(sources)
+}
+
+/// Insights other than `topic/name` whose text refers to it, as a link, as
+/// `topic/name` or by its bare name
+pub fn mentions(topic: &str, name: &str) -> Result<Vec<Insight>> {
+  let reference = format!("{topic}/{name}").to_lowercase();
+  let name = name.to_lowercase();
+
+  let mut sources = insight::get_insights(None)?;
+  sources.retain(|source| {
+    let text = format!("{}\n{}", source.overview, source.details).to_lowercase();
+    // An insight of the same name in another topic naming itself is no reference
+    let by_name = !source.name.eq_ignore_ascii_case(&name) && mentions_word(&text, &name);
+    !(source.topic.eq_ignore_ascii_case(topic) && source.name.eq_ignore_ascii_case(&name))
+      && (text.contains(&reference) || by_name)
+  });
+  Ok(sources)
+}
+
+/// Whether `word` occurs in `text` on its own rather than inside a longer
+/// name, e.g. "cache" in "see cache." but not in "cache-strategy"
+fn mentions_word(text: &str, word: &str) -> bool {
+  let is_name_char = |c: char| c.is_alphanumeric() || c == '-' || c == '_';
+  !word.is_empty()
+    && text.match_indices(word).any(|(start, _)| {
+      let before = text[..start].chars().next_back();
+      let after = text[start + word.len()..].chars().next();
+      !before.is_some_and(is_name_char) && !after.is_some_and(is_name_char)
+    })
+}
@@ -1,16 +1,43 @@
 use anyhow::Result;
 use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
 
+use archive::ConflictStrategy;
+use output::OutputFormat;
+use scope::Scope;
+
+mod archive;
+mod bm25;
 mod commands;
 #[cfg(feature = "neural")]
+mod daemon;
+#[cfg(feature = "neural")]
 mod embedding_client;
 #[cfg(feature = "neural")]
 mod embedding_model;
+mod eval;
+mod history;
+mod index;
+mod ingest;
 mod insight;
+mod links;
+#[cfg(feature = "neural")]
+mod model_registry;
+mod output;
+mod provenance;
+mod query;
+#[cfg(feature = "neural")]
+mod query_cache;
+mod ranking;
+#[cfg(feature = "neural")]
+mod related;
+mod scope;
 mod search;
 #[cfg(feature = "semantic")]
 mod semantic;
 mod similarity;
+mod snippet;
+mod stem;
 
 #[derive(Parser)]
 #[command(name = "insights")]
@@ -19,10 +46,53 @@ mod similarity;
 )]
 #[command(version = concat!(env!("CARGO_PKG_VERSION"), ", courtesy of kernelle"))]
 struct Cli {
+  /// Output format for command results
+  #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+  format: OutputFormat,
   #[command(subcommand)]
   command: Command,
 }
 
+/// Embedding model management
+#[cfg(feature = "neural")]
+#[derive(Subcommand)]
+enum ModelCommand {
+  /// List registered embedding models and whether they are installed
+  List,
+  /// Copy a model file into the model cache so it can be used offline
+  Install {
+    /// Model to install (defaults to the active model)
+    name: Option<String>,
+    /// Local ONNX model file to install
+    #[arg(long)]
+    from: PathBuf,
+    /// Expected SHA-256 of the file (defaults to the checksum in models.yaml)
+    #[arg(long)]
+    sha256: Option<String>,
+  },
+}
+
+#[cfg(feature = "neural")]
+#[derive(Subcommand)]
+enum DaemonCommand {
+  /// Start the embedding daemon in the background
+  Start {
+    /// Seconds without a connection before the daemon exits (0 keeps it running)
+    #[arg(long)]
+    idle_timeout: Option<u64>,
+  },
+  /// Stop the running embedding daemon
+  Stop,
+  /// Show whether the daemon is running, its model, uptime and requests served
+  Status,
+  /// Stop the daemon and start a fresh one, e.g. after changing models
+  Restart {
+    /// Seconds without a connection before the daemon exits (0 keeps it running)
+    #[arg(long)]
+    idle_timeout: Option<u64>,
+  },
+}
+
 /// Common insight identifier arguments
 #[derive(Args)]
 struct InsightId {
@@ -43,12 +113,24 @@ enum Command {
     overview: String,
     /// Detailed content of the insight
     details: String,
+    /// Tag to attach to the insight (repeatable)
+    #[arg(long = "tag")]
+    tags: Vec<String>,
+    /// Store to add the insight to
+    #[arg(long, value_enum, default_value_t = Scope::Global)]
+    scope: Scope,
+    /// Add the insight even if it nearly duplicates an existing one
+    #[arg(long)]
+    force: bool,
   },
   /// Search through all insights for matching content
   Search {
     #[command(flatten)]
     options: search::SearchCommandOptions,
-    /// Search terms (space-separated)
+    /// Search terms (space-separated). Supports "quoted phrases", AND/OR/NOT,
+    /// `-exclude`, (groups), topic:/name:/overview:/details:/tag: prefixes and
+    /// `*` or `?` wildcards. Quote the query, or give it after `--`, when a
+    /// term starts with a dash
     #[arg(required = true)]
     terms: Vec<String>,
   },
@@ -59,6 +141,9 @@ enum Command {
     /// Show only the overview section
     #[arg(short, long)]
     overview: bool,
+    /// Also show the insights this one links to
+    #[arg(short, long)]
+    expand: bool,
   },
   /// List insights in a topic or all topics
   List {
@@ -67,6 +152,9 @@ enum Command {
     /// Show overview content for each insight
     #[arg(short, long)]
     verbose: bool,
+    /// Only list insights with this tag (repeatable, all must match)
+    #[arg(long = "tag")]
+    tags: Vec<String>,
   },
   /// Update an existing insight
   Update {
@@ -78,6 +166,9 @@ enum Command {
     /// New details content
     #[arg(short, long)]
     details: Option<String>,
+    /// Replace the insight's tags (repeatable)
+    #[arg(long = "tag")]
+    tags: Option<Vec<String>>,
   },
   /// Delete an insight
   Delete {
@@ -87,43 +178,408 @@ enum Command {
     #[arg(short, long)]
     force: bool,
   },
+  /// Rename an insight or move it to another topic
+  Mv {
+    #[command(flatten)]
+    id: InsightId,
+    /// Topic to move the insight to
+    new_topic: String,
+    /// New name for the insight (defaults to the current name)
+    new_name: Option<String>,
+  },
+  /// Write insights to a portable bundle file
+  Export {
+    /// Only export insights in this topic
+    #[arg(short, long)]
+    topic: Option<String>,
+    /// File to write the bundle to
+    #[arg(long)]
+    out: PathBuf,
+    /// Leave embeddings out of the bundle; they are recomputed on demand
+    #[arg(long)]
+    strip_embeddings: bool,
+  },
+  /// Import insights from a bundle file
+  Import {
+    /// Bundle file written by `export`
+    file: PathBuf,
+    /// How to handle insights that already exist
+    #[arg(long, value_enum, default_value_t = ConflictStrategy::Skip)]
+    on_conflict: ConflictStrategy,
+    /// Store to import the insights into
+    #[arg(long, value_enum, default_value_t = Scope::Global)]
+    scope: Scope,
+  },
+  /// Turn a directory of Markdown notes into insights
+  Ingest {
+    /// Directory to read; folders become topics and files become insights
+    dir: PathBuf,
+    /// Store to write the insights to
+    #[arg(long, value_enum, default_value_t = Scope::Global)]
+    scope: Scope,
+  },
+  /// Show an insight's outgoing links and backlinks
+  Links {
+    #[command(flatten)]
+    id: InsightId,
+  },
+  /// Show the stored revisions of an insight
+  History {
+    #[command(flatten)]
+    id: InsightId,
+  },
+  /// Show changes between revisions of an insight
+  Diff {
+    #[command(flatten)]
+    id: InsightId,
+    /// Revision to compare from (defaults to the latest revision)
+    #[arg(long)]
+    from: Option<u32>,
+    /// Revision to compare to (defaults to the current insight)
+    #[arg(long)]
+    to: Option<u32>,
+  },
+  /// Restore an insight to a previous revision
+  Revert {
+    #[command(flatten)]
+    id: InsightId,
+    /// Revision to restore
+    #[arg(long)]
+    to: u32,
+  },
   /// List all available topics
   Topics,
+  /// Measure search quality against a fixture file of queries and relevant insights
+  Eval {
+    /// YAML file with the insights to search and the queries to run on them
+    fixtures: PathBuf,
+    /// Number of top results scored per query
+    #[arg(short, default_value_t = eval::DEFAULT_K)]
+    k: usize,
+    /// Search mode to evaluate (repeatable, defaults to every mode)
+    #[arg(long = "mode", value_enum)]
+    modes: Vec<eval::Mode>,
+    /// Rank with deterministic hashed word embeddings instead of the embedding model
+    #[cfg(feature = "neural")]
+    #[arg(long)]
+    hashing_embeddings: bool,
+  },
   /// Recompute embeddings for all insights
   #[cfg(feature = "neural")]
   Index {
     /// Force recompute even for insights that already have embeddings
     #[arg(short, long)]
     force: bool,
+    /// Number of insights to embed per request to the embedding daemon
+    #[arg(long, default_value_t = commands::DEFAULT_INDEX_BATCH_SIZE)]
+    batch_size: usize,
+    /// Report missing, stale and outdated embeddings without writing; fails if there are any
+    #[arg(long, conflicts_with = "force")]
+    check: bool,
+  },
+  /// Show the insights most similar to an insight
+  #[cfg(feature = "neural")]
+  Related {
+    #[command(flatten)]
+    id: InsightId,
+    /// Number of related insights to show
+    #[arg(short, long, default_value_t = related::DEFAULT_LIMIT)]
+    limit: usize,
+  },
+  /// Find insights that store the same knowledge, across all topics
+  #[cfg(feature = "neural")]
+  Duplicates {
+    /// Similarity (0 to 1) at which insights count as near-duplicates
+    #[arg(long, default_value_t = related::DEFAULT_DUPLICATE_THRESHOLD)]
+    threshold: f32,
+  },
+  /// Manage the local embedding models
+  #[cfg(feature = "neural")]
+  Model {
+    #[command(subcommand)]
+    command: ModelCommand,
+  },
+  /// Manage the background embedding daemon
+  #[cfg(feature = "neural")]
+  Daemon {
+    #[command(subcommand)]
+    command: DaemonCommand,
   },
 }
 
-fn handle(command: Command) -> Result<()> {
+fn handle(command: Command, format: OutputFormat) -> Result<()> {
   match command {
-    Command::Add { id, overview, details } => {
-      commands::add_insight(&id.topic, &id.name, &overview, &details)
+    Command::Add { id, overview, details, tags, scope, force } => {
+      let added = commands::add_insight(&commands::NewInsight {
+        tags: &tags,
+        scope,
+        force,
+        ..commands::NewInsight::new(&id.topic, &id.name, &overview, &details)
+      })?;
+      #[cfg(feature = "neural")]
+      output::warn_similar_insights(&added.insight, &added.similar);
+      let insight = added.insight;
+      output::emit(
+        format,
+        || output::insight_record(&insight, None),
+        || output::print_added(&insight),
+      )
     }
     Command::Search { options, terms } => {
       let opts = search::SearchOptions::from(&options);
-      let results = search::search(&terms, &opts)?;
-      search::display_results(&results, &terms, opts.overview_only);
+      let outcome = search::search_with_outcome(&terms, &opts)?;
+      if let Some(reason) = &outcome.neural_unavailable {
+        output::warn_neural_unavailable(reason);
+      }
+      let results = outcome.results;
+      output::emit(
+        format,
+        || output::search_page_record(&results, outcome.total, opts.offset, opts.full),
+        || {
+          search::display_results(&results, &terms, opts.overview_only);
+          search::display_page_summary(results.len(), outcome.total, opts.offset);
+        },
+      )
+    }
+    Command::Get { id, overview, expand } => {
+      let insight = commands::get_insight(&id.topic, &id.name)?;
+      if !expand {
+        return output::emit(
+          format,
+          || output::insight_record(&insight, None),
+          || output::print_insight(&insight, overview),
+        );
+      }
+
+      let linked = commands::linked_insights(&insight);
+      output::emit(
+        format,
+        || output::expanded_insight_record(&insight, &linked),
+        || output::print_expanded_insight(&insight, &linked, overview),
+      )
+    }
+    Command::List { topic, verbose, tags } => {
+      let insights = commands::list_insights(topic.as_deref(), &tags)?;
+      output::emit(
+        format,
+        || output::insight_records(&insights),
+        || output::print_insights(&insights, topic.as_deref(), verbose),
+      )
+    }
+    Command::Update { id, overview, details, tags } => {
+      let insight = commands::update_insight(
+        &id.topic,
+        &id.name,
+        overview.as_deref(),
+        details.as_deref(),
+        tags.as_deref(),
+      )?;
+      output::emit(
+        format,
+        || output::insight_record(&insight, None),
+        || output::print_updated(&insight),
+      )
+    }
+    Command::Delete { id, force } => {
+      let insight = commands::delete_insight(&id.topic, &id.name, force)?;
+      output::emit(
+        format,
+        || output::insight_record(&insight, None),
+        || output::print_deleted(&insight),
+      )?;
+
+      let broken = commands::broken_backlinks(&id.topic, &id.name)?;
+      output::warn_stale_references(&insight.topic, &insight.name, &broken);
+      Ok(())
+    }
+    Command::Mv { id, new_topic, new_name } => {
+      let insight = commands::move_insight(&id.topic, &id.name, &new_topic, new_name.as_deref())?;
+      output::emit(
+        format,
+        || output::insight_record(&insight, None),
+        || output::print_moved(&id.topic, &id.name, &insight),
+      )?;
+
+      let stale = commands::stale_references(&id.topic, &id.name)?;
+      output::warn_stale_references(&id.topic, &id.name, &stale);
       Ok(())
     }
-    Command::Get { id, overview } => commands::get_insight(&id.topic, &id.name, overview),
-    Command::List { topic, verbose } => commands::list_insights(topic.as_deref(), verbose),
-    Command::Update { id, overview, details } => {
-      commands::update_insight(&id.topic, &id.name, overview.as_deref(), details.as_deref())
+    Command::Export { topic, out, strip_embeddings } => {
+      let summary = commands::export_insights(topic.as_deref(), strip_embeddings, &out)?;
+      output::emit(
+        format,
+        || Ok(output::export_record(&summary, &out)),
+        || output::print_export_summary(&summary, &out),
+      )
+    }
+    Command::Import { file, on_conflict, scope } => {
+      let results = commands::import_insights(&file, on_conflict, scope)?;
+      output::emit(
+        format,
+        || Ok(output::import_records(&results)),
+        || output::print_import_results(&results),
+      )
+    }
+    Command::Ingest { dir, scope } => {
+      let results = commands::ingest_directory(&dir, scope)?;
+      output::emit(
+        format,
+        || Ok(output::ingest_records(&results)),
+        || output::print_ingest_results(&results),
+      )
+    }
+    Command::Links { id } => {
+      let links = commands::insight_links(&id.topic, &id.name)?;
+      output::emit(format, || Ok(output::links_record(&links)), || output::print_links(&links))
+    }
+    Command::History { id } => {
+      let revisions = commands::insight_history(&id.topic, &id.name)?;
+      output::emit(
+        format,
+        || Ok(output::revision_records(&revisions)),
+        || output::print_history(&revisions),
+      )
+    }
+    Command::Diff { id, from, to } => {
+      let diff = commands::diff_insight(&id.topic, &id.name, from, to)?;
+      output::emit(format, || Ok(output::diff_record(&diff)), || output::print_diff(&diff))
+    }
+    Command::Revert { id, to } => {
+      let insight = commands::revert_insight(&id.topic, &id.name, to)?;
+      output::emit(
+        format,
+        || output::insight_record(&insight, None),
+        || output::print_reverted(&insight, to),
+      )
+    }
+    Command::Topics => {
+      let topics = commands::list_topics()?;
+      output::emit(format, || Ok(output::topic_records(&topics)), || output::print_topics(&topics))
+    }
+    Command::Eval {
+      fixtures,
+      k,
+      modes,
+      #[cfg(feature = "neural")]
+      hashing_embeddings,
+    } => {
+      let options = eval::EvalOptions {
+        k,
+        modes,
+        #[cfg(feature = "neural")]
+        hashing_embeddings,
+      };
+      let report = commands::evaluate_search(&fixtures, &options)?;
+      output::emit(
+        format,
+        || Ok(output::eval_record(&report)),
+        || output::print_eval_report(&report),
+      )
+    }
+    #[cfg(feature = "neural")]
+    Command::Index { check: true, .. } => {
+      let drift = commands::check_embeddings()?;
+      output::emit(
+        format,
+        || Ok(output::drift_records(&drift)),
+        || output::print_embedding_drift(&drift),
+      )?;
+
+      if drift.is_empty() {
+        Ok(())
+      } else {
+        Err(anyhow::anyhow!("{} insights have outdated embeddings", drift.len()))
+      }
+    }
+    #[cfg(feature = "neural")]
+    Command::Index { force, batch_size, .. } => {
+      let summary = commands::index_insights(force, batch_size, output::print_index_progress)?;
+      output::emit(
+        format,
+        || output::index_record(&summary),
+        || output::print_index_summary(&summary),
+      )?;
+
+      if summary.failed.is_empty() {
+        Ok(())
+      } else {
+        Err(anyhow::anyhow!("{} insights could not be embedded", summary.failed.len()))
+      }
+    }
+    #[cfg(feature = "neural")]
+    Command::Related { id, limit } => {
+      let neighbours = commands::related_insights(&id.topic, &id.name, limit)?;
+      output::emit(
+        format,
+        || output::neighbour_records(&neighbours),
+        || output::print_related(&id.topic, &id.name, &neighbours),
+      )
+    }
+    #[cfg(feature = "neural")]
+    Command::Duplicates { threshold } => {
+      let groups = commands::find_duplicates(threshold)?;
+      output::emit(
+        format,
+        || output::duplicate_records(&groups),
+        || output::print_duplicates(&groups),
+      )
     }
-    Command::Delete { id, force } => commands::delete_insight(&id.topic, &id.name, force),
-    Command::Topics => commands::list_topics(),
     #[cfg(feature = "neural")]
-    Command::Index { force } => commands::index_insights(force),
+    Command::Model { command: ModelCommand::List } => {
+      let models = commands::list_models()?;
+      output::emit(format, || Ok(output::model_records(&models)), || output::print_models(&models))
+    }
+    #[cfg(feature = "neural")]
+    Command::Model { command: ModelCommand::Install { name, from, sha256 } } => {
+      let installed = commands::install_model(name.as_deref(), &from, sha256.as_deref())?;
+      output::emit(
+        format,
+        || Ok(output::installed_model_record(&installed)),
+        || output::print_installed_model(&installed),
+      )
+    }
+    #[cfg(feature = "neural")]
+    Command::Daemon { command: DaemonCommand::Start { idle_timeout } } => {
+      let start = commands::start_daemon(idle_timeout)?;
+      output::emit(
+        format,
+        || Ok(output::daemon_record(Some(&start.status))),
+        || output::print_daemon_started(&start),
+      )
+    }
+    #[cfg(feature = "neural")]
+    Command::Daemon { command: DaemonCommand::Stop } => {
+      let stopped = commands::stop_daemon()?;
+      output::emit(
+        format,
+        || Ok(output::daemon_record(None)),
+        || output::print_daemon_stopped(stopped.as_ref()),
+      )
+    }
+    #[cfg(feature = "neural")]
+    Command::Daemon { command: DaemonCommand::Status } => {
+      let status = commands::daemon_status()?;
+      output::emit(
+        format,
+        || Ok(output::daemon_record(status.as_ref())),
+        || output::print_daemon_status(status.as_ref()),
+      )
+    }
+    #[cfg(feature = "neural")]
+    Command::Daemon { command: DaemonCommand::Restart { idle_timeout } } => {
+      let start = commands::restart_daemon(idle_timeout)?;
+      output::emit(
+        format,
+        || Ok(output::daemon_record(Some(&start.status))),
+        || output::print_daemon_started(&start),
+      )
+    }
   }
 }
 
 fn main() -> Result<()> {
   let cli = Cli::parse();
 
-  handle(cli.command)?;
+  handle(cli.command, cli.format)?;
   Ok(())
 }
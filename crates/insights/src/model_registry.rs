@@ -0,0 +1,404 @@
+//! Registry of the local ONNX sentence-transformer models insights can embed with.
+//!
+//! The built-in all-MiniLM-L6-v2 model is always available. Other models are
+//! registered in `~/.kernelle/models.yaml`:
+//!
+//! ```yaml
+//! default: bge-small
+//! models:
+//!   bge-small:
+//!     model: ~/models/bge-small/model.onnx
+//!     tokenizer: ~/models/bge-small/tokenizer.json
+//!     sha256: 4f0d5b...
+//!     pooling: cls
+//!     dimension: 384
+//!     max_tokens: 512
+//! ```
+//!
+//! Relative paths are resolved against the directory of the registry file.
+//! Models without a `model` path live in the managed cache under
+//! `~/.kernelle/models/<name>/` and are put there with `insights model install`;
+//! nothing is ever downloaded implicitly. `INSIGHTS_MODEL` selects a model for a
+//! single run, `INSIGHTS_MODELS_CONFIG` points at a different registry file and
+//! `INSIGHTS_MODEL_CACHE` at a different cache.
+
+use anyhow::{anyhow, Context, Result};
+use dirs::home_dir;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Name of the built-in model, also recorded as its embedding version
+pub const BUILTIN_MODEL: &str = "all-MiniLM-L6-v2";
+
+const BUILTIN_MODEL_URL: &str =
+  "https://huggingface.co/sentence-transformers/all-MiniLM-L6-v2/resolve/main/onnx/model.onnx";
+
+const MODEL_ENV: &str = "INSIGHTS_MODEL";
+const CONFIG_ENV: &str = "INSIGHTS_MODELS_CONFIG";
+const CACHE_ENV: &str = "INSIGHTS_MODEL_CACHE";
+const CONFIG_FILE: &str = "models.yaml";
+
+/// File name of a model in the cache, and of the checksum recorded next to it
+const CACHED_MODEL_FILE: &str = "model.onnx";
+const CHECKSUM_FILE: &str = "model.onnx.sha256";
+
+/// How token embeddings are combined into one sentence embedding
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Pooling {
+  /// Average of the token embeddings, ignoring padding
+  #[default]
+  Mean,
+  /// Embedding of the leading `[CLS]` token
+  Cls,
+}
+
+/// Everything needed to load and run one embedding model
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelSpec {
+  /// Registry name, recorded on every embedding as its version
+  pub name: String,
+  /// ONNX model file
+  pub model: PathBuf,
+  /// Where the model can be downloaded from, shown when it is not installed
+  pub url: Option<String>,
+  /// Expected SHA-256 of the model file
+  pub sha256: Option<String>,
+  /// `tokenizer.json` file; the built-in model's tokenizer is compiled in
+  pub tokenizer: Option<PathBuf>,
+  pub pooling: Pooling,
+  /// Length of the embeddings the model produces
+  pub dimension: usize,
+  /// Longest input, in tokens, the model accepts
+  pub max_tokens: usize,
+}
+
+/// A model entry as written in the registry file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ModelEntry {
+  #[serde(default)]
+  model: Option<PathBuf>,
+  #[serde(default)]
+  url: Option<String>,
+  #[serde(default)]
+  sha256: Option<String>,
+  tokenizer: PathBuf,
+  #[serde(default)]
+  pooling: Pooling,
+  dimension: usize,
+  max_tokens: usize,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RegistryFile {
+  #[serde(default)]
+  default: Option<String>,
+  #[serde(default)]
+  models: BTreeMap<String, ModelEntry>,
+}
+
+/// The built-in model together with any configured ones
+#[derive(Debug)]
+pub struct Registry {
+  pub default: Option<String>,
+  pub models: BTreeMap<String, ModelSpec>,
+}
+
+impl Registry {
+  /// The model selected by `INSIGHTS_MODEL`, the registry default, or the built-in model
+  pub fn active(&self) -> Result<&ModelSpec> {
+    let name = env::var(MODEL_ENV)
+      .ok()
+      .filter(|name| !name.is_empty())
+      .or_else(|| self.default.clone())
+      .unwrap_or_else(|| BUILTIN_MODEL.to_string());
+
+    self.get(&name)
+  }
+
+  pub fn get(&self, name: &str) -> Result<&ModelSpec> {
+    self.models.get(name).ok_or_else(|| {
+      let known: Vec<&str> = self.models.keys().map(String::as_str).collect();
+      anyhow!("Unknown embedding model '{}' (available: {})", name, known.join(", "))
+    })
+  }
+}
+
+pub fn builtin() -> Result<ModelSpec> {
+  Ok(ModelSpec {
+    name: BUILTIN_MODEL.to_string(),
+    model: cached_model_path(BUILTIN_MODEL)?,
+    url: Some(BUILTIN_MODEL_URL.to_string()),
+    sha256: None,
+    tokenizer: None,
+    pooling: Pooling::Mean,
+    dimension: 384,
+    max_tokens: 256,
+  })
+}
+
+pub fn registry_path() -> Result<PathBuf> {
+  if let Ok(custom_path) = env::var(CONFIG_ENV) {
+    return Ok(PathBuf::from(custom_path));
+  }
+
+  let home = home_dir().ok_or_else(|| anyhow!("Could not find home directory"))?;
+  Ok(home.join(".kernelle").join(CONFIG_FILE))
+}
+
+/// Directory installed models are kept in
+pub fn cache_dir() -> Result<PathBuf> {
+  if let Ok(custom_path) = env::var(CACHE_ENV) {
+    return Ok(PathBuf::from(custom_path));
+  }
+
+  let home = home_dir().ok_or_else(|| anyhow!("Could not find home directory"))?;
+  Ok(home.join(".kernelle").join("models"))
+}
+
+/// Where `insights model install` puts a model
+pub fn cached_model_path(name: &str) -> Result<PathBuf> {
+  Ok(cache_dir()?.join(name).join(CACHED_MODEL_FILE))
+}
+
+/// Load the registry. Without a registry file only the built-in model is available.
+pub fn load() -> Result<Registry> {
+  let path = registry_path()?;
+  let file = if path.exists() {
+    let content = fs::read_to_string(&path)?;
+    serde_yaml::from_str::<RegistryFile>(&content)
+      .with_context(|| format!("Invalid model registry {}", path.display()))?
+  } else {
+    RegistryFile::default()
+  };
+
+  let base = path.parent().unwrap_or(Path::new("."));
+  let mut models = BTreeMap::from([(BUILTIN_MODEL.to_string(), builtin()?)]);
+  for (name, entry) in file.models {
+    let spec = spec_from_entry(&name, entry, base)?;
+    models.insert(name, spec);
+  }
+
+  Ok(Registry { default: file.default, models })
+}
+
+/// The model embeddings should currently be computed with
+pub fn active_model() -> Result<ModelSpec> {
+  load()?.active().cloned()
+}
+
+fn spec_from_entry(name: &str, entry: ModelEntry, base: &Path) -> Result<ModelSpec> {
+  if name == BUILTIN_MODEL {
+    return Err(anyhow!("Model name '{}' is reserved for the built-in model", name));
+  }
+  if entry.dimension == 0 || entry.max_tokens == 0 {
+    return Err(anyhow!("Model '{}' needs a non-zero dimension and max_tokens", name));
+  }
+
+  let model = match &entry.model {
+    Some(model) => resolve(model, base),
+    None => cached_model_path(name)?,
+  };
+
+  Ok(ModelSpec {
+    name: name.to_string(),
+    model,
+    url: entry.url,
+    sha256: entry.sha256.map(|sha256| sha256.to_lowercase()),
+    tokenizer: Some(resolve(&entry.tokenizer, base)),
+    pooling: entry.pooling,
+    dimension: entry.dimension,
+    max_tokens: entry.max_tokens,
+  })
+}
+
+/// Expand `~/` and resolve relative paths against the registry file's directory
+fn resolve(path: &Path, base: &Path) -> PathBuf {
+  if let Ok(relative_to_home) = path.strip_prefix("~") {
+    if let Some(home) = home_dir() {
+      return home.join(relative_to_home);
+    }
+  }
+  if path.is_relative() {
+    base.join(path)
+  } else {
+    path.to_path_buf()
+  }
+}
+
+/// Whether a model's file is in place and matches its checksum
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelState {
+  /// The file matches the configured or recorded checksum
+  Verified,
+  /// The file exists, but there is no checksum to compare it with
+  Unverified,
+  /// The file no longer matches its checksum
+  Corrupt,
+  Missing,
+}
+
+/// The state of every registered model, for `insights model list`
+#[derive(Debug)]
+pub struct ModelStatus {
+  pub spec: ModelSpec,
+  pub state: ModelState,
+  pub active: bool,
+}
+
+/// A model copied into the cache by [`install`]
+#[derive(Debug)]
+pub struct InstalledModel {
+  pub name: String,
+  pub path: PathBuf,
+  pub sha256: String,
+}
+
+/// Make sure a model can be loaded without going to the network: the file
+/// has to exist and match its checksum, if one is known
+pub fn verify(spec: &ModelSpec) -> Result<()> {
+  ensure_installed(spec)?;
+  match state(spec)? {
+    ModelState::Verified | ModelState::Unverified => Ok(()),
+    ModelState::Corrupt => Err(anyhow!(
+      "Model file {} does not match its checksum; reinstall it with `insights model install {} --from <file>`",
+      spec.model.display(),
+      spec.name
+    )),
+    ModelState::Missing => Err(not_installed(spec)),
+  }
+}
+
+/// Cheap check that a model's file exists, without hashing it
+pub fn ensure_installed(spec: &ModelSpec) -> Result<()> {
+  if spec.model.exists() {
+    Ok(())
+  } else {
+    Err(not_installed(spec))
+  }
+}
+
+pub fn state(spec: &ModelSpec) -> Result<ModelState> {
+  if !spec.model.exists() {
+    return Ok(ModelState::Missing);
+  }
+
+  let Some(expected) = expected_checksum(spec)? else {
+    return Ok(ModelState::Unverified);
+  };
+  if sha256_file(&spec.model)? == expected {
+    Ok(ModelState::Verified)
+  } else {
+    Ok(ModelState::Corrupt)
+  }
+}
+
+pub fn list() -> Result<Vec<ModelStatus>> {
+  let registry = load()?;
+  let active = registry.active().ok().map(|spec| spec.name.clone());
+
+  registry
+    .models
+    .into_values()
+    .map(|spec| {
+      let state = state(&spec)?;
+      let active = active.as_deref() == Some(spec.name.as_str());
+      Ok(ModelStatus { spec, state, active })
+    })
+    .collect()
+}
+
+/// Copy a model file into the cache, checking it against `sha256` or the
+/// registry's checksum first. Defaults to the active model.
+pub fn install(name: Option<&str>, from: &Path, sha256: Option<&str>) -> Result<InstalledModel> {
+  let registry = load()?;
+  let spec = match name {
+    Some(name) => registry.get(name)?,
+    None => registry.active()?,
+  };
+
+  let target = cached_model_path(&spec.name)?;
+  if spec.model != target {
+    return Err(anyhow!(
+      "Model '{}' is loaded from {}; point models.yaml at the new file instead",
+      spec.name,
+      spec.model.display()
+    ));
+  }
+
+  let actual = sha256_file(from).with_context(|| format!("Cannot read {}", from.display()))?;
+  let expected = sha256.map(str::to_lowercase).or_else(|| spec.sha256.clone());
+  if let Some(expected) = expected {
+    if actual != expected {
+      return Err(anyhow!(
+        "Checksum mismatch for {}: expected {}, got {}",
+        from.display(),
+        expected,
+        actual
+      ));
+    }
+  }
+
+  // Copy next to the target first so an interrupted install never leaves a partial model
+  let dir = target.parent().expect("cached models are in a directory");
+  fs::create_dir_all(dir)?;
+  let partial = dir.join(format!("{CACHED_MODEL_FILE}.partial"));
+  fs::copy(from, &partial)?;
+  fs::rename(&partial, &target)?;
+  fs::write(dir.join(CHECKSUM_FILE), format!("{actual}\n"))?;
+
+  Ok(InstalledModel { name: spec.name.clone(), path: target, sha256: actual })
+}
+
+fn not_installed(spec: &ModelSpec) -> anyhow::Error {
+  let download = match &spec.url {
+    Some(url) => format!("download {url} and "),
+    None => String::new(),
+  };
+  anyhow!(
+    "Embedding model '{}' is not installed at {}; {}run `insights model install {} --from <file>`",
+    spec.name,
+    spec.model.display(),
+    download,
+    spec.name
+  )
+}
+
+/// The registry's checksum, or the one recorded when the model was installed
+fn expected_checksum(spec: &ModelSpec) -> Result<Option<String>> {
+  if spec.sha256.is_some() {
+    return Ok(spec.sha256.clone());
+  }
+
+  let recorded = spec.model.with_file_name(CHECKSUM_FILE);
+  if spec.model.file_name() != Some(CACHED_MODEL_FILE.as_ref()) || !recorded.exists() {
+    return Ok(None);
+  }
+  Ok(Some(fs::read_to_string(recorded)?.trim().to_lowercase()))
+}
+
+/// SHA-256 of a model's file, hashing it even when a checksum is recorded
+pub fn checksum(spec: &ModelSpec) -> Result<String> {
+  sha256_file(&spec.model)
+}
+
+/// What embeddings from a model are labelled with, and its query cache keyed
+/// by: the model's name and the checksum of its file, so a model replaced
+/// under the same name outdates both
+pub fn embedding_version(spec: &ModelSpec) -> Result<String> {
+  Ok(format!("{}@{}", spec.name, checksum(spec)?))
+}
+
+fn sha256_file(path: &Path) -> Result<String> {
+  let mut hasher = Sha256::new();
+  io::copy(&mut File::open(path)?, &mut hasher)?;
+  Ok(hasher.finalize().iter().map(|byte| format!("{byte:02x}")).collect())
+}
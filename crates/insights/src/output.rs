@@ -0,0 +1,1096 @@
+//! Output rendering for CLI commands.
+//!
+//! Commands return plain data; this module turns it into either the colored
+//! human-readable text or stable JSON/YAML records for scripts and agents.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::archive::{ExportSummary, ImportAction, ImportResult};
+#[cfg(feature = "neural")]
+use crate::commands::{EmbeddingDrift, IndexSummary};
+use crate::commands::{InsightDiff, InsightLinks};
+#[cfg(feature = "neural")]
+use crate::daemon::{DaemonStart, DaemonStatus};
+use crate::eval::{EvalReport, Metrics, Mode};
+use crate::history::Revision;
+use crate::ingest::{IngestAction, IngestResult};
+#[cfg(feature = "neural")]
+use crate::insight::EmbeddingStatus;
+use crate::insight::{self, Insight};
+#[cfg(feature = "neural")]
+use crate::model_registry::{InstalledModel, ModelState, ModelStatus, Pooling};
+use crate::provenance::InsightSource;
+use crate::ranking::Strategy;
+#[cfg(feature = "neural")]
+use crate::related::{DuplicateGroup, Neighbour};
+use crate::scope::Scope;
+use crate::search::SearchResult;
+
+/// Output format selected with the global `--format` option
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+  #[default]
+  Text,
+  Json,
+  Yaml,
+}
+
+/// Stable machine-readable representation of a single insight
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct InsightRecord {
+  pub topic: String,
+  pub name: String,
+  pub overview: String,
+  /// Left out of search results unless `--full` is given
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub details: Option<String>,
+  pub score: Option<f32>,
+  pub tags: Vec<String>,
+  pub scope: Scope,
+  pub created: Option<DateTime<Utc>>,
+  pub updated: Option<DateTime<Utc>>,
+  pub author: Option<String>,
+  pub source: Option<InsightSource>,
+  pub origin: Option<String>,
+  pub embedding_version: Option<String>,
+  pub embedding_computed: Option<DateTime<Utc>>,
+  pub file_path: PathBuf,
+}
+
+/// One page of search results, with how many matched before paging
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SearchPageRecord {
+  pub total: usize,
+  pub offset: usize,
+  pub results: Vec<SearchRecord>,
+}
+
+/// A search result, with the explanation of its score when `--explain` is given
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SearchRecord {
+  #[serde(flatten)]
+  pub insight: InsightRecord,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub explanation: Option<ExplanationRecord>,
+  /// Best-matching passages of long details, or all of short ones
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub snippet: Option<String>,
+}
+
+/// Stable machine-readable breakdown of a search score
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExplanationRecord {
+  pub contributions: Vec<ContributionRecord>,
+  pub matched_terms: Vec<String>,
+}
+
+/// What one search strategy added to a fused score
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ContributionRecord {
+  pub strategy: Strategy,
+  pub raw_score: f32,
+  pub rank: usize,
+  pub score: f32,
+}
+
+/// An insight together with the insights it links to, for `get --expand`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExpandedInsightRecord {
+  #[serde(flatten)]
+  pub insight: InsightRecord,
+  pub linked: Vec<InsightRecord>,
+}
+
+/// Stable machine-readable representation of a link between insights
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LinkRecord {
+  pub topic: String,
+  pub name: String,
+  pub exists: bool,
+}
+
+/// Stable machine-readable representation of an insight's links
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LinksRecord {
+  pub outgoing: Vec<LinkRecord>,
+  pub backlinks: Vec<LinkRecord>,
+}
+
+/// Stable machine-readable representation of a topic
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TopicRecord {
+  pub topic: String,
+}
+
+/// Stable machine-readable representation of a stored revision
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RevisionRecord {
+  pub revision: u32,
+  pub written: Option<DateTime<Utc>>,
+  pub author: Option<String>,
+  pub overview: String,
+  pub file_path: PathBuf,
+}
+
+/// Stable machine-readable representation of a diff between revisions
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DiffRecord {
+  pub from: String,
+  pub to: String,
+  pub diff: String,
+}
+
+/// Stable machine-readable summary of an `export` run
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExportRecord {
+  pub file_path: PathBuf,
+  pub insights: usize,
+  pub embeddings: bool,
+}
+
+/// Stable machine-readable result of importing one bundled insight
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ImportRecord {
+  pub line: usize,
+  pub topic: String,
+  pub name: String,
+  pub action: ImportAction,
+  pub detail: Option<String>,
+}
+
+/// Stable machine-readable result of ingesting one note section
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IngestRecord {
+  pub file: PathBuf,
+  pub topic: String,
+  pub name: String,
+  pub action: IngestAction,
+  pub detail: Option<String>,
+}
+
+/// Stable machine-readable summary of an `index` run
+#[cfg(feature = "neural")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IndexRecord {
+  pub processed: usize,
+  pub indexed: usize,
+  pub updated: Vec<InsightRecord>,
+  #[serde(default)]
+  pub failed: Vec<InsightRecord>,
+}
+
+/// Stable machine-readable representation of an outdated embedding, from `index --check`
+#[cfg(feature = "neural")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DriftRecord {
+  pub topic: String,
+  pub name: String,
+  pub scope: Scope,
+  pub status: EmbeddingStatus,
+  pub embedding_version: Option<String>,
+}
+
+/// Stable machine-readable group of near-duplicate insights, from `duplicates`
+#[cfg(feature = "neural")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DuplicateGroupRecord {
+  pub similarity: f32,
+  pub insights: Vec<InsightRecord>,
+}
+
+/// Stable machine-readable result of `eval`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EvalRecord {
+  pub k: usize,
+  pub modes: Vec<EvalModeRecord>,
+}
+
+/// Metrics of one search mode, averaged over the queries
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EvalModeRecord {
+  pub mode: Mode,
+  #[serde(flatten)]
+  pub metrics: Metrics,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub fallback: Option<String>,
+  pub queries: Vec<EvalQueryRecord>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EvalQueryRecord {
+  pub query: String,
+  pub found: Vec<String>,
+  #[serde(flatten)]
+  pub metrics: Metrics,
+}
+
+/// Stable machine-readable representation of a registered embedding model
+#[cfg(feature = "neural")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ModelRecord {
+  pub name: String,
+  pub path: PathBuf,
+  pub state: ModelState,
+  pub active: bool,
+  pub pooling: Pooling,
+  pub dimension: usize,
+  pub max_tokens: usize,
+}
+
+/// Stable machine-readable result of `model install`
+#[cfg(feature = "neural")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct InstalledModelRecord {
+  pub name: String,
+  pub path: PathBuf,
+  pub sha256: String,
+}
+
+/// Stable machine-readable state of the embedding daemon after a `daemon` command
+#[cfg(feature = "neural")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DaemonRecord {
+  pub running: bool,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub pid: Option<u32>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub model: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub uptime_secs: Option<u64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub requests_served: Option<u64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub idle_timeout_secs: Option<u64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub cached_queries: Option<usize>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub cache_hits: Option<u64>,
+}
+
+/// Print a structured record for machine-readable formats, or run the text
+/// renderer. The record is only built when it will be printed.
+pub fn emit<T: Serialize>(
+  format: OutputFormat,
+  record: impl FnOnce() -> Result<T>,
+  text: impl FnOnce(),
+) -> Result<()> {
+  match format {
+    OutputFormat::Text => text(),
+    OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&record()?)?),
+    OutputFormat::Yaml => print!("{}", serde_yaml::to_string(&record()?)?),
+  }
+  Ok(())
+}
+
+pub fn insight_record(insight: &Insight, score: Option<f32>) -> Result<InsightRecord> {
+  Ok(InsightRecord {
+    topic: insight.topic.clone(),
+    name: insight.name.clone(),
+    overview: insight.overview.clone(),
+    details: Some(insight.details.clone()),
+    score,
+    tags: insight.tags.clone(),
+    scope: insight.scope,
+    created: insight.created,
+    updated: insight.updated,
+    author: insight.author.clone(),
+    source: insight.source.clone(),
+    origin: insight.origin.clone(),
+    embedding_version: insight.embedding_version.clone(),
+    embedding_computed: insight.embedding_computed,
+    file_path: insight::file_path(insight)?,
+  })
+}
+
+pub fn insight_records(insights: &[Insight]) -> Result<Vec<InsightRecord>> {
+  insights.iter().map(|insight| insight_record(insight, None)).collect()
+}
+
+/// Build records for search results, loading each insight for its metadata.
+/// Unless `full` is set, the snippet stands in for the details.
+pub fn search_records(results: &[SearchResult], full: bool) -> Result<Vec<SearchRecord>> {
+  results
+    .iter()
+    .map(|result| {
+      let mut insight =
+        insight_record(&insight::load(&result.topic, &result.name)?, Some(result.score))?;
+      let snippet = if full {
+        None
+      } else {
+        let details = insight.details.take().filter(|details| !details.is_empty());
+        result.snippet.clone().or(details)
+      };
+      Ok(SearchRecord {
+        insight,
+        explanation: result.matched_terms.as_ref().map(|matched_terms| ExplanationRecord {
+          contributions: result
+            .contributions
+            .iter()
+            .map(|contribution| ContributionRecord {
+              strategy: contribution.strategy,
+              raw_score: contribution.raw_score,
+              rank: contribution.rank,
+              score: contribution.score,
+            })
+            .collect(),
+          matched_terms: matched_terms.clone(),
+        }),
+        snippet,
+      })
+    })
+    .collect()
+}
+
+pub fn search_page_record(
+  results: &[SearchResult],
+  total: usize,
+  offset: usize,
+  full: bool,
+) -> Result<SearchPageRecord> {
+  Ok(SearchPageRecord { total, offset, results: search_records(results, full)? })
+}
+
+pub fn expanded_insight_record(
+  insight: &Insight,
+  linked: &[Insight],
+) -> Result<ExpandedInsightRecord> {
+  Ok(ExpandedInsightRecord {
+    insight: insight_record(insight, None)?,
+    linked: insight_records(linked)?,
+  })
+}
+
+pub fn links_record(links: &InsightLinks) -> LinksRecord {
+  LinksRecord {
+    outgoing: links
+      .outgoing
+      .iter()
+      .map(|resolved| LinkRecord {
+        topic: resolved.link.topic.clone(),
+        name: resolved.link.name.clone(),
+        exists: resolved.exists,
+      })
+      .collect(),
+    backlinks: links
+      .backlinks
+      .iter()
+      .map(|source| LinkRecord {
+        topic: source.topic.clone(),
+        name: source.name.clone(),
+        exists: true,
+      })
+      .collect(),
+  }
+}
+
+pub fn topic_records(topics: &[String]) -> Vec<TopicRecord> {
+  topics.iter().map(|topic| TopicRecord { topic: topic.clone() }).collect()
+}
+
+pub fn revision_records(revisions: &[Revision]) -> Vec<RevisionRecord> {
+  revisions
+    .iter()
+    .map(|revision| RevisionRecord {
+      revision: revision.number,
+      written: revision.written(),
+      author: revision.insight.author.clone(),
+      overview: revision.insight.overview.clone(),
+      file_path: revision.path.clone(),
+    })
+    .collect()
+}
+
+pub fn diff_record(diff: &InsightDiff) -> DiffRecord {
+  DiffRecord { from: diff.from.clone(), to: diff.to.clone(), diff: diff.unified.clone() }
+}
+
+pub fn export_record(summary: &ExportSummary, file_path: &Path) -> ExportRecord {
+  ExportRecord {
+    file_path: file_path.to_path_buf(),
+    insights: summary.insights,
+    embeddings: summary.embeddings,
+  }
+}
+
+pub fn import_records(results: &[ImportResult]) -> Vec<ImportRecord> {
+  results
+    .iter()
+    .map(|result| ImportRecord {
+      line: result.line,
+      topic: result.topic.clone(),
+      name: result.name.clone(),
+      action: result.action,
+      detail: result.detail.clone(),
+    })
+    .collect()
+}
+
+pub fn ingest_records(results: &[IngestResult]) -> Vec<IngestRecord> {
+  results
+    .iter()
+    .map(|result| IngestRecord {
+      file: result.file.clone(),
+      topic: result.topic.clone(),
+      name: result.name.clone(),
+      action: result.action,
+      detail: result.detail.clone(),
+    })
+    .collect()
+}
+
+#[cfg(feature = "neural")]
+pub fn drift_records(drift: &[EmbeddingDrift]) -> Vec<DriftRecord> {
+  drift
+    .iter()
+    .map(|entry| DriftRecord {
+      topic: entry.insight.topic.clone(),
+      name: entry.insight.name.clone(),
+      scope: entry.insight.scope,
+      status: entry.status,
+      embedding_version: entry.insight.embedding_version.clone(),
+    })
+    .collect()
+}
+
+/// Build records for related insights, scored by similarity
+#[cfg(feature = "neural")]
+pub fn neighbour_records(neighbours: &[Neighbour]) -> Result<Vec<InsightRecord>> {
+  neighbours
+    .iter()
+    .map(|neighbour| insight_record(&neighbour.insight, Some(neighbour.similarity)))
+    .collect()
+}
+
+#[cfg(feature = "neural")]
+pub fn duplicate_records(groups: &[DuplicateGroup]) -> Result<Vec<DuplicateGroupRecord>> {
+  groups
+    .iter()
+    .map(|group| {
+      Ok(DuplicateGroupRecord {
+        similarity: group.similarity,
+        insights: insight_records(&group.insights)?,
+      })
+    })
+    .collect()
+}
+
+pub fn eval_record(report: &EvalReport) -> EvalRecord {
+  EvalRecord {
+    k: report.k,
+    modes: report
+      .modes
+      .iter()
+      .map(|mode| EvalModeRecord {
+        mode: mode.mode,
+        metrics: mode.metrics,
+        fallback: mode.fallback.clone(),
+        queries: mode
+          .queries
+          .iter()
+          .map(|query| EvalQueryRecord {
+            query: query.query.clone(),
+            found: query.found.clone(),
+            metrics: query.metrics,
+          })
+          .collect(),
+      })
+      .collect(),
+  }
+}
+
+#[cfg(feature = "neural")]
+pub fn model_records(models: &[ModelStatus]) -> Vec<ModelRecord> {
+  models
+    .iter()
+    .map(|status| ModelRecord {
+      name: status.spec.name.clone(),
+      path: status.spec.model.clone(),
+      state: status.state,
+      active: status.active,
+      pooling: status.spec.pooling,
+      dimension: status.spec.dimension,
+      max_tokens: status.spec.max_tokens,
+    })
+    .collect()
+}
+
+#[cfg(feature = "neural")]
+pub fn installed_model_record(model: &InstalledModel) -> InstalledModelRecord {
+  InstalledModelRecord {
+    name: model.name.clone(),
+    path: model.path.clone(),
+    sha256: model.sha256.clone(),
+  }
+}
+
+#[cfg(feature = "neural")]
+pub fn daemon_record(status: Option<&DaemonStatus>) -> DaemonRecord {
+  DaemonRecord {
+    running: status.is_some(),
+    pid: status.map(|status| status.pid),
+    model: status.and_then(|status| status.model.clone()),
+    uptime_secs: status.map(|status| status.uptime_secs),
+    requests_served: status.map(|status| status.requests_served),
+    idle_timeout_secs: status.map(|status| status.idle_timeout_secs),
+    cached_queries: status.map(|status| status.cached_queries),
+    cache_hits: status.map(|status| status.cache_hits),
+  }
+}
+
+#[cfg(feature = "neural")]
+pub fn index_record(summary: &IndexSummary) -> Result<IndexRecord> {
+  Ok(IndexRecord {
+    processed: summary.processed,
+    indexed: summary.indexed,
+    updated: insight_records(&summary.updated)?,
+    failed: insight_records(&summary.failed)?,
+  })
+}
+
+pub fn print_added(insight: &Insight) {
+  println!(
+    "{} Added insight {}/{} {}",
+    "✓".green(),
+    insight.topic.cyan(),
+    insight.name.yellow(),
+    format!("[{}]", insight.scope).dimmed()
+  );
+}
+
+pub fn print_updated(insight: &Insight) {
+  println!("{} Updated insight {}/{}", "✓".green(), insight.topic.cyan(), insight.name.yellow());
+}
+
+pub fn print_deleted(insight: &Insight) {
+  println!("{} Deleted insight {}/{}", "✓".green(), insight.topic.cyan(), insight.name.yellow());
+}
+
+pub fn print_moved(from_topic: &str, from_name: &str, insight: &Insight) {
+  println!(
+    "{} Moved insight {}/{} to {}/{}",
+    "✓".green(),
+    from_topic.cyan(),
+    from_name.yellow(),
+    insight.topic.cyan(),
+    insight.name.yellow()
+  );
+}
+
+pub fn print_insight(insight: &Insight, overview_only: bool) {
+  if overview_only {
+    println!("{}", insight.overview);
+  } else {
+    println!("---\n{}\n---\n\n{}", insight.overview, insight.details);
+  }
+}
+
+pub fn print_expanded_insight(insight: &Insight, linked: &[Insight], overview_only: bool) {
+  print_insight(insight, overview_only);
+
+  for linked_insight in linked {
+    println!("\n{} {}/{}", "→".cyan(), linked_insight.topic.cyan(), linked_insight.name.yellow());
+    print_insight(linked_insight, overview_only);
+  }
+}
+
+pub fn print_links(links: &InsightLinks) {
+  println!("{}", "Outgoing links:".bold());
+  if links.outgoing.is_empty() {
+    println!("  none");
+  }
+  for resolved in &links.outgoing {
+    if resolved.exists {
+      println!("  {} {}/{}", "→".green(), resolved.link.topic.cyan(), resolved.link.name.yellow());
+    } else {
+      println!(
+        "  {} {}/{} {}",
+        "✗".red(),
+        resolved.link.topic.cyan(),
+        resolved.link.name.yellow(),
+        "(broken)".red()
+      );
+    }
+  }
+
+  println!("{}", "Backlinks:".bold());
+  if links.backlinks.is_empty() {
+    println!("  none");
+  }
+  for source in &links.backlinks {
+    println!("  {} {}/{}", "←".green(), source.topic.cyan(), source.name.yellow());
+  }
+}
+
+/// Warn on stderr about insights that still refer to a removed or moved
+/// `topic/name`, so machine-readable stdout stays intact
+pub fn warn_stale_references(topic: &str, name: &str, sources: &[Insight]) {
+  if sources.is_empty() {
+    return;
+  }
+
+  eprintln!(
+    "{} {} insight(s) still refer to {}/{}:",
+    "⚠".yellow(),
+    sources.len(),
+    topic.cyan(),
+    name.yellow()
+  );
+  for source in sources {
+    eprintln!("  {} {}/{}", "←".yellow(), source.topic.cyan(), source.name.yellow());
+  }
+}
+
+pub fn print_insights(insights: &[Insight], filter: Option<&str>, verbose: bool) {
+  if insights.is_empty() {
+    if let Some(topic) = filter {
+      println!("No insights found in topic: {}", topic.yellow());
+    } else {
+      println!("No insights found.");
+    }
+    return;
+  }
+
+  for insight in insights {
+    let formatted_name = if verbose {
+      format!(
+        "{}/{} {} - {}",
+        insight.topic.cyan(),
+        insight.name.yellow(),
+        format!("[{}]", insight.scope).dimmed(),
+        insight.overview
+      )
+    } else {
+      format!("{}/{}", insight.topic.cyan(), insight.name.yellow())
+    };
+
+    if verbose && !insight.tags.is_empty() {
+      println!("{formatted_name} {}", format!("[{}]", insight.tags.join(", ")).dimmed());
+    } else {
+      println!("{formatted_name}");
+    }
+  }
+}
+
+pub fn print_reverted(insight: &Insight, revision: u32) {
+  println!(
+    "{} Reverted insight {}/{} to revision {}",
+    "✓".green(),
+    insight.topic.cyan(),
+    insight.name.yellow(),
+    revision.to_string().cyan()
+  );
+}
+
+pub fn print_history(revisions: &[Revision]) {
+  if revisions.is_empty() {
+    println!("No revisions found.");
+    return;
+  }
+
+  for revision in revisions {
+    let written =
+      revision.written().map_or_else(|| "unknown".to_string(), |written| written.to_rfc3339());
+    println!(
+      "{} {} {}",
+      format!("@{}", revision.number).cyan(),
+      written.dimmed(),
+      revision.insight.overview
+    );
+  }
+}
+
+pub fn print_diff(diff: &InsightDiff) {
+  if !diff.unified.lines().any(|line| line.starts_with('+') || line.starts_with('-')) {
+    println!("No differences between {} and {}", diff.from.cyan(), diff.to.cyan());
+    return;
+  }
+
+  for line in diff.unified.lines() {
+    if line.starts_with("+++") || line.starts_with("---") {
+      println!("{}", line.bold());
+    } else if line.starts_with('+') {
+      println!("{}", line.green());
+    } else if line.starts_with('-') {
+      println!("{}", line.red());
+    } else if line.starts_with("@@") {
+      println!("{}", line.cyan());
+    } else {
+      println!("{line}");
+    }
+  }
+}
+
+pub fn print_export_summary(summary: &ExportSummary, file_path: &Path) {
+  let embeddings = if summary.embeddings { "with" } else { "without" };
+  println!(
+    "{} Exported {} insights {} embeddings to {}",
+    "✓".green(),
+    summary.insights.to_string().yellow(),
+    embeddings,
+    file_path.display().to_string().cyan()
+  );
+}
+
+pub fn print_import_results(results: &[ImportResult]) {
+  for result in results {
+    let id = format!("{}/{}", result.topic.cyan(), result.name.yellow());
+    let detail = result.detail.as_deref().map(|detail| format!(" ({detail})")).unwrap_or_default();
+    let (marker, label) = match result.action {
+      ImportAction::Added => ("✓".green(), "added".green()),
+      ImportAction::Overwritten => ("✓".green(), "overwritten".green()),
+      ImportAction::Renamed => ("✓".green(), "renamed".green()),
+      ImportAction::Merged => ("✓".green(), "merged".green()),
+      ImportAction::Skipped => ("-".dimmed(), "skipped".dimmed()),
+      ImportAction::Unchanged => ("-".dimmed(), "unchanged".dimmed()),
+      ImportAction::Failed => ("✗".red(), "failed".red()),
+    };
+    println!("  {marker} {id} {label}{detail} [line {}]", result.line);
+  }
+
+  let failed = results.iter().filter(|result| result.action == ImportAction::Failed).count();
+  println!(
+    "{} Imported {} of {} insights ({} failed)",
+    if failed == 0 { "✓".green() } else { "⚠".yellow() },
+    (results.len() - failed).to_string().yellow(),
+    results.len().to_string().cyan(),
+    failed
+  );
+}
+
+pub fn print_ingest_results(results: &[IngestResult]) {
+  for result in results {
+    let id = format!("{}/{}", result.topic.cyan(), result.name.yellow());
+    let detail = result.detail.as_deref().map(|detail| format!(" ({detail})")).unwrap_or_default();
+    let (marker, label) = match result.action {
+      IngestAction::Added => ("✓".green(), "added".green()),
+      IngestAction::Updated => ("✓".green(), "updated".green()),
+      IngestAction::Removed => ("✓".green(), "removed".green()),
+      IngestAction::Unchanged => ("-".dimmed(), "unchanged".dimmed()),
+      IngestAction::Failed => ("✗".red(), "failed".red()),
+    };
+    println!("  {marker} {id} {label}{detail} [{}]", result.file.display());
+  }
+
+  let count = |action| results.iter().filter(|result| result.action == action).count();
+  println!(
+    "{} Ingested {} notes: {} added, {} updated, {} unchanged, {} removed, {} failed",
+    if count(IngestAction::Failed) == 0 { "✓".green() } else { "⚠".yellow() },
+    results.len().to_string().cyan(),
+    count(IngestAction::Added),
+    count(IngestAction::Updated),
+    count(IngestAction::Unchanged),
+    count(IngestAction::Removed),
+    count(IngestAction::Failed)
+  );
+
+  #[cfg(feature = "neural")]
+  if count(IngestAction::Added) + count(IngestAction::Updated) > 0 {
+    println!("{} Run `insights index` to embed them for neural search", "ℹ".blue());
+  }
+}
+
+pub fn print_topics(topics: &[String]) {
+  if topics.is_empty() {
+    println!("No topics found.");
+    return;
+  }
+
+  for topic in topics {
+    println!("{}", topic.cyan());
+  }
+}
+
+pub fn print_eval_report(report: &EvalReport) {
+  let k = report.k;
+  println!(
+    "{}",
+    format!(
+      "{:<10} {:>6} {:>6} {:>6} {:>7}",
+      "mode",
+      format!("P@{k}"),
+      format!("R@{k}"),
+      "MRR",
+      format!("nDCG@{k}")
+    )
+    .bold()
+  );
+  for mode in &report.modes {
+    let Metrics { precision, recall, mrr, ndcg } = mode.metrics;
+    println!(
+      "{:<10} {:>6.3} {:>6.3} {:>6.3} {:>7.3}",
+      mode.mode.to_string().cyan(),
+      precision,
+      recall,
+      mrr,
+      ndcg
+    );
+  }
+
+  for mode in &report.modes {
+    if let Some(reason) = &mode.fallback {
+      println!("  {} {} ran without neural ranking: {}", "⚠".yellow(), mode.mode, reason);
+    }
+    for query in mode.queries.iter().filter(|query| query.metrics.mrr == 0.0) {
+      println!(
+        "  {} {}: no relevant insight in the top {} for \"{}\"",
+        "✗".red(),
+        mode.mode,
+        k,
+        query.query.yellow()
+      );
+    }
+  }
+}
+
+#[cfg(feature = "neural")]
+pub fn print_embedding_drift(drift: &[EmbeddingDrift]) {
+  if drift.is_empty() {
+    println!("{} All embeddings are up to date", "✓".green());
+    return;
+  }
+
+  for entry in drift {
+    let reason = match entry.status {
+      EmbeddingStatus::Missing => "missing".red(),
+      EmbeddingStatus::Stale => "stale".yellow(),
+      EmbeddingStatus::ModelMismatch => "model mismatch".yellow(),
+      EmbeddingStatus::Current => "current".green(),
+    };
+    println!(
+      "  {} {}/{} {}",
+      "✗".red(),
+      entry.insight.topic.cyan(),
+      entry.insight.name.yellow(),
+      reason
+    );
+  }
+  println!(
+    "{} {} insights need reindexing; run `insights index`",
+    "⚠".yellow(),
+    drift.len().to_string().yellow()
+  );
+}
+
+#[cfg(feature = "neural")]
+pub fn print_related(topic: &str, name: &str, neighbours: &[Neighbour]) {
+  if neighbours.is_empty() {
+    println!("No insights related to {}/{}", topic.cyan(), name.yellow());
+    return;
+  }
+
+  for neighbour in neighbours {
+    println!(
+      "  {} {}/{} {}",
+      format!("{:.2}", neighbour.similarity).dimmed(),
+      neighbour.insight.topic.cyan(),
+      neighbour.insight.name.yellow(),
+      neighbour.insight.overview
+    );
+  }
+}
+
+#[cfg(feature = "neural")]
+pub fn print_duplicates(groups: &[DuplicateGroup]) {
+  if groups.is_empty() {
+    println!("{} No near-duplicate insights found", "✓".green());
+    return;
+  }
+
+  for group in groups {
+    println!("{}", format!("{:.2} similar:", group.similarity).bold());
+    for insight in &group.insights {
+      println!("  {} {}/{}", "•".yellow(), insight.topic.cyan(), insight.name.yellow());
+    }
+  }
+  println!(
+    "{} {} groups of near-duplicates; consider merging them",
+    "⚠".yellow(),
+    groups.len().to_string().yellow()
+  );
+}
+
+/// Warn on stderr that a new insight closely resembles existing ones
+#[cfg(feature = "neural")]
+pub fn warn_similar_insights(insight: &Insight, similar: &[Neighbour]) {
+  if similar.is_empty() {
+    return;
+  }
+
+  eprintln!(
+    "{} {}/{} is very similar to {} existing insight(s):",
+    "⚠".yellow(),
+    insight.topic.cyan(),
+    insight.name.yellow(),
+    similar.len()
+  );
+  for neighbour in similar {
+    eprintln!(
+      "  {} {}/{} {}",
+      "≈".yellow(),
+      neighbour.insight.topic.cyan(),
+      neighbour.insight.name.yellow(),
+      format!("({:.2})", neighbour.similarity).dimmed()
+    );
+  }
+}
+
+#[cfg(feature = "neural")]
+pub fn print_models(models: &[ModelStatus]) {
+  for status in models {
+    let marker = if status.active { "*".green() } else { " ".normal() };
+    let state = match status.state {
+      ModelState::Verified => "verified".green(),
+      ModelState::Unverified => "installed".green(),
+      ModelState::Corrupt => "checksum mismatch".red(),
+      ModelState::Missing => "not installed".red(),
+    };
+    println!(
+      "{} {} {} {}",
+      marker,
+      status.spec.name.cyan(),
+      state,
+      status.spec.model.display().to_string().dimmed()
+    );
+  }
+}
+
+#[cfg(feature = "neural")]
+pub fn print_installed_model(model: &InstalledModel) {
+  println!(
+    "{} Installed model {} to {}",
+    "✓".green(),
+    model.name.cyan(),
+    model.path.display().to_string().dimmed()
+  );
+  println!("  sha256 {}", model.sha256.dimmed());
+}
+
+#[cfg(feature = "neural")]
+pub fn print_daemon_status(status: Option<&DaemonStatus>) {
+  let Some(status) = status else {
+    println!("{} Embedding daemon is not running", "○".dimmed());
+    return;
+  };
+  println!("{} Embedding daemon running {}", "●".green(), format!("(pid {})", status.pid).dimmed());
+  println!("  model         {}", status.model.as_deref().unwrap_or("unknown").cyan());
+  println!("  uptime        {}", format_duration(status.uptime_secs));
+  println!("  requests      {}", status.requests_served);
+  println!("  query cache   {} queries, {} hits", status.cached_queries, status.cache_hits);
+  let idle_timeout = match status.idle_timeout_secs {
+    0 => "never".to_string(),
+    secs => format_duration(secs),
+  };
+  println!("  idle timeout  {idle_timeout}");
+}
+
+#[cfg(feature = "neural")]
+pub fn print_daemon_started(start: &DaemonStart) {
+  let status = &start.status;
+  let model = status.model.as_deref().unwrap_or("unknown");
+  if start.already_running {
+    println!(
+      "{} Embedding daemon already running {}",
+      "ℹ".blue(),
+      format!("(pid {}, model {model})", status.pid).dimmed()
+    );
+  } else {
+    println!(
+      "{} Started embedding daemon {}",
+      "✓".green(),
+      format!("(pid {}, model {model})", status.pid).dimmed()
+    );
+  }
+}
+
+#[cfg(feature = "neural")]
+pub fn print_daemon_stopped(status: Option<&DaemonStatus>) {
+  match status {
+    Some(status) => println!(
+      "{} Stopped embedding daemon {}",
+      "✓".green(),
+      format!("(pid {}, served {} requests)", status.pid, status.requests_served).dimmed()
+    ),
+    None => println!("{} Embedding daemon is not running", "○".dimmed()),
+  }
+}
+
+/// Render seconds as e.g. "1h 2m 5s"
+#[cfg(feature = "neural")]
+fn format_duration(secs: u64) -> String {
+  let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+  match (hours, minutes) {
+    (0, 0) => format!("{seconds}s"),
+    (0, _) => format!("{minutes}m {seconds}s"),
+    _ => format!("{hours}h {minutes}m {seconds}s"),
+  }
+}
+
+/// Explain on stderr that search fell back to lexical and semantic ranking
+pub fn warn_neural_unavailable(reason: &str) {
+  eprintln!("{} Neural search unavailable, using lexical and semantic ranking only", "⚠".yellow());
+  eprintln!("  {reason}");
+}
+
+/// Redraw the `index` progress bar on stderr. Nothing is drawn unless stderr is a terminal.
+#[cfg(feature = "neural")]
+pub fn print_index_progress(done: usize, total: usize) {
+  const WIDTH: usize = 30;
+
+  let term = console::Term::stderr();
+  if !term.is_term() || total == 0 {
+    return;
+  }
+
+  let filled = done * WIDTH / total;
+  let _ = term.clear_line();
+  let _ = term.write_str(&format!(
+    "  Embedding [{}{}] {}/{}",
+    "#".repeat(filled).green(),
+    "-".repeat(WIDTH - filled).dimmed(),
+    done,
+    total
+  ));
+  if done == total {
+    let _ = term.clear_line();
+  }
+}
+
+#[cfg(feature = "neural")]
+pub fn print_index_summary(summary: &IndexSummary) {
+  if summary.processed == 0 {
+    println!("No topics found to index.");
+    return;
+  }
+
+  for insight in &summary.updated {
+    println!(
+      "  {} Updated embeddings for {}/{}",
+      "✓".green(),
+      insight.topic.cyan(),
+      insight.name.yellow()
+    );
+  }
+
+  for insight in &summary.failed {
+    println!(
+      "  {} No embedding computed for {}/{}",
+      "✗".red(),
+      insight.topic.cyan(),
+      insight.name.yellow()
+    );
+  }
+
+  println!(
+    "{} Indexed {} of {} insights ({} vectors in index, {} failed)",
+    if summary.failed.is_empty() { "✓".green() } else { "⚠".yellow() },
+    summary.updated.len().to_string().yellow(),
+    summary.processed.to_string().cyan(),
+    summary.indexed,
+    summary.failed.len()
+  );
+}
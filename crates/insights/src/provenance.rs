@@ -0,0 +1,72 @@
+//! Where and by whom an insight was written.
+//!
+//! Author and source are detected from the environment and the git checkout
+//! the CLI runs in. Detection never fails; missing information is left empty.
+//! Detection runs git, so its results are computed once per process.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+use std::sync::OnceLock;
+
+/// Overrides the detected author (useful for agents and tests)
+const AUTHOR_ENV: &str = "INSIGHTS_AUTHOR";
+
+/// Git location an insight was written from
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct InsightSource {
+  pub repo: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub branch: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub commit: Option<String>,
+}
+
+/// Detect the current author from `INSIGHTS_AUTHOR`, git config, or the login user
+pub fn current_author() -> Option<String> {
+  static DETECTED: OnceLock<Option<String>> = OnceLock::new();
+
+  // The override is only an env lookup, so it is honoured even after detection ran
+  std::env::var(AUTHOR_ENV)
+    .ok()
+    .filter(|author| !author.trim().is_empty())
+    .or_else(|| DETECTED.get_or_init(detect_author).clone())
+}
+
+/// Detect the git repository, branch and commit of the working directory
+pub fn current_source() -> Option<InsightSource> {
+  static DETECTED: OnceLock<Option<InsightSource>> = OnceLock::new();
+  DETECTED.get_or_init(detect_source).clone()
+}
+
+fn detect_author() -> Option<String> {
+  git(&["config", "user.name"])
+    .or_else(|| std::env::var("USER").ok())
+    .or_else(|| std::env::var("USERNAME").ok())
+    .filter(|author| !author.trim().is_empty())
+}
+
+fn detect_source() -> Option<InsightSource> {
+  let toplevel = git(&["rev-parse", "--show-toplevel"])?;
+  let repo = Path::new(&toplevel).file_name()?.to_str()?.to_string();
+
+  Some(InsightSource {
+    repo,
+    branch: git(&["rev-parse", "--abbrev-ref", "HEAD"]),
+    commit: git(&["rev-parse", "HEAD"]),
+  })
+}
+
+fn git(args: &[&str]) -> Option<String> {
+  let output = Command::new("git").args(args).output().ok()?;
+  if !output.status.success() {
+    return None;
+  }
+
+  let value = String::from_utf8(output.stdout).ok()?.trim().to_string();
+  if value.is_empty() {
+    None
+  } else {
+    Some(value)
+  }
+}
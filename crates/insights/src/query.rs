@@ -0,0 +1,713 @@
+//! Query language for `insights search`.
+//!
+//! Search terms are parsed into a small AST supporting quoted phrases,
+//! `AND`/`OR`/`NOT`, `-exclude`, parentheses, field prefixes (`topic:`,
+//! `name:`, `overview:`, `details:`, `tag:`) and `*`/`?` wildcards. Operators
+//! are only recognized in upper case, so lower-case `and`, `or` and `not` stay
+//! ordinary words.
+//!
+//! Clauses written one after another form a sequence. Plain words in the
+//! top-level sequence only rank results, so `insights search deploy rollback`
+//! behaves like the bag of words it always was, while every other clause
+//! (phrases, fields, wildcards, operators) must match for an insight to be a
+//! candidate at all.
+
+use anyhow::{anyhow, Result};
+use std::fmt;
+
+use crate::bm25;
+use crate::insight::{self, Insight};
+
+/// Part of an insight a term can be restricted to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+  Topic,
+  Name,
+  Overview,
+  Details,
+  Tag,
+}
+
+impl Field {
+  fn prefix(self) -> &'static str {
+    match self {
+      Self::Topic => "topic",
+      Self::Name => "name",
+      Self::Overview => "overview",
+      Self::Details => "details",
+      Self::Tag => "tag",
+    }
+  }
+
+  fn from_prefix(prefix: &str) -> Option<Self> {
+    match prefix.to_lowercase().as_str() {
+      "topic" => Some(Self::Topic),
+      "name" => Some(Self::Name),
+      "overview" => Some(Self::Overview),
+      "details" => Some(Self::Details),
+      "tag" => Some(Self::Tag),
+      _ => None,
+    }
+  }
+}
+
+/// What a single term looks for
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+  /// Matches any token containing the word
+  Word(String),
+  /// Matches the words as consecutive tokens
+  Phrase(Vec<String>),
+  /// Matches a whole token, with `*` for any run of characters and `?` for one
+  Wildcard(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Term {
+  pub field: Option<Field>,
+  pub pattern: Pattern,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Query {
+  Term(Term),
+  And(Vec<Query>),
+  Or(Vec<Query>),
+  Not(Box<Query>),
+  /// Clauses written next to each other without an operator
+  Sequence(Vec<Query>),
+}
+
+/// Decides whether an indexed token matches a query term, for BM25 scoring
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenPattern {
+  Contains(String),
+  Glob(String),
+}
+
+impl TokenPattern {
+  pub fn matches(&self, token: &str) -> bool {
+    match self {
+      TokenPattern::Contains(text) => token.contains(text.as_str()),
+      TokenPattern::Glob(pattern) => glob_match(pattern, token),
+    }
+  }
+}
+
+/// An insight prepared for matching, tokenized the way BM25 indexes it
+pub struct Document<'a> {
+  insight: &'a Insight,
+  case_sensitive: bool,
+  overview_only: bool,
+}
+
+impl<'a> Document<'a> {
+  pub fn new(insight: &'a Insight, case_sensitive: bool, overview_only: bool) -> Self {
+    Self { insight, case_sensitive, overview_only }
+  }
+
+  fn tokens(&self, field: Option<Field>) -> Vec<String> {
+    let insight = self.insight;
+    let texts: Vec<&str> = match field {
+      Some(Field::Topic) => vec![&insight.topic],
+      Some(Field::Name) => vec![&insight.name],
+      Some(Field::Overview) => vec![&insight.overview],
+      Some(Field::Details) => vec![&insight.details],
+      Some(Field::Tag) => insight.tags.iter().map(String::as_str).collect(),
+      None if self.overview_only => vec![&insight.topic, &insight.name, &insight.overview],
+      None => vec![&insight.topic, &insight.name, &insight.overview, &insight.details],
+    };
+    texts.iter().flat_map(|text| bm25::tokenize(text)).map(|token| self.fold(token)).collect()
+  }
+
+  fn fold(&self, text: &str) -> String {
+    if self.case_sensitive {
+      text.to_string()
+    } else {
+      text.to_lowercase()
+    }
+  }
+}
+
+/// Parse search terms as given on the command line. Terms that don't parse
+/// as a query, e.g. with an unclosed quote, are searched as plain words.
+pub fn parse_terms(terms: &[String]) -> Query {
+  let input = terms.join(" ");
+  parse(&input).unwrap_or_else(|_| {
+    Query::Sequence(
+      bm25::tokenize(&input)
+        .map(|word| Query::Term(Term { field: None, pattern: Pattern::Word(word.to_string()) }))
+        .collect(),
+    )
+  })
+}
+
+pub fn parse(input: &str) -> Result<Query> {
+  let tokens = lex(input)?;
+  let mut parser = Parser { tokens, position: 0 };
+  let query = parser.sequence()?;
+  match parser.peek() {
+    None => Ok(query),
+    Some(token) => Err(anyhow!("Invalid query: unexpected {}", token.describe())),
+  }
+}
+
+impl Query {
+  /// Whether an insight is a search candidate: plain words in the top-level
+  /// sequence are left to ranking, everything else must match
+  pub fn admits(&self, document: &Document) -> bool {
+    match self {
+      Query::Sequence(clauses) => clauses
+        .iter()
+        .filter(|clause| !clause.is_plain_word())
+        .all(|clause| clause.matches(document)),
+      _ => self.matches(document),
+    }
+  }
+
+  /// Whether the query holds anything beyond plain words, i.e. can rule
+  /// insights out before they are ranked
+  #[cfg(feature = "neural")]
+  pub fn has_constraints(&self) -> bool {
+    match self {
+      Query::Sequence(clauses) => clauses.iter().any(|clause| !clause.is_plain_word()),
+      _ => !self.is_plain_word(),
+    }
+  }
+
+  /// Strict evaluation, used for clauses that must hold
+  fn matches(&self, document: &Document) -> bool {
+    match self {
+      Query::Term(term) => term.matches(document),
+      Query::And(clauses) => clauses.iter().all(|clause| clause.matches(document)),
+      Query::Or(clauses) => clauses.iter().any(|clause| clause.matches(document)),
+      Query::Not(clause) => !clause.matches(document),
+      Query::Sequence(clauses) => {
+        let (words, required): (Vec<&Query>, Vec<&Query>) =
+          clauses.iter().partition(|clause| clause.is_plain_word());
+        required.iter().all(|clause| clause.matches(document))
+          && (words.is_empty() || words.iter().any(|word| word.matches(document)))
+      }
+    }
+  }
+
+  fn is_plain_word(&self) -> bool {
+    matches!(self, Query::Term(Term { field: None, pattern: Pattern::Word(_) }))
+  }
+
+  /// Terms the query looks for, leaving out excluded ones
+  fn positive_terms(&self) -> Vec<&Term> {
+    match self {
+      Query::Term(term) => vec![term],
+      Query::Not(_) => Vec::new(),
+      Query::And(clauses) | Query::Or(clauses) | Query::Sequence(clauses) => {
+        clauses.iter().flat_map(Query::positive_terms).collect()
+      }
+    }
+  }
+
+  /// Text terms to rank by, leaving out excluded terms and tags. Wildcards
+  /// keep the text around their `*` and `?`.
+  pub fn text_terms(&self) -> Vec<String> {
+    self
+      .positive_terms()
+      .into_iter()
+      .filter(|term| term.field != Some(Field::Tag))
+      .flat_map(|term| match &term.pattern {
+        Pattern::Word(word) => vec![word.clone()],
+        Pattern::Phrase(words) => words.clone(),
+        Pattern::Wildcard(pattern) => {
+          pattern.split(['*', '?']).filter(|part| !part.is_empty()).map(str::to_string).collect()
+        }
+      })
+      .collect()
+  }
+
+  /// Terms to highlight in results: words, whole phrases and the fixed part
+  /// of wildcards
+  pub fn highlight_terms(&self) -> Vec<String> {
+    self
+      .positive_terms()
+      .into_iter()
+      .filter(|term| term.field != Some(Field::Tag))
+      .filter_map(|term| match &term.pattern {
+        Pattern::Word(word) => Some(word.clone()),
+        Pattern::Phrase(words) => Some(words.join(" ")),
+        Pattern::Wildcard(pattern) => {
+          pattern.split(['*', '?']).find(|part| !part.is_empty()).map(str::to_string)
+        }
+      })
+      .collect()
+  }
+
+  /// The terms an insight matches, as written in the query, leaving out
+  /// excluded ones
+  pub fn matched_terms(&self, document: &Document) -> Vec<String> {
+    self
+      .positive_terms()
+      .into_iter()
+      .filter(|term| term.matches(document))
+      .map(Term::to_string)
+      .collect()
+  }
+
+  /// Token patterns BM25 scores documents against
+  pub fn token_patterns(&self, case_sensitive: bool) -> Vec<TokenPattern> {
+    let fold = |text: &str| if case_sensitive { text.to_string() } else { text.to_lowercase() };
+
+    self
+      .positive_terms()
+      .into_iter()
+      .filter(|term| term.field != Some(Field::Tag))
+      .flat_map(|term| match &term.pattern {
+        Pattern::Word(word) => {
+          bm25::tokenize(word).map(|token| TokenPattern::Contains(fold(token))).collect()
+        }
+        Pattern::Phrase(words) => {
+          words.iter().map(|word| TokenPattern::Contains(fold(word))).collect::<Vec<_>>()
+        }
+        Pattern::Wildcard(pattern) => vec![TokenPattern::Glob(fold(pattern))],
+      })
+      .collect()
+  }
+}
+
+impl fmt::Display for Term {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if let Some(field) = self.field {
+      write!(f, "{}:", field.prefix())?;
+    }
+    match &self.pattern {
+      Pattern::Word(word) | Pattern::Wildcard(word) => f.write_str(word),
+      Pattern::Phrase(words) => write!(f, "\"{}\"", words.join(" ")),
+    }
+  }
+}
+
+impl Term {
+  fn matches(&self, document: &Document) -> bool {
+    if self.field == Some(Field::Tag) {
+      return self.matches_tag(document);
+    }
+
+    let tokens = document.tokens(self.field);
+    match &self.pattern {
+      // A word of punctuation alone has nothing to look for
+      Pattern::Word(word) => {
+        let mut parts = bm25::tokenize(word).peekable();
+        parts.peek().is_some()
+          && parts.all(|part| {
+            let part = document.fold(part);
+            tokens.iter().any(|token| token.contains(&part))
+          })
+      }
+      Pattern::Phrase(words) => {
+        let words: Vec<String> = words.iter().map(|word| document.fold(word)).collect();
+        tokens.windows(words.len()).any(|window| window == words.as_slice())
+      }
+      Pattern::Wildcard(pattern) => {
+        let pattern = document.fold(pattern);
+        tokens.iter().any(|token| glob_match(&pattern, token))
+      }
+    }
+  }
+
+  /// Both sides are normalized, since hand-edited files may hold tags that
+  /// were never normalized
+  fn matches_tag(&self, document: &Document) -> bool {
+    let tags = insight::normalize_tags(&document.insight.tags);
+    match &self.pattern {
+      Pattern::Word(word) => tags.contains(&insight::normalize_tag(word)),
+      Pattern::Phrase(words) => tags.contains(&insight::normalize_tag(&words.join(" "))),
+      Pattern::Wildcard(pattern) => {
+        let pattern = insight::normalize_tag(pattern);
+        tags.iter().any(|tag| glob_match(&pattern, tag))
+      }
+    }
+  }
+}
+
+/// Match a whole string against a pattern with `*` and `?` wildcards
+fn glob_match(pattern: &str, text: &str) -> bool {
+  let pattern: Vec<char> = pattern.chars().collect();
+  let text: Vec<char> = text.chars().collect();
+  let (mut p, mut t) = (0, 0);
+  // Position after the last `*` and the text position it was tried at
+  let mut backtrack: Option<(usize, usize)> = None;
+
+  while t < text.len() {
+    if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+      p += 1;
+      t += 1;
+    } else if p < pattern.len() && pattern[p] == '*' {
+      backtrack = Some((p + 1, t));
+      p += 1;
+    } else if let Some((star_p, star_t)) = backtrack {
+      // Let the last `*` swallow one more character
+      backtrack = Some((star_p, star_t + 1));
+      p = star_p;
+      t = star_t + 1;
+    } else {
+      return false;
+    }
+  }
+
+  pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+  Word(String),
+  Phrase(String),
+  Field(Field),
+  And,
+  Or,
+  Not,
+  Minus,
+  Open,
+  Close,
+}
+
+impl Token {
+  fn describe(&self) -> String {
+    match self {
+      Token::Word(word) => format!("'{word}'"),
+      Token::Phrase(phrase) => format!("\"{phrase}\""),
+      Token::Field(_) => "field".to_string(),
+      Token::And => "AND".to_string(),
+      Token::Or => "OR".to_string(),
+      Token::Not => "NOT".to_string(),
+      Token::Minus => "'-'".to_string(),
+      Token::Open => "'('".to_string(),
+      Token::Close => "')'".to_string(),
+    }
+  }
+}
+
+fn lex(input: &str) -> Result<Vec<Token>> {
+  let mut tokens = Vec::new();
+  let mut chars = input.chars().peekable();
+
+  while let Some(&c) = chars.peek() {
+    match c {
+      c if c.is_whitespace() => {
+        chars.next();
+      }
+      '(' => {
+        chars.next();
+        tokens.push(Token::Open);
+      }
+      ')' => {
+        chars.next();
+        tokens.push(Token::Close);
+      }
+      '"' => {
+        chars.next();
+        let mut phrase = String::new();
+        loop {
+          match chars.next() {
+            Some('"') => break,
+            Some(c) => phrase.push(c),
+            None => return Err(anyhow!("Invalid query: unclosed quote")),
+          }
+        }
+        tokens.push(Token::Phrase(phrase));
+      }
+      '-' => {
+        chars.next();
+        match chars.peek() {
+          Some(&next) if !next.is_whitespace() && next != ')' => tokens.push(Token::Minus),
+          // A lone dash is just a word
+          _ => tokens.push(Token::Word("-".to_string())),
+        }
+      }
+      _ => {
+        let mut word = String::new();
+        while let Some(&c) = chars.peek() {
+          if c.is_whitespace() || matches!(c, '(' | ')' | '"') {
+            break;
+          }
+          word.push(c);
+          chars.next();
+        }
+        tokens.extend(word_tokens(word));
+      }
+    }
+  }
+
+  Ok(tokens)
+}
+
+/// Split a bare word into an operator, or a field prefix and its value
+fn word_tokens(word: String) -> Vec<Token> {
+  match word.as_str() {
+    "AND" => return vec![Token::And],
+    "OR" => return vec![Token::Or],
+    "NOT" => return vec![Token::Not],
+    _ => {}
+  }
+
+  if let Some((prefix, value)) = word.split_once(':') {
+    if let Some(field) = Field::from_prefix(prefix) {
+      if value.is_empty() {
+        return vec![Token::Field(field)];
+      }
+      return vec![Token::Field(field), Token::Word(value.to_string())];
+    }
+  }
+  vec![Token::Word(word)]
+}
+
+struct Parser {
+  tokens: Vec<Token>,
+  position: usize,
+}
+
+impl Parser {
+  fn peek(&self) -> Option<&Token> {
+    self.tokens.get(self.position)
+  }
+
+  fn next(&mut self) -> Option<Token> {
+    let token = self.tokens.get(self.position).cloned();
+    self.position += 1;
+    token
+  }
+
+  fn eat(&mut self, expected: &Token) -> bool {
+    if self.peek() == Some(expected) {
+      self.position += 1;
+      true
+    } else {
+      false
+    }
+  }
+
+  /// Clauses up to the end of the input or a closing parenthesis
+  fn sequence(&mut self) -> Result<Query> {
+    let mut clauses = Vec::new();
+    while !matches!(self.peek(), None | Some(Token::Close)) {
+      clauses.push(self.or()?);
+    }
+    Ok(Query::Sequence(clauses))
+  }
+
+  fn or(&mut self) -> Result<Query> {
+    let mut clauses = vec![self.and()?];
+    while self.eat(&Token::Or) {
+      clauses.push(self.and()?);
+    }
+    Ok(if clauses.len() == 1 { clauses.remove(0) } else { Query::Or(clauses) })
+  }
+
+  fn and(&mut self) -> Result<Query> {
+    let mut clauses = vec![self.unary()?];
+    while self.eat(&Token::And) {
+      clauses.push(self.unary()?);
+    }
+    Ok(if clauses.len() == 1 { clauses.remove(0) } else { Query::And(clauses) })
+  }
+
+  fn unary(&mut self) -> Result<Query> {
+    if self.eat(&Token::Not) || self.eat(&Token::Minus) {
+      return Ok(Query::Not(Box::new(self.unary()?)));
+    }
+    self.primary()
+  }
+
+  fn primary(&mut self) -> Result<Query> {
+    match self.next() {
+      Some(Token::Open) => {
+        let group = self.sequence()?;
+        if !self.eat(&Token::Close) {
+          return Err(anyhow!("Invalid query: unclosed parenthesis"));
+        }
+        Ok(group)
+      }
+      Some(Token::Field(field)) => match self.next() {
+        Some(Token::Word(word)) => {
+          Ok(Query::Term(Term { field: Some(field), pattern: word_pattern(word) }))
+        }
+        Some(Token::Phrase(phrase)) => {
+          Ok(Query::Term(Term { field: Some(field), pattern: phrase_pattern(&phrase)? }))
+        }
+        _ => Err(anyhow!("Invalid query: expected a word or phrase after a field prefix")),
+      },
+      Some(Token::Word(word)) => Ok(Query::Term(Term { field: None, pattern: word_pattern(word) })),
+      Some(Token::Phrase(phrase)) => {
+        Ok(Query::Term(Term { field: None, pattern: phrase_pattern(&phrase)? }))
+      }
+      Some(token) => Err(anyhow!("Invalid query: unexpected {}", token.describe())),
+      None => Err(anyhow!("Invalid query: expected a term at the end")),
+    }
+  }
+}
+
+fn word_pattern(word: String) -> Pattern {
+  if word.contains(['*', '?']) {
+    Pattern::Wildcard(word)
+  } else {
+    Pattern::Word(word)
+  }
+}
+
+fn phrase_pattern(phrase: &str) -> Result<Pattern> {
+  let words: Vec<String> = bm25::tokenize(phrase).map(str::to_string).collect();
+  if words.is_empty() {
+    return Err(anyhow!("Invalid query: empty phrase"));
+  }
+  Ok(Pattern::Phrase(words))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn insight(topic: &str, name: &str, overview: &str, details: &str, tags: &[&str]) -> Insight {
+    let mut insight =
+      Insight::new(topic.to_string(), name.to_string(), overview.to_string(), details.to_string());
+    insight.tags = tags.iter().map(|tag| tag.to_string()).collect();
+    insight
+  }
+
+  fn admits(query: &str, insight: &Insight) -> bool {
+    parse(query).unwrap().admits(&Document::new(insight, false, false))
+  }
+
+  fn word(text: &str) -> Query {
+    Query::Term(Term { field: None, pattern: Pattern::Word(text.to_string()) })
+  }
+
+  #[test]
+  fn test_operator_precedence() {
+    let query = parse("a OR b AND NOT c d").unwrap();
+    assert_eq!(
+      query,
+      Query::Sequence(vec![
+        Query::Or(vec![word("a"), Query::And(vec![word("b"), Query::Not(Box::new(word("c")))])]),
+        word("d"),
+      ])
+    );
+  }
+
+  #[test]
+  fn test_lower_case_operators_are_words() {
+    assert_eq!(
+      parse("rock and roll").unwrap(),
+      Query::Sequence(vec![word("rock"), word("and"), word("roll")])
+    );
+  }
+
+  #[test]
+  fn test_fields_phrases_and_wildcards() {
+    let query = parse("topic:ops overview:\"blue green\" deplo*").unwrap();
+    assert_eq!(
+      query,
+      Query::Sequence(vec![
+        Query::Term(Term { field: Some(Field::Topic), pattern: Pattern::Word("ops".to_string()) }),
+        Query::Term(Term {
+          field: Some(Field::Overview),
+          pattern: Pattern::Phrase(vec!["blue".to_string(), "green".to_string()]),
+        }),
+        Query::Term(Term { field: None, pattern: Pattern::Wildcard("deplo*".to_string()) }),
+      ])
+    );
+    // Unknown prefixes stay part of the word
+    assert_eq!(parse("http://example").unwrap(), Query::Sequence(vec![word("http://example")]));
+  }
+
+  #[test]
+  fn test_invalid_queries() {
+    assert!(parse("\"unclosed phrase").is_err());
+    assert!(parse("(deploy OR rollback").is_err());
+    assert!(parse("deploy)").is_err());
+    assert!(parse("deploy AND").is_err());
+    assert!(parse("topic:").is_err());
+  }
+
+  #[test]
+  fn test_unparsable_terms_are_plain_words() {
+    let terms = vec!["\"unclosed".to_string(), "(deploy".to_string()];
+    assert_eq!(parse_terms(&terms), Query::Sequence(vec![word("unclosed"), word("deploy")]));
+  }
+
+  #[test]
+  fn test_punctuation_words_match_nothing() {
+    let deploy = insight("ops", "deploys", "How we deploy", "", &[]);
+    assert!(!admits("deploy AND topic:-", &deploy));
+    assert!(!admits("deploy AND ---", &deploy));
+  }
+
+  #[test]
+  fn test_hand_edited_tags_are_normalized() {
+    let deploy = insight("ops", "deploys", "How we deploy", "", &[" Release "]);
+    assert!(admits("tag:release", &deploy));
+    assert!(admits("tag:RELEASE", &deploy));
+    assert!(admits("tag:rel*", &deploy));
+  }
+
+  #[test]
+  fn test_plain_words_do_not_filter() {
+    let deploy = insight("ops", "deploys", "How we deploy", "Every tuesday", &["release"]);
+    assert!(admits("unrelated words", &deploy));
+    assert!(!admits("unrelated AND words", &deploy));
+    assert!(admits("deploy OR unrelated", &deploy));
+  }
+
+  #[test]
+  fn test_exclusions_and_fields() {
+    let deploy =
+      insight("ops", "deploys", "How we deploy", "Every tuesday to staging", &["release"]);
+    assert!(!admits("deploy -staging", &deploy));
+    assert!(!admits("deploy NOT staging", &deploy));
+    assert!(admits("topic:ops", &deploy));
+    assert!(!admits("topic:deploy", &deploy));
+    assert!(admits("details:tuesday", &deploy));
+    assert!(!admits("overview:tuesday", &deploy));
+    assert!(admits("tag:Release", &deploy));
+    assert!(!admits("-tag:release", &deploy));
+  }
+
+  #[test]
+  fn test_phrases_match_consecutive_words() {
+    let deploy = insight("ops", "deploys", "Blue-green deploys", "Switch traffic", &[]);
+    assert!(admits("\"blue green\"", &deploy));
+    assert!(!admits("\"green blue\"", &deploy));
+    assert!(admits("\"switch traffic\" OR \"nothing here\"", &deploy));
+  }
+
+  #[test]
+  fn test_wildcards_match_whole_tokens() {
+    let deploy = insight("ops", "deploys", "Deployment checklist", "", &["release-2024"]);
+    assert!(admits("deploy*", &deploy));
+    assert!(admits("d?ployment", &deploy));
+    assert!(admits("*list", &deploy));
+    assert!(!admits("ploy*", &deploy));
+    assert!(admits("tag:release-*", &deploy));
+  }
+
+  #[test]
+  fn test_matched_terms() {
+    let deploy = insight("ops", "deploys", "Blue green deploys", "", &["release"]);
+    let query = parse("deploy \"blue green\" rollback -staging tag:release").unwrap();
+    assert_eq!(
+      query.matched_terms(&Document::new(&deploy, false, false)),
+      vec!["deploy", "\"blue green\"", "tag:release"]
+    );
+  }
+
+  #[test]
+  fn test_text_terms_leave_out_exclusions_and_tags() {
+    let query = parse("deploy \"blue green\" -staging tag:ops roll*").unwrap();
+    assert_eq!(query.text_terms(), vec!["deploy", "blue", "green", "roll"]);
+    assert_eq!(query.highlight_terms(), vec!["deploy", "blue green", "roll"]);
+  }
+
+  #[cfg(feature = "neural")]
+  #[test]
+  fn test_plain_words_have_no_constraints() {
+    assert!(parse("deploy -staging").unwrap().has_constraints());
+    assert!(parse("deploy OR rollback").unwrap().has_constraints());
+    assert!(!parse("deploy rollback").unwrap().has_constraints());
+  }
+}
@@ -0,0 +1,181 @@
+//! Least-recently-used cache of query embeddings, held by the embedding
+//! daemon so repeated searches skip inference. The cache can be saved next to
+//! the model it was computed with and loaded again by the next daemon.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::model_registry;
+
+/// Queries kept when no size is configured
+pub const DEFAULT_CAPACITY: usize = 1024;
+/// Overrides the number of cached queries; 0 disables the cache
+#[allow(dead_code)] // used by the daemon binary
+pub const CAPACITY_ENV: &str = "INSIGHTS_QUERY_CACHE_SIZE";
+/// Set to save the cache when the daemon exits and load it when it starts
+#[allow(dead_code)] // used by the daemon binary
+pub const PERSIST_ENV: &str = "INSIGHTS_PERSIST_QUERY_CACHE";
+
+const CACHE_FILE: &str = "query-cache.json";
+
+pub struct QueryCache {
+  capacity: usize,
+  entries: HashMap<String, Entry>,
+  /// Increases with every use; the entry with the lowest stamp is evicted
+  clock: u64,
+  hits: u64,
+  /// Where the cache is saved, if it is persistent
+  file: Option<PersistentFile>,
+}
+
+struct Entry {
+  embedding: Vec<f32>,
+  last_used: u64,
+}
+
+struct PersistentFile {
+  path: PathBuf,
+  model: String,
+}
+
+/// On-disk form, least recently used query first
+#[derive(Serialize, Deserialize)]
+struct CacheFile {
+  model: String,
+  queries: Vec<CachedQuery>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedQuery {
+  text: String,
+  embedding: Vec<f32>,
+}
+
+impl QueryCache {
+  /// An in-memory cache holding up to `capacity` queries
+  pub fn new(capacity: usize) -> Self {
+    Self { capacity, entries: HashMap::new(), clock: 0, hits: 0, file: None }
+  }
+
+  /// A cache saved to `path` by `save`, starting from whatever an earlier
+  /// daemon saved there for the same model
+  #[allow(dead_code)] // used by the daemon binary
+  pub fn persistent(capacity: usize, path: PathBuf, model: &str) -> Self {
+    let mut cache = Self::new(capacity);
+    if let Some(saved) = read_cache_file(&path).filter(|saved| saved.model == model) {
+      for query in saved.queries {
+        cache.insert(query.text, query.embedding);
+      }
+    }
+    cache.file = Some(PersistentFile { path, model: model.to_string() });
+    cache
+  }
+
+  /// Look up a query, marking it as recently used
+  pub fn get(&mut self, text: &str) -> Option<Vec<f32>> {
+    self.clock += 1;
+    let entry = self.entries.get_mut(text)?;
+    entry.last_used = self.clock;
+    self.hits += 1;
+    Some(entry.embedding.clone())
+  }
+
+  /// Remember a query's embedding, evicting the least recently used query if
+  /// the cache is full
+  pub fn insert(&mut self, text: String, embedding: Vec<f32>) {
+    if self.capacity == 0 {
+      return;
+    }
+    if self.entries.len() >= self.capacity && !self.entries.contains_key(&text) {
+      let oldest = self.entries.iter().min_by_key(|(_, entry)| entry.last_used);
+      if let Some(oldest) = oldest.map(|(text, _)| text.clone()) {
+        self.entries.remove(&oldest);
+      }
+    }
+    self.clock += 1;
+    self.entries.insert(text, Entry { embedding, last_used: self.clock });
+  }
+
+  pub fn len(&self) -> usize {
+    self.entries.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.entries.is_empty()
+  }
+
+  /// Lookups answered from the cache
+  pub fn hits(&self) -> u64 {
+    self.hits
+  }
+
+  /// Write the cache to its file, if it is persistent
+  pub fn save(&self) -> Result<()> {
+    let Some(file) = &self.file else {
+      return Ok(());
+    };
+    if self.is_empty() && !file.path.exists() {
+      return Ok(());
+    }
+
+    let mut entries: Vec<(&String, &Entry)> = self.entries.iter().collect();
+    entries.sort_by_key(|(_, entry)| entry.last_used);
+    let saved = CacheFile {
+      model: file.model.clone(),
+      queries: entries
+        .into_iter()
+        .map(|(text, entry)| CachedQuery { text: text.clone(), embedding: entry.embedding.clone() })
+        .collect(),
+    };
+
+    if let Some(parent) = file.path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+    let partial = file.path.with_extension("json.partial");
+    fs::write(&partial, serde_json::to_string(&saved)?)?;
+    fs::rename(&partial, &file.path)?;
+    Ok(())
+  }
+}
+
+/// Where the query cache for a model is saved, next to the installed model
+#[allow(dead_code)] // used by the daemon binary
+pub fn cache_path(model: &str) -> Result<PathBuf> {
+  Ok(model_registry::cache_dir()?.join(model).join(CACHE_FILE))
+}
+
+/// A saved cache, ignoring files that are missing or unreadable
+fn read_cache_file(path: &Path) -> Option<CacheFile> {
+  serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_least_recently_used_query_is_evicted() {
+    let mut cache = QueryCache::new(2);
+    cache.insert("deploy".to_string(), vec![1.0]);
+    cache.insert("rollback".to_string(), vec![2.0]);
+
+    assert_eq!(cache.get("deploy"), Some(vec![1.0]));
+    cache.insert("backup".to_string(), vec![3.0]);
+
+    assert_eq!(cache.len(), 2);
+    assert!(cache.get("rollback").is_none());
+    assert_eq!(cache.get("backup"), Some(vec![3.0]));
+    assert_eq!(cache.hits(), 2);
+  }
+
+  #[test]
+  fn test_zero_capacity_disables_cache() {
+    let mut cache = QueryCache::new(0);
+    cache.insert("deploy".to_string(), vec![1.0]);
+    assert!(cache.is_empty());
+    assert!(cache.get("deploy").is_none());
+  }
+}
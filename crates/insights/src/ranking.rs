@@ -0,0 +1,201 @@
+//! Score fusion across the lexical, semantic and neural search signals.
+//!
+//! Each signal scores on its own scale, so results are fused by rank using
+//! weighted reciprocal rank fusion rather than by comparing raw scores.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::search::SearchResult;
+
+/// Dampens the advantage of top ranks so agreement across signals matters
+const RRF_K: f32 = 60.0;
+
+pub const DEFAULT_WEIGHT: f32 = 1.0;
+
+/// Relative weight of each search signal when fusing rankings
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FusionWeights {
+  pub lexical: f32,
+  #[cfg(feature = "semantic")]
+  pub semantic: f32,
+  #[cfg(feature = "neural")]
+  pub neural: f32,
+}
+
+impl Default for FusionWeights {
+  fn default() -> Self {
+    Self {
+      lexical: DEFAULT_WEIGHT,
+      #[cfg(feature = "semantic")]
+      semantic: DEFAULT_WEIGHT,
+      #[cfg(feature = "neural")]
+      neural: DEFAULT_WEIGHT,
+    }
+  }
+}
+
+/// Search signal a ranking comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Strategy {
+  /// BM25 over the query terms
+  Exact,
+  /// Word overlap similarity
+  Semantic,
+  /// Cosine similarity of embeddings
+  Neural,
+}
+
+impl fmt::Display for Strategy {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let name = match self {
+      Strategy::Exact => "exact",
+      Strategy::Semantic => "semantic",
+      Strategy::Neural => "neural",
+    };
+    f.write_str(name)
+  }
+}
+
+/// What one signal added to a result's fused score
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contribution {
+  pub strategy: Strategy,
+  /// Score on the signal's own scale, e.g. BM25 or cosine similarity
+  pub raw_score: f32,
+  /// Position in the signal's ranking, starting at 1
+  pub rank: usize,
+  /// Share of the fused score
+  pub score: f32,
+}
+
+/// A signal's results ordered best-first, with the weight it contributes
+pub struct Ranking {
+  pub strategy: Strategy,
+  pub weight: f32,
+  pub results: Vec<SearchResult>,
+}
+
+/// Order results best-first by raw score, breaking ties by topic and name
+pub fn sort_by_score(results: &mut [SearchResult]) {
+  results.sort_by(|a, b| {
+    b.score
+      .partial_cmp(&a.score)
+      .unwrap_or(std::cmp::Ordering::Equal)
+      .then_with(|| a.topic.cmp(&b.topic).then_with(|| a.name.cmp(&b.name)))
+  });
+}
+
+/// Merge rankings into one list scored by weighted reciprocal rank fusion.
+/// Each result records what every signal contributed to its score.
+///
+/// Rankings with a zero weight are ignored, so a signal can be switched off
+/// by weighting it out.
+pub fn fuse(rankings: Vec<Ranking>) -> Vec<SearchResult> {
+  let mut fused: HashMap<(String, String), SearchResult> = HashMap::new();
+
+  for ranking in rankings.into_iter().filter(|ranking| ranking.weight > 0.0) {
+    for (rank, result) in ranking.results.into_iter().enumerate() {
+      let contribution = Contribution {
+        strategy: ranking.strategy,
+        raw_score: result.score,
+        rank: rank + 1,
+        score: ranking.weight / (RRF_K + rank as f32 + 1.0),
+      };
+      let existing = fused
+        .entry((result.topic.clone(), result.name.clone()))
+        .or_insert(SearchResult { score: 0.0, contributions: Vec::new(), ..result });
+      existing.score += contribution.score;
+      existing.contributions.push(contribution);
+    }
+  }
+
+  let mut results: Vec<SearchResult> = fused.into_values().collect();
+  sort_by_score(&mut results);
+  results
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn result(name: &str, score: f32) -> SearchResult {
+    SearchResult {
+      topic: "topic".to_string(),
+      name: name.to_string(),
+      overview: String::new(),
+      details: String::new(),
+      scope: Default::default(),
+      score,
+      contributions: Vec::new(),
+      matched_terms: None,
+      snippet: None,
+    }
+  }
+
+  fn ranking(strategy: Strategy, weight: f32, results: Vec<SearchResult>) -> Ranking {
+    Ranking { strategy, weight, results }
+  }
+
+  fn names(results: &[SearchResult]) -> Vec<&str> {
+    results.iter().map(|result| result.name.as_str()).collect()
+  }
+
+  #[test]
+  fn test_raw_score_scale_does_not_dominate() {
+    // A large lexical count must not outrank agreement between two signals
+    let lexical =
+      ranking(Strategy::Exact, 1.0, vec![result("counted", 7.0), result("agreed", 1.0)]);
+    let neural = ranking(Strategy::Neural, 1.0, vec![result("agreed", 0.9)]);
+
+    let fused = fuse(vec![lexical, neural]);
+    assert_eq!(names(&fused), vec!["agreed", "counted"]);
+  }
+
+  #[test]
+  fn test_weights_shift_ranking() {
+    let lexical = ranking(Strategy::Exact, 1.0, vec![result("lexical", 3.0)]);
+    let neural = ranking(Strategy::Neural, 2.0, vec![result("neural", 0.5)]);
+
+    let fused = fuse(vec![lexical, neural]);
+    assert_eq!(names(&fused), vec!["neural", "lexical"]);
+  }
+
+  #[test]
+  fn test_zero_weight_drops_signal() {
+    let lexical = ranking(Strategy::Exact, 0.0, vec![result("lexical", 3.0)]);
+    let neural = ranking(Strategy::Neural, 1.0, vec![result("neural", 0.5)]);
+
+    let fused = fuse(vec![lexical, neural]);
+    assert_eq!(names(&fused), vec!["neural"]);
+  }
+
+  #[test]
+  fn test_duplicates_are_merged() {
+    let first = ranking(Strategy::Exact, 1.0, vec![result("same", 1.0)]);
+    let second = ranking(Strategy::Neural, 1.0, vec![result("same", 1.0)]);
+
+    let fused = fuse(vec![first, second]);
+    assert_eq!(fused.len(), 1);
+    assert!((fused[0].score - 2.0 / (RRF_K + 1.0)).abs() < f32::EPSILON);
+  }
+
+  #[test]
+  fn test_contributions_explain_fused_score() {
+    let lexical = ranking(Strategy::Exact, 1.0, vec![result("other", 5.0), result("same", 3.0)]);
+    let neural = ranking(Strategy::Neural, 2.0, vec![result("same", 0.8)]);
+
+    let fused = fuse(vec![lexical, neural]);
+    let same = fused.iter().find(|result| result.name == "same").unwrap();
+    assert_eq!(same.contributions.len(), 2);
+    assert_eq!(same.contributions[0].strategy, Strategy::Exact);
+    assert_eq!(same.contributions[0].rank, 2);
+    assert_eq!(same.contributions[0].raw_score, 3.0);
+    assert_eq!(same.contributions[1].strategy, Strategy::Neural);
+    assert_eq!(same.contributions[1].raw_score, 0.8);
+    let total: f32 = same.contributions.iter().map(|contribution| contribution.score).sum();
+    assert!((same.score - total).abs() < f32::EPSILON);
+  }
+}
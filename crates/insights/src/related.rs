@@ -0,0 +1,129 @@
+//! Related and near-duplicate insights, found by comparing the embeddings
+//! stored with each insight. Nothing is embedded here, so insights without an
+//! embedding, or with one from another model, are left out.
+
+use crate::index;
+use crate::insight::Insight;
+use crate::similarity;
+
+/// Neighbours shown by `related` when no limit is given
+pub const DEFAULT_LIMIT: usize = 5;
+/// Similarity at which two insights are considered to hold the same knowledge
+pub const DEFAULT_DUPLICATE_THRESHOLD: f32 = 0.9;
+
+/// An insight and how similar it is to the one it was compared with
+#[derive(Debug, Clone)]
+pub struct Neighbour {
+  pub insight: Insight,
+  pub similarity: f32,
+}
+
+/// Insights that are all near-duplicates of at least one other member
+#[derive(Debug)]
+pub struct DuplicateGroup {
+  /// Members ordered by topic and name
+  pub insights: Vec<Insight>,
+  /// Highest similarity between two members
+  pub similarity: f32,
+}
+
+/// Cosine similarity of two insights' stored embeddings, or None unless both
+/// were embedded by the same model
+pub fn similarity(a: &Insight, b: &Insight) -> Option<f32> {
+  let first = a.embedding.as_deref().filter(|embedding| !embedding.is_empty())?;
+  let second = b.embedding.as_deref().filter(|embedding| !embedding.is_empty())?;
+  if a.embedding_version != b.embedding_version {
+    return None;
+  }
+  similarity::cosine(first, second)
+}
+
+/// The `limit` candidates most similar to `insight`, most similar first
+pub fn nearest(insight: &Insight, candidates: Vec<Insight>, limit: usize) -> Vec<Neighbour> {
+  let mut neighbours: Vec<Neighbour> = candidates
+    .into_iter()
+    .filter(|candidate| !is_same_insight(candidate, insight))
+    .filter_map(|candidate| {
+      Some(Neighbour { similarity: similarity(insight, &candidate)?, insight: candidate })
+    })
+    .collect();
+
+  neighbours.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+  neighbours.truncate(limit);
+  neighbours
+}
+
+/// Candidates at least `threshold` similar to `insight`, most similar first
+pub fn similar(insight: &Insight, candidates: Vec<Insight>, threshold: f32) -> Vec<Neighbour> {
+  let mut neighbours = nearest(insight, candidates, usize::MAX);
+  neighbours.retain(|neighbour| neighbour.similarity >= threshold);
+  neighbours
+}
+
+/// Cluster insights whose similarity reaches `threshold`. An insight joins a
+/// group when it is a near-duplicate of any member, so every pair in a group
+/// is connected through near-duplicates. Groups are returned most similar first.
+pub fn duplicates(insights: Vec<Insight>, threshold: f32) -> Vec<DuplicateGroup> {
+  let mut clusters = Clusters::new(insights.len());
+  let mut strongest = vec![f32::MIN; insights.len()];
+
+  for (i, first) in insights.iter().enumerate() {
+    for (j, second) in insights.iter().enumerate().skip(i + 1) {
+      let Some(score) = similarity(first, second).filter(|score| *score >= threshold) else {
+        continue;
+      };
+      clusters.join(i, j);
+      strongest[i] = strongest[i].max(score);
+      strongest[j] = strongest[j].max(score);
+    }
+  }
+
+  let mut groups: Vec<DuplicateGroup> = Vec::new();
+  let mut group_of_root = vec![None; insights.len()];
+  for (i, insight) in insights.into_iter().enumerate() {
+    if strongest[i] == f32::MIN {
+      continue;
+    }
+    let root = clusters.root(i);
+    let group = *group_of_root[root].get_or_insert_with(|| {
+      groups.push(DuplicateGroup { insights: Vec::new(), similarity: f32::MIN });
+      groups.len() - 1
+    });
+    groups[group].insights.push(insight);
+    groups[group].similarity = groups[group].similarity.max(strongest[i]);
+  }
+
+  for group in &mut groups {
+    group.insights.sort_by(|a, b| (&a.topic, &a.name).cmp(&(&b.topic, &b.name)));
+  }
+  groups.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+  groups
+}
+
+fn is_same_insight(a: &Insight, b: &Insight) -> bool {
+  a.scope == b.scope && index::key(&a.topic, &a.name) == index::key(&b.topic, &b.name)
+}
+
+/// Union-find over insight positions
+struct Clusters {
+  parent: Vec<usize>,
+}
+
+impl Clusters {
+  fn new(size: usize) -> Self {
+    Self { parent: (0..size).collect() }
+  }
+
+  fn root(&mut self, mut node: usize) -> usize {
+    while self.parent[node] != node {
+      self.parent[node] = self.parent[self.parent[node]];
+      node = self.parent[node];
+    }
+    node
+  }
+
+  fn join(&mut self, a: usize, b: usize) {
+    let (root_a, root_b) = (self.root(a), self.root(b));
+    self.parent[root_b] = root_a;
+  }
+}
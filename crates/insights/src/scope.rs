@@ -0,0 +1,116 @@
+//! Insight stores and their precedence.
+//!
+//! Insights live either in the personal global store or in a project store at
+//! `.kernelle/insights` inside a repository. Both are read together; when the
+//! same topic/name exists in both, the project insight takes precedence and
+//! the global one is hidden.
+
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::env;
+use std::path::{Path, PathBuf};
+
+use crate::insight;
+
+/// Overrides project store discovery. An empty value disables the project store.
+pub const PROJECT_ROOT_ENV: &str = "INSIGHTS_PROJECT_ROOT";
+pub const GLOBAL_ROOT_ENV: &str = "INSIGHTS_ROOT";
+
+const PROJECT_DIR: &str = ".kernelle";
+const PROJECT_STORE_DIR: &str = "insights";
+const REPOSITORY_MARKER: &str = ".git";
+
+thread_local! {
+  /// Root of the only store while `with_root` runs
+  static ROOT_OVERRIDE: RefCell<Option<PathBuf>> = const { RefCell::new(None) };
+}
+
+/// Which store an insight lives in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+  /// Shared with a repository, under `.kernelle/insights`
+  Project,
+  /// Personal store under the home directory
+  #[default]
+  Global,
+}
+
+impl std::fmt::Display for Scope {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Scope::Project => write!(f, "project"),
+      Scope::Global => write!(f, "global"),
+    }
+  }
+}
+
+/// Root of the project store for the current directory, whether or not it exists yet.
+///
+/// This is the nearest `.kernelle/insights` directory above the current
+/// directory, or the one at the top of the enclosing git repository.
+/// `INSIGHTS_ROOT` only moves the global store, so discovery still runs.
+pub fn project_root() -> Option<PathBuf> {
+  if root_override().is_some() {
+    return None;
+  }
+  if let Ok(custom_root) = env::var(PROJECT_ROOT_ENV) {
+    return (!custom_root.is_empty()).then(|| PathBuf::from(custom_root));
+  }
+
+  let current_dir = env::current_dir().ok()?;
+  current_dir.ancestors().find_map(|dir| {
+    let store = dir.join(PROJECT_DIR).join(PROJECT_STORE_DIR);
+    (store.is_dir() || dir.join(REPOSITORY_MARKER).exists()).then_some(store)
+  })
+}
+
+/// Root directory of a store
+pub fn root(scope: Scope) -> Result<PathBuf> {
+  match scope {
+    Scope::Global => insight::get_insights_root(),
+    Scope::Project => project_root().ok_or_else(|| {
+      anyhow!("Not inside a project: run from a git repository or set {}", PROJECT_ROOT_ENV)
+    }),
+  }
+}
+
+/// Stores to read from, highest precedence first. The project store is only
+/// included once it exists.
+pub fn stores() -> Result<Vec<(Scope, PathBuf)>> {
+  let mut stores = Vec::new();
+  if let Some(project) = project_root().filter(|root| root.is_dir()) {
+    stores.push((Scope::Project, project));
+  }
+  stores.push((Scope::Global, insight::get_insights_root()?));
+  Ok(stores)
+}
+
+/// Run `f` with `root` as the global store and no project store, e.g. to
+/// work in a scratch store without touching the configured ones
+pub fn with_root<T>(root: &Path, f: impl FnOnce() -> T) -> T {
+  struct Restore(Option<PathBuf>);
+  impl Drop for Restore {
+    fn drop(&mut self) {
+      ROOT_OVERRIDE.with(|root| *root.borrow_mut() = self.0.take());
+    }
+  }
+
+  let _restore = Restore(ROOT_OVERRIDE.with(|previous| previous.replace(Some(root.to_path_buf()))));
+  f()
+}
+
+/// Root set by `with_root` on this thread, if any
+pub fn root_override() -> Option<PathBuf> {
+  ROOT_OVERRIDE.with(|root| root.borrow().clone())
+}
+
+/// Store that a path inside one of the stores belongs to
+pub fn of_path(path: &Path) -> Scope {
+  match project_root() {
+    Some(project) if path.starts_with(&project) => Scope::Project,
+    _ => Scope::Global,
+  }
+}
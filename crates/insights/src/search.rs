@@ -2,14 +2,24 @@ use anyhow::Result;
 use clap::Args;
 use colored::*;
 
-use std::fs;
-use std::path::{Path, PathBuf};
+#[cfg(feature = "neural")]
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "neural")]
+use std::path::Path;
 
+use crate::bm25;
 #[cfg(feature = "neural")]
 use crate::embedding_client;
+#[cfg(feature = "neural")]
+use crate::index;
 use crate::insight;
-#[cfg(any(feature = "semantic", feature = "neural"))]
+use crate::query::{self, Document, Query};
+use crate::ranking::{self, Contribution, FusionWeights, Ranking, Strategy};
+#[cfg(feature = "neural")]
+use crate::scope;
+use crate::scope::Scope;
 use crate::similarity;
+use crate::snippet;
 
 // Semantic similarity threshold for meaningful results
 #[cfg(feature = "semantic")]
@@ -24,7 +34,14 @@ pub struct SearchResult {
   pub name: String,
   pub overview: String,
   pub details: String,
-  pub score: f32, // number of matching terms
+  pub scope: Scope,
+  pub score: f32, // fused rank score
+  /// What each strategy added to `score`
+  pub contributions: Vec<Contribution>,
+  /// Query terms the insight matched, only worked out when `explain` is set
+  pub matched_terms: Option<Vec<String>>,
+  /// Best-matching passages of long details, shown instead of the full text
+  pub snippet: Option<String>,
 }
 
 /// Search configuration options
@@ -46,19 +63,91 @@ pub struct SearchCommandOptions {
   /// Use exact term matching only (fastest, drops neural and semantic)
   #[arg(short, long)]
   exact: bool,
+  /// Only search insights with this tag (repeatable, all must match)
+  #[arg(long = "tag")]
+  tags: Vec<String>,
+  /// Weight of the BM25 lexical ranking when fusing results; 0 leaves it out
+  #[arg(long, default_value_t = ranking::DEFAULT_WEIGHT)]
+  lexical_weight: f32,
+  /// Weight of the semantic similarity ranking when fusing results
+  #[cfg(feature = "semantic")]
+  #[arg(long, default_value_t = ranking::DEFAULT_WEIGHT)]
+  semantic_weight: f32,
+  /// Weight of the neural embedding ranking when fusing results
+  #[cfg(feature = "neural")]
+  #[arg(long, default_value_t = ranking::DEFAULT_WEIGHT)]
+  neural_weight: f32,
+  /// Show at most this many results
+  #[arg(short, long)]
+  limit: Option<usize>,
+  /// Skip this many results, to page through them with --limit
+  #[arg(long, default_value_t = 0)]
+  offset: usize,
+  /// Drop results whose fused score is below this. Fused scores add up
+  /// weight / (60 + rank) over the strategies that found a result, so a first
+  /// hit from one strategy at weight 1 scores about 0.016; see --explain
+  #[arg(long)]
+  min_score: Option<f32>,
+  /// Show what each strategy contributed to every score and which terms matched
+  #[arg(long)]
+  explain: bool,
+  /// Show the whole details of every result instead of the best-matching passages
+  #[arg(long)]
+  full: bool,
+  /// Pick snippet passages by embedding similarity to the query instead of matching terms
+  #[cfg(feature = "neural")]
+  #[arg(long, conflicts_with = "full")]
+  embedding_snippets: bool,
 }
 
 pub struct SearchOptions {
   pub topic: Option<String>,
   pub case_sensitive: bool,
   pub overview_only: bool,
+  /// Leave out neural ranking, which is all it changes
   #[cfg(feature = "semantic")]
+  #[cfg_attr(not(feature = "neural"), allow(dead_code))]
   pub semantic: bool,
   pub exact: bool,
+  pub tags: Vec<String>,
+  pub weights: FusionWeights,
+  pub limit: Option<usize>,
+  pub offset: usize,
+  pub min_score: Option<f32>,
+  pub explain: bool,
+  pub full: bool,
+  #[cfg(feature = "neural")]
+  pub embedding_snippets: bool,
   #[cfg(feature = "neural")]
   pub embedding_client: embedding_client::EmbeddingClient,
 }
 
+/// Every strategy with default weights, no filters and no paging, embedding
+/// through the daemon
+impl Default for SearchOptions {
+  fn default() -> Self {
+    Self {
+      topic: None,
+      case_sensitive: false,
+      overview_only: false,
+      #[cfg(feature = "semantic")]
+      semantic: false,
+      exact: false,
+      tags: Vec::new(),
+      weights: FusionWeights::default(),
+      limit: None,
+      offset: 0,
+      min_score: None,
+      explain: false,
+      full: false,
+      #[cfg(feature = "neural")]
+      embedding_snippets: false,
+      #[cfg(feature = "neural")]
+      embedding_client: embedding_client::create(),
+    }
+  }
+}
+
 impl SearchOptions {
   pub fn from(options: &SearchCommandOptions) -> Self {
     Self {
@@ -68,69 +157,172 @@ impl SearchOptions {
       #[cfg(feature = "semantic")]
       semantic: options.semantic,
       exact: options.exact,
+      tags: options.tags.clone(),
+      weights: FusionWeights {
+        lexical: options.lexical_weight,
+        #[cfg(feature = "semantic")]
+        semantic: options.semantic_weight,
+        #[cfg(feature = "neural")]
+        neural: options.neural_weight,
+      },
+      limit: options.limit,
+      offset: options.offset,
+      min_score: options.min_score,
+      explain: options.explain,
+      full: options.full,
+      #[cfg(feature = "neural")]
+      embedding_snippets: options.embedding_snippets,
       #[cfg(feature = "neural")]
       embedding_client: embedding_client::create(),
     }
   }
 }
 
+/// Search results, and why neural ranking was left out if it had to be
+pub struct SearchOutcome {
+  /// The requested page of results
+  pub results: Vec<SearchResult>,
+  /// Number of results above the minimum score, before `offset` and `limit`
+  pub total: usize,
+  pub neural_unavailable: Option<String>,
+}
+
+#[allow(dead_code)] // Library entry point; the CLI reports the outcome
 pub fn search(terms: &[String], options: &SearchOptions) -> Result<Vec<SearchResult>> {
-  let mut results = Vec::new();
+  Ok(search_with_outcome(terms, options)?.results)
+}
 
-  if can_use_exact_search(options) {
-    results.extend(search_topic(terms, get_exact_match, 0.0, options)?);
-  }
+/// Search by fusing the BM25, semantic and neural rankings, falling back to
+/// the first two when neural embeddings can't be computed.
+///
+/// The terms are parsed with the query language in `query`: its constraints
+/// pick the candidates every stage ranks, and its text is what they rank by.
+/// A query made only of constraints, such as `tag:ops`, lists the candidates
+/// through the lexical stage.
+pub fn search_with_outcome(terms: &[String], options: &SearchOptions) -> Result<SearchOutcome> {
+  let query = query::parse_terms(terms);
+  let text_terms = query.text_terms();
+  let mut rankings = Vec::new();
+  #[allow(unused_mut)]
+  let mut neural_unavailable = None;
+
+  #[cfg(feature = "neural")]
+  let neural = if can_use_embedding_search(options) && !text_terms.is_empty() {
+    let results = search_embeddings(&query, &text_terms, options)?;
+    if results.is_none() {
+      neural_unavailable = Some(
+        embedding_client::unavailable(&options.embedding_client)
+          .or_else(|| embedding_client::last_error(&options.embedding_client))
+          .unwrap_or_else(|| "the query could not be embedded".to_string()),
+      );
+    }
+    results
+  } else {
+    None
+  };
+
+  // BM25 joins every search; a zero lexical weight leaves it out of the fusion
+  rankings.push(Ranking {
+    strategy: Strategy::Exact,
+    weight: options.weights.lexical,
+    results: search_lexical(&query, options)?,
+  });
 
   #[cfg(feature = "semantic")]
-  if can_use_semantic_similarity_search(options) {
-    results.extend(search_topic(
-      terms,
-      get_semantic_match,
-      SEMANTIC_SIMILARITY_THRESHOLD,
-      options,
-    )?);
+  if can_use_semantic_similarity_search(options) && !text_terms.is_empty() {
+    similarity::stop_words()?;
+    rankings.push(Ranking {
+      strategy: Strategy::Semantic,
+      weight: options.weights.semantic,
+      results: search_topic(
+        &text_terms,
+        &query,
+        get_semantic_match,
+        SEMANTIC_SIMILARITY_THRESHOLD,
+        options,
+      )?,
+    });
   }
 
   #[cfg(feature = "neural")]
-  if can_use_embedding_search(options) {
-    results.extend(search_topic(
-      terms,
-      get_embedding_match,
-      EMBEDDING_SIMILARITY_THRESHOLD,
-      options,
-    )?);
+  if let Some(results) = neural {
+    rankings.push(Ranking { strategy: Strategy::Neural, weight: options.weights.neural, results });
   }
 
-  results.sort_by(|a, b| {
-    b.score
-      .partial_cmp(&a.score)
-      .unwrap_or(std::cmp::Ordering::Equal)
-      .then_with(|| a.topic.cmp(&b.topic).then_with(|| a.name.cmp(&b.name)))
-  });
+  for ranking in &mut rankings {
+    ranking::sort_by_score(&mut ranking.results);
+  }
 
-  results.dedup_by(|a, b| a.topic == b.topic && a.name == b.name);
+  let mut results = ranking::fuse(rankings);
+  if let Some(min_score) = options.min_score {
+    results.retain(|result| result.score >= min_score);
+  }
+  let total = results.len();
+  let mut results: Vec<SearchResult> =
+    results.into_iter().skip(options.offset).take(options.limit.unwrap_or(usize::MAX)).collect();
+  #[cfg(feature = "neural")]
+  load_indexed_content(&mut results)?;
 
-  Ok(results)
+  if options.explain {
+    for result in &mut results {
+      let insight = insight::load(&result.topic, &result.name)?;
+      let document = Document::new(&insight, options.case_sensitive, options.overview_only);
+      result.matched_terms = Some(query.matched_terms(&document));
+    }
+  }
+
+  if !options.full && !options.overview_only {
+    add_snippets(&mut results, &query, &text_terms, options);
+  }
+
+  Ok(SearchOutcome { results, total, neural_unavailable })
 }
 
-/// Check if exact search should be used (default behavior unless explicitly disabled)
-fn can_use_exact_search(options: &SearchOptions) -> bool {
-  // Don't run exact search when we want neural-only mode
-  #[cfg(feature = "semantic")]
-  if !options.semantic && !options.exact {
-    false // Neural-only mode for testing
+/// Replace long details with their passages that best match the query
+fn add_snippets(
+  results: &mut [SearchResult],
+  query: &Query,
+  text_terms: &[String],
+  options: &SearchOptions,
+) {
+  let patterns = query.token_patterns(options.case_sensitive);
+  #[cfg(feature = "neural")]
+  let query_embedding = if options.embedding_snippets && !text_terms.is_empty() {
+    Some(embed_query(text_terms, options)).filter(|embedding| !embedding.is_empty())
   } else {
-    #[cfg(feature = "semantic")]
-    {
-      !options.semantic
-    } // Run exact unless semantic-only mode
-    #[cfg(not(feature = "semantic"))]
-    {
-      !options.exact
+    None
+  };
+  #[cfg(not(feature = "neural"))]
+  let _ = text_terms;
+
+  for result in results.iter_mut().filter(|result| result.details.len() >= snippet::MIN_LENGTH) {
+    let sentences = snippet::sentences(&result.details);
+    #[allow(unused_mut)]
+    let mut scores = snippet::term_scores(&sentences, &patterns, options.case_sensitive);
+    #[cfg(feature = "neural")]
+    if let Some(query_embedding) = &query_embedding {
+      if let Some(similarities) = passage_similarities(&sentences, query_embedding, options) {
+        scores = similarities;
+      }
     }
+    result.snippet = Some(snippet::select(&sentences, &scores));
   }
-  #[cfg(not(feature = "semantic"))]
-  !options.exact
+}
+
+/// Similarity of every sentence of a result to the query, or None if the
+/// sentences couldn't be embedded
+#[cfg(feature = "neural")]
+fn passage_similarities(
+  sentences: &[snippet::Sentence],
+  query_embedding: &[f32],
+  options: &SearchOptions,
+) -> Option<Vec<f32>> {
+  let passages: Vec<String> = sentences.iter().map(|sentence| sentence.text.to_string()).collect();
+
+  embedding_client::embed_texts(&options.embedding_client, &passages)
+    .iter()
+    .map(|embedding| similarity::cosine(query_embedding, &embedding.embedding))
+    .collect()
 }
 
 /// Check if embedding search feature can be used
@@ -152,37 +344,77 @@ fn can_use_semantic_similarity_search(options: &SearchOptions) -> bool {
   !options.exact
 }
 
-/// Search a topic for matches based on a search strategy
+/// Search the query's candidates for matches based on a search strategy
+#[cfg(feature = "semantic")]
 fn search_topic(
   terms: &[String],
+  query: &Query,
   search_strategy: fn(&insight::Insight, &[String], &SearchOptions) -> f32,
   threshold: f32,
   options: &SearchOptions,
 ) -> Result<Vec<SearchResult>> {
   let mut results = Vec::new();
 
-  let insights_dir = insight::get_valid_insights_dir()?;
-  let search_paths = get_search_paths(&insights_dir, options.topic.as_deref())?;
-
-  for topic_path in search_paths {
-    for entry in fs::read_dir(&topic_path)? {
-      let entry = entry?;
-      let path = entry.path();
-
-      if insight::is_insight_file(&path) {
-        let insight = insight::load_from_path(&path)?;
-        if let Ok(Some(result)) =
-          search_insight(&insight, search_strategy, terms, threshold, options)
-        {
-          results.push(result);
-        }
-      }
+  for insight in collect_candidates(query, options)? {
+    if let Ok(Some(result)) = search_insight(&insight, search_strategy, terms, threshold, options) {
+      results.push(result);
     }
   }
 
   Ok(results)
 }
 
+/// Rank the query's candidates with BM25 over an inverted index of them.
+/// Without any text to rank by, every candidate is kept.
+fn search_lexical(query: &Query, options: &SearchOptions) -> Result<Vec<SearchResult>> {
+  let insights = collect_candidates(query, options)?;
+  let documents: Vec<String> =
+    insights.iter().map(|insight| get_normalized_content(insight, options)).collect();
+  let documents: Vec<String> = if options.case_sensitive {
+    documents
+  } else {
+    documents.iter().map(|document| document.to_lowercase()).collect()
+  };
+
+  let index = bm25::build(&documents);
+  let patterns = query.token_patterns(options.case_sensitive);
+  let scores =
+    bm25::score_by(&index, patterns.iter().map(|pattern| |token: &str| pattern.matches(token)));
+  let keep_all = patterns.is_empty();
+
+  Ok(
+    insights
+      .into_iter()
+      .zip(scores)
+      .filter(|(_, score)| *score > 0.0 || keep_all)
+      .map(|(insight, score)| SearchResult {
+        topic: insight.topic,
+        name: insight.name,
+        overview: insight.overview,
+        details: insight.details,
+        scope: insight.scope,
+        score,
+        contributions: Vec::new(),
+        matched_terms: None,
+        snippet: None,
+      })
+      .collect(),
+  )
+}
+
+/// Load every insight in the searched topics that the query admits
+fn collect_candidates(query: &Query, options: &SearchOptions) -> Result<Vec<insight::Insight>> {
+  insight::check_insights_exist()?;
+
+  let mut insights = insight::get_insights(options.topic.as_deref())?;
+  insights.retain(|insight| {
+    insight::has_tags(insight, &options.tags)
+      && query.admits(&Document::new(insight, options.case_sensitive, options.overview_only))
+  });
+  Ok(insights)
+}
+
+#[cfg(feature = "semantic")]
 fn search_insight(
   insight: &insight::Insight,
   search_strategy: fn(&insight::Insight, &[String], &SearchOptions) -> f32,
@@ -197,7 +429,11 @@ fn search_insight(
       name: insight.name.to_string(),
       overview: insight.overview.to_string(),
       details: insight.details.to_string(),
+      scope: insight.scope,
       score,
+      contributions: Vec::new(),
+      matched_terms: None,
+      snippet: None,
     }))
   } else {
     Ok(None)
@@ -212,6 +448,7 @@ fn get_normalized_content(insight: &insight::Insight, options: &SearchOptions) -
   }
 }
 
+#[cfg(feature = "neural")]
 fn get_normalized_terms(terms: &[String], options: &SearchOptions) -> Vec<String> {
   if options.case_sensitive {
     terms.to_vec()
@@ -220,42 +457,180 @@ fn get_normalized_terms(terms: &[String], options: &SearchOptions) -> Vec<String
   }
 }
 
-fn get_exact_match(insight: &insight::Insight, terms: &[String], options: &SearchOptions) -> f32 {
-  let normalized_content = get_normalized_content(insight, options);
-  let normalized_terms = get_normalized_terms(terms, options);
-
-  normalized_terms.iter().map(|term| normalized_content.matches(term).count()).sum::<usize>() as f32
-}
-
 #[cfg(feature = "semantic")]
 fn get_semantic_match(
   insight: &insight::Insight,
   terms: &[String],
   options: &SearchOptions,
 ) -> f32 {
-  let normalized_content = get_normalized_content(insight, options);
-  let normalized_terms = get_normalized_terms(terms, options);
+  // Matching is case-insensitive either way, and the original case splits camelCase terms
+  similarity::semantic(&terms.iter().cloned().collect(), &get_normalized_content(insight, options))
+}
+
+/// Score every insight against the query using the persistent vector index.
+///
+/// The query text is embedded once. When the query has constraints, only the
+/// insights it admits are scored. Store directories are only listed, not
+/// read: indexed insights are scored and filtered from their index entry,
+/// and their content is loaded later for the results that are shown. Insights
+/// missing from the index or modified since they were indexed are read and,
+/// unless they carry a current embedding, embedded lazily and written back so
+/// subsequent searches can use the index. Returns `None` when neural
+/// embeddings are unavailable.
+#[cfg(feature = "neural")]
+fn search_embeddings(
+  query: &Query,
+  text_terms: &[String],
+  options: &SearchOptions,
+) -> Result<Option<Vec<SearchResult>>> {
+  if embedding_client::unavailable(&options.embedding_client).is_some() {
+    return Ok(None);
+  }
+
+  let query_embedding = embed_query(text_terms, options);
+  if query_embedding.is_empty() {
+    return Ok(None);
+  }
+
+  insight::check_insights_exist()?;
+  let candidates: Option<HashSet<(Scope, String)>> = if query.has_constraints() {
+    let admitted = collect_candidates(query, options)?;
+    Some(
+      admitted
+        .iter()
+        .map(|insight| (insight.scope, index::key(&insight.topic, &insight.name)))
+        .collect(),
+    )
+  } else {
+    None
+  };
+  let vector_indexes = scope::stores()?
+    .into_iter()
+    .map(|(scope, _)| Ok((scope, index::load(scope)?)))
+    .collect::<Result<HashMap<_, _>>>()?;
+  let mut results = Vec::new();
+
+  for path in insight::get_insight_paths(options.topic.as_deref())? {
+    let scope = scope::of_path(&path);
+    let Some((topic, name)) = index::path_id(&path) else {
+      continue;
+    };
+    if candidates
+      .as_ref()
+      .is_some_and(|candidates| !candidates.contains(&(scope, index::key(topic, name))))
+    {
+      continue;
+    }
+
+    let result = match index::get(&vector_indexes[&scope], topic, name) {
+      Some(entry) if index::is_fresh(entry, &path) => {
+        score_indexed(entry, scope, &query_embedding, options)?
+      }
+      // Unindexed, or modified since it was indexed
+      _ => score_unindexed(&path, &query_embedding, options)?,
+    };
+    results.extend(result);
+  }
 
-  similarity::semantic(&normalized_terms.into_iter().collect(), &normalized_content)
+  Ok(Some(results))
 }
 
+/// Score an indexed insight without reading its file. Its content is left
+/// empty for `load_indexed_content` to fill in.
 #[cfg(feature = "neural")]
-fn get_embedding_match(
-  insight: &insight::Insight,
-  terms: &[String],
+fn score_indexed(
+  entry: &index::IndexEntry,
+  scope: Scope,
+  query_embedding: &[f32],
   options: &SearchOptions,
-) -> f32 {
-  try_get_embedding(insight, terms, options).unwrap_or(0.0)
+) -> Result<Option<SearchResult>> {
+  // Embeddings from a model with another dimension can't be compared until
+  // `insights index` recomputes them
+  let score = similarity::cosine(query_embedding, &entry.embedding).unwrap_or(0.0);
+  if score <= EMBEDDING_SIMILARITY_THRESHOLD {
+    return Ok(None);
+  }
+
+  let has_tags = match &entry.tags {
+    Some(tags) => {
+      let tags = insight::normalize_tags(tags);
+      insight::normalize_tags(&options.tags).iter().all(|tag| tags.contains(tag))
+    }
+    // Indexed before tags were recorded
+    None => {
+      insight::has_tags(&index::load_indexed(&entry.topic, &entry.name, scope)?, &options.tags)
+    }
+  };
+  if !has_tags {
+    return Ok(None);
+  }
+
+  Ok(Some(SearchResult {
+    topic: entry.topic.clone(),
+    name: entry.name.clone(),
+    overview: String::new(),
+    details: String::new(),
+    scope,
+    score,
+    contributions: Vec::new(),
+    matched_terms: None,
+    snippet: None,
+  }))
 }
 
+/// Score an insight from its file, embedding it again if its embedding is
+/// missing or was computed from other content. Either way the index is
+/// brought up to date.
 #[cfg(feature = "neural")]
-fn try_get_embedding(
-  insight: &insight::Insight,
-  terms: &[String],
+fn score_unindexed(
+  path: &Path,
+  query_embedding: &[f32],
   options: &SearchOptions,
-) -> Result<f32> {
-  let client = &options.embedding_client;
+) -> Result<Option<SearchResult>> {
+  let insight = insight::load_from_path(path)?;
+  let embedding = match insight::embedding_status(&insight, None) {
+    insight::EmbeddingStatus::Current => {
+      index::record(&insight)?;
+      insight.embedding.clone().unwrap_or_default()
+    }
+    _ => recompute_embedding(&insight, options)?,
+  };
+
+  let score = similarity::cosine(query_embedding, &embedding).unwrap_or(0.0);
+  if score <= EMBEDDING_SIMILARITY_THRESHOLD || !insight::has_tags(&insight, &options.tags) {
+    return Ok(None);
+  }
 
+  Ok(Some(SearchResult {
+    topic: insight.topic,
+    name: insight.name,
+    overview: insight.overview,
+    details: insight.details,
+    scope: insight.scope,
+    score,
+    contributions: Vec::new(),
+    matched_terms: None,
+    snippet: None,
+  }))
+}
+
+/// Read the content of results only the index ranked, which were scored
+/// without reading their files
+#[cfg(feature = "neural")]
+fn load_indexed_content(results: &mut [SearchResult]) -> Result<()> {
+  let indexed_only = |result: &&mut SearchResult| {
+    result.contributions.iter().all(|contribution| contribution.strategy == Strategy::Neural)
+  };
+  for result in results.iter_mut().filter(indexed_only) {
+    let insight = index::load_indexed(&result.topic, &result.name, result.scope)?;
+    result.overview = insight.overview;
+    result.details = insight.details;
+  }
+  Ok(())
+}
+
+#[cfg(feature = "neural")]
+fn embed_query(terms: &[String], options: &SearchOptions) -> Vec<f32> {
   let normalized_terms = get_normalized_terms(terms, options);
 
   // Create a temporary insight for query embedding
@@ -266,15 +641,7 @@ fn try_get_embedding(
     "".to_string(),
   );
 
-  let query_embedding_obj = embedding_client::embed_insight(client, &mut query_insight);
-  let query_embedding = query_embedding_obj.embedding;
-  let content_embedding = if let Some(embedding) = insight.embedding.as_ref() {
-    embedding.clone()
-  } else {
-    recompute_embedding(insight, options)?
-  };
-
-  Ok(similarity::cosine(&query_embedding, &content_embedding))
+  embedding_client::embed_query(&options.embedding_client, &mut query_insight).embedding
 }
 
 /// Recompute the embedding for an insight and save it to the file system.
@@ -291,6 +658,10 @@ fn recompute_embedding(insight: &insight::Insight, options: &SearchOptions) -> R
   );
 
   let embedding = embedding_client::embed_insight(&options.embedding_client, &mut temp_insight);
+  if embedding.embedding.is_empty() {
+    // Don't overwrite the insight with a placeholder if the daemon failed
+    return Ok(embedding.embedding);
+  }
 
   // Lazily recompute and save embedding.
   let mut to_save = insight.clone();
@@ -299,51 +670,50 @@ fn recompute_embedding(insight: &insight::Insight, options: &SearchOptions) -> R
   Ok(embedding.embedding)
 }
 
-/// Highlight search terms
+/// Highlight search terms, and words that match them after stemming or with a small typo
 fn highlight_keywords(text: &str, terms: &[String]) -> String {
-  let mut result = text.to_string();
-
-  let mut sorted = terms.to_vec();
-  sorted.sort_by_key(|b| std::cmp::Reverse(b.len()));
-
-  for term in sorted {
-    if term.is_empty() {
-      continue;
+  let mut ranges: Vec<(usize, usize)> = Vec::new();
+
+  // Lowercasing can change byte offsets outside ASCII; only words are matched then
+  let text_lower = text.to_lowercase();
+  if text_lower.len() == text.len() {
+    for term in terms.iter().filter(|term| !term.is_empty()) {
+      let term_lower = term.to_lowercase();
+      ranges.extend(
+        text_lower.match_indices(&term_lower).map(|(start, found)| (start, start + found.len())),
+      );
     }
+  }
 
-    let term_lower = term.to_lowercase();
-    let mut highlighted = String::new();
-    let mut end = 0;
-
-    let result_lower = result.to_lowercase();
-    let mut start = 0;
-
-    while let Some(pos) = result_lower[start..].find(&term_lower) {
-      let abs_pos = start + pos;
-
-      highlighted.push_str(&result[end..abs_pos]);
-
-      let match_text = &result[abs_pos..abs_pos + term.len()];
-      highlighted.push_str(&match_text.yellow().bold().to_string());
-
-      end = abs_pos + term.len();
-      start = end;
+  let mut start = None;
+  for (index, c) in text.char_indices().chain([(text.len(), ' ')]) {
+    match (start, c.is_alphanumeric()) {
+      (None, true) => start = Some(index),
+      (Some(word_start), false) => {
+        let word = &text[word_start..index];
+        if terms.iter().any(|term| similarity::words_match(term, word)) {
+          ranges.push((word_start, index));
+        }
+        start = None;
+      }
+      _ => {}
     }
-
-    highlighted.push_str(&result[end..]);
-    result = highlighted;
   }
 
-  result
-}
-
-/// Build search paths based on topic filter
-fn get_search_paths(insights_root: &Path, topic_filter: Option<&str>) -> Result<Vec<PathBuf>> {
-  if let Some(topic) = topic_filter {
-    Ok(vec![insights_root.join(topic)])
-  } else {
-    Ok(insight::get_topics()?.into_iter().map(|topic| insights_root.join(topic)).collect())
+  ranges.sort();
+  let mut result = String::new();
+  let mut end = 0;
+  for (range_start, range_end) in ranges {
+    if range_end <= end {
+      continue;
+    }
+    let range_start = range_start.max(end);
+    result.push_str(&text[end..range_start]);
+    result.push_str(&text[range_start..range_end].yellow().bold().to_string());
+    end = range_end;
   }
+  result.push_str(&text[end..]);
+  result
 }
 
 /// Display the combined search results
@@ -351,17 +721,26 @@ pub fn display_results(results: &[SearchResult], terms: &[String], overview_only
   if results.is_empty() {
     println!("No matches found for: {}", terms.join(" ").yellow());
   } else {
+    let highlights = query::parse_terms(terms).highlight_terms();
     for result in results {
-      display_single_result(result, terms, overview_only);
+      display_single_result(result, &highlights, overview_only);
     }
   }
 }
 
 /// Display a single search result with keyword highlighting
 fn display_single_result(result: &SearchResult, terms: &[String], overview_only: bool) {
-  let header = format!("=== {}/{} ===", result.topic.blue().bold(), result.name.yellow().bold());
+  let header = format!(
+    "=== {}/{} === {}",
+    result.topic.blue().bold(),
+    result.name.yellow().bold(),
+    format!("[{}]", result.scope).dimmed()
+  );
 
   println!("{header}");
+  if let Some(matched_terms) = &result.matched_terms {
+    display_explanation(result, matched_terms);
+  }
 
   // Wrap and display the content with proper formatting
   let wrap_with = if header.len() < 80 { 80 } else { header.len() };
@@ -369,7 +748,8 @@ fn display_single_result(result: &SearchResult, terms: &[String], overview_only:
   let content = if overview_only {
     result.overview.to_string()
   } else {
-    format!("{}\n\n{}", result.overview, result.details)
+    let details = result.snippet.as_ref().unwrap_or(&result.details);
+    format!("{}\n\n{}", result.overview, details)
   };
 
   let highlighted_content = highlight_keywords(&content, terms);
@@ -380,6 +760,43 @@ fn display_single_result(result: &SearchResult, terms: &[String], overview_only:
   println!();
 }
 
+/// Show how a result's score was put together and which terms it matched
+fn display_explanation(result: &SearchResult, matched_terms: &[String]) {
+  let parts: Vec<String> = result
+    .contributions
+    .iter()
+    .map(|contribution| {
+      format!(
+        "{} {:.4} (#{}, raw {:.3})",
+        contribution.strategy, contribution.score, contribution.rank, contribution.raw_score
+      )
+    })
+    .collect();
+  println!("{} {:.4} = {}", "score".dimmed(), result.score, parts.join(" + "));
+
+  let matched =
+    if matched_terms.is_empty() { "none".to_string() } else { matched_terms.join(", ") };
+  println!("{} {}", "matched".dimmed(), matched);
+}
+
+/// Tell the reader more results are available beyond the shown page
+pub fn display_page_summary(shown: usize, total: usize, offset: usize) {
+  if shown == 0 || shown >= total {
+    return;
+  }
+  println!(
+    "{}",
+    format!(
+      "Showing {}-{} of {} results; use --offset {} for more",
+      offset + 1,
+      offset + shown,
+      total,
+      offset + shown
+    )
+    .dimmed()
+  );
+}
+
 /// Wrap text to fit within a specified width
 fn wrap_text(text: &str, width: usize) -> Vec<String> {
   let mut lines = Vec::new();
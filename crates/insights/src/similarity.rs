@@ -1,4 +1,17 @@
+//! Word-level similarity for the semantic search stage.
+//!
+//! Text is split into words, code identifiers such as `snake_case` and
+//! `camelCase` into their parts, and stop words are dropped. Words are then
+//! compared by stem, so "caching" matches "cache", and by edit distance, so
+//! small typos still match.
+
+use anyhow::{anyhow, Context, Result};
 use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::sync::OnceLock;
+
+use crate::stem;
 
 // violet ignore chunk
 /// Common English stop words to filter out
@@ -9,11 +22,66 @@ pub const STOP_WORDS: &[&str] = &[
   "its",
 ];
 
-/// Calculate cosine similarity between two embeddings
+// violet ignore chunk
+/// Keywords common to most programming languages, for stores full of code
+pub const CODE_STOP_WORDS: &[&str] = &[
+  "fn", "let", "mut", "pub", "impl", "def", "var", "const", "return", "self", "this", "true",
+  "false", "null", "nil", "none",
+];
+
+/// Chooses the stop words: a comma-separated list of `english`, `code`,
+/// `none`, or paths to files with one word per line
+pub const STOP_WORDS_ENV: &str = "INSIGHTS_STOP_WORDS";
+
+const DEFAULT_STOP_WORDS: &str = "english";
+
+/// How much a likely typo counts compared to a word with the same stem
+const TYPO_MATCH: f32 = 0.8;
+
+static CONFIGURED_STOP_WORDS: OnceLock<Result<HashSet<String>, String>> = OnceLock::new();
+
+/// The stop words chosen with `INSIGHTS_STOP_WORDS`, loaded once per process
+pub fn stop_words() -> Result<&'static HashSet<String>> {
+  CONFIGURED_STOP_WORDS
+    .get_or_init(|| {
+      let spec = env::var(STOP_WORDS_ENV).unwrap_or_else(|_| DEFAULT_STOP_WORDS.to_string());
+      load_stop_words(&spec).map_err(|e| format!("Invalid {STOP_WORDS_ENV}: {e:#}"))
+    })
+    .as_ref()
+    .map_err(|e| anyhow!("{e}"))
+}
+
+/// Load the stop words named by a comma-separated list of built-in lists and files
+pub fn load_stop_words(spec: &str) -> Result<HashSet<String>> {
+  let mut words = HashSet::new();
+  for list in spec.split(',').map(str::trim).filter(|list| !list.is_empty()) {
+    match list {
+      "english" => words.extend(STOP_WORDS.iter().map(|word| word.to_string())),
+      "code" => words.extend(CODE_STOP_WORDS.iter().map(|word| word.to_string())),
+      "none" => {}
+      path => {
+        let contents = fs::read_to_string(path)
+          .with_context(|| format!("Could not read stop words from {path}"))?;
+        words.extend(
+          contents
+            .lines()
+            .map(|line| line.trim().to_lowercase())
+            .filter(|line| !line.is_empty() && !line.starts_with('#')),
+        );
+      }
+    }
+  }
+  Ok(words)
+}
+
+/// Calculate cosine similarity between two embeddings.
+///
+/// Returns `None` when the embeddings have different dimensions, i.e. they were
+/// computed by different models and can't be compared.
 #[cfg(feature = "neural")]
-pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+pub fn cosine(a: &[f32], b: &[f32]) -> Option<f32> {
   if a.len() != b.len() {
-    return 0.0;
+    return None;
   }
 
   let dot_product: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
@@ -21,47 +89,142 @@ pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
   let magnitude_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
 
   if magnitude_a == 0.0 || magnitude_b == 0.0 {
-    0.0
+    Some(0.0)
   } else {
-    dot_product / (magnitude_a * magnitude_b)
+    Some(dot_product / (magnitude_a * magnitude_b))
   }
 }
 
-/// Calculate semantic similarity using Jaccard + frequency analysis
+/// Calculate semantic similarity using Jaccard + frequency analysis over
+/// stemmed words, counting likely typos as partial matches
 pub fn semantic(query_words: &HashSet<String>, content: &str) -> f32 {
-  let content_words = extract_words(&content.to_lowercase());
+  let query_stems: HashSet<String> =
+    query_words.iter().flat_map(|word| extract_words(word)).map(|word| stem::stem(&word)).collect();
+  let content_stems: Vec<String> =
+    word_list(content, configured_stop_words()).iter().map(|word| stem::stem(word)).collect();
+  let distinct_content: HashSet<&String> = content_stems.iter().collect();
 
-  if query_words.is_empty() || content_words.is_empty() {
+  if query_stems.is_empty() || content_stems.is_empty() {
     return 0.0;
   }
 
   // Jaccard similarity (intersection over union)
-  let intersection: HashSet<_> = query_words.intersection(&content_words).collect();
-  let union: HashSet<_> = query_words.union(&content_words).collect();
-  let jaccard = intersection.len() as f32 / union.len() as f32;
+  let intersection: f32 = query_stems
+    .iter()
+    .map(|query| {
+      distinct_content.iter().map(|content| stem_match(query, content)).fold(0.0, f32::max)
+    })
+    .sum();
+  let union = (query_stems.len() + distinct_content.len()) as f32 - intersection;
+  let jaccard = intersection / union;
 
   // Frequency boost for repeated terms
   let mut frequency_score = 0.0;
-  let content_lower = content.to_lowercase();
-  for query_word in query_words {
-    let count = content_lower.matches(query_word).count();
-    frequency_score += (count as f32).ln_1p(); // Natural log for diminishing returns
+  for query in &query_stems {
+    let count: f32 = content_stems.iter().map(|content| stem_match(query, content)).sum();
+    frequency_score += count.ln_1p(); // Natural log for diminishing returns
   }
-  frequency_score /= query_words.len() as f32;
+  frequency_score /= query_stems.len() as f32;
 
   // Combined score: 60% Jaccard + 40% frequency
   (jaccard * 0.6) + (frequency_score.min(1.0) * 0.4)
 }
 
-/// Extract meaningful words from text, filtering out common stop words
+/// Whether two words match after stemming, allowing for small typos
+pub fn words_match(a: &str, b: &str) -> bool {
+  stem_match(&stem::stem(&a.to_lowercase()), &stem::stem(&b.to_lowercase())) > 0.0
+}
+
+/// 1 for equal stems, less for stems a typo apart, 0 otherwise
+fn stem_match(a: &str, b: &str) -> f32 {
+  if a == b {
+    return 1.0;
+  }
+
+  // Typos in the first letter are rare, and checking it skips most distance computations
+  let allowed = allowed_typos(a.chars().count().min(b.chars().count()));
+  if allowed == 0
+    || a.chars().next() != b.chars().next()
+    || a.chars().count().abs_diff(b.chars().count()) > allowed
+  {
+    return 0.0;
+  }
+
+  if strsim::damerau_levenshtein(a, b) <= allowed {
+    TYPO_MATCH
+  } else {
+    0.0
+  }
+}
+
+/// Edits tolerated between words of a length; short words must match exactly
+fn allowed_typos(length: usize) -> usize {
+  match length {
+    0..=4 => 0,
+    5..=8 => 1,
+    _ => 2,
+  }
+}
+
+/// Extract meaningful words from text, filtering out stop words. Identifiers
+/// are split into their parts.
 pub fn extract_words(text: &str) -> HashSet<String> {
+  word_list(text, configured_stop_words()).into_iter().collect()
+}
+
+/// Like `extract_words`, with an explicit list of stop words
+#[allow(dead_code)] // Library entry point; the CLI uses the configured stop words
+pub fn extract_words_with(text: &str, stop_words: &HashSet<String>) -> HashSet<String> {
+  word_list(text, stop_words).into_iter().collect()
+}
+
+/// Every lowercase word of the text in order, repeats included
+fn word_list(text: &str, stop_words: &HashSet<String>) -> Vec<String> {
   text
     .split_whitespace()
-    .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase())
-    .filter(|word| !word.is_empty() && !STOP_WORDS.contains(&word.as_str()))
+    .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()))
+    .flat_map(split_identifier)
+    .map(str::to_lowercase)
+    .filter(|word| !word.is_empty() && !stop_words.contains(word))
     .collect()
 }
 
+/// The configured stop words, or the English ones if the configuration is
+/// broken; searches report that before they get here
+fn configured_stop_words() -> &'static HashSet<String> {
+  static ENGLISH: OnceLock<HashSet<String>> = OnceLock::new();
+  stop_words().unwrap_or_else(|_| {
+    ENGLISH.get_or_init(|| STOP_WORDS.iter().map(|word| word.to_string()).collect())
+  })
+}
+
+/// Split an identifier like `parse_json`, `parse-json`, `json::parse` or
+/// `parseJSONFile` into its parts
+pub fn split_identifier(word: &str) -> Vec<&str> {
+  let mut parts = Vec::new();
+  for piece in word.split(['_', '-', '.', '/', ':']).filter(|piece| !piece.is_empty()) {
+    let chars: Vec<(usize, char)> = piece.char_indices().collect();
+    let mut start = 0;
+    for i in 1..chars.len() {
+      let (index, c) = chars[i];
+      let previous = chars[i - 1].1;
+      let next_is_lower = chars.get(i + 1).is_some_and(|(_, next)| next.is_lowercase());
+      // A capital starts a new part after a lowercase letter or digit, and so
+      // does the last capital of an acronym followed by a lowercase letter
+      if c.is_uppercase()
+        && (previous.is_lowercase()
+          || previous.is_ascii_digit()
+          || (previous.is_uppercase() && next_is_lower))
+      {
+        parts.push(&piece[start..index]);
+        start = index;
+      }
+    }
+    parts.push(&piece[start..]);
+  }
+  parts
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -178,4 +341,73 @@ mod tests {
     let similarity = semantic(&query_words, content);
     assert!(similarity > 0.6); // Should be high similarity
   }
+
+  #[test]
+  fn test_split_identifier() {
+    assert_eq!(split_identifier("parse_json"), vec!["parse", "json"]);
+    assert_eq!(split_identifier("json::parse-now"), vec!["json", "parse", "now"]);
+    assert_eq!(split_identifier("parseJSONFile"), vec!["parse", "JSON", "File"]);
+    assert_eq!(split_identifier("base64Encode"), vec!["base64", "Encode"]);
+    assert_eq!(split_identifier("plain"), vec!["plain"]);
+  }
+
+  #[test]
+  fn test_extract_words_splits_identifiers() {
+    let words = extract_words("Call `loadConfig` from config_loader.rs");
+    for word in ["call", "load", "config", "loader", "rs"] {
+      assert!(words.contains(word), "{word}: {words:?}");
+    }
+    assert!(!words.contains("loadconfig"));
+  }
+
+  #[test]
+  fn test_semantic_similarity_matches_stems_and_typos() {
+    let query = |word: &str| -> HashSet<String> { [word.to_string()].into() };
+    let content = "Caching layer in front of the database";
+
+    assert!(semantic(&query("cache"), content) > 0.2);
+    assert!(semantic(&query("databse"), content) > 0.2);
+    assert!(semantic(&query("cache"), content) > semantic(&query("cahce"), content));
+    assert!(semantic(&query("layer"), content) > semantic(&query("player"), content));
+    assert_eq!(semantic(&query("dog"), content), 0.0);
+  }
+
+  #[test]
+  fn test_words_match() {
+    assert!(words_match("Caching", "caches"));
+    assert!(words_match("recieve", "receive"));
+    assert!(!words_match("cat", "cut")); // Too short for a typo
+    assert!(!words_match("fetch", "batch"));
+  }
+
+  #[test]
+  fn test_load_stop_words() -> Result<()> {
+    let file = tempfile::NamedTempFile::new()?;
+    fs::write(file.path(), "# project words\nInsight\n\nkernelle\n")?;
+
+    let words = load_stop_words(&format!("code, {}", file.path().display()))?;
+    assert!(words.contains("fn") && words.contains("insight") && words.contains("kernelle"));
+    assert!(!words.contains("the"));
+    assert!(load_stop_words("none")?.is_empty());
+
+    let text = "the insight fn";
+    assert_eq!(extract_words_with(text, &words), ["the".to_string()].into());
+    assert!(load_stop_words("english,/missing/stop-words.txt").is_err());
+    Ok(())
+  }
+
+  #[cfg(feature = "neural")]
+  #[test]
+  fn test_cosine_identical_and_orthogonal() {
+    assert_eq!(cosine(&[1.0, 2.0], &[1.0, 2.0]), Some(1.0));
+    assert_eq!(cosine(&[1.0, 0.0], &[0.0, 1.0]), Some(0.0));
+    assert_eq!(cosine(&[0.0, 0.0], &[1.0, 1.0]), Some(0.0));
+  }
+
+  #[cfg(feature = "neural")]
+  #[test]
+  fn test_cosine_dimension_mismatch() {
+    assert_eq!(cosine(&[0.1; 384], &[0.1; 768]), None);
+    assert_eq!(cosine(&[], &[0.1; 384]), None);
+  }
 }
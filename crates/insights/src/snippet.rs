@@ -0,0 +1,259 @@
+//! Snippets of long insights for search results.
+//!
+//! Details are split into sentences (a fenced code block counts as one), each
+//! sentence is scored against the query, and the best ones are shown together
+//! with the sentences around them. Skipped text is marked with an ellipsis.
+
+use crate::bm25;
+use crate::query::TokenPattern;
+
+/// Details shorter than this are shown in full
+pub const MIN_LENGTH: usize = 400;
+/// Best-matching sentences a snippet is built around
+pub const MAX_PASSAGES: usize = 2;
+/// Sentences of context shown on each side of a matching sentence
+pub const CONTEXT_SENTENCES: usize = 1;
+
+const ELLIPSIS: &str = "…";
+
+/// A sentence and the paragraph it belongs to. Context never crosses paragraphs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sentence<'a> {
+  pub text: &'a str,
+  pub paragraph: usize,
+  /// Whether the sentence starts its own line, so it is joined to the one
+  /// before with a newline rather than a space
+  pub starts_line: bool,
+}
+
+/// Split text into sentences. Paragraphs are separated by blank lines, and
+/// every line is a sentence of its own so Markdown lists stay intact. A
+/// fenced code block is kept whole as a single sentence.
+pub fn sentences(text: &str) -> Vec<Sentence<'_>> {
+  let mut sentences = Vec::new();
+  let mut paragraph = 0;
+  let mut in_paragraph = false;
+  let mut fence: Option<(usize, &str)> = None;
+  let mut offset = 0;
+
+  for line in text.split_inclusive('\n') {
+    let start = offset;
+    offset += line.len();
+    let line = line.trim_end_matches(['\n', '\r']);
+
+    if let Some((fence_start, marker)) = fence {
+      if line.trim_start().starts_with(marker) {
+        let block = text[fence_start..start + line.len()].trim();
+        sentences.push(Sentence { text: block, paragraph, starts_line: true });
+        fence = None;
+      }
+      continue;
+    }
+    if line.trim().is_empty() {
+      if in_paragraph {
+        paragraph += 1;
+        in_paragraph = false;
+      }
+      continue;
+    }
+    in_paragraph = true;
+    if let Some(marker) = fence_marker(line) {
+      fence = Some((start, marker));
+      continue;
+    }
+    sentences.extend(split_line(line).enumerate().map(|(index, text)| Sentence {
+      text,
+      paragraph,
+      starts_line: index == 0,
+    }));
+  }
+
+  // An unclosed fence runs to the end of the text
+  if let Some((fence_start, _)) = fence {
+    sentences.push(Sentence { text: text[fence_start..].trim(), paragraph, starts_line: true });
+  }
+
+  sentences
+}
+
+/// The marker opening a fenced code block on this line, if any
+fn fence_marker(line: &str) -> Option<&'static str> {
+  let line = line.trim_start();
+  ["```", "~~~"].into_iter().find(|marker| line.starts_with(marker))
+}
+
+/// Split a line after every `.`, `!` or `?` that is followed by whitespace
+fn split_line(line: &str) -> impl Iterator<Item = &str> {
+  let mut ends: Vec<usize> = line
+    .char_indices()
+    .zip(line.chars().skip(1))
+    .filter(|((_, c), next)| matches!(c, '.' | '!' | '?') && next.is_whitespace())
+    .map(|((index, c), _)| index + c.len_utf8())
+    .collect();
+  ends.push(line.len());
+
+  let mut start = 0;
+  ends.into_iter().filter_map(move |end| {
+    let sentence = line[start..end].trim();
+    start = end;
+    (!sentence.is_empty()).then_some(sentence)
+  })
+}
+
+/// Score each sentence by the query terms it contains: every distinct term
+/// counts fully, repeats add a little
+pub fn term_scores(
+  sentences: &[Sentence],
+  patterns: &[TokenPattern],
+  case_sensitive: bool,
+) -> Vec<f32> {
+  sentences
+    .iter()
+    .map(|sentence| {
+      let tokens: Vec<String> = bm25::tokenize(sentence.text)
+        .map(|token| if case_sensitive { token.to_string() } else { token.to_lowercase() })
+        .collect();
+      patterns
+        .iter()
+        .map(|pattern| tokens.iter().filter(|token| pattern.matches(token)).count())
+        .filter(|&count| count > 0)
+        .map(|count| 1.0 + (count as f32).ln())
+        .sum()
+    })
+    .collect()
+}
+
+/// Build a snippet from the best scoring sentences and their neighbours, in
+/// text order. Without any scoring sentence the opening sentences are used.
+pub fn select(sentences: &[Sentence], scores: &[f32]) -> String {
+  if sentences.is_empty() {
+    return String::new();
+  }
+
+  let mut ranked: Vec<usize> = (0..sentences.len()).filter(|&i| scores[i] > 0.0).collect();
+  ranked.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]).then(a.cmp(&b)));
+  ranked.truncate(MAX_PASSAGES);
+  if ranked.is_empty() {
+    ranked.push(0);
+  }
+  ranked.sort();
+
+  let mut windows: Vec<(usize, usize)> = Vec::new();
+  for center in ranked {
+    let paragraph = sentences[center].paragraph;
+    let mut start = center.saturating_sub(CONTEXT_SENTENCES);
+    while sentences[start].paragraph != paragraph {
+      start += 1;
+    }
+    let mut end = (center + CONTEXT_SENTENCES).min(sentences.len() - 1);
+    while sentences[end].paragraph != paragraph {
+      end -= 1;
+    }
+
+    match windows.last_mut() {
+      Some(last) if start <= last.1 + 1 => last.1 = last.1.max(end),
+      _ => windows.push((start, end)),
+    }
+  }
+
+  let mut snippet = String::new();
+  if windows[0].0 > 0 {
+    snippet.push_str(ELLIPSIS);
+    snippet.push(' ');
+  }
+  let passages: Vec<String> = windows
+    .iter()
+    .map(|&(start, end)| {
+      let mut passage = sentences[start].text.to_string();
+      for sentence in &sentences[start + 1..=end] {
+        passage.push(if sentence.starts_line { '\n' } else { ' ' });
+        passage.push_str(sentence.text);
+      }
+      passage
+    })
+    .collect();
+  snippet.push_str(&passages.join(&format!(" {ELLIPSIS} ")));
+  if windows[windows.len() - 1].1 < sentences.len() - 1 {
+    snippet.push(' ');
+    snippet.push_str(ELLIPSIS);
+  }
+  snippet
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn texts<'a>(sentences: &[Sentence<'a>]) -> Vec<&'a str> {
+    sentences.iter().map(|sentence| sentence.text).collect()
+  }
+
+  #[test]
+  fn test_sentences_follow_punctuation_lines_and_paragraphs() {
+    let sentences =
+      sentences("First one. Second one? Version 1.2 ships\n- a list item\n\nNew paragraph!");
+    assert_eq!(
+      texts(&sentences),
+      vec!["First one.", "Second one?", "Version 1.2 ships", "- a list item", "New paragraph!"]
+    );
+    assert_eq!(sentences[3].paragraph, 0);
+    assert_eq!(sentences[4].paragraph, 1);
+    assert!(sentences[3].starts_line && !sentences[1].starts_line);
+  }
+
+  #[test]
+  fn test_fenced_code_is_a_single_sentence() {
+    let text = "Run this. Then wait.\n```sh\nmake clean\n\nmake all. done\n```\nAfter.";
+    let sentences = sentences(text);
+    assert_eq!(
+      texts(&sentences),
+      vec!["Run this.", "Then wait.", "```sh\nmake clean\n\nmake all. done\n```", "After."]
+    );
+    assert!(sentences.iter().all(|sentence| sentence.paragraph == 0));
+  }
+
+  #[test]
+  fn test_lists_and_code_keep_their_lines() {
+    let text = "Intro. Steps:\n- flush the cache\n- restart\n```\ncache --flush\n```\nEnd. Bye.";
+    let sentences = sentences(text);
+    let scores = term_scores(&sentences, &[TokenPattern::Contains("restart".to_string())], false);
+
+    assert_eq!(
+      select(&sentences, &scores),
+      "… - flush the cache\n- restart\n```\ncache --flush\n``` …"
+    );
+  }
+
+  #[test]
+  fn test_best_sentence_is_shown_with_context() {
+    let text = "Zero. One. Two has the cache. Three. Four. Five.";
+    let sentences = sentences(text);
+    let scores = term_scores(&sentences, &[TokenPattern::Contains("cache".to_string())], false);
+
+    assert_eq!(select(&sentences, &scores), "… One. Two has the cache. Three. …");
+  }
+
+  #[test]
+  fn test_separate_matches_are_joined_with_ellipsis() {
+    let sentences = sentences("Cache first. One. Two. Three. Four. Last cache.");
+    let scores = term_scores(&sentences, &[TokenPattern::Contains("cache".to_string())], false);
+
+    assert_eq!(select(&sentences, &scores), "Cache first. One. … Four. Last cache.");
+  }
+
+  #[test]
+  fn test_context_stays_within_the_paragraph() {
+    let sentences = sentences("Intro.\n\nThe cache matters. More.");
+    let scores = term_scores(&sentences, &[TokenPattern::Contains("cache".to_string())], false);
+
+    assert_eq!(select(&sentences, &scores), "… The cache matters. More.");
+  }
+
+  #[test]
+  fn test_without_matches_the_opening_is_shown() {
+    let sentences = sentences("Zero. One. Two. Three.");
+    let scores = vec![0.0; sentences.len()];
+
+    assert_eq!(select(&sentences, &scores), "Zero. One. …");
+  }
+}
@@ -0,0 +1,113 @@
+//! A light English suffix stemmer in the spirit of Porter's.
+//!
+//! It only has to map inflections of a word onto the same key, so "cache",
+//! "caches", "cached" and "caching" all become "cach". The keys are not meant
+//! to be shown to anyone.
+
+/// Stems shorter than this are never cut further
+const MIN_STEM: usize = 3;
+
+/// Derivational suffixes and what they are replaced with, longest first
+const SUFFIXES: &[(&str, &str)] = &[
+  ("izations", "ize"),
+  ("ization", "ize"),
+  ("ational", "ate"),
+  ("fulness", "ful"),
+  ("ations", "ate"),
+  ("ation", "ate"),
+  ("izers", "ize"),
+  ("izer", "ize"),
+  ("ness", ""),
+  ("ment", ""),
+  ("ful", ""),
+  ("ly", ""),
+];
+
+/// Stem a lowercase word. Words with anything but ASCII letters are left alone.
+pub fn stem(word: &str) -> String {
+  if word.len() <= MIN_STEM || !word.bytes().all(|b| b.is_ascii_lowercase()) {
+    return word.to_string();
+  }
+
+  let mut stem = strip_inflection(&strip_plural(word));
+  if let Some((suffix, replacement)) = SUFFIXES
+    .iter()
+    .find(|(suffix, _)| stem.ends_with(suffix) && stem.len() - suffix.len() >= MIN_STEM)
+  {
+    stem.truncate(stem.len() - suffix.len());
+    stem.push_str(replacement);
+  }
+  if stem.len() > MIN_STEM && stem.ends_with('e') {
+    stem.pop();
+  }
+  stem
+}
+
+fn strip_plural(word: &str) -> String {
+  if let Some(base) = word.strip_suffix("ies") {
+    format!("{base}y")
+  } else if word.ends_with("sses") {
+    word[..word.len() - 2].to_string()
+  } else if word.ends_with('s') && !["ss", "us", "is"].iter().any(|end| word.ends_with(end)) {
+    word[..word.len() - 1].to_string()
+  } else {
+    word.to_string()
+  }
+}
+
+/// Drop `-ing` and `-ed`, undoubling the consonant they leave behind
+fn strip_inflection(word: &str) -> String {
+  let Some(base) = word.strip_suffix("ing").or_else(|| word.strip_suffix("ed")) else {
+    return word.to_string();
+  };
+  if base.len() < MIN_STEM || !base.bytes().any(is_vowel) {
+    return word.to_string();
+  }
+
+  let bytes = base.as_bytes();
+  let last = bytes[bytes.len() - 1];
+  if bytes[bytes.len() - 2] == last && !is_vowel(last) && !matches!(last, b'l' | b's' | b'z') {
+    base[..base.len() - 1].to_string()
+  } else {
+    base.to_string()
+  }
+}
+
+fn is_vowel(byte: u8) -> bool {
+  matches!(byte, b'a' | b'e' | b'i' | b'o' | b'u' | b'y')
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_inflections_share_a_stem() {
+    for word in ["cache", "caches", "cached", "caching"] {
+      assert_eq!(stem(word), "cach", "{word}");
+    }
+    for word in ["run", "runs", "running"] {
+      assert_eq!(stem(word), "run", "{word}");
+    }
+    assert_eq!(stem("queries"), stem("query"));
+    assert_eq!(stem("indexes"), stem("index"));
+  }
+
+  #[test]
+  fn test_derivational_suffixes() {
+    assert_eq!(stem("tokenization"), stem("tokenize"));
+    assert_eq!(stem("tokenizer"), stem("tokenize"));
+    assert_eq!(stem("quickly"), "quick");
+    assert_eq!(stem("deployment"), "deploy");
+  }
+
+  #[test]
+  fn test_short_and_unusual_words_are_kept() {
+    assert_eq!(stem("bus"), "bus");
+    assert_eq!(stem("class"), "class");
+    assert_eq!(stem("analysis"), "analysis");
+    assert_eq!(stem("utf8"), "utf8");
+    assert_eq!(stem("thing"), "thing");
+    assert_eq!(stem("naïve"), "naïve");
+  }
+}
@@ -0,0 +1,215 @@
+#[cfg(test)]
+mod archive_tests {
+  use anyhow::Result;
+  use insights::archive::{self, ConflictStrategy, ImportAction};
+  use insights::history;
+  use insights::insight::{self, Insight};
+  use insights::scope::Scope;
+  use serial_test::serial;
+  use std::env;
+  use std::fs;
+  use std::path::PathBuf;
+  use tempfile::TempDir;
+
+  fn setup_temp_insights_root(_test_name: &str) -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    env::set_var("INSIGHTS_ROOT", temp_dir.path());
+    temp_dir
+  }
+
+  fn save(topic: &str, name: &str, details: &str, tags: &[&str]) -> Result<()> {
+    let mut insight = Insight::new(
+      topic.to_string(),
+      name.to_string(),
+      format!("{name} overview"),
+      details.to_string(),
+    );
+    insight.tags = tags.iter().map(|tag| tag.to_string()).collect();
+    insight.embedding_version = Some(model_version());
+    insight.embedding = Some(vec![0.5, 0.5]);
+    insight::save(&insight)
+  }
+
+  /// Version of embeddings that imports keep
+  fn model_version() -> String {
+    #[cfg(feature = "neural")]
+    return insights::model_registry::active_model().unwrap().name;
+    #[cfg(not(feature = "neural"))]
+    "test-version".to_string()
+  }
+
+  /// Export the current store to a bundle outside of it, then switch to a fresh store
+  fn export_to_fresh_store(
+    topic: Option<&str>,
+    strip: bool,
+  ) -> Result<(PathBuf, TempDir, TempDir)> {
+    let bundle_dir = TempDir::new()?;
+    let bundle = bundle_dir.path().join("shared").join("bundle.jsonl");
+    archive::export(topic, strip, &bundle)?;
+    let fresh = setup_temp_insights_root("archive_fresh");
+    Ok((bundle, bundle_dir, fresh))
+  }
+
+  #[test]
+  #[serial]
+  fn test_export_import_round_trip() -> Result<()> {
+    let _source = setup_temp_insights_root("archive_round_trip");
+    save("auth", "tokens", "Rotate tokens", &["security"])?;
+    save("ops", "deploys", "Deploy on Tuesdays", &[])?;
+    let original = insight::load("auth", "tokens")?;
+
+    let (bundle, _bundle_dir, _fresh) = export_to_fresh_store(None, false)?;
+    let results = archive::import(&bundle, ConflictStrategy::Skip, Scope::Global)?;
+
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|result| result.action == ImportAction::Added));
+
+    let imported = insight::load("auth", "tokens")?;
+    assert_eq!(imported.details, "Rotate tokens");
+    assert_eq!(imported.tags, vec!["security"]);
+    assert_eq!(imported.created, original.created);
+    assert_eq!(imported.author, original.author);
+    assert_eq!(imported.embedding, Some(vec![0.5, 0.5]));
+    assert!(insight::load("ops", "deploys").is_ok());
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_export_topic_without_embeddings() -> Result<()> {
+    let _source = setup_temp_insights_root("archive_topic");
+    save("auth", "tokens", "Rotate tokens", &[])?;
+    save("ops", "deploys", "Deploy on Tuesdays", &[])?;
+
+    let (bundle, _bundle_dir, _fresh) = export_to_fresh_store(Some("auth"), true)?;
+    let content = fs::read_to_string(&bundle)?;
+    assert_eq!(content.lines().count(), 2);
+    assert!(!content.contains("\"embedding\""));
+
+    archive::import(&bundle, ConflictStrategy::Skip, Scope::Global)?;
+    assert!(insight::load("auth", "tokens")?.embedding.is_none());
+    assert!(insight::load("ops", "deploys").is_err());
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_conflict_strategies() -> Result<()> {
+    let _source = setup_temp_insights_root("archive_conflicts");
+    save("auth", "tokens", "Bundled details", &["bundled"])?;
+    let (bundle, _bundle_dir, _fresh) = export_to_fresh_store(None, false)?;
+
+    save("auth", "tokens", "Local details", &["local"])?;
+
+    let skipped = archive::import(&bundle, ConflictStrategy::Skip, Scope::Global)?;
+    assert_eq!(skipped[0].action, ImportAction::Skipped);
+    assert_eq!(insight::load("auth", "tokens")?.details, "Local details");
+
+    let renamed = archive::import(&bundle, ConflictStrategy::Rename, Scope::Global)?;
+    assert_eq!(renamed[0].action, ImportAction::Renamed);
+    assert_eq!(renamed[0].detail.as_deref(), Some("auth/tokens-2"));
+    assert_eq!(insight::load("auth", "tokens-2")?.details, "Bundled details");
+    assert!(insight::load("auth", "tokens-2")?.embedding.is_none());
+
+    let merged = archive::import(&bundle, ConflictStrategy::Merge, Scope::Global)?;
+    assert_eq!(merged[0].action, ImportAction::Merged);
+    let loaded = insight::load("auth", "tokens")?;
+    assert_eq!(loaded.details, "Local details\n\nBundled details");
+    assert_eq!(loaded.tags, vec!["bundled", "local"]);
+    assert_eq!(history::list("auth", "tokens")?.len(), 1);
+
+    let again = archive::import(&bundle, ConflictStrategy::Merge, Scope::Global)?;
+    assert_eq!(again[0].action, ImportAction::Unchanged);
+
+    let overwritten = archive::import(&bundle, ConflictStrategy::Overwrite, Scope::Global)?;
+    assert_eq!(overwritten[0].action, ImportAction::Overwritten);
+    let loaded = insight::load("auth", "tokens")?;
+    assert_eq!(loaded.details, "Bundled details");
+    assert_eq!(loaded.tags, vec!["bundled"]);
+
+    // Every replaced version is still in the history
+    let revisions = history::list("auth", "tokens")?;
+    assert_eq!(revisions.last().unwrap().insight.details, "Local details\n\nBundled details");
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_import_reports_invalid_insights() -> Result<()> {
+    let temp = setup_temp_insights_root("archive_invalid");
+    let bundle = temp.path().join("bundle.jsonl");
+    let manifest = r#"{"format":"insights-bundle","version":1,"exported":"2025-01-01T00:00:00Z","insights":5,"embeddings":false}"#;
+    let lines = [
+      manifest,
+      r#"{"topic":"ok","name":"fine","overview":"Overview","details":"Details"}"#,
+      r#"{"topic":"../escape","name":"bad","overview":"Overview","details":"Details"}"#,
+      r#"{"topic":"ok","name":"no-version","overview":"O","details":"D","embedding":[0.1]}"#,
+      r#"{"topic":"ok","overview":"Missing name"}"#,
+      r#"{"topic":"","name":"","overview":"Empty ids are allowed","details":""}"#,
+    ];
+    fs::write(&bundle, lines.join("\n"))?;
+
+    let results = archive::import(&bundle, ConflictStrategy::Skip, Scope::Global)?;
+    let actions: Vec<_> = results.iter().map(|result| (result.line, result.action)).collect();
+    assert_eq!(
+      actions,
+      vec![
+        (2, ImportAction::Added),
+        (3, ImportAction::Failed),
+        (4, ImportAction::Failed),
+        (5, ImportAction::Failed),
+        (6, ImportAction::Added),
+      ]
+    );
+    assert!(results[1].detail.as_deref().unwrap().contains("Invalid topic"));
+    assert!(insight::load("ok", "fine").is_ok());
+    Ok(())
+  }
+
+  #[cfg(feature = "neural")]
+  #[test]
+  #[serial]
+  fn test_import_drops_embeddings_from_other_models() -> Result<()> {
+    let temp = setup_temp_insights_root("archive_other_model");
+    let bundle = temp.path().join("bundle.jsonl");
+    let manifest = r#"{"format":"insights-bundle","version":1,"exported":"2025-01-01T00:00:00Z","insights":2,"embeddings":true}"#;
+    let current = format!(
+      r#"{{"topic":"ok","name":"current","overview":"O","details":"D","embedding_version":"{}","embedding":[0.1]}}"#,
+      model_version()
+    );
+    let lines = [
+      manifest,
+      &current,
+      r#"{"topic":"ok","name":"other","overview":"O","details":"D","embedding_version":"other-model","embedding":[0.1]}"#,
+    ];
+    fs::write(&bundle, lines.join("\n"))?;
+
+    archive::import(&bundle, ConflictStrategy::Skip, Scope::Global)?;
+    assert_eq!(insight::load("ok", "current")?.embedding, Some(vec![0.1]));
+    let other = insight::load("ok", "other")?;
+    assert!(other.embedding.is_none());
+    assert!(other.embedding_version.is_none());
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_import_rejects_bad_bundles() -> Result<()> {
+    let temp = setup_temp_insights_root("archive_bad_bundle");
+    let bundle = temp.path().join("bundle.jsonl");
+
+    fs::write(&bundle, "")?;
+    assert!(archive::import(&bundle, ConflictStrategy::Skip, Scope::Global).is_err());
+
+    fs::write(&bundle, r#"{"topic":"ok","name":"fine","overview":"O","details":"D"}"#)?;
+    assert!(archive::import(&bundle, ConflictStrategy::Skip, Scope::Global).is_err());
+
+    let truncated = r#"{"format":"insights-bundle","version":1,"exported":"2025-01-01T00:00:00Z","insights":2,"embeddings":false}
+{"topic":"ok","name":"fine","overview":"O","details":"D"}"#;
+    fs::write(&bundle, truncated)?;
+    let error = archive::import(&bundle, ConflictStrategy::Skip, Scope::Global).unwrap_err();
+    assert!(error.to_string().contains("incomplete"));
+    assert!(insight::load("ok", "fine").is_err());
+    Ok(())
+  }
+}
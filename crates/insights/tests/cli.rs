@@ -102,3 +102,661 @@ fn test_search_update_delete() {
 
   temp.close().unwrap();
 }
+
+/// Run an `insights` command and parse its stdout as JSON.
+fn insights_json(insights_dir: &assert_fs::TempDir, args: &[&str]) -> serde_json::Value {
+  let output = insights_cmd(insights_dir).args(args).output().expect("command runs");
+  assert!(output.status.success(), "command failed: {args:?}");
+  serde_json::from_slice(&output.stdout).expect("stdout is valid JSON")
+}
+
+fn assert_insight_schema(record: &serde_json::Value) {
+  let keys: Vec<&str> = record.as_object().unwrap().keys().map(String::as_str).collect();
+  assert_eq!(
+    keys,
+    vec![
+      "author",
+      "created",
+      "details",
+      "embedding_computed",
+      "embedding_version",
+      "file_path",
+      "name",
+      "origin",
+      "overview",
+      "scope",
+      "score",
+      "source",
+      "tags",
+      "topic",
+      "updated"
+    ]
+  );
+}
+
+// violet ignore chunk
+#[test]
+#[serial]
+fn test_json_output() {
+  let temp = assert_fs::TempDir::new().unwrap();
+
+  let added = insights_json(
+    &temp,
+    &["add", "json_topic", "json_insight", "JSON overview", "JSON details", "--format", "json"],
+  );
+  assert_insight_schema(&added);
+  assert_eq!(added["topic"], "json_topic");
+  assert_eq!(added["name"], "json_insight");
+  assert!(added["file_path"].as_str().unwrap().ends_with("json_insight.insight.md"));
+
+  let fetched = insights_json(&temp, &["--format", "json", "get", "json_topic", "json_insight"]);
+  assert_insight_schema(&fetched);
+  assert_eq!(fetched["overview"], "JSON overview");
+  assert_eq!(fetched["details"], "JSON details");
+  assert!(fetched["score"].is_null());
+
+  let listed = insights_json(&temp, &["list", "--format", "json"]);
+  let listed = listed.as_array().unwrap();
+  assert_eq!(listed.len(), 1);
+  assert_insight_schema(&listed[0]);
+
+  let topics = insights_json(&temp, &["topics", "--format", "json"]);
+  assert_eq!(topics, serde_json::json!([{ "topic": "json_topic" }]));
+
+  let found = insights_json(&temp, &["search", "--exact", "overview", "--format", "json"]);
+  assert_eq!(found["total"], 1);
+  let results = found["results"].as_array().unwrap();
+  assert_eq!(results.len(), 1);
+  assert!(results[0].get("details").is_none());
+  assert_eq!(results[0]["snippet"], "JSON details");
+  assert!(results[0]["score"].as_f64().unwrap() > 0.0);
+
+  let empty = insights_json(&temp, &["search", "--exact", "missing", "--format", "json"]);
+  assert_eq!(empty, serde_json::json!({ "total": 0, "offset": 0, "results": [] }));
+
+  temp.close().unwrap();
+}
+
+#[test]
+#[serial]
+fn test_search_without_a_store_keeps_stdout_clean() {
+  let temp = assert_fs::TempDir::new().unwrap();
+
+  insights_cmd(&temp)
+    .env("INSIGHTS_ROOT", temp.path().join("missing"))
+    .env("INSIGHTS_PROJECT_ROOT", "")
+    .args(["search", "cache", "--format", "json"])
+    .assert()
+    .failure()
+    .stdout("")
+    .stderr(contains("No insights found"));
+
+  temp.close().unwrap();
+}
+
+#[test]
+#[serial]
+fn test_yaml_output() {
+  let temp = assert_fs::TempDir::new().unwrap();
+
+  insights_cmd(&temp)
+    .args(["add", "yaml_topic", "yaml_insight", "YAML overview", "YAML details"])
+    .assert()
+    .success();
+
+  insights_cmd(&temp)
+    .args(["get", "yaml_topic", "yaml_insight", "--format", "yaml"])
+    .assert()
+    .success()
+    .stdout(
+      contains("topic: yaml_topic")
+        .and(contains("overview: YAML overview"))
+        .and(contains("score: null"))
+        .and(contains("\u{1b}[").not()),
+    );
+
+  temp.close().unwrap();
+}
+
+#[test]
+#[serial]
+fn test_tag_filters() {
+  let temp = assert_fs::TempDir::new().unwrap();
+
+  insights_cmd(&temp)
+    .args(["add", "tagged", "cache", "Cache overview", "Cache details", "--tag", "Perf"])
+    .args(["--tag", "storage"])
+    .assert()
+    .success();
+  insights_cmd(&temp)
+    .args(["add", "tagged", "logging", "Logging overview", "Logging details", "--tag", "ops"])
+    .assert()
+    .success();
+
+  let listed = insights_json(&temp, &["list", "--tag", "perf", "--format", "json"]);
+  let listed = listed.as_array().unwrap();
+  assert_eq!(listed.len(), 1);
+  assert_eq!(listed[0]["name"], "cache");
+  assert_eq!(listed[0]["tags"], serde_json::json!(["perf", "storage"]));
+
+  let none = insights_json(&temp, &["list", "--tag", "perf", "--tag", "ops", "--format", "json"]);
+  assert_eq!(none, serde_json::json!([]));
+
+  let results =
+    insights_json(&temp, &["search", "--exact", "overview", "--tag", "ops", "--format", "json"]);
+  let results = results["results"].as_array().unwrap();
+  assert_eq!(results.len(), 1);
+  assert_eq!(results[0]["name"], "logging");
+
+  // Retagging keeps the content and moves the insight between filters
+  insights_cmd(&temp).args(["update", "tagged", "logging", "--tag", "perf"]).assert().success();
+  let listed = insights_json(&temp, &["list", "--tag", "perf", "--format", "json"]);
+  assert_eq!(listed.as_array().unwrap().len(), 2);
+
+  temp.close().unwrap();
+}
+
+#[test]
+#[serial]
+fn test_search_pages_and_explains_results() {
+  let temp = assert_fs::TempDir::new().unwrap();
+  for (name, details) in [("one", "cache"), ("two", "cache cache"), ("three", "cache cache cache")]
+  {
+    insights_cmd(&temp).args(["add", "paged", name, "Overview", details]).assert().success();
+  }
+
+  let all = insights_json(&temp, &["search", "--exact", "cache", "--format", "json"]);
+  let names: Vec<&str> = all["results"]
+    .as_array()
+    .unwrap()
+    .iter()
+    .map(|result| result["name"].as_str().unwrap())
+    .collect();
+  assert_eq!(names, vec!["three", "two", "one"]);
+  assert!(all["results"][0].get("explanation").is_none());
+
+  let page = insights_json(
+    &temp,
+    &["search", "--exact", "cache", "--limit", "1", "--offset", "1", "--format", "json"],
+  );
+  assert_eq!(page["total"], 3);
+  assert_eq!(page["offset"], 1);
+  assert_eq!(page["results"].as_array().unwrap().len(), 1);
+  assert_eq!(page["results"][0]["name"], "two");
+
+  let none =
+    insights_json(&temp, &["search", "--exact", "cache", "--min-score", "1", "--format", "json"]);
+  assert_eq!(none["total"], 0);
+
+  let explained = insights_json(
+    &temp,
+    &[
+      "search",
+      "--exact",
+      "cache -overview:missing",
+      "--explain",
+      "--limit",
+      "1",
+      "--format",
+      "json",
+    ],
+  );
+  let explanation = &explained["results"][0]["explanation"];
+  assert_eq!(explanation["matched_terms"], serde_json::json!(["cache"]));
+  assert_eq!(explanation["contributions"][0]["strategy"], "exact");
+  assert_eq!(explanation["contributions"][0]["rank"], 1);
+  assert!(explanation["contributions"][0]["raw_score"].as_f64().unwrap() > 0.0);
+
+  insights_cmd(&temp)
+    .args(["search", "--exact", "cache", "--limit", "2", "--explain"])
+    .assert()
+    .success()
+    .stdout(
+      contains("matched")
+        .and(contains("exact"))
+        .and(contains("Showing 1-2 of 3 results; use --offset 2 for more")),
+    );
+
+  temp.close().unwrap();
+}
+
+#[test]
+#[serial]
+fn test_search_shows_snippets_of_long_insights() {
+  let temp = assert_fs::TempDir::new().unwrap();
+  let filler = "Unrelated background sentence about the team. ".repeat(12);
+  let details = format!("{filler}The cache is flushed on every deploy. {filler}Closing remark.");
+  insights_cmd(&temp).args(["add", "long", "notes", "Overview", &details]).assert().success();
+
+  let results = insights_json(&temp, &["search", "--exact", "flushed", "--format", "json"]);
+  let snippet = results["results"][0]["snippet"].as_str().unwrap();
+  assert!(snippet.contains("The cache is flushed on every deploy."), "{snippet}");
+  assert!(snippet.starts_with('…') && snippet.ends_with('…'), "{snippet}");
+  assert!(results["results"][0].get("details").is_none());
+  assert!(!snippet.contains("Closing remark"), "{snippet}");
+
+  insights_cmd(&temp)
+    .args(["search", "--exact", "flushed"])
+    .assert()
+    .success()
+    .stdout(contains("flushed").and(contains("Closing remark").not()));
+
+  let full = insights_json(&temp, &["search", "--exact", "flushed", "--full", "--format", "json"]);
+  assert!(full["results"][0].get("snippet").is_none());
+  assert_eq!(full["results"][0]["details"], details.as_str());
+  insights_cmd(&temp)
+    .args(["search", "--exact", "flushed", "--full"])
+    .assert()
+    .success()
+    .stdout(contains("Closing remark"));
+
+  temp.close().unwrap();
+}
+
+#[test]
+#[serial]
+fn test_semantic_search_matches_word_forms_typos_and_identifiers() {
+  let temp = assert_fs::TempDir::new().unwrap();
+  insights_cmd(&temp)
+    .args(["add", "perf", "invalidation", "Cache rules", "Call clearCache on every deploy"])
+    .assert()
+    .success();
+
+  for query in ["caching", "deploys", "dpeloy", "clear_cache"] {
+    let results = insights_json(&temp, &["search", "--semantic", query, "--format", "json"]);
+    assert_eq!(results["results"][0]["name"], "invalidation", "{query}");
+  }
+
+  insights_cmd(&temp)
+    .args(["search", "--semantic", "caching"])
+    .env("INSIGHTS_STOP_WORDS", "english,code")
+    .assert()
+    .success()
+    .stdout(contains("invalidation"));
+
+  insights_cmd(&temp)
+    .args(["search", "--semantic", "caching"])
+    .env("INSIGHTS_STOP_WORDS", temp.path().join("missing.txt"))
+    .assert()
+    .failure()
+    .stderr(contains("Invalid INSIGHTS_STOP_WORDS"));
+
+  temp.close().unwrap();
+}
+
+#[test]
+#[serial]
+fn test_eval_reports_metrics_per_mode() {
+  let temp = assert_fs::TempDir::new().unwrap();
+  let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/search-eval.yaml");
+
+  let report = insights_json(&temp, &["eval", fixtures, "--mode", "exact", "--format", "json"]);
+  assert_eq!(report["k"], 5);
+  assert_eq!(report["modes"][0]["mode"], "exact");
+  assert_eq!(report["modes"][0]["queries"][0]["ndcg"], 0.0);
+  assert!(report["modes"][0]["mrr"].as_f64().unwrap() > 0.0);
+
+  insights_cmd(&temp)
+    .args(["eval", fixtures, "-k", "3", "--mode", "exact"])
+    .assert()
+    .success()
+    .stdout(contains("P@3").and(contains("nDCG@3")).and(contains("\"caching\"")));
+
+  // Fixture insights never end up in the store being used
+  insights_cmd(&temp).args(["topics"]).assert().success().stdout(contains("No topics found"));
+
+  temp.close().unwrap();
+}
+
+#[test]
+#[serial]
+fn test_history_diff_and_revert() {
+  let temp = assert_fs::TempDir::new().unwrap();
+
+  insights_cmd(&temp)
+    .args(["add", "history", "note", "First overview", "First details"])
+    .assert()
+    .success();
+  insights_cmd(&temp)
+    .args(["update", "history", "note", "--details", "Second details"])
+    .assert()
+    .success();
+
+  let revisions = insights_json(&temp, &["history", "history", "note", "--format", "json"]);
+  let revisions = revisions.as_array().unwrap();
+  assert_eq!(revisions.len(), 1);
+  assert_eq!(revisions[0]["revision"], 1);
+  assert_eq!(revisions[0]["overview"], "First overview");
+
+  insights_cmd(&temp)
+    .args(["diff", "history", "note"])
+    .assert()
+    .success()
+    .stdout(contains("-First details"))
+    .stdout(contains("+Second details"));
+
+  insights_cmd(&temp)
+    .args(["revert", "history", "note", "--to", "1"])
+    .assert()
+    .success()
+    .stdout(contains("Reverted insight"));
+  insights_cmd(&temp)
+    .args(["get", "history", "note"])
+    .assert()
+    .success()
+    .stdout(contains("First details"));
+
+  insights_cmd(&temp).args(["revert", "history", "note", "--to", "5"]).assert().failure();
+
+  temp.close().unwrap();
+}
+
+#[test]
+#[serial]
+fn test_links_and_expand() {
+  let temp = assert_fs::TempDir::new().unwrap();
+
+  insights_cmd(&temp)
+    .args(["add", "auth", "token-refresh", "Refresh overview", "Rotate tokens"])
+    .assert()
+    .success();
+  insights_cmd(&temp)
+    .args(["add", "auth", "login", "Login overview", "Then see [[auth/token-refresh]]"])
+    .assert()
+    .success();
+
+  let links = insights_json(&temp, &["links", "auth", "token-refresh", "--format", "json"]);
+  assert_eq!(links["outgoing"], serde_json::json!([]));
+  assert_eq!(
+    links["backlinks"],
+    serde_json::json!([{ "topic": "auth", "name": "login", "exists": true }])
+  );
+
+  let expanded = insights_json(&temp, &["get", "auth", "login", "--expand", "--format", "json"]);
+  assert_eq!(expanded["name"], "login");
+  assert_eq!(expanded["linked"][0]["name"], "token-refresh");
+  assert_eq!(expanded["linked"][0]["details"], "Rotate tokens");
+
+  insights_cmd(&temp)
+    .args(["delete", "auth", "token-refresh", "--force"])
+    .assert()
+    .success()
+    .stderr(contains("auth/login"));
+
+  insights_cmd(&temp)
+    .args(["links", "auth", "login"])
+    .assert()
+    .success()
+    .stdout(contains("(broken)"));
+
+  temp.close().unwrap();
+}
+
+#[test]
+#[serial]
+fn test_mv_renames_and_moves() {
+  let temp = assert_fs::TempDir::new().unwrap();
+
+  insights_cmd(&temp)
+    .args(["add", "drafts", "caching", "Cache overview", "Cache details"])
+    .assert()
+    .success();
+  insights_cmd(&temp)
+    .args(["add", "ops", "runbook", "Runbook overview", "See [[drafts/caching]]"])
+    .assert()
+    .success();
+
+  insights_cmd(&temp)
+    .args(["mv", "drafts", "caching", "performance", "cache-strategy"])
+    .assert()
+    .success()
+    .stdout(contains("Moved insight drafts/caching to performance/cache-strategy"))
+    .stderr(contains("ops/runbook"));
+
+  insights_cmd(&temp).args(["get", "drafts", "caching"]).assert().failure();
+  let moved = insights_json(&temp, &["get", "performance", "cache-strategy", "--format", "json"]);
+  assert_eq!(moved["details"], "Cache details");
+  assert!(!temp.path().join("drafts").exists());
+
+  // Moving keeps the name when no new one is given
+  insights_cmd(&temp).args(["mv", "performance", "cache-strategy", "archive"]).assert().success();
+  insights_cmd(&temp).args(["get", "archive", "cache-strategy"]).assert().success();
+
+  temp.close().unwrap();
+}
+
+#[test]
+#[serial]
+fn test_project_scope() {
+  let global = assert_fs::TempDir::new().unwrap();
+  let project = assert_fs::TempDir::new().unwrap();
+  let scoped_cmd = || {
+    let mut cmd = insights_cmd(&global);
+    cmd.env("INSIGHTS_PROJECT_ROOT", project.path());
+    cmd
+  };
+
+  scoped_cmd()
+    .args(["add", "auth", "tokens", "Global tokens", "Personal notes"])
+    .assert()
+    .success()
+    .stdout(contains("[global]"));
+  scoped_cmd()
+    .args(["add", "auth", "tokens", "Project tokens", "Team notes", "--scope", "project"])
+    .assert()
+    .success()
+    .stdout(contains("[project]"));
+  scoped_cmd()
+    .args(["add", "auth", "sessions", "Project sessions", "Team notes", "--scope", "project"])
+    .assert()
+    .success();
+  assert!(project.path().join("auth").join("tokens.insight.md").exists());
+
+  // The project insight shadows the global one with the same topic/name
+  let listed = insights_json(&global, &["list", "--format", "json"]);
+  let listed = listed.as_array().unwrap();
+  assert_eq!(listed.len(), 1, "without the project store only the global insight is visible");
+
+  let output = scoped_cmd().args(["list", "--format", "json"]).output().unwrap();
+  let listed: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+  let listed: Vec<_> = listed
+    .as_array()
+    .unwrap()
+    .iter()
+    .map(|record| (record["name"].as_str().unwrap(), record["scope"].as_str().unwrap()))
+    .collect();
+  assert_eq!(listed, vec![("sessions", "project"), ("tokens", "project")]);
+
+  scoped_cmd()
+    .args(["search", "--exact", "notes"])
+    .assert()
+    .success()
+    .stdout(contains("[project]"))
+    .stdout(contains("Team notes"))
+    .stdout(contains("Personal notes").not());
+
+  // Without a project the project scope is rejected
+  insights_cmd(&global)
+    .env("INSIGHTS_PROJECT_ROOT", "")
+    .args(["add", "auth", "other", "Overview", "Details", "--scope", "project"])
+    .assert()
+    .failure()
+    .stderr(contains("Not inside a project"));
+
+  global.close().unwrap();
+  project.close().unwrap();
+}
+
+#[test]
+#[serial]
+fn test_export_and_import() {
+  let source = assert_fs::TempDir::new().unwrap();
+  let target = assert_fs::TempDir::new().unwrap();
+  let bundle = source.path().join("bundle.jsonl");
+  let bundle_arg = bundle.to_str().unwrap();
+
+  insights_cmd(&source)
+    .args(["add", "shared", "cache", "Cache overview", "Cache details", "--tag", "perf"])
+    .assert()
+    .success();
+
+  let exported = insights_json(
+    &source,
+    &["export", "--out", bundle_arg, "--strip-embeddings", "--format", "json"],
+  );
+  assert_eq!(exported["insights"], 1);
+  assert_eq!(exported["embeddings"], false);
+
+  let imported = insights_json(&target, &["import", bundle_arg, "--format", "json"]);
+  assert_eq!(imported[0]["action"], "added");
+  assert_eq!(imported[0]["name"], "cache");
+
+  insights_cmd(&target)
+    .args(["import", bundle_arg, "--on-conflict", "rename"])
+    .assert()
+    .success()
+    .stdout(contains("renamed (shared/cache-2)"));
+
+  let listed = insights_json(&target, &["list", "--tag", "perf", "--format", "json"]);
+  assert_eq!(listed.as_array().unwrap().len(), 2);
+
+  source.close().unwrap();
+  target.close().unwrap();
+}
+
+#[test]
+#[serial]
+fn test_ingest_markdown_directory() {
+  let temp = assert_fs::TempDir::new().unwrap();
+  let vault = assert_fs::TempDir::new().unwrap();
+  std::fs::create_dir_all(vault.path().join("ops")).unwrap();
+  std::fs::write(vault.path().join("ops/deploys.md"), "# Deploys\n\nDeploy on Tuesdays.").unwrap();
+  let vault_arg = vault.path().to_str().unwrap();
+
+  let ingested = insights_json(&temp, &["ingest", vault_arg, "--format", "json"]);
+  assert_eq!(ingested[0]["action"], "added");
+  assert_eq!(ingested[0]["topic"], "ops");
+  assert_eq!(ingested[0]["file"], "ops/deploys.md");
+
+  let fetched = insights_json(&temp, &["get", "ops", "deploys", "--format", "json"]);
+  assert_eq!(fetched["overview"], "Deploy on Tuesdays.");
+  assert!(fetched["origin"].as_str().unwrap().ends_with("deploys.md"));
+
+  insights_cmd(&temp)
+    .args(["ingest", vault_arg])
+    .assert()
+    .success()
+    .stdout(contains("1 unchanged"));
+
+  temp.close().unwrap();
+  vault.close().unwrap();
+}
+
+#[cfg(feature = "neural")]
+#[test]
+#[serial]
+fn test_index_check_reports_missing_embeddings() {
+  let temp = assert_fs::TempDir::new().unwrap();
+  std::fs::create_dir_all(temp.path().join("ci")).unwrap();
+  std::fs::write(
+    temp.path().join("ci/unindexed.insight.md"),
+    "---\ntopic: ci\nname: unindexed\noverview: Never embedded\n---\n\n# Details\nDetails",
+  )
+  .unwrap();
+
+  insights_cmd(&temp)
+    .args(["index", "--check"])
+    .assert()
+    .failure()
+    .stdout(contains("ci/unindexed"))
+    .stdout(contains("missing"))
+    .stderr(contains("1 insights have outdated embeddings"));
+
+  let content = std::fs::read_to_string(temp.path().join("ci/unindexed.insight.md")).unwrap();
+  assert!(!content.contains("embedding"));
+
+  temp.close().unwrap();
+}
+
+#[cfg(feature = "neural")]
+#[test]
+#[serial]
+fn test_model_install_and_offline_search() {
+  let temp = assert_fs::TempDir::new().unwrap();
+  let cache = assert_fs::TempDir::new().unwrap();
+  let model_cmd = || {
+    let mut cmd = insights_cmd(&temp);
+    cmd.env("INSIGHTS_MODEL_CACHE", cache.path());
+    cmd.env("INSIGHTS_MODELS_CONFIG", temp.path().join("models.yaml"));
+    cmd
+  };
+
+  model_cmd().args(["add", "ops", "deploys", "Deploy on Tuesdays", "After the freeze"]).assert();
+
+  // Without an installed model search still works, but says what is missing
+  model_cmd()
+    .args(["search", "tuesdays"])
+    .assert()
+    .success()
+    .stdout(contains("ops"))
+    .stderr(contains("Neural search unavailable"))
+    .stderr(contains("insights model install"));
+
+  model_cmd()
+    .args(["model", "list"])
+    .assert()
+    .success()
+    .stdout(contains("all-MiniLM-L6-v2"))
+    .stdout(contains("not installed"));
+
+  let download = temp.path().join("download.onnx");
+  std::fs::write(&download, "model bytes").unwrap();
+  model_cmd()
+    .args(["model", "install", "--from", download.to_str().unwrap(), "--sha256", &"0".repeat(64)])
+    .assert()
+    .failure()
+    .stderr(contains("Checksum mismatch"));
+
+  model_cmd()
+    .args(["--format", "json", "model", "install", "--from", download.to_str().unwrap()])
+    .assert()
+    .success()
+    .stdout(contains("9cb7487000bc86ac36ce83c4acfabe8878552be99572a6770f65ab1d048a5c48"));
+  assert!(cache.path().join("all-MiniLM-L6-v2/model.onnx").exists());
+
+  model_cmd().args(["model", "list"]).assert().success().stdout(contains("verified"));
+
+  temp.close().unwrap();
+}
+
+#[cfg(feature = "neural")]
+#[test]
+#[serial]
+fn test_daemon_commands_without_a_model() {
+  let temp = assert_fs::TempDir::new().unwrap();
+  let daemon_cmd = || {
+    let mut cmd = insights_cmd(&temp);
+    cmd.env("INSIGHTS_RUNTIME_DIR", temp.path().join("run"));
+    cmd.env("INSIGHTS_MODEL_CACHE", temp.path().join("models"));
+    cmd.env("INSIGHTS_MODELS_CONFIG", temp.path().join("models.yaml"));
+    cmd
+  };
+
+  daemon_cmd().args(["daemon", "status"]).assert().success().stdout(contains("not running"));
+  daemon_cmd()
+    .args(["--format", "json", "daemon", "status"])
+    .assert()
+    .success()
+    .stdout(contains("\"running\": false"));
+
+  // The model is checked before a daemon is spawned
+  daemon_cmd()
+    .args(["daemon", "start", "--idle-timeout", "5"])
+    .assert()
+    .failure()
+    .stderr(contains("insights model install"));
+
+  daemon_cmd().args(["daemon", "stop"]).assert().success().stdout(contains("not running"));
+
+  temp.close().unwrap();
+}
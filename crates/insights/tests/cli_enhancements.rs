@@ -9,6 +9,8 @@ use insights::embedding_client::MockEmbeddingService;
 #[cfg(feature = "neural")]
 use insights::insight;
 #[cfg(feature = "neural")]
+use insights::output;
+#[cfg(feature = "neural")]
 use serial_test::serial;
 #[cfg(feature = "neural")]
 use std::env;
@@ -34,19 +36,21 @@ mod cli_enhancement_tests {
 
     // Test add -> get -> list flow
     add_insight_with_client(
-      "workflow",
-      "basic",
-      "Basic workflow test",
-      "Testing the basic command flow",
+      &NewInsight::new(
+        "workflow",
+        "basic",
+        "Basic workflow test",
+        "Testing the basic command flow",
+      ),
       &client,
     )?;
 
-    get_insight("workflow", "basic", false)?;
-    get_insight("workflow", "basic", true)?;
+    let insight = get_insight("workflow", "basic")?;
+    assert_eq!(insight.overview, "Basic workflow test");
 
-    list_insights(Some("workflow"), false)?;
-    list_insights(None, false)?;
-    list_topics()?;
+    assert_eq!(list_insights(Some("workflow"), &[])?.len(), 1);
+    assert_eq!(list_insights(None, &[])?.len(), 1);
+    assert_eq!(list_topics()?, vec!["workflow".to_string()]);
 
     Ok(())
   }
@@ -58,15 +62,39 @@ mod cli_enhancement_tests {
     let client = embedding_client::with_service(Box::new(MockEmbeddingService));
 
     // Create multiple insights across topics
-    add_insight_with_client("ai", "basics", "AI Basics", "Introduction to AI", &client)?;
-    add_insight_with_client("ai", "advanced", "Advanced AI", "Deep AI concepts", &client)?;
-    add_insight_with_client("rust", "ownership", "Ownership", "Rust ownership model", &client)?;
-    add_insight_with_client("rust", "borrowing", "Borrowing", "Rust borrowing rules", &client)?;
+    add_insight_with_client(
+      &NewInsight {
+        force: true,
+        ..NewInsight::new("ai", "basics", "AI Basics", "Introduction to AI")
+      },
+      &client,
+    )?;
+    add_insight_with_client(
+      &NewInsight {
+        force: true,
+        ..NewInsight::new("ai", "advanced", "Advanced AI", "Deep AI concepts")
+      },
+      &client,
+    )?;
+    add_insight_with_client(
+      &NewInsight {
+        force: true,
+        ..NewInsight::new("rust", "ownership", "Ownership", "Rust ownership model")
+      },
+      &client,
+    )?;
+    add_insight_with_client(
+      &NewInsight {
+        force: true,
+        ..NewInsight::new("rust", "borrowing", "Borrowing", "Rust borrowing rules")
+      },
+      &client,
+    )?;
 
     // Test listing and filtering
-    list_insights(None, false)?;
-    list_insights(Some("ai"), false)?;
-    list_insights(Some("rust"), false)?;
+    list_insights(None, &[])?;
+    list_insights(Some("ai"), &[])?;
+    list_insights(Some("rust"), &[])?;
     list_topics()?;
 
     Ok(())
@@ -79,16 +107,20 @@ mod cli_enhancement_tests {
     let client = embedding_client::with_service(Box::new(MockEmbeddingService));
 
     // Create initial insight
-    add_insight_with_client("updates", "test", "Original overview", "Original details", &client)?;
+    add_insight_with_client(
+      &NewInsight::new("updates", "test", "Original overview", "Original details"),
+      &client,
+    )?;
 
     // Test various update scenarios
-    update_insight_with_client("updates", "test", Some("Updated overview"), None, &client)?;
-    update_insight_with_client("updates", "test", None, Some("Updated details"), &client)?;
+    update_insight_with_client("updates", "test", Some("Updated overview"), None, None, &client)?;
+    update_insight_with_client("updates", "test", None, Some("Updated details"), None, &client)?;
     update_insight_with_client(
       "updates",
       "test",
       Some("Final overview"),
       Some("Final details"),
+      None,
       &client,
     )?;
 
@@ -107,9 +139,27 @@ mod cli_enhancement_tests {
     let client = embedding_client::with_service(Box::new(MockEmbeddingService));
 
     // Create insights to delete
-    add_insight_with_client("deleteme", "first", "First insight", "First details", &client)?;
-    add_insight_with_client("deleteme", "second", "Second insight", "Second details", &client)?;
-    add_insight_with_client("keepme", "safe", "Safe insight", "Safe details", &client)?;
+    add_insight_with_client(
+      &NewInsight {
+        force: true,
+        ..NewInsight::new("deleteme", "first", "First insight", "First details")
+      },
+      &client,
+    )?;
+    add_insight_with_client(
+      &NewInsight {
+        force: true,
+        ..NewInsight::new("deleteme", "second", "Second insight", "Second details")
+      },
+      &client,
+    )?;
+    add_insight_with_client(
+      &NewInsight {
+        force: true,
+        ..NewInsight::new("keepme", "safe", "Safe insight", "Safe details")
+      },
+      &client,
+    )?;
 
     // Delete one insight
     delete_insight("deleteme", "first", true)?;
@@ -138,9 +188,27 @@ mod cli_enhancement_tests {
     list_topics()?;
 
     // Add insights to create topics
-    add_insight_with_client("topic1", "insight1", "Overview 1", "Details 1", &client)?;
-    add_insight_with_client("topic2", "insight2", "Overview 2", "Details 2", &client)?;
-    add_insight_with_client("topic3", "insight3", "Overview 3", "Details 3", &client)?;
+    add_insight_with_client(
+      &NewInsight {
+        force: true,
+        ..NewInsight::new("topic1", "insight1", "Overview 1", "Details 1")
+      },
+      &client,
+    )?;
+    add_insight_with_client(
+      &NewInsight {
+        force: true,
+        ..NewInsight::new("topic2", "insight2", "Overview 2", "Details 2")
+      },
+      &client,
+    )?;
+    add_insight_with_client(
+      &NewInsight {
+        force: true,
+        ..NewInsight::new("topic3", "insight3", "Overview 3", "Details 3")
+      },
+      &client,
+    )?;
 
     // Test topic listing
     list_topics()?;
@@ -161,12 +229,12 @@ mod cli_enhancement_tests {
     let client = embedding_client::with_service(Box::new(MockEmbeddingService));
 
     // Test getting non-existent insight
-    let result = get_insight("nonexistent", "insight", false);
+    let result = get_insight("nonexistent", "insight");
     assert!(result.is_err());
 
     // Test updating non-existent insight
     let result =
-      update_insight_with_client("nonexistent", "insight", Some("overview"), None, &client);
+      update_insight_with_client("nonexistent", "insight", Some("overview"), None, None, &client);
     assert!(result.is_err());
 
     // Test deleting non-existent insight
@@ -174,8 +242,14 @@ mod cli_enhancement_tests {
     assert!(result.is_err());
 
     // Test duplicate addition
-    add_insight_with_client("errors", "duplicate", "Original", "Original", &client)?;
-    let result = add_insight_with_client("errors", "duplicate", "Duplicate", "Duplicate", &client);
+    add_insight_with_client(
+      &NewInsight::new("errors", "duplicate", "Original", "Original"),
+      &client,
+    )?;
+    let result = add_insight_with_client(
+      &NewInsight::new("errors", "duplicate", "Duplicate", "Duplicate"),
+      &client,
+    );
     assert!(result.is_err());
 
     Ok(())
@@ -188,26 +262,46 @@ mod cli_enhancement_tests {
     let client = embedding_client::with_service(Box::new(MockEmbeddingService));
 
     // Test with empty content
-    add_insight_with_client("empty", "test1", "", "", &client)?;
-    add_insight_with_client("empty", "test2", "Overview", "", &client)?;
-    add_insight_with_client("empty", "test3", "", "Details", &client)?;
+    add_insight_with_client(
+      &NewInsight { force: true, ..NewInsight::new("empty", "test1", "", "") },
+      &client,
+    )?;
+    add_insight_with_client(
+      &NewInsight { force: true, ..NewInsight::new("empty", "test2", "Overview", "") },
+      &client,
+    )?;
+    add_insight_with_client(
+      &NewInsight { force: true, ..NewInsight::new("empty", "test3", "", "Details") },
+      &client,
+    )?;
 
     // Test with special characters
     add_insight_with_client(
-      "special",
-      "chars",
-      "Overview with émojis 🚀 and symbols: @#$%",
-      "Details with\nmultiple\nlines\nand unicode: ñáéíóú",
+      &NewInsight {
+        force: true,
+        ..NewInsight::new(
+          "special",
+          "chars",
+          "Overview with émojis 🚀 and symbols: @#$%",
+          "Details with\nmultiple\nlines\nand unicode: ñáéíóú",
+        )
+      },
       &client,
     )?;
 
     // Test with long content
     let long_overview = "A".repeat(1000);
     let long_details = "B".repeat(5000);
-    add_insight_with_client("long", "content", &long_overview, &long_details, &client)?;
+    add_insight_with_client(
+      &NewInsight {
+        force: true,
+        ..NewInsight::new("long", "content", &long_overview, &long_details)
+      },
+      &client,
+    )?;
 
     // Verify all can be retrieved
-    list_insights(None, false)?;
+    list_insights(None, &[])?;
 
     Ok(())
   }
@@ -219,18 +313,38 @@ mod cli_enhancement_tests {
     let client = embedding_client::with_service(Box::new(MockEmbeddingService));
 
     // Create test data
-    add_insight_with_client("output", "test1", "Short overview", "Short details", &client)?;
-    add_insight_with_client("output", "test2", "Another overview", "More details here", &client)?;
+    add_insight_with_client(
+      &NewInsight {
+        force: true,
+        ..NewInsight::new("output", "test1", "Short overview", "Short details")
+      },
+      &client,
+    )?;
+    add_insight_with_client(
+      &NewInsight {
+        force: true,
+        ..NewInsight::new("output", "test2", "Another overview", "More details here")
+      },
+      &client,
+    )?;
 
     // Test different output modes
-    get_insight("output", "test1", false)?; // Full content
-    get_insight("output", "test1", true)?; // Overview only
-
-    list_insights(Some("output"), false)?; // Normal list
-    list_insights(Some("output"), true)?; // Verbose list
-
-    list_insights(None, false)?; // All insights normal
-    list_insights(None, true)?; // All insights verbose
+    let insight = get_insight("output", "test1")?;
+    output::print_insight(&insight, false); // Full content
+    output::print_insight(&insight, true); // Overview only
+
+    let topic_insights = list_insights(Some("output"), &[])?;
+    output::print_insights(&topic_insights, Some("output"), false); // Normal list
+    output::print_insights(&topic_insights, Some("output"), true); // Verbose list
+
+    let all_insights = list_insights(None, &[])?;
+    output::print_insights(&all_insights, None, false); // All insights normal
+    output::print_insights(&all_insights, None, true); // All insights verbose
+
+    // Structured records carry the same data
+    let records = output::insight_records(&all_insights)?;
+    assert_eq!(records.len(), 2);
+    assert!(records.iter().all(|record| record.file_path.exists()));
 
     Ok(())
   }
@@ -10,6 +10,7 @@ mod command_tests {
   use anyhow::Result;
   use insights::commands::*;
   use insights::insight;
+  use insights::output;
   use serial_test::serial;
   use std::env;
   use tempfile::TempDir;
@@ -26,7 +27,10 @@ mod command_tests {
     let _temp = setup_temp_insights_root("add_insight_success");
     let client = embedding_client::with_service(Box::new(MockEmbeddingService));
 
-    add_insight_with_client("test_topic", "test_name", "Test overview", "Test details", &client)?;
+    add_insight_with_client(
+      &NewInsight::new("test_topic", "test_name", "Test overview", "Test details"),
+      &client,
+    )?;
 
     // Verify the insight was created
     let loaded = insight::load("test_topic", "test_name")?;
@@ -45,9 +49,18 @@ mod command_tests {
     let client = embedding_client::with_service(Box::new(MockEmbeddingService));
 
     // Empty fields should be allowed (creating unusual but valid insights)
-    add_insight_with_client("", "", "", "", &client)?;
-    add_insight_with_client("topic", "name", "", "details", &client)?;
-    add_insight_with_client("topic2", "name2", "overview", "", &client)?;
+    add_insight_with_client(
+      &NewInsight { force: true, ..NewInsight::new("", "", "", "") },
+      &client,
+    )?;
+    add_insight_with_client(
+      &NewInsight { force: true, ..NewInsight::new("topic", "name", "", "details") },
+      &client,
+    )?;
+    add_insight_with_client(
+      &NewInsight { force: true, ..NewInsight::new("topic2", "name2", "overview", "") },
+      &client,
+    )?;
 
     Ok(())
   }
@@ -58,14 +71,14 @@ mod command_tests {
     let _temp = setup_temp_insights_root("add_duplicate");
     let client = embedding_client::with_service(Box::new(MockEmbeddingService));
 
-    add_insight_with_client("test_topic", "test_name", "Overview", "Details", &client)?;
+    add_insight_with_client(
+      &NewInsight::new("test_topic", "test_name", "Overview", "Details"),
+      &client,
+    )?;
 
     // Adding the same insight again should fail
     let result = add_insight_with_client(
-      "test_topic",
-      "test_name",
-      "Different overview",
-      "Different details",
+      &NewInsight::new("test_topic", "test_name", "Different overview", "Different details"),
       &client,
     );
     assert!(result.is_err());
@@ -79,10 +92,15 @@ mod command_tests {
     let _temp = setup_temp_insights_root("get_insight_full");
     let client = embedding_client::with_service(Box::new(MockEmbeddingService));
 
-    add_insight_with_client("test_topic", "test_name", "Test overview", "Test details", &client)?;
+    add_insight_with_client(
+      &NewInsight::new("test_topic", "test_name", "Test overview", "Test details"),
+      &client,
+    )?;
 
-    // Should not panic and should run successfully
-    get_insight("test_topic", "test_name", false)?;
+    let insight = get_insight("test_topic", "test_name")?;
+    assert_eq!(insight.overview, "Test overview");
+    assert_eq!(insight.details, "Test details");
+    output::print_insight(&insight, false);
 
     Ok(())
   }
@@ -93,10 +111,14 @@ mod command_tests {
     let _temp = setup_temp_insights_root("get_insight_overview");
     let client = embedding_client::with_service(Box::new(MockEmbeddingService));
 
-    add_insight_with_client("test_topic", "test_name", "Test overview", "Test details", &client)?;
+    add_insight_with_client(
+      &NewInsight::new("test_topic", "test_name", "Test overview", "Test details"),
+      &client,
+    )?;
 
     // Should not panic and should run successfully
-    get_insight("test_topic", "test_name", true)?;
+    let insight = get_insight("test_topic", "test_name")?;
+    output::print_insight(&insight, true);
 
     Ok(())
   }
@@ -106,7 +128,7 @@ mod command_tests {
   fn test_get_nonexistent_insight() -> Result<()> {
     let _temp = setup_temp_insights_root("get_nonexistent");
 
-    let result = get_insight("nonexistent_topic", "nonexistent_name", false);
+    let result = get_insight("nonexistent_topic", "nonexistent_name");
     assert!(result.is_err());
 
     Ok(())
@@ -119,15 +141,19 @@ mod command_tests {
     let client = embedding_client::with_service(Box::new(MockEmbeddingService));
 
     add_insight_with_client(
-      "special_topic",
-      "special_name",
-      "Overview with émojis 🚀",
-      "Details with special chars: @#$%^&*()",
+      &NewInsight::new(
+        "special_topic",
+        "special_name",
+        "Overview with émojis 🚀",
+        "Details with special chars: @#$%^&*()",
+      ),
       &client,
     )?;
 
-    get_insight("special_topic", "special_name", false)?;
-    get_insight("special_topic", "special_name", true)?;
+    let insight = get_insight("special_topic", "special_name")?;
+    assert_eq!(insight.overview, "Overview with émojis 🚀");
+    output::print_insight(&insight, false);
+    output::print_insight(&insight, true);
 
     Ok(())
   }
@@ -137,8 +163,9 @@ mod command_tests {
   fn test_list_insights_empty() -> Result<()> {
     let _temp = setup_temp_insights_root("list_empty");
 
-    list_insights(None, false)?;
-    list_insights(Some("nonexistent_topic"), true)?;
+    assert!(list_insights(None, &[])?.is_empty());
+    assert!(list_insights(Some("nonexistent_topic"), &[])?.is_empty());
+    output::print_insights(&[], Some("nonexistent_topic"), false);
 
     Ok(())
   }
@@ -149,14 +176,36 @@ mod command_tests {
     let _temp = setup_temp_insights_root("list_with_data");
     let client = embedding_client::with_service(Box::new(MockEmbeddingService));
 
-    add_insight_with_client("topic1", "insight1", "Overview 1", "Details 1", &client)?;
-    add_insight_with_client("topic1", "insight2", "Overview 2", "Details 2", &client)?;
-    add_insight_with_client("topic2", "insight3", "Overview 3", "Details 3", &client)?;
+    add_insight_with_client(
+      &NewInsight {
+        force: true,
+        ..NewInsight::new("topic1", "insight1", "Overview 1", "Details 1")
+      },
+      &client,
+    )?;
+    add_insight_with_client(
+      &NewInsight {
+        force: true,
+        ..NewInsight::new("topic1", "insight2", "Overview 2", "Details 2")
+      },
+      &client,
+    )?;
+    add_insight_with_client(
+      &NewInsight {
+        force: true,
+        ..NewInsight::new("topic2", "insight3", "Overview 3", "Details 3")
+      },
+      &client,
+    )?;
+
+    let all = list_insights(None, &[])?;
+    assert_eq!(all.len(), 3);
+    output::print_insights(&all, None, false);
+    output::print_insights(&all, None, true);
 
-    list_insights(None, false)?;
-    list_insights(None, true)?;
-    list_insights(Some("topic1"), false)?;
-    list_insights(Some("topic1"), true)?;
+    let topic1 = list_insights(Some("topic1"), &[])?;
+    assert_eq!(topic1.len(), 2);
+    output::print_insights(&topic1, Some("topic1"), true);
 
     Ok(())
   }
@@ -167,9 +216,12 @@ mod command_tests {
     let _temp = setup_temp_insights_root("list_nonexistent_topic");
     let client = embedding_client::with_service(Box::new(MockEmbeddingService));
 
-    add_insight_with_client("real_topic", "insight1", "Overview", "Details", &client)?;
+    add_insight_with_client(
+      &NewInsight::new("real_topic", "insight1", "Overview", "Details"),
+      &client,
+    )?;
 
-    list_insights(Some("nonexistent_topic"), false)?;
+    list_insights(Some("nonexistent_topic"), &[])?;
 
     Ok(())
   }
@@ -190,9 +242,27 @@ mod command_tests {
     let _temp = setup_temp_insights_root("list_topics_with_data");
     let client = embedding_client::with_service(Box::new(MockEmbeddingService));
 
-    add_insight_with_client("topic1", "insight1", "Overview 1", "Details 1", &client)?;
-    add_insight_with_client("topic2", "insight2", "Overview 2", "Details 2", &client)?;
-    add_insight_with_client("topic3", "insight3", "Overview 3", "Details 3", &client)?;
+    add_insight_with_client(
+      &NewInsight {
+        force: true,
+        ..NewInsight::new("topic1", "insight1", "Overview 1", "Details 1")
+      },
+      &client,
+    )?;
+    add_insight_with_client(
+      &NewInsight {
+        force: true,
+        ..NewInsight::new("topic2", "insight2", "Overview 2", "Details 2")
+      },
+      &client,
+    )?;
+    add_insight_with_client(
+      &NewInsight {
+        force: true,
+        ..NewInsight::new("topic3", "insight3", "Overview 3", "Details 3")
+      },
+      &client,
+    )?;
 
     list_topics()?;
 
@@ -206,15 +276,19 @@ mod command_tests {
     let client = embedding_client::with_service(Box::new(MockEmbeddingService));
 
     add_insight_with_client(
+      &NewInsight::new("test_topic", "test_name", "Original overview", "Original details"),
+      &client,
+    )?;
+
+    update_insight_with_client(
       "test_topic",
       "test_name",
-      "Original overview",
-      "Original details",
+      Some("Updated overview"),
+      None,
+      None,
       &client,
     )?;
 
-    update_insight_with_client("test_topic", "test_name", Some("Updated overview"), None, &client)?;
-
     let loaded = insight::load("test_topic", "test_name")?;
     assert_eq!(loaded.overview, "Updated overview");
     assert_eq!(loaded.details, "Original details");
@@ -229,15 +303,19 @@ mod command_tests {
     let client = embedding_client::with_service(Box::new(MockEmbeddingService));
 
     add_insight_with_client(
+      &NewInsight::new("test_topic", "test_name", "Original overview", "Original details"),
+      &client,
+    )?;
+
+    update_insight_with_client(
       "test_topic",
       "test_name",
-      "Original overview",
-      "Original details",
+      None,
+      Some("Updated details"),
+      None,
       &client,
     )?;
 
-    update_insight_with_client("test_topic", "test_name", None, Some("Updated details"), &client)?;
-
     let loaded = insight::load("test_topic", "test_name")?;
     assert_eq!(loaded.overview, "Original overview");
     assert_eq!(loaded.details, "Updated details");
@@ -252,10 +330,7 @@ mod command_tests {
     let client = embedding_client::with_service(Box::new(MockEmbeddingService));
 
     add_insight_with_client(
-      "test_topic",
-      "test_name",
-      "Original overview",
-      "Original details",
+      &NewInsight::new("test_topic", "test_name", "Original overview", "Original details"),
       &client,
     )?;
 
@@ -264,6 +339,7 @@ mod command_tests {
       "test_name",
       Some("Updated overview"),
       Some("Updated details"),
+      None,
       &client,
     )?;
 
@@ -281,14 +357,11 @@ mod command_tests {
     let client = embedding_client::with_service(Box::new(MockEmbeddingService));
 
     add_insight_with_client(
-      "test_topic",
-      "test_name",
-      "Original overview",
-      "Original details",
+      &NewInsight::new("test_topic", "test_name", "Original overview", "Original details"),
       &client,
     )?;
 
-    let result = update_insight_with_client("test_topic", "test_name", None, None, &client);
+    let result = update_insight_with_client("test_topic", "test_name", None, None, None, &client);
     assert!(result.is_err());
 
     Ok(())
@@ -305,6 +378,7 @@ mod command_tests {
       "nonexistent_name",
       Some("New overview"),
       None,
+      None,
       &client,
     );
     assert!(result.is_err());
@@ -318,7 +392,10 @@ mod command_tests {
     let _temp = setup_temp_insights_root("delete_force");
     let client = embedding_client::with_service(Box::new(MockEmbeddingService));
 
-    add_insight_with_client("test_topic", "test_name", "Overview", "Details", &client)?;
+    add_insight_with_client(
+      &NewInsight::new("test_topic", "test_name", "Overview", "Details"),
+      &client,
+    )?;
 
     delete_insight("test_topic", "test_name", true)?;
 
@@ -1,222 +1,547 @@
-// TODO: Fix daemon tests after refactoring - daemon types moved to binary
-/*
-use anyhow::Result;
-use insights::embedding_model::MockEmbeddingModel;
-
 #[cfg(test)]
+#[cfg(feature = "neural")]
 mod daemon_tests {
-  use super::*;
-
-  #[tokio::test]
-  async fn test_embedding_service_creation() {
-    let model = MockEmbeddingModel::new();
-    let _service = EmbeddingService::new(model);
-
-    // Service should be created successfully
-    // This tests the basic constructor
+  use anyhow::Result;
+  #[cfg(unix)]
+  use insights::daemon::{self, DaemonConnection, InsightsListener};
+  use insights::daemon::{DaemonRequest, DaemonResponse, EmbeddingService, RequestKind};
+  use insights::embedding_model::MockEmbeddingModel;
+  use insights::model_registry;
+  use insights::query_cache::QueryCache;
+  #[cfg(unix)]
+  use serial_test::serial;
+  #[cfg(unix)]
+  use std::collections::HashSet;
+  #[cfg(unix)]
+  use std::env;
+  #[cfg(unix)]
+  use std::fs;
+  #[cfg(unix)]
+  use std::os::unix::fs::PermissionsExt;
+  #[cfg(unix)]
+  use std::path::{Path, PathBuf};
+  #[cfg(unix)]
+  use std::time::Duration;
+  #[cfg(unix)]
+  use tempfile::TempDir;
+  #[cfg(unix)]
+  use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+  #[cfg(unix)]
+  use tokio::net::{UnixListener, UnixStream};
+
+  fn embed_request(texts: &[&str]) -> DaemonRequest {
+    DaemonRequest::new(RequestKind::Embed, texts.iter().map(|text| text.to_string()).collect())
   }
 
-  #[tokio::test]
-  async fn test_handle_request_successful() {
-    let model =
-      MockEmbeddingModel::new().with_embeddings(vec![vec![0.1, 0.2, 0.3], vec![0.4, 0.5, 0.6]]);
-
-    let mut service = EmbeddingService::new(model);
-
-    let request = EmbeddingRequest {
-      texts: vec!["test text".to_string(), "another text".to_string()],
-      id: "test-id-123".to_string(),
+  #[test]
+  fn test_handle_request_successful() {
+    let model = MockEmbeddingModel {
+      response_embeddings: vec![vec![0.1, 0.2, 0.3], vec![0.4, 0.5, 0.6]],
+      ..MockEmbeddingModel::new()
     };
+    let mut service = EmbeddingService::new(model, 300);
 
-    let response = service.handle_request(request).await;
+    let request = embed_request(&["test text", "another text"]);
+    let id = request.id.clone();
+    let response = service.handle_request(request);
 
-    assert_eq!(response.id, "test-id-123");
+    assert_eq!(response.id, id);
     assert!(response.error.is_none());
-    assert_eq!(response.embeddings.len(), 2);
-    assert_eq!(response.embeddings[0], vec![0.1, 0.2, 0.3]);
-    assert_eq!(response.embeddings[1], vec![0.4, 0.5, 0.6]);
+    assert_eq!(response.embeddings, vec![vec![0.1, 0.2, 0.3], vec![0.4, 0.5, 0.6]]);
+    assert!(response.status.is_none());
   }
 
-  #[tokio::test]
-  async fn test_handle_request_with_model_failure() {
-    let model = MockEmbeddingModel::new().with_failure_on("failing text".to_string());
-
-    let mut service = EmbeddingService::new(model);
-
-    let request =
-      EmbeddingRequest { texts: vec!["failing text".to_string()], id: "test-id-456".to_string() };
+  #[test]
+  fn test_handle_request_with_model_failure() {
+    let model = MockEmbeddingModel {
+      fail_on_texts: vec!["failing text".to_string()],
+      ..MockEmbeddingModel::new()
+    };
+    let mut service = EmbeddingService::new(model, 300);
 
-    let response = service.handle_request(request).await;
+    let response = service.handle_request(embed_request(&["failing text"]));
 
-    assert_eq!(response.id, "test-id-456");
-    assert!(response.error.is_some());
-    assert!(response.error.as_ref().unwrap().contains("Mock failure for text"));
+    assert!(response.error.as_deref().unwrap().contains("Mock failure for text"));
     assert!(response.embeddings.is_empty());
   }
 
-  #[tokio::test]
-  async fn test_handle_request_empty_texts() {
-    let model = MockEmbeddingModel::new();
-    let mut service = EmbeddingService::new(model);
-
-    let request = EmbeddingRequest { texts: vec![], id: "empty-test".to_string() };
+  #[test]
+  fn test_status_reports_requests_served() {
+    let mut service = EmbeddingService::new(MockEmbeddingModel::new(), 0);
+    service.handle_request(embed_request(&["one"]));
+    service.handle_request(embed_request(&["two", "three"]));
+
+    let response = service.handle_request(DaemonRequest::new(RequestKind::Status, vec![]));
+    let status = response.status.unwrap();
+    assert_eq!(status.pid, std::process::id());
+    assert_eq!(status.requests_served, 2);
+    assert_eq!(status.idle_timeout_secs, 0);
+    assert!(response.embeddings.is_empty());
 
-    let response = service.handle_request(request).await;
+    // Pings answer with the status without counting as served requests
+    let ping = service.handle_request(DaemonRequest::new(RequestKind::Ping, vec![]));
+    assert_eq!(ping.status.unwrap().requests_served, 2);
+  }
 
-    assert_eq!(response.id, "empty-test");
-    assert!(response.error.is_none());
-    assert!(response.embeddings.is_empty());
+  #[test]
+  fn test_requests_without_type_are_embedding_requests() -> Result<()> {
+    // The line format sent by clients that predate request types
+    let request: DaemonRequest =
+      serde_json::from_str(r#"{"texts": ["hello", "world"], "id": "legacy"}"#)?;
+    assert_eq!(request.kind, RequestKind::Embed);
+    assert_eq!(request.texts, vec!["hello", "world"]);
+
+    let request: DaemonRequest = serde_json::from_str(r#"{"type": "shutdown", "id": "stop"}"#)?;
+    assert_eq!(request.kind, RequestKind::Shutdown);
+    assert!(request.texts.is_empty());
+
+    let missing_id: Result<DaemonRequest, _> = serde_json::from_str(r#"{"texts": ["test"]}"#);
+    assert!(missing_id.is_err());
+    Ok(())
   }
 
-  #[tokio::test]
-  async fn test_handle_request_large_batch() {
-    let model = MockEmbeddingModel::new().with_embeddings(vec![vec![1.0, 2.0]; 50]); // 50 identical embeddings
+  #[test]
+  fn test_response_serialization() -> Result<()> {
+    let response = DaemonResponse {
+      id: "response-test".to_string(),
+      embeddings: vec![vec![0.1, 0.2]],
+      ..Default::default()
+    };
 
-    let mut service = EmbeddingService::new(model);
+    let json = serde_json::to_string(&response)?;
+    assert!(!json.contains("status"));
+    let deserialized: DaemonResponse = serde_json::from_str(&json)?;
+    assert_eq!(deserialized.embeddings, vec![vec![0.1, 0.2]]);
+    assert!(deserialized.error.is_none());
 
-    let texts: Vec<String> = (0..25).map(|i| format!("text {i}")).collect();
-    let request = EmbeddingRequest { texts, id: "batch-test".to_string() };
+    // Responses from daemons that predate model names and status
+    let legacy: DaemonResponse =
+      serde_json::from_str(r#"{"embeddings": [], "id": "old", "error": "Something went wrong"}"#)?;
+    assert_eq!(legacy.error.as_deref(), Some("Something went wrong"));
+    assert!(legacy.model.is_none() && legacy.status.is_none());
+    Ok(())
+  }
 
-    let response = service.handle_request(request).await;
+  #[test]
+  fn test_batches_are_split_back_into_requests() {
+    let model = MockEmbeddingModel {
+      response_embeddings: vec![vec![1.0], vec![2.0], vec![3.0]],
+      ..MockEmbeddingModel::new()
+    };
+    let batch_sizes = model.batch_sizes.clone();
+    let mut service = EmbeddingService::new(model, 300);
 
-    assert_eq!(response.id, "batch-test");
-    assert!(response.error.is_none());
-    assert_eq!(response.embeddings.len(), 25);
+    let responses = service.handle_batch(vec![embed_request(&["a", "b"]), embed_request(&["c"])]);
 
-    // All embeddings should be the same due to our mock setup
-    for embedding in response.embeddings {
-      assert_eq!(embedding, vec![1.0, 2.0]);
-    }
+    assert_eq!(responses[0].embeddings, vec![vec![1.0], vec![2.0]]);
+    assert_eq!(responses[1].embeddings, vec![vec![3.0]]);
+    assert_eq!(*batch_sizes.lock().unwrap(), vec![3]);
+    assert_eq!(service.status().requests_served, 2);
   }
 
   #[test]
-  fn test_embedding_request_serialization() -> Result<()> {
-    let request = EmbeddingRequest {
-      texts: vec!["hello".to_string(), "world".to_string()],
-      id: "serial-test".to_string(),
-    };
+  fn test_failing_text_only_fails_its_own_request() {
+    let model =
+      MockEmbeddingModel { fail_on_texts: vec!["bad".to_string()], ..MockEmbeddingModel::new() };
+    let mut service = EmbeddingService::new(model, 300);
+
+    let responses = service.handle_batch(vec![
+      embed_request(&["good"]),
+      embed_request(&["bad"]),
+      embed_request(&["fine", "also fine"]),
+    ]);
+
+    assert!(responses[0].error.is_none() && responses[0].embeddings.len() == 1);
+    assert!(responses[1].error.as_deref().unwrap().contains("bad"));
+    assert!(responses[2].error.is_none() && responses[2].embeddings.len() == 2);
+  }
 
-    let json = serde_json::to_string(&request)?;
-    let deserialized: EmbeddingRequest = serde_json::from_str(&json)?;
+  /// A daemon serving a mock model on a socket in a temporary directory
+  #[cfg(unix)]
+  struct TestDaemon {
+    _dir: TempDir,
+    socket: PathBuf,
+    server: tokio::task::JoinHandle<Result<()>>,
+  }
 
-    assert_eq!(deserialized.texts, vec!["hello", "world"]);
-    assert_eq!(deserialized.id, "serial-test");
+  #[cfg(unix)]
+  fn start_daemon(model: MockEmbeddingModel) -> Result<TestDaemon> {
+    let dir = TempDir::new()?;
+    let socket = dir.path().join("daemon.sock");
+    let listener = InsightsListener::Unix(UnixListener::bind(&socket)?);
+    let server = tokio::spawn(daemon::serve(listener, EmbeddingService::new(model, 0)));
+    Ok(TestDaemon { _dir: dir, socket, server })
+  }
 
+  #[cfg(unix)]
+  async fn run_client(socket: &Path, client: usize, requests: usize) -> Result<()> {
+    let mut connection = DaemonConnection::connect_at(socket).await?;
+    for i in 0..requests {
+      let texts = vec![format!("client {client} request {i}"); 1 + i % 3];
+      let request = DaemonRequest::new(RequestKind::Embed, texts);
+      let response = connection.send(&request).await?;
+      assert_eq!(response.id, request.id);
+      assert!(response.error.is_none());
+      assert_eq!(response.embeddings.len(), request.texts.len());
+    }
     Ok(())
   }
 
-  #[test]
-  fn test_embedding_response_serialization() -> Result<()> {
-    let response = EmbeddingResponse {
-      embeddings: vec![vec![0.1, 0.2], vec![0.3, 0.4]],
-      id: "response-test".to_string(),
-      error: None,
-    };
+  #[cfg(unix)]
+  #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+  async fn test_concurrent_clients_are_coalesced_into_batches() -> Result<()> {
+    const CLIENTS: usize = 16;
+    const REQUESTS: usize = 12;
 
-    let json = serde_json::to_string(&response)?;
-    let deserialized: EmbeddingResponse = serde_json::from_str(&json)?;
+    let model =
+      MockEmbeddingModel { latency: Duration::from_millis(10), ..MockEmbeddingModel::new() };
+    let batch_sizes = model.batch_sizes.clone();
+    let daemon = start_daemon(model)?;
+
+    let clients: Vec<_> = (0..CLIENTS)
+      .map(|client| {
+        let socket = daemon.socket.clone();
+        tokio::spawn(async move { run_client(&socket, client, REQUESTS).await })
+      })
+      .collect();
+    for client in clients {
+      client.await??;
+    }
 
-    assert_eq!(deserialized.embeddings, vec![vec![0.1, 0.2], vec![0.3, 0.4]]);
-    assert_eq!(deserialized.id, "response-test");
-    assert!(deserialized.error.is_none());
+    // Every text went through the model exactly once, in fewer calls than requests
+    let texts_per_client: usize = (0..REQUESTS).map(|i| 1 + i % 3).sum();
+    let batch_sizes = batch_sizes.lock().unwrap().clone();
+    assert_eq!(batch_sizes.iter().sum::<usize>(), CLIENTS * texts_per_client);
+    assert!(batch_sizes.len() < CLIENTS * REQUESTS, "no requests were batched: {batch_sizes:?}");
+
+    let mut connection = DaemonConnection::connect_at(&daemon.socket).await?;
+    let status = connection.status(RequestKind::Status).await?;
+    assert_eq!(status.requests_served, (CLIENTS * REQUESTS) as u64);
 
+    connection.status(RequestKind::Shutdown).await?;
+    tokio::time::timeout(Duration::from_secs(5), daemon.server).await???;
     Ok(())
   }
 
-  #[test]
-  fn test_embedding_response_serialization_with_error() -> Result<()> {
-    let response = EmbeddingResponse {
-      embeddings: vec![],
-      id: "error-test".to_string(),
-      error: Some("Something went wrong".to_string()),
-    };
+  #[cfg(unix)]
+  #[tokio::test]
+  async fn test_pipelined_requests_on_one_connection() -> Result<()> {
+    let model =
+      MockEmbeddingModel { latency: Duration::from_millis(5), ..MockEmbeddingModel::new() };
+    let daemon = start_daemon(model)?;
+    let stream = UnixStream::connect(&daemon.socket).await?;
+    let (reader, mut writer) = stream.into_split();
+
+    // Send every request before reading any response
+    let requests: Vec<DaemonRequest> = (0..8)
+      .map(|i| DaemonRequest::new(RequestKind::Embed, vec![format!("text {i}")]))
+      .chain([DaemonRequest::new(RequestKind::Ping, vec![])])
+      .collect();
+    for request in &requests {
+      writer.write_all(format!("{}\n", serde_json::to_string(request)?).as_bytes()).await?;
+    }
+    writer.write_all(b"not json\n").await?;
+    writer.shutdown().await?;
+
+    // Responses arrive as they complete and are matched up by id
+    let mut lines = BufReader::new(reader).lines();
+    let mut ids = HashSet::new();
+    let mut invalid = 0;
+    while let Some(line) = lines.next_line().await? {
+      let response: DaemonResponse = serde_json::from_str(&line)?;
+      match response.error {
+        Some(error) => {
+          assert!(error.contains("Invalid request"));
+          invalid += 1;
+        }
+        None => assert!(ids.insert(response.id)),
+      }
+    }
+    assert_eq!(ids, requests.iter().map(|request| request.id.clone()).collect());
+    assert_eq!(invalid, 1);
 
-    let json = serde_json::to_string(&response)?;
-    let deserialized: EmbeddingResponse = serde_json::from_str(&json)?;
+    daemon.server.abort();
+    Ok(())
+  }
 
-    assert!(deserialized.embeddings.is_empty());
-    assert_eq!(deserialized.id, "error-test");
-    assert_eq!(deserialized.error, Some("Something went wrong".to_string()));
+  /// Points the daemon's runtime directory at a temporary directory until dropped
+  #[cfg(unix)]
+  struct RuntimeDir {
+    dir: TempDir,
+  }
 
-    Ok(())
+  #[cfg(unix)]
+  impl Drop for RuntimeDir {
+    fn drop(&mut self) {
+      env::remove_var(daemon::RUNTIME_DIR_ENV);
+    }
   }
 
-  #[test]
-  fn test_embedding_request_invalid_json() {
-    let invalid_json = r#"{"texts": ["test"], "missing_id": true}"#;
-    let result: Result<EmbeddingRequest, _> = serde_json::from_str(invalid_json);
-    assert!(result.is_err());
+  #[cfg(unix)]
+  fn isolated_runtime_dir() -> RuntimeDir {
+    let runtime = RuntimeDir { dir: TempDir::new().unwrap() };
+    env::set_var(daemon::RUNTIME_DIR_ENV, runtime.dir.path().join("run"));
+    runtime
+  }
+
+  #[cfg(unix)]
+  fn mode(path: &Path) -> u32 {
+    fs::metadata(path).unwrap().permissions().mode() & 0o777
   }
 
+  #[cfg(unix)]
   #[tokio::test]
-  async fn test_service_handles_unicode_text() {
-    let model = MockEmbeddingModel::new().with_embeddings(vec![vec![0.7, 0.8, 0.9]]);
+  #[serial]
+  async fn test_lifecycle_in_isolated_runtime_dir() -> Result<()> {
+    let _runtime = isolated_runtime_dir();
+    assert!(daemon::status().await?.is_none());
+    assert!(daemon::stop().await?.is_none());
+
+    let listener = daemon::bind().await?;
+    let socket = daemon::socket_path()?;
+    assert_eq!(mode(&daemon::runtime_dir()?), 0o700);
+    assert_eq!(mode(&socket), 0o600);
+    let server =
+      tokio::spawn(daemon::serve(listener, EmbeddingService::new(MockEmbeddingModel::new(), 60)));
+
+    let status = daemon::status().await?.unwrap();
+    assert_eq!(status.pid, std::process::id());
+    assert_eq!(status.idle_timeout_secs, 60);
+
+    let stopped = daemon::stop().await?.unwrap();
+    assert_eq!(stopped.pid, status.pid);
+    tokio::time::timeout(Duration::from_secs(5), server).await???;
+    Ok(())
+  }
 
-    let mut service = EmbeddingService::new(model);
+  #[cfg(unix)]
+  #[tokio::test]
+  #[serial]
+  async fn test_only_one_daemon_holds_the_pid_lock() -> Result<()> {
+    let _runtime = isolated_runtime_dir();
 
-    let request = EmbeddingRequest {
-      texts: vec!["Hello 世界 🌍".to_string()],
-      id: "unicode-test".to_string(),
-    };
+    let lock = daemon::lock_pid_file()?.expect("the first daemon should get the lock");
+    assert_eq!(daemon::read_pid_file(), Some(std::process::id()));
+    assert!(daemon::lock_pid_file()?.is_none());
 
-    let response = service.handle_request(request).await;
+    // A daemon still loading its model doesn't answer, but its pid file stays
+    assert!(daemon::status().await?.is_none());
+    assert!(daemon::pid_file()?.exists());
 
-    assert_eq!(response.id, "unicode-test");
-    assert!(response.error.is_none());
-    assert_eq!(response.embeddings.len(), 1);
-    assert_eq!(response.embeddings[0], vec![0.7, 0.8, 0.9]);
+    drop(lock);
+    assert!(!daemon::pid_file()?.exists());
+    assert!(daemon::lock_pid_file()?.is_some());
+    Ok(())
   }
 
+  #[cfg(unix)]
   #[tokio::test]
-  async fn test_service_handles_very_long_text() {
-    let model = MockEmbeddingModel::new();
-    let mut service = EmbeddingService::new(model);
+  #[serial]
+  async fn test_exiting_daemon_leaves_a_successor_socket_alone() -> Result<()> {
+    let _runtime = isolated_runtime_dir();
+
+    let lock = daemon::lock_pid_file()?.unwrap();
+    let _listener = daemon::bind().await?;
+    fs::write(daemon::pid_file()?, "1")?;
 
-    // Create a very long text (more than typical token limits)
-    let long_text = "word ".repeat(1000);
+    drop(lock);
+    assert!(daemon::socket_path()?.exists());
+    Ok(())
+  }
 
-    let request = EmbeddingRequest { texts: vec![long_text], id: "long-text-test".to_string() };
+  #[cfg(unix)]
+  #[tokio::test]
+  #[serial]
+  async fn test_bind_keeps_a_socket_that_answers() -> Result<()> {
+    let _runtime = isolated_runtime_dir();
+    let listener = daemon::bind().await?;
+    let server =
+      tokio::spawn(daemon::serve(listener, EmbeddingService::new(MockEmbeddingModel::new(), 60)));
+
+    assert!(daemon::bind().await.is_err());
+    assert!(daemon::status().await?.is_some());
+
+    daemon::stop().await?;
+    tokio::time::timeout(Duration::from_secs(5), server).await???;
+    Ok(())
+  }
 
-    let response = service.handle_request(request).await;
+  #[cfg(unix)]
+  #[tokio::test]
+  #[serial]
+  async fn test_daemon_running_another_model_is_replaced() -> Result<()> {
+    let _runtime = isolated_runtime_dir();
+    let registry = TempDir::new()?;
+    let config = registry.path().join("models.yaml");
+    fs::write(
+      &config,
+      "default: other\nmodels:\n  other:\n    model: other.onnx\n    tokenizer: tokenizer.json\n    dimension: 8\n    max_tokens: 16\n",
+    )?;
+    env::set_var("INSIGHTS_MODELS_CONFIG", &config);
+
+    // The mock model reports no name, like a daemon running the built-in model
+    let listener = daemon::bind().await?;
+    let server =
+      tokio::spawn(daemon::serve(listener, EmbeddingService::new(MockEmbeddingModel::new(), 60)));
+
+    // The old daemon is stopped; the replacement can't load the uninstalled model
+    let error = daemon::connect_or_start().await.err();
+    env::remove_var("INSIGHTS_MODELS_CONFIG");
+    assert!(error.expect("the daemon should not start").to_string().contains("other"));
+    tokio::time::timeout(Duration::from_secs(5), server).await???;
+    Ok(())
+  }
 
-    assert_eq!(response.id, "long-text-test");
-    // Should either succeed or fail gracefully, but not panic
-    assert!(response.error.is_none() || response.error.is_some());
+  #[cfg(unix)]
+  #[test]
+  #[serial]
+  fn test_failed_request_does_not_stop_the_next_one() -> Result<()> {
+    use insights::embedding_client::{EmbeddingService as _, ProductionEmbeddingService};
+    use insights::insight::Insight;
+
+    let _runtime = isolated_runtime_dir();
+    let cache = TempDir::new()?;
+    let model = cache.path().join(model_registry::BUILTIN_MODEL).join("model.onnx");
+    fs::create_dir_all(model.parent().unwrap())?;
+    fs::write(&model, "")?;
+    env::set_var("INSIGHTS_MODEL_CACHE", cache.path());
+    env::set_var("INSIGHTS_MODELS_CONFIG", cache.path().join("models.yaml"));
+
+    let service = ProductionEmbeddingService::default();
+    let mut insight =
+      Insight::new("topic".into(), "name".into(), "Overview".into(), "Details".into());
+
+    // No daemon binary next to the test executable, so the first request fails
+    let failed = service.embed_insight(&mut insight);
+    assert!(failed.embedding.is_empty());
+    assert!(service.last_error().is_some());
+    assert!(service.unavailable().is_none());
+
+    // The daemon that comes up runs the active model, so it is used as it is
+    let version = model_registry::embedding_version(&model_registry::active_model()?)?;
+    let model = MockEmbeddingModel { version: Some(version), ..MockEmbeddingModel::new() };
+    let server = tokio::runtime::Runtime::new()?;
+    let listener = server.block_on(daemon::bind())?;
+    server.spawn(daemon::serve(listener, EmbeddingService::new(model, 60)));
+
+    let embedded = service.embed_insight(&mut insight);
+    env::remove_var("INSIGHTS_MODEL_CACHE");
+    env::remove_var("INSIGHTS_MODELS_CONFIG");
+    assert_eq!(embedded.embedding, vec![0.1, 0.2, 0.3]);
+    assert!(service.last_error().is_none());
+    Ok(())
   }
 
+  #[cfg(unix)]
   #[tokio::test]
-  async fn test_service_preserves_request_id() {
+  async fn test_shared_runtime_dir_is_refused() -> Result<()> {
+    let runtime = TempDir::new()?;
+    let dir = runtime.path().join("run");
+    fs::create_dir_all(&dir)?;
+    fs::set_permissions(&dir, fs::Permissions::from_mode(0o777))?;
+
+    let error = daemon::bind_at(&dir).await.err().expect("a shared directory should be refused");
+    assert!(error.to_string().contains("accessible to other users"));
+    assert_eq!(mode(&dir), 0o777);
+    assert!(!dir.join("embeddings.sock").exists());
+    Ok(())
+  }
+
+  #[cfg(unix)]
+  #[tokio::test]
+  async fn test_daemons_in_separate_runtime_dirs() -> Result<()> {
+    let runtime = TempDir::new()?;
+    let dir = runtime.path().join("run");
+    assert!(daemon::status_at(&dir).await?.is_none());
+
+    let listener = daemon::bind_at(&dir).await?;
+    assert_eq!(mode(&dir), 0o700);
+    let server =
+      tokio::spawn(daemon::serve(listener, EmbeddingService::new(MockEmbeddingModel::new(), 60)));
+
+    assert!(daemon::try_connect_at(&dir.join("embeddings.sock")).await?.is_some());
+    let status = daemon::status_at(&dir).await?.unwrap();
+    assert_eq!(status.pid, std::process::id());
+
+    let stopped = daemon::stop_at(&dir).await?.unwrap();
+    assert_eq!(stopped.pid, status.pid);
+    tokio::time::timeout(Duration::from_secs(5), server).await???;
+    Ok(())
+  }
+
+  #[test]
+  fn test_repeated_queries_skip_the_model() {
     let model = MockEmbeddingModel::new();
-    let mut service = EmbeddingService::new(model);
+    let batch_sizes = model.batch_sizes.clone();
+    let mut service = EmbeddingService::new(model, 300);
+    let query = || DaemonRequest::query(vec!["deploy tuesday".to_string()]);
+
+    let first = service.handle_request(query());
+    let second = service.handle_request(query());
+    assert_eq!(first.embeddings, second.embeddings);
+    assert_eq!(*batch_sizes.lock().unwrap(), vec![1]);
+
+    // Insight texts are not cached
+    service.handle_request(embed_request(&["deploy tuesday"]));
+    service.handle_request(embed_request(&["deploy tuesday"]));
+    assert_eq!(*batch_sizes.lock().unwrap(), vec![1, 1, 1]);
+
+    // A cached query in a batch leaves only the others for the model
+    let responses = service.handle_batch(vec![query(), embed_request(&["a", "b"])]);
+    assert_eq!(responses.len(), 2);
+    assert_eq!(responses[0].embeddings, first.embeddings);
+    assert_eq!(*batch_sizes.lock().unwrap(), vec![1, 1, 1, 2]);
+
+    let status = service.status();
+    assert_eq!(status.cached_queries, 1);
+    assert_eq!(status.cache_hits, 2);
+    assert_eq!(status.requests_served, 6);
+  }
 
-    let unique_id = "very-unique-id-12345";
-    let request = EmbeddingRequest { texts: vec!["test".to_string()], id: unique_id.to_string() };
+  #[test]
+  fn test_failed_queries_are_not_cached() {
+    let model =
+      MockEmbeddingModel { fail_on_texts: vec!["bad".to_string()], ..MockEmbeddingModel::new() };
+    let mut service = EmbeddingService::new(model, 300);
 
-    let response = service.handle_request(request).await;
-    assert_eq!(response.id, unique_id);
+    let response = service.handle_request(DaemonRequest::query(vec!["bad".to_string()]));
+    assert!(response.error.is_some());
+    assert_eq!(service.status().cached_queries, 0);
   }
 
+  #[cfg(unix)]
   #[tokio::test]
-  async fn test_mock_model_cycling_behavior() {
-    let model = MockEmbeddingModel::new().with_embeddings(vec![vec![1.0, 1.0], vec![2.0, 2.0]]);
-
-    let mut service = EmbeddingService::new(model);
-
-    let request = EmbeddingRequest {
-      texts: vec![
-        "text1".to_string(),
-        "text2".to_string(),
-        "text3".to_string(), // Should cycle back to first embedding
-      ],
-      id: "cycle-test".to_string(),
+  async fn test_query_cache_is_saved_for_the_next_daemon() -> Result<()> {
+    let dir = TempDir::new()?;
+    let path = dir.path().join("tiny").join("query-cache.json");
+    let model = MockEmbeddingModel {
+      response_embeddings: vec![vec![0.25, 0.75]],
+      ..MockEmbeddingModel::new()
     };
-
-    let response = service.handle_request(request).await;
-
-    assert_eq!(response.embeddings.len(), 3);
-    assert_eq!(response.embeddings[0], vec![1.0, 1.0]);
-    assert_eq!(response.embeddings[1], vec![2.0, 2.0]);
-    assert_eq!(response.embeddings[2], vec![1.0, 1.0]); // Cycled back
+    let batch_sizes = model.batch_sizes.clone();
+    let socket = dir.path().join("daemon.sock");
+    let listener = InsightsListener::Unix(UnixListener::bind(&socket)?);
+    let service = EmbeddingService::with_query_cache(
+      model,
+      0,
+      QueryCache::persistent(16, path.clone(), "tiny"),
+    );
+    let server = tokio::spawn(daemon::serve(listener, service));
+
+    let mut connection = DaemonConnection::connect_at(&socket).await?;
+    connection.send(&DaemonRequest::query(vec!["deploy".to_string()])).await?;
+    connection.send(&DaemonRequest::query(vec!["deploy".to_string()])).await?;
+    connection.status(RequestKind::Shutdown).await?;
+    tokio::time::timeout(Duration::from_secs(5), server).await???;
+    assert_eq!(*batch_sizes.lock().unwrap(), vec![1]);
+
+    // The next daemon for the same model starts with the saved queries
+    let mut reloaded = QueryCache::persistent(16, path.clone(), "tiny");
+    assert_eq!(reloaded.get("deploy"), Some(vec![0.25, 0.75]));
+
+    // Embeddings from another model are not reused
+    let mut other_model = QueryCache::persistent(16, path, "other");
+    assert!(other_model.get("deploy").is_none());
+    Ok(())
   }
 }
-*/
@@ -27,7 +27,7 @@ mod edge_case_tests {
     let client = embedding_client::with_service(Box::new(MockEmbeddingService));
 
     // Empty topic and name should be allowed (although unusual)
-    add_insight_with_client("", "", "", "", &client)?;
+    add_insight_with_client(&NewInsight::new("", "", "", ""), &client)?;
 
     // Verify it was stored and can be retrieved
     let loaded = insight::load("", "")?;
@@ -52,7 +52,10 @@ mod edge_case_tests {
     let long_overview = "c".repeat(10000);
     let long_details = "d".repeat(50000);
 
-    add_insight_with_client(&long_topic, &long_name, &long_overview, &long_details, &client)?;
+    add_insight_with_client(
+      &NewInsight::new(&long_topic, &long_name, &long_overview, &long_details),
+      &client,
+    )?;
 
     let loaded = insight::load(&long_topic, &long_name)?;
     assert_eq!(loaded.overview.len(), 10000);
@@ -73,10 +76,7 @@ mod edge_case_tests {
     let unicode_details = "Mixed content: 日本語 العربية Русский français 中文";
 
     add_insight_with_client(
-      unicode_topic,
-      unicode_name,
-      unicode_overview,
-      unicode_details,
+      &NewInsight::new(unicode_topic, unicode_name, unicode_overview, unicode_details),
       &client,
     )?;
 
@@ -105,7 +105,13 @@ mod edge_case_tests {
     ];
 
     for (topic, name) in special_cases {
-      add_insight_with_client(topic, name, "Test overview", "Test details", &client)?;
+      add_insight_with_client(
+        &NewInsight {
+          force: true,
+          ..NewInsight::new(topic, name, "Test overview", "Test details")
+        },
+        &client,
+      )?;
 
       let loaded = insight::load(topic, name)?;
       assert_eq!(loaded.topic, topic);
@@ -122,7 +128,10 @@ mod edge_case_tests {
     let client = embedding_client::with_service(Box::new(MockEmbeddingService));
 
     // Create a valid insight first
-    add_insight_with_client("yaml_test", "valid", "Valid overview", "Valid details", &client)?;
+    add_insight_with_client(
+      &NewInsight::new("yaml_test", "valid", "Valid overview", "Valid details"),
+      &client,
+    )?;
 
     // Verify it loads correctly
     let loaded = insight::load("yaml_test", "valid")?;
@@ -139,12 +148,21 @@ mod edge_case_tests {
     let client = embedding_client::with_service(Box::new(MockEmbeddingService));
 
     // Test multiple operations in sequence
-    add_insight_with_client("multi", "test1", "Overview 1", "Details 1", &client)?;
-    add_insight_with_client("multi", "test2", "Overview 2", "Details 2", &client)?;
-    add_insight_with_client("multi", "test3", "Overview 3", "Details 3", &client)?;
+    add_insight_with_client(
+      &NewInsight { force: true, ..NewInsight::new("multi", "test1", "Overview 1", "Details 1") },
+      &client,
+    )?;
+    add_insight_with_client(
+      &NewInsight { force: true, ..NewInsight::new("multi", "test2", "Overview 2", "Details 2") },
+      &client,
+    )?;
+    add_insight_with_client(
+      &NewInsight { force: true, ..NewInsight::new("multi", "test3", "Overview 3", "Details 3") },
+      &client,
+    )?;
 
     // Update one while others exist
-    update_insight_with_client("multi", "test2", Some("Updated overview"), None, &client)?;
+    update_insight_with_client("multi", "test2", Some("Updated overview"), None, None, &client)?;
 
     // Delete one while others exist
     delete_insight("multi", "test3", true)?;
@@ -170,7 +188,10 @@ mod edge_case_tests {
     let client = embedding_client::with_service(Box::new(MockEmbeddingService));
 
     // Test that deeply nested topics create proper directory structures
-    add_insight_with_client("new_topic", "new_insight", "New overview", "New details", &client)?;
+    add_insight_with_client(
+      &NewInsight::new("new_topic", "new_insight", "New overview", "New details"),
+      &client,
+    )?;
 
     let loaded = insight::load("new_topic", "new_insight")?;
     assert_eq!(loaded.topic, "new_topic");
@@ -185,10 +206,13 @@ mod edge_case_tests {
     let _temp = setup_temp_insights_root("no_changes");
     let client = embedding_client::with_service(Box::new(MockEmbeddingService));
 
-    add_insight_with_client("update_test", "unchanged", "Original", "Original details", &client)?;
+    add_insight_with_client(
+      &NewInsight::new("update_test", "unchanged", "Original", "Original details"),
+      &client,
+    )?;
 
     // Attempt update with no changes should fail
-    let result = update_insight_with_client("update_test", "unchanged", None, None, &client);
+    let result = update_insight_with_client("update_test", "unchanged", None, None, None, &client);
     assert!(result.is_err());
 
     Ok(())
@@ -201,10 +225,7 @@ mod edge_case_tests {
     let client = embedding_client::with_service(Box::new(MockEmbeddingService));
 
     add_insight_with_client(
-      "delete_test",
-      "protected",
-      "Protected",
-      "Should not be deleted",
+      &NewInsight::new("delete_test", "protected", "Protected", "Should not be deleted"),
       &client,
     )?;
 
@@ -229,7 +250,10 @@ mod edge_case_tests {
     let tricky_overview = "Overview with --- separators in content";
     let tricky_details = "Details with\n---\nseparators and\n---\nmore content";
 
-    add_insight_with_client("tricky", "separators", tricky_overview, tricky_details, &client)?;
+    add_insight_with_client(
+      &NewInsight::new("tricky", "separators", tricky_overview, tricky_details),
+      &client,
+    )?;
 
     let loaded = insight::load("tricky", "separators")?;
     assert_eq!(loaded.overview, tricky_overview);
@@ -248,7 +272,10 @@ mod edge_case_tests {
     let multiline_details =
       "Details line 1\n\nDetails line 3 (with blank line above)\n\n\nMultiple blank lines above";
 
-    add_insight_with_client("multiline", "test", multiline_overview, multiline_details, &client)?;
+    add_insight_with_client(
+      &NewInsight::new("multiline", "test", multiline_overview, multiline_details),
+      &client,
+    )?;
 
     let loaded = insight::load("multiline", "test")?;
     assert_eq!(loaded.overview, multiline_overview);
@@ -268,10 +295,7 @@ mod edge_case_tests {
     let whitespace_details = "\tDetails with tabs and spaces\n  ";
 
     add_insight_with_client(
-      "whitespace",
-      "test",
-      whitespace_overview,
-      whitespace_details,
+      &NewInsight::new("whitespace", "test", whitespace_overview, whitespace_details),
       &client,
     )?;
 
@@ -291,14 +315,14 @@ mod edge_case_tests {
 
     // Test that topic and name are case-normalized for cross-platform compatibility
     // Both of these should be treated as the same insight
-    add_insight_with_client("CaseSensitive", "TestName", "Overview", "Details", &client)?;
+    add_insight_with_client(
+      &NewInsight::new("CaseSensitive", "TestName", "Overview", "Details"),
+      &client,
+    )?;
 
     // This should fail because case is normalized, so it's the same insight
     let result = add_insight_with_client(
-      "casesensitive",
-      "testname",
-      "Different overview",
-      "Different details",
+      &NewInsight::new("casesensitive", "testname", "Different overview", "Different details"),
       &client,
     );
     assert!(result.is_err());
@@ -368,6 +392,7 @@ mod edge_case_tests {
       "Legacy-Name",
       Some("Updated legacy overview"),
       None,
+      None,
       &client,
     )?;
 
@@ -415,7 +440,7 @@ mod edge_case_tests {
     let client = embedding_client::with_service(Box::new(MockEmbeddingService));
 
     // Test purely numeric content
-    add_insight_with_client("123", "456", "789", "101112", &client)?;
+    add_insight_with_client(&NewInsight::new("123", "456", "789", "101112"), &client)?;
 
     let loaded = insight::load("123", "456")?;
     assert_eq!(loaded.topic, "123");
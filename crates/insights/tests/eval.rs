@@ -0,0 +1,100 @@
+#[cfg(test)]
+mod eval_tests {
+  use anyhow::Result;
+  use insights::commands;
+  use insights::eval::{self, EvalOptions, EvalReport, Mode};
+  use serial_test::serial;
+  use std::env;
+  use std::path::{Path, PathBuf};
+  use tempfile::TempDir;
+
+  fn fixtures() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/search-eval.yaml")
+  }
+
+  fn options(modes: Vec<Mode>) -> EvalOptions {
+    EvalOptions {
+      k: 3,
+      modes,
+      #[cfg(feature = "neural")]
+      hashing_embeddings: true,
+    }
+  }
+
+  fn mode(report: &EvalReport, mode: Mode) -> &eval::ModeReport {
+    report.modes.iter().find(|report| report.mode == mode).unwrap()
+  }
+
+  #[test]
+  #[serial]
+  fn test_exact_mode_is_scored_per_query() -> Result<()> {
+    let report = commands::evaluate_search(&fixtures(), &options(vec![Mode::Exact]))?;
+    assert_eq!(report.k, 3);
+    assert_eq!(report.modes.len(), 1);
+
+    let exact = mode(&report, Mode::Exact);
+    assert_eq!(exact.queries.len(), 4);
+    let deploy = &exact.queries[1];
+    assert_eq!(deploy.found[0], "ops/deploys");
+    assert_eq!(deploy.metrics.recall, 1.0);
+    assert_eq!(deploy.metrics.mrr, 1.0);
+
+    // Exact matching can't see that "caching" is about the cache
+    let caching = &exact.queries[0];
+    assert_eq!(caching.metrics.mrr, 0.0);
+    assert!(exact.metrics.mrr < 1.0 && exact.metrics.mrr > 0.0);
+    Ok(())
+  }
+
+  #[cfg(feature = "semantic")]
+  #[test]
+  #[serial]
+  fn test_every_mode_is_evaluated_by_default() -> Result<()> {
+    let report = commands::evaluate_search(&fixtures(), &options(Vec::new()))?;
+    assert_eq!(report.modes.len(), Mode::all().len());
+
+    let semantic = mode(&report, Mode::Semantic);
+    assert_eq!(semantic.queries[0].found[0], "perf/cache_invalidation");
+    assert!(semantic.metrics.mrr > mode(&report, Mode::Exact).metrics.mrr);
+
+    #[cfg(feature = "neural")]
+    {
+      let hybrid = mode(&report, Mode::Hybrid);
+      assert!(hybrid.fallback.is_none(), "{:?}", hybrid.fallback);
+      assert!(hybrid.metrics.ndcg > 0.5, "{:?}", hybrid.metrics);
+    }
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_scratch_store_leaves_the_real_store_alone() -> Result<()> {
+    let temp = TempDir::new()?;
+    env::set_var("INSIGHTS_ROOT", temp.path());
+
+    commands::evaluate_search(&fixtures(), &options(vec![Mode::Exact]))?;
+    assert_eq!(env::var_os("INSIGHTS_ROOT").as_deref(), Some(temp.path().as_os_str()));
+    assert!(commands::list_topics()?.is_empty());
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_invalid_fixtures_are_rejected() -> Result<()> {
+    let temp = TempDir::new()?;
+    let path = temp.path().join("fixtures.yaml");
+    std::fs::write(
+      &path,
+      "insights:\n  - {topic: a, name: b, overview: c}\nqueries:\n  - {query: c, relevant: [a/missing]}\n",
+    )?;
+
+    let error = commands::evaluate_search(&path, &options(vec![Mode::Exact])).unwrap_err();
+    assert!(error.to_string().contains("unknown insight a/missing"), "{error}");
+
+    std::fs::write(&path, "insights: []\nqueries:\n  - {query: c, relevant: [a/b]}\n")?;
+    let error = commands::evaluate_search(&path, &options(vec![Mode::Exact])).unwrap_err();
+    assert!(error.to_string().contains("no insights"), "{error}");
+    assert!(commands::evaluate_search(&temp.path().join("none.yaml"), &options(vec![])).is_err());
+    Ok(())
+  }
+}
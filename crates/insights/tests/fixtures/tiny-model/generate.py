@@ -0,0 +1,149 @@
+#!/usr/bin/env python3
+"""Regenerate the tiny word-level model used by tests/onnx_model.rs.
+
+The model is a single Gather over a fixed 4-dimensional embedding table, so
+pooled results can be worked out by hand. It is written with a minimal
+protobuf encoder, so nothing beyond the standard library is needed:
+
+    cd crates/insights/tests/fixtures/tiny-model && python3 generate.py
+"""
+
+import json
+import struct
+
+VOCAB = ["[PAD]", "[UNK]", "[CLS]", "[SEP]", "deploy", "rollback", "release", "tuesday", "database", "backup"]
+HIDDEN = 4
+
+# Each word gets a distinct direction; [PAD] is deliberately large so any
+# padding that leaks into mean pooling changes the result.
+TABLE = [
+    [9, 9, 9, 9],  # [PAD]
+    [0.5, 0.5, 0, 0],  # [UNK]
+    [0, 0, 0, 1],  # [CLS]
+    [0, 0, 1, 0],  # [SEP]
+    [1, 0, 0, 0],  # deploy
+    [0.9, 0.1, 0, 0],  # rollback
+    [0.8, 0.2, 0, 0],  # release
+    [0.7, 0, 0.3, 0],  # tuesday
+    [0, 1, 0, 0],  # database
+    [0, 0.9, 0.1, 0],  # backup
+]
+
+# ONNX tensor element types
+INT64 = 7
+FLOAT = 1
+
+
+def varint(n):
+    out = b""
+    while True:
+        byte = n & 0x7F
+        n >>= 7
+        if n:
+            out += bytes([byte | 0x80])
+        else:
+            return out + bytes([byte])
+
+
+def key(field, wire_type):
+    return varint((field << 3) | wire_type)
+
+
+def length_delimited(field, payload):
+    if isinstance(payload, str):
+        payload = payload.encode()
+    return key(field, 2) + varint(len(payload)) + payload
+
+
+def integer(field, n):
+    return key(field, 0) + varint(n)
+
+
+def dim(value=None, param=None):
+    return length_delimited(1, integer(1, value) if value is not None else length_delimited(2, param))
+
+
+def value_info(name, elem_type, dims):
+    tensor_type = integer(1, elem_type) + length_delimited(2, b"".join(dims))
+    return length_delimited(1, name) + length_delimited(2, length_delimited(1, tensor_type))
+
+
+def model():
+    raw = b"".join(struct.pack("<f", v) for row in TABLE for v in row)
+    table = (
+        integer(1, len(VOCAB))
+        + integer(1, HIDDEN)
+        + integer(2, FLOAT)
+        + length_delimited(8, "embeddings")
+        + length_delimited(9, raw)
+    )
+    node = (
+        length_delimited(1, "embeddings")
+        + length_delimited(1, "input_ids")
+        + length_delimited(2, "last_hidden_state")
+        + length_delimited(3, "lookup")
+        + length_delimited(4, "Gather")
+    )
+    tokens = [dim(param="batch"), dim(param="tokens")]
+    graph = (
+        length_delimited(1, node)
+        + length_delimited(2, "tiny-embedder")
+        + length_delimited(5, table)
+        + length_delimited(11, value_info("input_ids", INT64, tokens))
+        + length_delimited(12, value_info("last_hidden_state", FLOAT, tokens + [dim(value=HIDDEN)]))
+    )
+    # IR version 8, opset 13
+    opset = length_delimited(1, "") + integer(2, 13)
+    return integer(1, 8) + length_delimited(2, "insights-tests") + length_delimited(7, graph) + length_delimited(8, opset)
+
+
+def special(id, token):
+    return {
+        "id": id,
+        "content": token,
+        "single_word": False,
+        "lstrip": False,
+        "rstrip": False,
+        "normalized": False,
+        "special": True,
+    }
+
+
+def tokenizer():
+    cls = {"SpecialToken": {"id": "[CLS]", "type_id": 0}}
+    return {
+        "version": "1.0",
+        "truncation": None,
+        "padding": None,
+        "added_tokens": [special(i, token) for i, token in enumerate(VOCAB[:4])],
+        "normalizer": {"type": "Lowercase"},
+        "pre_tokenizer": {"type": "Whitespace"},
+        "post_processor": {
+            "type": "TemplateProcessing",
+            "single": [
+                cls,
+                {"Sequence": {"id": "A", "type_id": 0}},
+                {"SpecialToken": {"id": "[SEP]", "type_id": 0}},
+            ],
+            "pair": [
+                cls,
+                {"Sequence": {"id": "A", "type_id": 0}},
+                {"SpecialToken": {"id": "[SEP]", "type_id": 0}},
+                {"Sequence": {"id": "B", "type_id": 1}},
+                {"SpecialToken": {"id": "[SEP]", "type_id": 1}},
+            ],
+            "special_tokens": {
+                "[CLS]": {"id": "[CLS]", "ids": [2], "tokens": ["[CLS]"]},
+                "[SEP]": {"id": "[SEP]", "ids": [3], "tokens": ["[SEP]"]},
+            },
+        },
+        "decoder": None,
+        "model": {"type": "WordLevel", "vocab": {token: i for i, token in enumerate(VOCAB)}, "unk_token": "[UNK]"},
+    }
+
+
+if __name__ == "__main__":
+    with open("model.onnx", "wb") as file:
+        file.write(model())
+    with open("tokenizer.json", "w") as file:
+        json.dump(tokenizer(), file, indent=2)
@@ -0,0 +1,175 @@
+#[cfg(all(test, feature = "neural"))]
+mod history_tests {
+  use anyhow::Result;
+  use insights::commands;
+  use insights::embedding_client::{self, MockEmbeddingService};
+  use insights::history;
+  use insights::insight;
+  use serial_test::serial;
+  use std::env;
+  use tempfile::TempDir;
+
+  fn setup_temp_insights_root(_test_name: &str) -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    env::set_var("INSIGHTS_ROOT", temp_dir.path());
+    temp_dir
+  }
+
+  fn add(topic: &str, name: &str, overview: &str, details: &str) -> Result<()> {
+    let client = embedding_client::with_service(Box::new(MockEmbeddingService));
+    commands::add_insight_with_client(
+      &commands::NewInsight::new(topic, name, overview, details),
+      &client,
+    )?;
+    Ok(())
+  }
+
+  fn update(topic: &str, name: &str, overview: Option<&str>, details: Option<&str>) -> Result<()> {
+    let client = embedding_client::with_service(Box::new(MockEmbeddingService));
+    commands::update_insight_with_client(topic, name, overview, details, None, &client)?;
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_new_insight_has_no_history() -> Result<()> {
+    let _temp = setup_temp_insights_root("history_new");
+
+    add("topic", "fresh", "Overview", "Details")?;
+
+    assert!(commands::insight_history("topic", "fresh")?.is_empty());
+    assert!(commands::insight_history("topic", "missing").is_err());
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_update_keeps_previous_revisions() -> Result<()> {
+    let _temp = setup_temp_insights_root("history_update");
+
+    add("topic", "evolving", "First overview", "First details")?;
+    update("topic", "evolving", Some("Second overview"), None)?;
+    update("topic", "evolving", None, Some("Third details"))?;
+
+    let revisions = commands::insight_history("topic", "evolving")?;
+    assert_eq!(revisions.iter().map(|r| r.number).collect::<Vec<_>>(), vec![1, 2]);
+    assert_eq!(revisions[0].insight.overview, "First overview");
+    assert_eq!(revisions[0].insight.details, "First details");
+    assert_eq!(revisions[1].insight.overview, "Second overview");
+    assert_eq!(revisions[1].insight.details, "First details");
+    assert!(revisions[0].written().is_some());
+
+    let current = insight::load("topic", "evolving")?;
+    assert_eq!(current.overview, "Second overview");
+    assert_eq!(current.details, "Third details");
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_update_with_tags_records_one_revision() -> Result<()> {
+    let _temp = setup_temp_insights_root("history_update_tags");
+    let client = embedding_client::with_service(Box::new(MockEmbeddingService));
+
+    add("topic", "retagged", "First overview", "Details")?;
+    let tags = vec!["New".to_string()];
+    commands::update_insight_with_client(
+      "topic",
+      "retagged",
+      Some("Second overview"),
+      None,
+      Some(&tags),
+      &client,
+    )?;
+
+    let revisions = commands::insight_history("topic", "retagged")?;
+    assert_eq!(revisions.len(), 1);
+    assert_eq!(revisions[0].insight.overview, "First overview");
+    assert!(revisions[0].insight.tags.is_empty());
+
+    let current = insight::load("topic", "retagged")?;
+    assert_eq!(current.overview, "Second overview");
+    assert_eq!(current.tags, vec!["new"]);
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_history_survives_delete() -> Result<()> {
+    let _temp = setup_temp_insights_root("history_delete");
+
+    add("topic", "doomed", "Overview", "Details")?;
+    commands::delete_insight("topic", "doomed", true)?;
+
+    let revisions = history::list("topic", "doomed")?;
+    assert_eq!(revisions.len(), 1);
+    assert_eq!(revisions[0].insight.overview, "Overview");
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_diff_defaults_to_latest_revision_against_current() -> Result<()> {
+    let _temp = setup_temp_insights_root("history_diff");
+
+    add("topic", "diffed", "Overview", "Line one\nLine two")?;
+    update("topic", "diffed", None, Some("Line one\nLine 2"))?;
+
+    let diff = commands::diff_insight("topic", "diffed", None, None)?;
+    assert_eq!(diff.from, "topic/diffed@1");
+    assert_eq!(diff.to, "topic/diffed");
+    assert!(diff.unified.contains("-Line two"));
+    assert!(diff.unified.contains("+Line 2"));
+    assert!(!diff.unified.contains("-Line one"));
+
+    update("topic", "diffed", Some("New overview"), None)?;
+    let diff = commands::diff_insight("topic", "diffed", Some(1), Some(2))?;
+    assert!(diff.unified.contains("+Line 2"));
+    assert!(!diff.unified.contains("New overview"));
+
+    assert!(commands::diff_insight("topic", "diffed", Some(9), None).is_err());
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_diff_without_revisions_fails() -> Result<()> {
+    let _temp = setup_temp_insights_root("history_diff_empty");
+
+    add("topic", "untouched", "Overview", "Details")?;
+
+    assert!(commands::diff_insight("topic", "untouched", None, None).is_err());
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_revert_restores_revision_and_records_current() -> Result<()> {
+    let _temp = setup_temp_insights_root("history_revert");
+
+    add("topic", "reverted", "Original overview", "Original details")?;
+    let client = embedding_client::with_service(Box::new(MockEmbeddingService));
+    let tags = vec!["Keep".to_string()];
+    commands::update_insight_with_client("topic", "reverted", None, None, Some(&tags), &client)?;
+    update("topic", "reverted", Some("Broken overview"), Some("Broken details"))?;
+
+    let reverted = commands::revert_insight_with_client("topic", "reverted", 2, &client)?;
+    assert_eq!(reverted.overview, "Original overview");
+    assert_eq!(reverted.details, "Original details");
+    assert_eq!(reverted.tags, vec!["keep"]);
+    assert!(reverted.embedding.is_some());
+
+    let loaded = insight::load("topic", "reverted")?;
+    assert_eq!(loaded.overview, "Original overview");
+    assert_eq!(loaded.tags, vec!["keep"]);
+    assert!(loaded.created.is_some());
+
+    // The broken version is itself kept, so the revert can be undone
+    let revisions = commands::insight_history("topic", "reverted")?;
+    assert_eq!(revisions.len(), 3);
+    assert_eq!(revisions[2].insight.overview, "Broken overview");
+
+    assert!(commands::revert_insight_with_client("topic", "reverted", 7, &client).is_err());
+    Ok(())
+  }
+}
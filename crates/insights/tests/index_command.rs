@@ -9,9 +9,14 @@ mod index_command_tests {
   use super::*;
   use anyhow::Result;
   use insights::commands::*;
-  use insights::insight::{self};
+  use insights::embedding_client::{Embedding, EmbeddingService};
+  use insights::index;
+  use insights::insight::{self, EmbeddingStatus, Insight};
+  use insights::scope::Scope;
   use serial_test::serial;
+  use std::cell::RefCell;
   use std::env;
+  use std::rc::Rc;
   use tempfile::TempDir;
 
   fn setup_temp_insights_root(_test_name: &str) -> TempDir {
@@ -27,7 +32,7 @@ mod index_command_tests {
     let client = embedding_client::with_service(Box::new(MockEmbeddingService));
 
     // Should handle empty database gracefully
-    index_insights_with_client(false, &client)?;
+    index_insights_with_client(false, DEFAULT_INDEX_BATCH_SIZE, &client, |_, _| {})?;
 
     Ok(())
   }
@@ -39,12 +44,30 @@ mod index_command_tests {
     let client = embedding_client::with_service(Box::new(MockEmbeddingService));
 
     // Create some insights first
-    add_insight_with_client("topic1", "insight1", "Overview 1", "Details 1", &client)?;
-    add_insight_with_client("topic1", "insight2", "Overview 2", "Details 2", &client)?;
-    add_insight_with_client("topic2", "insight3", "Overview 3", "Details 3", &client)?;
+    add_insight_with_client(
+      &NewInsight {
+        force: true,
+        ..NewInsight::new("topic1", "insight1", "Overview 1", "Details 1")
+      },
+      &client,
+    )?;
+    add_insight_with_client(
+      &NewInsight {
+        force: true,
+        ..NewInsight::new("topic1", "insight2", "Overview 2", "Details 2")
+      },
+      &client,
+    )?;
+    add_insight_with_client(
+      &NewInsight {
+        force: true,
+        ..NewInsight::new("topic2", "insight3", "Overview 3", "Details 3")
+      },
+      &client,
+    )?;
 
     // Force recompute all embeddings
-    index_insights_with_client(true, &client)?;
+    index_insights_with_client(true, DEFAULT_INDEX_BATCH_SIZE, &client, |_, _| {})?;
 
     Ok(())
   }
@@ -56,11 +79,23 @@ mod index_command_tests {
     let client = embedding_client::with_service(Box::new(MockEmbeddingService));
 
     // Create insights (they'll have embeddings from MockEmbeddingService)
-    add_insight_with_client("topic1", "insight1", "Overview 1", "Details 1", &client)?;
-    add_insight_with_client("topic2", "insight2", "Overview 2", "Details 2", &client)?;
+    add_insight_with_client(
+      &NewInsight {
+        force: true,
+        ..NewInsight::new("topic1", "insight1", "Overview 1", "Details 1")
+      },
+      &client,
+    )?;
+    add_insight_with_client(
+      &NewInsight {
+        force: true,
+        ..NewInsight::new("topic2", "insight2", "Overview 2", "Details 2")
+      },
+      &client,
+    )?;
 
     // Index only missing embeddings
-    index_insights_with_client(false, &client)?;
+    index_insights_with_client(false, DEFAULT_INDEX_BATCH_SIZE, &client, |_, _| {})?;
 
     Ok(())
   }
@@ -73,25 +108,43 @@ mod index_command_tests {
 
     // Create insights across multiple topics
     add_insight_with_client(
-      "ai",
-      "neural_networks",
-      "About neural networks",
-      "Deep learning details",
+      &NewInsight {
+        force: true,
+        ..NewInsight::new("ai", "neural_networks", "About neural networks", "Deep learning details")
+      },
+      &client,
+    )?;
+    add_insight_with_client(
+      &NewInsight {
+        force: true,
+        ..NewInsight::new("ai", "machine_learning", "About ML", "ML algorithms")
+      },
       &client,
     )?;
-    add_insight_with_client("ai", "machine_learning", "About ML", "ML algorithms", &client)?;
     add_insight_with_client(
-      "databases",
-      "postgresql",
-      "About PostgreSQL",
-      "Database management",
+      &NewInsight {
+        force: true,
+        ..NewInsight::new("databases", "postgresql", "About PostgreSQL", "Database management")
+      },
+      &client,
+    )?;
+    add_insight_with_client(
+      &NewInsight {
+        force: true,
+        ..NewInsight::new("databases", "redis", "About Redis", "In-memory store")
+      },
+      &client,
+    )?;
+    add_insight_with_client(
+      &NewInsight {
+        force: true,
+        ..NewInsight::new("rust", "ownership", "About ownership", "Memory management")
+      },
       &client,
     )?;
-    add_insight_with_client("databases", "redis", "About Redis", "In-memory store", &client)?;
-    add_insight_with_client("rust", "ownership", "About ownership", "Memory management", &client)?;
 
     // Index all insights
-    index_insights_with_client(false, &client)?;
+    index_insights_with_client(false, DEFAULT_INDEX_BATCH_SIZE, &client, |_, _| {})?;
 
     Ok(())
   }
@@ -103,7 +156,10 @@ mod index_command_tests {
     let client = embedding_client::with_service(Box::new(MockEmbeddingService));
 
     // Create an insight
-    add_insight_with_client("preserve", "test", "Original overview", "Original details", &client)?;
+    add_insight_with_client(
+      &NewInsight::new("preserve", "test", "Original overview", "Original details"),
+      &client,
+    )?;
 
     // Verify content before indexing
     let before = insight::load("preserve", "test")?;
@@ -111,7 +167,7 @@ mod index_command_tests {
     assert_eq!(before.details, "Original details");
 
     // Run indexing
-    index_insights_with_client(true, &client)?;
+    index_insights_with_client(true, DEFAULT_INDEX_BATCH_SIZE, &client, |_, _| {})?;
 
     // Verify content is preserved after indexing
     let after = insight::load("preserve", "test")?;
@@ -128,7 +184,10 @@ mod index_command_tests {
     let client = embedding_client::with_service(Box::new(MockEmbeddingService));
 
     // Create an insight
-    add_insight_with_client("metadata", "test", "Test overview", "Test details", &client)?;
+    add_insight_with_client(
+      &NewInsight::new("metadata", "test", "Test overview", "Test details"),
+      &client,
+    )?;
 
     // Load and verify it has embedding data (from MockEmbeddingService)
     let insight = insight::load("metadata", "test")?;
@@ -136,7 +195,7 @@ mod index_command_tests {
     assert!(insight.embedding_version.is_some());
 
     // Force reindex
-    index_insights_with_client(true, &client)?;
+    index_insights_with_client(true, DEFAULT_INDEX_BATCH_SIZE, &client, |_, _| {})?;
 
     // Verify it still has embedding metadata
     let reindexed = insight::load("metadata", "test")?;
@@ -154,15 +213,17 @@ mod index_command_tests {
 
     // Create insights with unicode content
     add_insight_with_client(
-      "unicode",
-      "test",
-      "Overview with émojis 🚀 and unicode: ñáéíóú",
-      "Details with Chinese: 你好世界, Arabic: مرحبا, Russian: Привет",
+      &NewInsight::new(
+        "unicode",
+        "test",
+        "Overview with émojis 🚀 and unicode: ñáéíóú",
+        "Details with Chinese: 你好世界, Arabic: مرحبا, Russian: Привет",
+      ),
       &client,
     )?;
 
     // Index should handle unicode content without issues
-    index_insights_with_client(false, &client)?;
+    index_insights_with_client(false, DEFAULT_INDEX_BATCH_SIZE, &client, |_, _| {})?;
 
     // Verify the insight still exists and has correct content
     let insight = insight::load("unicode", "test")?;
@@ -184,15 +245,12 @@ mod index_command_tests {
 
     // Create insight with specific content
     add_insight_with_client(
-      "content",
-      "preservation",
-      original_overview,
-      original_details,
+      &NewInsight::new("content", "preservation", original_overview, original_details),
       &client,
     )?;
 
     // Index the insights
-    index_insights_with_client(true, &client)?;
+    index_insights_with_client(true, DEFAULT_INDEX_BATCH_SIZE, &client, |_, _| {})?;
 
     // Verify exact content preservation
     let preserved = insight::load("content", "preservation")?;
@@ -202,6 +260,268 @@ mod index_command_tests {
     Ok(())
   }
 
+  /// Records the size of every batch it is asked to embed
+  struct BatchRecordingService {
+    batches: Rc<RefCell<Vec<usize>>>,
+  }
+
+  impl EmbeddingService for BatchRecordingService {
+    fn embed_insight(&self, insight: &mut Insight) -> Embedding {
+      MockEmbeddingService.embed_insight(insight)
+    }
+
+    fn embed_insights(&self, insights: &[Insight]) -> Vec<Embedding> {
+      self.batches.borrow_mut().push(insights.len());
+      insights
+        .iter()
+        .map(|insight| MockEmbeddingService.embed_insight(&mut insight.clone()))
+        .collect()
+    }
+  }
+
+  #[test]
+  #[serial]
+  fn test_index_insights_embeds_in_batches() -> Result<()> {
+    let _temp = setup_temp_insights_root("index_batches");
+    let client = embedding_client::with_service(Box::new(MockEmbeddingService));
+    for i in 0..5 {
+      add_insight_with_client(
+        &NewInsight {
+          force: true,
+          ..NewInsight::new("batched", &format!("insight_{i}"), "Overview", "Details")
+        },
+        &client,
+      )?;
+    }
+
+    let batches = Rc::new(RefCell::new(Vec::new()));
+    let recording =
+      embedding_client::with_service(Box::new(BatchRecordingService { batches: batches.clone() }));
+    let mut progress = Vec::new();
+    let summary =
+      index_insights_with_client(true, 2, &recording, |done, total| progress.push((done, total)))?;
+
+    assert_eq!(*batches.borrow(), vec![2, 2, 1]);
+    assert_eq!(progress, vec![(0, 5), (2, 5), (4, 5), (5, 5)]);
+    assert_eq!(summary.updated.len(), 5);
+    assert_eq!(summary.indexed, 5);
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_index_insights_skips_embedded_insights_in_batches() -> Result<()> {
+    let _temp = setup_temp_insights_root("index_batches_missing");
+    let client = embedding_client::with_service(Box::new(MockEmbeddingService));
+    add_insight_with_client(
+      &NewInsight::new("batched", "embedded", "Overview", "Details"),
+      &client,
+    )?;
+    insight::save(&Insight::new(
+      "batched".to_string(),
+      "pending".to_string(),
+      "Overview".to_string(),
+      "Details".to_string(),
+    ))?;
+
+    let batches = Rc::new(RefCell::new(Vec::new()));
+    let recording =
+      embedding_client::with_service(Box::new(BatchRecordingService { batches: batches.clone() }));
+    let summary = index_insights_with_client(false, 10, &recording, |_, _| {})?;
+
+    assert_eq!(*batches.borrow(), vec![1]);
+    assert_eq!(summary.processed, 2);
+    assert_eq!(summary.updated[0].name, "pending");
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_index_reembeds_hand_edited_insights() -> Result<()> {
+    let _temp = setup_temp_insights_root("index_hand_edited");
+    let client = embedding_client::with_service(Box::new(MockEmbeddingService));
+    add_insight_with_client(
+      &NewInsight { force: true, ..NewInsight::new("drift", "edited", "Overview", "Details") },
+      &client,
+    )?;
+    add_insight_with_client(
+      &NewInsight { force: true, ..NewInsight::new("drift", "untouched", "Overview", "Details") },
+      &client,
+    )?;
+    assert!(check_embeddings_with_client(&client)?.is_empty());
+
+    // Edit the file directly, as an editor would, leaving the old vector in place
+    let edited = insight::load("drift", "edited")?;
+    let path = insight::file_path(&edited)?;
+    let content = std::fs::read_to_string(&path)?;
+    std::fs::write(&path, content.replace("Details", "Rewritten details"))?;
+
+    let drift = check_embeddings_with_client(&client)?;
+    assert_eq!(drift.len(), 1);
+    assert_eq!(drift[0].insight.name, "edited");
+    assert_eq!(drift[0].status, EmbeddingStatus::Stale);
+    // Checking never writes
+    assert!(std::fs::read_to_string(&path)?.contains("Rewritten details"));
+    assert_eq!(check_embeddings_with_client(&client)?.len(), 1);
+
+    let summary = index_insights_with_client(false, DEFAULT_INDEX_BATCH_SIZE, &client, |_, _| {})?;
+    assert_eq!(summary.updated.len(), 1);
+    assert_eq!(summary.updated[0].name, "edited");
+    assert!(check_embeddings_with_client(&client)?.is_empty());
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_embedding_status_detects_model_and_legacy_embeddings() -> Result<()> {
+    let _temp = setup_temp_insights_root("index_model_mismatch");
+    let client = embedding_client::with_service(Box::new(MockEmbeddingService));
+
+    let mut old_model =
+      Insight::new("drift".to_string(), "old".to_string(), "O".to_string(), "D".to_string());
+    insight::set_embedding(
+      &mut old_model,
+      Embedding {
+        version: "old-model".to_string(),
+        created_at: chrono::Utc::now(),
+        embedding: vec![0.2; 4],
+      },
+    );
+    insight::save(&old_model)?;
+    assert_eq!(
+      insight::embedding_status(&old_model, Some("test-mock")),
+      EmbeddingStatus::ModelMismatch
+    );
+    assert_eq!(insight::embedding_status(&old_model, None), EmbeddingStatus::Current);
+
+    // Embedded before content hashes were recorded
+    let mut legacy =
+      Insight::new("drift".to_string(), "legacy".to_string(), "O".to_string(), "D".to_string());
+    legacy.embedding_version = Some("test-mock".to_string());
+    legacy.embedding = Some(vec![0.1; 4]);
+    insight::save(&legacy)?;
+
+    let mut statuses: Vec<_> = check_embeddings_with_client(&client)?
+      .into_iter()
+      .map(|drift| (drift.insight.name, drift.status))
+      .collect();
+    statuses.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(
+      statuses,
+      vec![
+        ("legacy".to_string(), EmbeddingStatus::Stale),
+        ("old".to_string(), EmbeddingStatus::ModelMismatch),
+      ]
+    );
+
+    index_insights_with_client(false, DEFAULT_INDEX_BATCH_SIZE, &client, |_, _| {})?;
+    assert!(check_embeddings_with_client(&client)?.is_empty());
+    assert_eq!(insight::load("drift", "old")?.embedding_version.as_deref(), Some("test-mock"));
+    Ok(())
+  }
+
+  /// A service whose model is not installed
+  struct UninstalledEmbeddingService;
+
+  impl EmbeddingService for UninstalledEmbeddingService {
+    fn embed_insight(&self, _insight: &mut Insight) -> Embedding {
+      Embedding {
+        version: "placeholder".to_string(),
+        created_at: chrono::Utc::now(),
+        embedding: vec![],
+      }
+    }
+
+    fn unavailable(&self) -> Option<String> {
+      Some("Embedding model 'test' is not installed".to_string())
+    }
+  }
+
+  #[test]
+  #[serial]
+  fn test_index_fails_without_model() -> Result<()> {
+    let _temp = setup_temp_insights_root("index_unavailable");
+    insight::save(&Insight::new(
+      "ops".to_string(),
+      "deploys".to_string(),
+      "Overview".to_string(),
+      "Details".to_string(),
+    ))?;
+    let client = embedding_client::with_service(Box::new(UninstalledEmbeddingService));
+
+    let error =
+      index_insights_with_client(true, DEFAULT_INDEX_BATCH_SIZE, &client, |_, _| {}).unwrap_err();
+    assert!(error.to_string().contains("not installed"));
+    assert!(insight::load("ops", "deploys")?.embedding.is_none());
+    Ok(())
+  }
+
+  /// A service that returns placeholders for insights whose name starts with "broken"
+  struct FlakyEmbeddingService;
+
+  impl EmbeddingService for FlakyEmbeddingService {
+    fn embed_insight(&self, insight: &mut Insight) -> Embedding {
+      if insight.name.starts_with("broken") {
+        UninstalledEmbeddingService.embed_insight(insight)
+      } else {
+        MockEmbeddingService.embed_insight(insight)
+      }
+    }
+  }
+
+  fn save_unembedded(name: &str) -> Result<()> {
+    insight::save(&Insight::new(
+      "flaky".to_string(),
+      name.to_string(),
+      "Overview".to_string(),
+      "Details".to_string(),
+    ))
+  }
+
+  #[test]
+  #[serial]
+  fn test_index_reports_insights_without_embeddings() -> Result<()> {
+    let _temp = setup_temp_insights_root("index_placeholders");
+    save_unembedded("broken")?;
+    save_unembedded("fine")?;
+    let client = embedding_client::with_service(Box::new(FlakyEmbeddingService));
+
+    let summary = index_insights_with_client(false, DEFAULT_INDEX_BATCH_SIZE, &client, |_, _| {})?;
+    assert_eq!(summary.updated.len(), 1);
+    assert_eq!(summary.updated[0].name, "fine");
+    assert_eq!(summary.failed.len(), 1);
+    assert_eq!(summary.failed[0].name, "broken");
+    assert_eq!(summary.indexed, 1);
+
+    let broken = insight::load("flaky", "broken")?;
+    assert!(broken.embedding.is_none());
+    assert!(broken.embedding_version.is_none());
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_failed_batch_does_not_stop_the_run() -> Result<()> {
+    let _temp = setup_temp_insights_root("index_batch_failed");
+    save_unembedded("broken-1")?;
+    save_unembedded("broken-2")?;
+    save_unembedded("fine")?;
+    let client = embedding_client::with_service(Box::new(FlakyEmbeddingService));
+
+    // The index is only written once every batch is done
+    let mut indexed_during_run = 0;
+    let summary = index_insights_with_client(false, 2, &client, |_, _| {
+      indexed_during_run += index::load(Scope::Global).unwrap().entries.len();
+    })?;
+    assert_eq!(indexed_during_run, 0);
+    let failed: Vec<&str> = summary.failed.iter().map(|insight| insight.name.as_str()).collect();
+    assert_eq!(failed, vec!["broken-1", "broken-2"]);
+    assert_eq!(summary.updated.len(), 1);
+    assert_eq!(summary.indexed, 1);
+    assert!(index::get(&index::load(Scope::Global)?, "flaky", "fine").is_some());
+    Ok(())
+  }
+
   // Helper function for creating test insights directory (unused in simplified tests)
   #[allow(dead_code)]
   fn create_test_insights_dir() -> Result<()> {
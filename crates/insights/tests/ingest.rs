@@ -0,0 +1,253 @@
+#[cfg(test)]
+mod ingest_tests {
+  use anyhow::Result;
+  use insights::history;
+  use insights::ingest::{self, IngestAction, IngestResult};
+  use insights::insight::{self, Insight};
+  use insights::scope::Scope;
+  use serial_test::serial;
+  use std::env;
+  use std::fs;
+  use std::path::Path;
+  use tempfile::TempDir;
+
+  fn setup_temp_insights_root(_test_name: &str) -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    env::set_var("INSIGHTS_ROOT", temp_dir.path());
+    temp_dir
+  }
+
+  fn write_note(vault: &Path, relative: &str, content: &str) {
+    let path = vault.join(relative);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+  }
+
+  fn actions(results: &[IngestResult]) -> Vec<(String, IngestAction)> {
+    results
+      .iter()
+      .map(|result| (format!("{}/{}", result.topic, result.name), result.action))
+      .collect()
+  }
+
+  /// A note long enough to be split, with the given section bodies
+  fn long_note(sections: &[(&str, &str)]) -> String {
+    let mut note = "# Runbook\n\nHow we operate the service.\n".to_string();
+    for (heading, body) in sections {
+      note.push_str(&format!("\n## {heading}\n\n{body}\n\n{}\n", "Filler text. ".repeat(120)));
+    }
+    note
+  }
+
+  #[test]
+  #[serial]
+  fn test_folders_become_topics_and_files_become_insights() -> Result<()> {
+    let _temp = setup_temp_insights_root("ingest_mapping");
+    let vault_dir = TempDir::new()?;
+    let vault = vault_dir.path().join("My Notes");
+    write_note(
+      vault.as_path(),
+      "Dev Ops/Deploy Process.md",
+      "---\ntags: [release, Ops]\n---\n# Deploying\n\nWe deploy on Tuesdays\nafter the freeze.\n\nMore details here.",
+    );
+    write_note(vault.as_path(), "Dev Ops/nested/alerts.md", "Page the on-call engineer.");
+    write_note(vault.as_path(), "inbox.md", "Loose note at the top.");
+    write_note(vault.as_path(), ".obsidian/workspace.md", "Editor state");
+    write_note(vault.as_path(), "Dev Ops/diagram.png", "not markdown");
+
+    let results = ingest::ingest(vault.as_path(), Scope::Global)?;
+    assert_eq!(
+      actions(&results),
+      vec![
+        ("dev-ops/deploy-process".to_string(), IngestAction::Added),
+        ("dev-ops-nested/alerts".to_string(), IngestAction::Added),
+        ("my-notes/inbox".to_string(), IngestAction::Added),
+      ]
+    );
+
+    let deploy = insight::load("dev-ops", "deploy-process")?;
+    assert_eq!(deploy.overview, "We deploy on Tuesdays after the freeze.");
+    assert_eq!(deploy.details, "We deploy on Tuesdays\nafter the freeze.\n\nMore details here.");
+    assert_eq!(deploy.tags, vec!["ops", "release"]);
+    let origin = vault.as_path().canonicalize()?.join("Dev Ops/Deploy Process.md");
+    assert_eq!(deploy.origin, Some(origin.to_string_lossy().to_string()));
+    assert!(deploy.created.is_some());
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_tags_and_subheadings_under_the_title_are_kept() -> Result<()> {
+    let _temp = setup_temp_insights_root("ingest_title_lines");
+    let vault = TempDir::new()?;
+    write_note(
+      vault.path(),
+      "ops/restore.md",
+      "# Restoring
+#ops #backups
+## Steps
+Stop the writers first.
+",
+    );
+
+    ingest::ingest(vault.path(), Scope::Global)?;
+    let restore = insight::load("ops", "restore")?;
+    assert_eq!(restore.overview, "Stop the writers first.");
+    assert_eq!(restore.details, "#ops #backups\n## Steps\nStop the writers first.");
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_long_documents_are_split_by_heading() -> Result<()> {
+    let _temp = setup_temp_insights_root("ingest_split");
+    let vault = TempDir::new()?;
+    let note = long_note(&[
+      ("Rollback", "Revert the release tag."),
+      ("Scaling", "Add replicas before events."),
+      ("Scaling", "A second scaling section."),
+    ]);
+    write_note(vault.path(), "ops/runbook.md", &note);
+
+    let results = ingest::ingest(vault.path(), Scope::Global)?;
+    assert_eq!(
+      actions(&results),
+      vec![
+        ("ops/runbook".to_string(), IngestAction::Added),
+        ("ops/runbook-rollback".to_string(), IngestAction::Added),
+        ("ops/runbook-scaling".to_string(), IngestAction::Added),
+        ("ops/runbook-scaling-2".to_string(), IngestAction::Added),
+      ]
+    );
+
+    assert_eq!(insight::load("ops", "runbook")?.overview, "How we operate the service.");
+    let rollback = insight::load("ops", "runbook-rollback")?;
+    assert_eq!(rollback.overview, "Revert the release tag.");
+    assert!(rollback.details.starts_with("Revert the release tag."));
+    assert!(!rollback.details.contains("Add replicas"));
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_unchanged_files_are_skipped() -> Result<()> {
+    let _temp = setup_temp_insights_root("ingest_unchanged");
+    let vault = TempDir::new()?;
+    write_note(vault.path(), "ops/deploys.md", "Deploy on Tuesdays.");
+    write_note(vault.path(), "ops/alerts.md", "Page the on-call engineer.");
+
+    ingest::ingest(vault.path(), Scope::Global)?;
+    write_note(vault.path(), "ops/alerts.md", "Page the secondary on-call engineer.");
+    let results = ingest::ingest(vault.path(), Scope::Global)?;
+
+    assert_eq!(
+      actions(&results),
+      vec![
+        ("ops/alerts".to_string(), IngestAction::Updated),
+        ("ops/deploys".to_string(), IngestAction::Unchanged),
+      ]
+    );
+    assert_eq!(insight::load("ops", "alerts")?.overview, "Page the secondary on-call engineer.");
+    assert_eq!(history::list("ops", "alerts")?.len(), 1);
+    assert!(history::list("ops", "deploys")?.is_empty());
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_removed_sections_are_deleted() -> Result<()> {
+    let _temp = setup_temp_insights_root("ingest_removed");
+    let vault = TempDir::new()?;
+    write_note(
+      vault.path(),
+      "ops/runbook.md",
+      &long_note(&[("Rollback", "Revert the tag."), ("Scaling", "Add replicas.")]),
+    );
+    ingest::ingest(vault.path(), Scope::Global)?;
+
+    write_note(
+      vault.path(),
+      "ops/runbook.md",
+      &long_note(&[("Rollback", "Revert the tag."), ("Backups", "Snapshot nightly.")]),
+    );
+    let results = ingest::ingest(vault.path(), Scope::Global)?;
+
+    assert_eq!(
+      actions(&results),
+      vec![
+        ("ops/runbook".to_string(), IngestAction::Unchanged),
+        ("ops/runbook-rollback".to_string(), IngestAction::Unchanged),
+        ("ops/runbook-backups".to_string(), IngestAction::Added),
+        ("ops/runbook-scaling".to_string(), IngestAction::Removed),
+      ]
+    );
+    assert!(insight::load("ops", "runbook-scaling").is_err());
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_deleted_notes_are_removed() -> Result<()> {
+    let _temp = setup_temp_insights_root("ingest_deleted");
+    let vault = TempDir::new()?;
+    write_note(vault.path(), "ops/kept.md", "Still here.");
+    write_note(vault.path(), "ops/gone.md", "Deleted later.");
+    ingest::ingest(vault.path(), Scope::Global)?;
+
+    fs::remove_file(vault.path().join("ops/gone.md"))?;
+    let results = ingest::ingest(vault.path(), Scope::Global)?;
+
+    assert_eq!(
+      actions(&results),
+      vec![
+        ("ops/kept".to_string(), IngestAction::Unchanged),
+        ("ops/gone".to_string(), IngestAction::Removed),
+      ]
+    );
+    assert_eq!(results[1].file, Path::new("ops/gone.md"));
+    assert!(insight::load("ops", "gone").is_err());
+
+    // Nothing is left to clean up on the next ingest
+    assert_eq!(ingest::ingest(vault.path(), Scope::Global)?.len(), 1);
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_windows_line_endings_in_frontmatter() -> Result<()> {
+    let _temp = setup_temp_insights_root("ingest_crlf");
+    let vault = TempDir::new()?;
+    write_note(
+      vault.path(),
+      "ops/deploys.md",
+      "---\r\ntags: [release]\r\n---\r\nWe deploy on Tuesdays.\r\n",
+    );
+    ingest::ingest(vault.path(), Scope::Global)?;
+
+    let ingested = insight::load("ops", "deploys")?;
+    assert_eq!(ingested.tags, vec!["release"]);
+    assert_eq!(ingested.overview, "We deploy on Tuesdays.");
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_existing_insights_are_not_overwritten() -> Result<()> {
+    let _temp = setup_temp_insights_root("ingest_conflict");
+    let vault = TempDir::new()?;
+    insight::save(&Insight::new(
+      "ops".to_string(),
+      "deploys".to_string(),
+      "Hand written".to_string(),
+      "Details".to_string(),
+    ))?;
+    write_note(vault.path(), "ops/deploys.md", "Deploy on Tuesdays.");
+
+    let results = ingest::ingest(vault.path(), Scope::Global)?;
+
+    assert_eq!(results[0].action, IngestAction::Failed);
+    assert!(results[0].detail.as_deref().unwrap().contains("already exists"));
+    assert_eq!(insight::load("ops", "deploys")?.overview, "Hand written");
+    Ok(())
+  }
+}
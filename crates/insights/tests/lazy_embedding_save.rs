@@ -2,7 +2,7 @@
 #[cfg(all(test, feature = "neural"))]
 use anyhow::Result;
 #[cfg(all(test, feature = "neural"))]
-use insights::embedding_client::{self, MockEmbeddingService};
+use insights::embedding_client::{self, Embedding, EmbeddingService, MockEmbeddingService};
 #[cfg(all(test, feature = "neural"))]
 use insights::insight::{self, Insight};
 #[cfg(all(test, feature = "neural"))]
@@ -44,15 +44,7 @@ fn test_lazy_embedding_save_on_search() -> Result<()> {
 
   // Create search options with mock embedding client
   let mock_client = embedding_client::with_service(Box::new(MockEmbeddingService));
-  let search_options = SearchOptions {
-    topic: None,
-    case_sensitive: false,
-    overview_only: false,
-    #[cfg(feature = "semantic")]
-    semantic: false, // Disable semantic to force neural search
-    exact: false, // Disable exact to force neural search
-    embedding_client: mock_client,
-  };
+  let search_options = SearchOptions { embedding_client: mock_client, ..Default::default() };
 
   // Should trigger lazy embedding recomputation
   let results = search::search(&["embedding".to_string()], &search_options)?;
@@ -101,15 +93,7 @@ fn test_existing_embedding_not_overwritten() -> Result<()> {
   insight::save(&insight)?;
 
   let mock_client = embedding_client::with_service(Box::new(MockEmbeddingService));
-  let search_options = SearchOptions {
-    topic: None,
-    case_sensitive: false,
-    overview_only: false,
-    #[cfg(feature = "semantic")]
-    semantic: false,
-    exact: false,
-    embedding_client: mock_client,
-  };
+  let search_options = SearchOptions { embedding_client: mock_client, ..Default::default() };
 
   let results = search::search(&["embedding".to_string()], &search_options)?;
 
@@ -134,3 +118,55 @@ fn test_existing_embedding_not_overwritten() -> Result<()> {
 
   Ok(())
 }
+
+/// A service whose model is not installed; it only hands out placeholders
+#[cfg(all(test, feature = "neural"))]
+struct UninstalledEmbeddingService;
+
+#[cfg(all(test, feature = "neural"))]
+impl EmbeddingService for UninstalledEmbeddingService {
+  fn embed_insight(&self, _insight: &mut Insight) -> Embedding {
+    Embedding {
+      version: "placeholder".to_string(),
+      created_at: chrono::Utc::now(),
+      embedding: vec![],
+    }
+  }
+
+  fn unavailable(&self) -> Option<String> {
+    Some("Embedding model 'test' is not installed".to_string())
+  }
+}
+
+#[cfg(all(test, feature = "neural"))]
+#[test]
+#[serial]
+fn test_search_falls_back_when_model_unavailable() -> Result<()> {
+  let _temp = setup_temp_insights_root("model_unavailable");
+
+  let insight = Insight::new(
+    "Deploys".to_string(),
+    "Schedule".to_string(),
+    "We deploy on Tuesdays".to_string(),
+    "Deploys happen after the weekly freeze.".to_string(),
+  );
+  insight::save(&insight)?;
+
+  let search_options = SearchOptions {
+    embedding_client: embedding_client::with_service(Box::new(UninstalledEmbeddingService)),
+    ..Default::default()
+  };
+
+  let outcome = search::search_with_outcome(&["tuesdays".to_string()], &search_options)?;
+
+  assert_eq!(
+    outcome.neural_unavailable.as_deref(),
+    Some("Embedding model 'test' is not installed")
+  );
+  assert_eq!(outcome.results.len(), 1, "Lexical ranking should still find the insight");
+  assert_eq!(outcome.results[0].name, "Schedule");
+
+  // No placeholder is written back to the insight
+  assert!(insight::load("Deploys", "Schedule")?.embedding.is_none());
+  Ok(())
+}
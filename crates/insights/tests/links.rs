@@ -0,0 +1,124 @@
+#[cfg(test)]
+mod links_tests {
+  use anyhow::Result;
+  use insights::commands;
+  use insights::insight::{self, Insight};
+  use insights::links::{self, InsightLink};
+  use serial_test::serial;
+  use std::env;
+  use tempfile::TempDir;
+
+  fn setup_temp_insights_root(_test_name: &str) -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    env::set_var("INSIGHTS_ROOT", temp_dir.path());
+    temp_dir
+  }
+
+  fn link(topic: &str, name: &str) -> InsightLink {
+    InsightLink { topic: topic.to_string(), name: name.to_string() }
+  }
+
+  fn save(topic: &str, name: &str, details: &str) -> Result<()> {
+    let insight = Insight::new(
+      topic.to_string(),
+      name.to_string(),
+      format!("{name} overview"),
+      details.to_string(),
+    );
+    insight::save(&insight)
+  }
+
+  #[test]
+  fn test_parse_links() {
+    let text = "See [[auth/token-refresh]] and [[ Auth/Token-Refresh | again ]], \
+                plus [[db/pooling|connection pools]].";
+    assert_eq!(links::parse(text), vec![link("auth", "token-refresh"), link("db", "pooling")]);
+  }
+
+  #[test]
+  fn test_parse_ignores_malformed_links() {
+    let text = "[[no-topic]] [[/name]] [[topic/]] [[a/b/c]] [[split\ntopic/name]] [[unclosed/link";
+    assert!(links::parse(text).is_empty());
+  }
+
+  #[test]
+  fn test_link_targets_ignore_case() {
+    assert!(link("Auth", "Tokens").targets("auth", "tokens"));
+    assert!(!link("auth", "tokens").targets("auth", "token"));
+  }
+
+  #[test]
+  fn test_parse_insight_with_metadata_collects_links() -> Result<()> {
+    let content = "---\noverview: Relates to [[auth/login]]\n---\n\n# Details\n\
+                   Refreshes via [[auth/token-refresh]].\n";
+    let (metadata, details) = insight::parse_insight_with_metadata(content)?;
+
+    assert_eq!(details, "Refreshes via [[auth/token-refresh]].");
+    assert_eq!(metadata.links, vec![link("auth", "login"), link("auth", "token-refresh")]);
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_outgoing_links_and_backlinks() -> Result<()> {
+    let _temp = setup_temp_insights_root("links_outgoing");
+
+    save("auth", "token-refresh", "Rotate tokens before expiry.")?;
+    save("auth", "login", "After login see [[auth/token-refresh]] and [[auth/sessions]].")?;
+    save("ops", "runbook", "Token issues: [[Auth/Token-Refresh]].")?;
+
+    let login = commands::insight_links("auth", "login")?;
+    let outgoing: Vec<_> =
+      login.outgoing.iter().map(|resolved| (resolved.link.to_string(), resolved.exists)).collect();
+    assert_eq!(
+      outgoing,
+      vec![("auth/token-refresh".to_string(), true), ("auth/sessions".to_string(), false)]
+    );
+    assert!(login.backlinks.is_empty());
+
+    let refresh = commands::insight_links("auth", "token-refresh")?;
+    assert!(refresh.outgoing.is_empty());
+    let mut sources: Vec<_> = refresh.backlinks.iter().map(|source| source.name.clone()).collect();
+    sources.sort();
+    assert_eq!(sources, vec!["login", "runbook"]);
+
+    assert!(commands::insight_links("auth", "missing").is_err());
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_linked_insights_skip_broken_links() -> Result<()> {
+    let _temp = setup_temp_insights_root("links_expand");
+
+    save("auth", "token-refresh", "Rotate tokens before expiry.")?;
+    save("auth", "login", "See [[auth/missing]] then [[auth/token-refresh]].")?;
+
+    let login = insight::load("auth", "login")?;
+    let linked = commands::linked_insights(&login);
+    assert_eq!(linked.len(), 1);
+    assert_eq!(linked[0].name, "token-refresh");
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_delete_reports_broken_backlinks() -> Result<()> {
+    let _temp = setup_temp_insights_root("links_broken");
+
+    save("auth", "token-refresh", "Rotate tokens before expiry.")?;
+    save("auth", "login", "See [[auth/token-refresh]].")?;
+
+    assert!(commands::broken_backlinks("auth", "token-refresh")?.is_empty());
+
+    commands::delete_insight("auth", "token-refresh", true)?;
+
+    let broken = commands::broken_backlinks("auth", "token-refresh")?;
+    assert_eq!(broken.len(), 1);
+    assert_eq!(broken[0].name, "login");
+
+    let login = commands::insight_links("auth", "login")?;
+    assert!(!login.outgoing[0].exists);
+    Ok(())
+  }
+}
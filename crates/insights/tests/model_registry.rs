@@ -0,0 +1,253 @@
+#[cfg(test)]
+#[cfg(feature = "neural")]
+mod model_registry_tests {
+  use anyhow::Result;
+  use insights::model_registry::{self, ModelState, Pooling, BUILTIN_MODEL};
+  use serial_test::serial;
+  use std::env;
+  use std::fs;
+  use std::path::PathBuf;
+  use tempfile::TempDir;
+
+  /// A registry file and model cache in temporary directories. The environment
+  /// overrides are removed again on drop so other tests use the defaults.
+  struct RegistryFile {
+    dir: TempDir,
+    cache: TempDir,
+  }
+
+  impl Drop for RegistryFile {
+    fn drop(&mut self) {
+      env::remove_var("INSIGHTS_MODELS_CONFIG");
+      env::remove_var("INSIGHTS_MODEL_CACHE");
+      env::remove_var("INSIGHTS_MODEL");
+    }
+  }
+
+  impl RegistryFile {
+    fn path(&self) -> PathBuf {
+      self.dir.path().join("models.yaml")
+    }
+
+    /// A file standing in for a downloaded model
+    fn download(&self, content: &str) -> PathBuf {
+      let path = self.dir.path().join("downloaded.onnx");
+      fs::write(&path, content).unwrap();
+      path
+    }
+  }
+
+  fn setup_registry(content: Option<&str>) -> RegistryFile {
+    let registry = RegistryFile { dir: TempDir::new().unwrap(), cache: TempDir::new().unwrap() };
+    if let Some(content) = content {
+      fs::write(registry.path(), content).unwrap();
+    }
+    env::set_var("INSIGHTS_MODELS_CONFIG", registry.path());
+    env::set_var("INSIGHTS_MODEL_CACHE", registry.cache.path());
+    env::remove_var("INSIGHTS_MODEL");
+    registry
+  }
+
+  /// SHA-256 of "model bytes"
+  const MODEL_SHA256: &str = "9cb7487000bc86ac36ce83c4acfabe8878552be99572a6770f65ab1d048a5c48";
+
+  const BGE_REGISTRY: &str = "
+default: bge-small
+models:
+  bge-small:
+    model: bge/model.onnx
+    tokenizer: bge/tokenizer.json
+    pooling: cls
+    dimension: 384
+    max_tokens: 512
+  mpnet:
+    model: /opt/models/mpnet.onnx
+    tokenizer: /opt/models/mpnet-tokenizer.json
+    dimension: 768
+    max_tokens: 384
+";
+
+  #[test]
+  #[serial]
+  fn test_builtin_model_without_registry_file() -> Result<()> {
+    let registry_file = setup_registry(None);
+
+    let registry = model_registry::load()?;
+    assert_eq!(registry.models.keys().collect::<Vec<_>>(), vec![BUILTIN_MODEL]);
+
+    let active = model_registry::active_model()?;
+    assert_eq!(active, model_registry::builtin()?);
+    assert_eq!(active.model, registry_file.cache.path().join(BUILTIN_MODEL).join("model.onnx"));
+    assert_eq!(active.dimension, 384);
+    assert_eq!(active.pooling, Pooling::Mean);
+    assert!(active.tokenizer.is_none());
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_configured_models_resolve_relative_paths() -> Result<()> {
+    let registry_file = setup_registry(Some(BGE_REGISTRY));
+
+    let active = model_registry::active_model()?;
+    assert_eq!(active.name, "bge-small");
+    assert_eq!(active.model, registry_file.dir.path().join("bge/model.onnx"));
+    assert_eq!(active.tokenizer, Some(registry_file.dir.path().join("bge/tokenizer.json")));
+    assert_eq!(active.pooling, Pooling::Cls);
+    assert_eq!(active.max_tokens, 512);
+    assert!(active.url.is_none());
+
+    let registry = model_registry::load()?;
+    let mpnet = &registry.models["mpnet"];
+    assert_eq!(mpnet.model, PathBuf::from("/opt/models/mpnet.onnx"));
+    assert_eq!(mpnet.pooling, Pooling::Mean);
+    assert_eq!(mpnet.dimension, 768);
+    assert!(registry.models.contains_key(BUILTIN_MODEL));
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_environment_selects_model() -> Result<()> {
+    let _registry = setup_registry(Some(BGE_REGISTRY));
+
+    env::set_var("INSIGHTS_MODEL", "mpnet");
+    assert_eq!(model_registry::active_model()?.name, "mpnet");
+
+    env::set_var("INSIGHTS_MODEL", BUILTIN_MODEL);
+    assert_eq!(model_registry::active_model()?.name, BUILTIN_MODEL);
+
+    env::set_var("INSIGHTS_MODEL", "missing");
+    let error = model_registry::active_model().unwrap_err().to_string();
+    assert!(error.contains("Unknown embedding model 'missing'"));
+    assert!(error.contains("bge-small"));
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_invalid_registries_are_rejected() {
+    let _registry = setup_registry(Some(
+      "models:\n  all-MiniLM-L6-v2:\n    model: m.onnx\n    tokenizer: t.json\n    dimension: 384\n    max_tokens: 256\n",
+    ));
+    assert!(model_registry::load().unwrap_err().to_string().contains("reserved"));
+
+    let _registry = setup_registry(Some(
+      "models:\n  tiny:\n    model: m.onnx\n    tokenizer: t.json\n    dimension: 0\n    max_tokens: 256\n",
+    ));
+    assert!(model_registry::load().unwrap_err().to_string().contains("non-zero"));
+
+    let _registry = setup_registry(Some(
+      "models:\n  tiny:\n    model: m.onnx\n    tokenizer: t.json\n    pooling: max\n    dimension: 8\n    max_tokens: 256\n",
+    ));
+    assert!(model_registry::load().is_err());
+  }
+
+  #[test]
+  #[serial]
+  fn test_missing_model_is_reported_with_install_hint() -> Result<()> {
+    let _registry = setup_registry(None);
+
+    let spec = model_registry::active_model()?;
+    assert_eq!(model_registry::state(&spec)?, ModelState::Missing);
+    let error = model_registry::verify(&spec).unwrap_err().to_string();
+    assert!(error.contains("not installed"));
+    assert!(error.contains("insights model install all-MiniLM-L6-v2 --from"));
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_install_copies_model_into_cache() -> Result<()> {
+    let registry_file = setup_registry(None);
+    let download = registry_file.download("model bytes");
+
+    let installed = model_registry::install(None, &download, None)?;
+    assert_eq!(installed.name, BUILTIN_MODEL);
+    assert_eq!(installed.path, model_registry::cached_model_path(BUILTIN_MODEL)?);
+    assert_eq!(fs::read_to_string(&installed.path)?, "model bytes");
+
+    // The checksum recorded at install time catches later corruption
+    let spec = model_registry::active_model()?;
+    assert_eq!(model_registry::state(&spec)?, ModelState::Verified);
+    model_registry::verify(&spec)?;
+
+    fs::write(&installed.path, "truncated")?;
+    assert_eq!(model_registry::state(&spec)?, ModelState::Corrupt);
+    assert!(model_registry::verify(&spec).unwrap_err().to_string().contains("checksum"));
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_install_rejects_checksum_mismatch() -> Result<()> {
+    let registry_file = setup_registry(None);
+    let download = registry_file.download("model bytes");
+    let wrong = "0".repeat(64);
+
+    let error = model_registry::install(None, &download, Some(&wrong)).unwrap_err();
+    assert!(error.to_string().contains("Checksum mismatch"));
+    assert!(!model_registry::cached_model_path(BUILTIN_MODEL)?.exists());
+
+    let installed = model_registry::install(None, &download, Some(MODEL_SHA256))?;
+    assert_eq!(installed.sha256, MODEL_SHA256);
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_embedding_version_follows_the_model_file() -> Result<()> {
+    let registry_file = setup_registry(None);
+    let installed =
+      model_registry::install(None, &registry_file.download("model bytes"), Some(MODEL_SHA256))?;
+    let spec = model_registry::active_model()?;
+    assert_eq!(
+      model_registry::embedding_version(&spec)?,
+      format!("{BUILTIN_MODEL}@{MODEL_SHA256}")
+    );
+
+    // A model replaced under the same name outdates what the old one embedded
+    fs::write(&installed.path, "other bytes")?;
+    assert_ne!(
+      model_registry::embedding_version(&spec)?,
+      format!("{BUILTIN_MODEL}@{MODEL_SHA256}")
+    );
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_registry_checksums_and_cached_models() -> Result<()> {
+    let registry_file = setup_registry(Some(&format!(
+      "models:\n  cached:\n    tokenizer: t.json\n    sha256: {}\n    dimension: 8\n    max_tokens: 64\n  pinned:\n    model: pinned.onnx\n    tokenizer: t.json\n    dimension: 8\n    max_tokens: 64\n",
+      MODEL_SHA256.to_uppercase()
+    )));
+    let download = registry_file.download("other bytes");
+
+    let cached = model_registry::load()?.get("cached")?.clone();
+    assert_eq!(cached.model, model_registry::cached_model_path("cached")?);
+    assert_eq!(cached.sha256.as_deref(), Some(MODEL_SHA256));
+
+    let error = model_registry::install(Some("cached"), &download, None).unwrap_err();
+    assert!(error.to_string().contains("Checksum mismatch"));
+
+    let error = model_registry::install(Some("pinned"), &download, None).unwrap_err();
+    assert!(error.to_string().contains("models.yaml"));
+
+    model_registry::install(Some("cached"), &registry_file.download("model bytes"), None)?;
+
+    let states: Vec<_> = model_registry::list()?
+      .into_iter()
+      .map(|status| (status.spec.name, status.state, status.active))
+      .collect();
+    assert_eq!(
+      states,
+      vec![
+        (BUILTIN_MODEL.to_string(), ModelState::Missing, true),
+        ("cached".to_string(), ModelState::Verified, false),
+        ("pinned".to_string(), ModelState::Missing, false),
+      ]
+    );
+    Ok(())
+  }
+}
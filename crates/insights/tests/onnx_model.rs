@@ -0,0 +1,113 @@
+#[cfg(test)]
+#[cfg(feature = "neural")]
+mod onnx_model_tests {
+  use anyhow::Result;
+  use insights::embedding_model::{create_model, EmbeddingModel, OnnxEmbeddingModel};
+  use insights::model_registry::{ModelSpec, Pooling};
+  use std::path::PathBuf;
+
+  /// A tiny word-level model in `tests/fixtures/tiny-model`: every token is
+  /// looked up in a fixed 4-dimensional table, so pooled results can be worked
+  /// out by hand. `[PAD]` maps to a large vector so leaked padding shows up.
+  /// Both files are written by `generate.py` in the same directory.
+  fn tiny_spec(pooling: Pooling, max_tokens: usize) -> ModelSpec {
+    let fixture = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tiny-model");
+    ModelSpec {
+      name: "tiny".to_string(),
+      model: fixture.join("model.onnx"),
+      url: None,
+      sha256: None,
+      tokenizer: Some(fixture.join("tokenizer.json")),
+      pooling,
+      dimension: 4,
+      max_tokens,
+    }
+  }
+
+  fn tiny_model(pooling: Pooling, max_tokens: usize) -> Result<OnnxEmbeddingModel> {
+    create_model(tiny_spec(pooling, max_tokens))
+  }
+
+  fn embed(model: &mut OnnxEmbeddingModel, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+    let texts: Vec<String> = texts.iter().map(|text| text.to_string()).collect();
+    model.compute_embeddings(&texts)
+  }
+
+  fn assert_close(actual: &[f32], expected: &[f32]) {
+    assert_eq!(actual.len(), expected.len());
+    for (a, e) in actual.iter().zip(expected) {
+      assert!((a - e).abs() < 1e-5, "{actual:?} != {expected:?}");
+    }
+  }
+
+  #[test]
+  fn test_mean_pooling_over_tokens() -> Result<()> {
+    let mut model = tiny_model(Pooling::Mean, 32)?;
+    let embeddings = embed(&mut model, &["deploy"])?;
+
+    // Mean of [CLS], "deploy" and [SEP], normalized
+    let third = 1.0 / 3f32.sqrt();
+    assert_eq!(embeddings.len(), 1);
+    assert_close(&embeddings[0], &[third, 0.0, third, third]);
+    Ok(())
+  }
+
+  #[test]
+  fn test_cls_pooling() -> Result<()> {
+    let mut model = tiny_model(Pooling::Cls, 32)?;
+    let embeddings = embed(&mut model, &["deploy rollback"])?;
+    assert_close(&embeddings[0], &[0.0, 0.0, 0.0, 1.0]);
+    Ok(())
+  }
+
+  #[test]
+  fn test_embeddings_do_not_depend_on_padding() -> Result<()> {
+    let mut model = tiny_model(Pooling::Mean, 32)?;
+    let alone = embed(&mut model, &["deploy"])?;
+    let batched = embed(&mut model, &["deploy", "database backup release tuesday rollback"])?;
+
+    assert_eq!(batched.len(), 2);
+    assert!(batched.iter().all(|embedding| embedding.len() == 4));
+    assert_close(&batched[0], &alone[0]);
+    Ok(())
+  }
+
+  #[test]
+  fn test_long_texts_are_chunked_instead_of_rejected() -> Result<()> {
+    let mut model = tiny_model(Pooling::Mean, 4)?;
+    let long_text = "deploy database ".repeat(5000);
+    let embeddings = embed(&mut model, &[&long_text, "deploy"])?;
+
+    assert_eq!(embeddings.len(), 2);
+    assert_eq!(embeddings[0].len(), 4);
+    let magnitude: f32 = embeddings[0].iter().map(|x| x * x).sum::<f32>().sqrt();
+    assert!((magnitude - 1.0).abs() < 1e-5);
+
+    // Both words contribute, not just the first window
+    assert!(embeddings[0][0] > 0.1 && embeddings[0][1] > 0.1);
+    Ok(())
+  }
+
+  #[test]
+  fn test_large_batches_are_run_in_parts() -> Result<()> {
+    let mut model = tiny_model(Pooling::Mean, 32)?;
+    let words = ["deploy", "rollback", "release", "tuesday", "database", "backup"];
+    let texts: Vec<&str> = words.iter().cycle().take(100).copied().collect();
+
+    let embeddings = embed(&mut model, &texts)?;
+    assert_eq!(embeddings.len(), texts.len());
+    for (text, embedding) in texts.iter().zip(&embeddings) {
+      assert_close(embedding, &embed(&mut model, &[text])?[0]);
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn test_dimension_mismatch_is_an_error() -> Result<()> {
+    let mut model = create_model(ModelSpec { dimension: 384, ..tiny_spec(Pooling::Mean, 32) })?;
+
+    let error = embed(&mut model, &["deploy"]).unwrap_err();
+    assert!(error.to_string().contains("4-dimensional"));
+    Ok(())
+  }
+}
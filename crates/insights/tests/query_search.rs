@@ -0,0 +1,197 @@
+#[cfg(test)]
+mod query_search_tests {
+  use anyhow::Result;
+  use insights::insight::{self, Insight};
+  use insights::search::{self, SearchOptions, SearchResult};
+  use serial_test::serial;
+  use std::env;
+  use tempfile::TempDir;
+
+  fn setup_temp_insights_root() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    env::set_var("INSIGHTS_ROOT", temp_dir.path());
+    temp_dir
+  }
+
+  fn save(topic: &str, name: &str, overview: &str, details: &str, tags: &[&str]) -> Result<()> {
+    let mut insight =
+      Insight::new(topic.to_string(), name.to_string(), overview.to_string(), details.to_string());
+    insight.tags = tags.iter().map(|tag| tag.to_string()).collect();
+    insight::save(&insight)
+  }
+
+  fn save_fixtures() -> Result<()> {
+    save(
+      "ops",
+      "deploys",
+      "Blue green deploys",
+      "Deploy on tuesday to staging first",
+      &["release"],
+    )?;
+    save("ops", "rollbacks", "Rolling back a deploy", "Revert the green environment", &[])?;
+    save(
+      "storage",
+      "backups",
+      "Nightly backups",
+      "Backups run before the deploy window",
+      &["ops"],
+    )?;
+    Ok(())
+  }
+
+  fn exact_options() -> SearchOptions {
+    SearchOptions { exact: true, ..Default::default() }
+  }
+
+  fn run(query: &str, options: &SearchOptions) -> Result<Vec<String>> {
+    let terms: Vec<String> = query.split(' ').map(str::to_string).collect();
+    let mut names: Vec<String> = search::search(&terms, options)?
+      .into_iter()
+      .map(|result: SearchResult| result.name)
+      .collect();
+    names.sort();
+    Ok(names)
+  }
+
+  #[test]
+  #[serial]
+  fn test_plain_words_still_match_any_term() -> Result<()> {
+    let _temp = setup_temp_insights_root();
+    save_fixtures()?;
+
+    assert_eq!(run("nightly revert", &exact_options())?, vec!["backups", "rollbacks"]);
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_boolean_operators_and_exclusions() -> Result<()> {
+    let _temp = setup_temp_insights_root();
+    save_fixtures()?;
+    let options = exact_options();
+
+    assert_eq!(run("deploy AND green", &options)?, vec!["deploys", "rollbacks"]);
+    assert_eq!(run("deploy -staging", &options)?, vec!["backups", "rollbacks"]);
+    assert_eq!(run("deploy NOT (green OR nightly)", &options)?, Vec::<String>::new());
+    assert_eq!(run("nightly OR tuesday", &options)?, vec!["backups", "deploys"]);
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_phrases_fields_and_wildcards() -> Result<()> {
+    let _temp = setup_temp_insights_root();
+    save_fixtures()?;
+    let options = exact_options();
+
+    assert_eq!(run("\"green environment\"", &options)?, vec!["rollbacks"]);
+    assert_eq!(run("overview:deploy*", &options)?, vec!["deploys", "rollbacks"]);
+    assert_eq!(run("topic:ops roll*", &options)?, vec!["rollbacks"]);
+    assert_eq!(run("name:backups", &options)?, vec!["backups"]);
+    assert_eq!(run("details:\"deploy window\"", &options)?, vec!["backups"]);
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_field_only_queries_list_candidates() -> Result<()> {
+    let _temp = setup_temp_insights_root();
+    save_fixtures()?;
+    let options = exact_options();
+
+    assert_eq!(run("tag:release", &options)?, vec!["deploys"]);
+    assert_eq!(run("tag:ops OR tag:release", &options)?, vec!["backups", "deploys"]);
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_invalid_query_is_searched_as_plain_words() -> Result<()> {
+    let _temp = setup_temp_insights_root();
+    save_fixtures()?;
+
+    assert_eq!(run("\"nightly (revert", &exact_options())?, vec!["backups", "rollbacks"]);
+    Ok(())
+  }
+
+  #[cfg(feature = "neural")]
+  #[test]
+  #[serial]
+  fn test_constraints_filter_neural_candidates() -> Result<()> {
+    use insights::embedding_client::{self, MockEmbeddingService};
+
+    let _temp = setup_temp_insights_root();
+    save_fixtures()?;
+    // The mock embeds everything identically, so neural ranking alone would return every insight
+    let options = SearchOptions {
+      #[cfg(feature = "semantic")]
+      semantic: false,
+      exact: false,
+      embedding_client: embedding_client::with_service(Box::new(MockEmbeddingService)),
+      ..exact_options()
+    };
+
+    assert_eq!(run("deploy", &options)?, vec!["backups", "deploys", "rollbacks"]);
+    assert_eq!(run("deploy -staging", &options)?, vec!["backups", "rollbacks"]);
+    assert_eq!(run("tag:release", &options)?, vec!["deploys"]);
+    Ok(())
+  }
+
+  #[cfg(feature = "neural")]
+  #[test]
+  #[serial]
+  fn test_snippet_passages_are_embedded_on_their_own() -> Result<()> {
+    use chrono::Utc;
+    use insights::embedding_client::{self, Embedding, EmbeddingService};
+    use std::sync::{Arc, Mutex};
+
+    /// Embeds whether a text mentions the cache, recording the passages it is given
+    struct PassageRecordingService {
+      passages: Arc<Mutex<Vec<String>>>,
+    }
+
+    fn embed(text: &str) -> Embedding {
+      let embedding = vec![text.to_lowercase().contains("cache") as u8 as f32, 1.0];
+      Embedding { version: "passages".to_string(), created_at: Utc::now(), embedding }
+    }
+
+    impl EmbeddingService for PassageRecordingService {
+      fn embed_insight(&self, insight: &mut Insight) -> Embedding {
+        embed(&insight::get_embedding_text(insight))
+      }
+
+      fn embed_texts(&self, texts: &[String]) -> Vec<Embedding> {
+        self.passages.lock().unwrap().extend(texts.iter().cloned());
+        texts.iter().map(|text| embed(text)).collect()
+      }
+
+      fn model_version(&self) -> Option<String> {
+        Some("passages".to_string())
+      }
+    }
+
+    let _temp = setup_temp_insights_root();
+    let filler = "Unrelated background sentence about the team. ".repeat(12);
+    let details = format!("{filler}The cache is flushed on every deploy. {filler}");
+    save("cache", "notes", "Overview", &details, &[])?;
+
+    let passages = Arc::new(Mutex::new(Vec::new()));
+    let service = PassageRecordingService { passages: passages.clone() };
+    let options = SearchOptions {
+      embedding_snippets: true,
+      embedding_client: embedding_client::with_service(Box::new(service)),
+      ..Default::default()
+    };
+    let terms = vec!["cache".to_string()];
+    let results = search::search(&terms, &options)?;
+
+    let snippet = results[0].snippet.as_deref().unwrap();
+    assert!(snippet.contains("The cache is flushed on every deploy."), "{snippet}");
+    let passages = passages.lock().unwrap();
+    assert!(passages.contains(&"The cache is flushed on every deploy.".to_string()));
+    // Only the passage itself is embedded, not the insight's topic and name
+    assert!(passages.iter().all(|passage| !passage.contains("notes")), "{passages:?}");
+    assert_eq!(passages.iter().filter(|passage| passage.contains("cache")).count(), 1);
+    Ok(())
+  }
+}
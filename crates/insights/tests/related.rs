@@ -0,0 +1,187 @@
+#[cfg(test)]
+#[cfg(feature = "neural")]
+mod related_tests {
+  use anyhow::Result;
+  use chrono::Utc;
+  use insights::commands::*;
+  use insights::embedding_client::{self, Embedding, EmbeddingClient, EmbeddingService};
+  use insights::insight::{self, Insight};
+  use insights::related;
+  use serial_test::serial;
+  use std::env;
+  use tempfile::TempDir;
+
+  fn setup_temp_insights_root() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    env::set_var("INSIGHTS_ROOT", temp_dir.path());
+    temp_dir
+  }
+
+  /// Embeds an insight's overview as counts of a few keywords, so tests can
+  /// control how similar insights are
+  struct KeywordEmbeddingService {
+    version: &'static str,
+  }
+
+  impl EmbeddingService for KeywordEmbeddingService {
+    fn embed_insight(&self, insight: &mut Insight) -> Embedding {
+      let words: Vec<String> =
+        insight.overview.split_whitespace().map(|word| word.to_lowercase()).collect();
+      let embedding = ["deploy", "backup", "cache", "tuesday"]
+        .iter()
+        .map(|keyword| words.iter().filter(|word| word == keyword).count() as f32)
+        .collect();
+      Embedding { version: self.version.to_string(), created_at: Utc::now(), embedding }
+    }
+
+    fn model_version(&self) -> Option<String> {
+      Some(self.version.to_string())
+    }
+  }
+
+  fn keyword_client(version: &'static str) -> EmbeddingClient {
+    embedding_client::with_service(Box::new(KeywordEmbeddingService { version }))
+  }
+
+  fn add(topic: &str, name: &str, overview: &str, client: &EmbeddingClient) -> Result<Insight> {
+    let new = NewInsight { force: true, ..NewInsight::new(topic, name, overview, "Details") };
+    Ok(add_insight_with_client(&new, client)?.insight)
+  }
+
+  fn names(insights: &[Insight]) -> Vec<String> {
+    insights.iter().map(|insight| format!("{}/{}", insight.topic, insight.name)).collect()
+  }
+
+  #[test]
+  #[serial]
+  fn test_related_insights_are_ranked_by_similarity() -> Result<()> {
+    let _temp = setup_temp_insights_root();
+    let client = keyword_client("keywords-v1");
+    add("ops", "deploys", "deploy deploy tuesday", &client)?;
+    add("ops", "releases", "deploy deploy deploy tuesday", &client)?;
+    add("ops", "schedule", "deploy tuesday tuesday", &client)?;
+    add("storage", "backups", "backup cache", &client)?;
+    add("legacy", "deploys", "deploy tuesday", &keyword_client("keywords-v0"))?;
+
+    let neighbours = related_insights("ops", "deploys", 2)?;
+    let found: Vec<String> = neighbours
+      .iter()
+      .map(|neighbour| format!("{}/{}", neighbour.insight.topic, neighbour.insight.name))
+      .collect();
+    assert_eq!(found, vec!["ops/releases", "ops/schedule"]);
+    assert!(neighbours[0].similarity > neighbours[1].similarity);
+
+    // Embeddings from another model and the insight itself are never neighbours
+    let all = related_insights("ops", "deploys", 10)?;
+    assert_eq!(all.len(), 3);
+    assert!(all.iter().all(|neighbour| neighbour.insight.topic != "legacy"));
+    assert!(all
+      .iter()
+      .all(|neighbour| neighbour.insight.topic != "ops" || neighbour.insight.name != "deploys"));
+
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_related_and_duplicates_read_the_vector_index() -> Result<()> {
+    use insights::index;
+    use insights::scope::Scope;
+
+    let _temp = setup_temp_insights_root();
+    let client = keyword_client("keywords-v1");
+    add("ops", "deploys", "deploy deploy tuesday", &client)?;
+    add("ops", "releases", "deploy deploy deploy tuesday", &client)?;
+    add("storage", "backups", "backup cache", &client)?;
+
+    // Point the indexed vectors elsewhere without touching the insight files
+    let mut vector_index = index::load(Scope::Global)?;
+    vector_index.entries.get_mut(&index::key("ops", "releases")).unwrap().embedding =
+      vec![0.0, 1.0, 1.0, 0.0];
+    vector_index.entries.get_mut(&index::key("storage", "backups")).unwrap().embedding =
+      vec![2.0, 0.0, 0.0, 1.0];
+    index::save(Scope::Global, &vector_index)?;
+
+    let neighbours = related_insights("ops", "deploys", 1)?;
+    assert_eq!(neighbours[0].insight.name, "backups");
+    assert_eq!(neighbours[0].insight.overview, "backup cache");
+
+    let groups = find_duplicates(0.95)?;
+    assert_eq!(names(&groups[0].insights), vec!["ops/deploys", "storage/backups"]);
+    assert_eq!(groups[0].insights[1].overview, "backup cache");
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_related_requires_an_embedding() -> Result<()> {
+    let _temp = setup_temp_insights_root();
+    insight::save(&Insight::new(
+      "ops".to_string(),
+      "unindexed".to_string(),
+      "deploy".to_string(),
+      "Details".to_string(),
+    ))?;
+
+    let error = related_insights("ops", "unindexed", 5).unwrap_err();
+    assert!(error.to_string().contains("insights index"), "{error}");
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_duplicates_are_clustered_across_topics() -> Result<()> {
+    let _temp = setup_temp_insights_root();
+    let client = keyword_client("keywords-v1");
+    add("ops", "deploys", "deploy deploy deploy tuesday", &client)?;
+    add("release", "deploy_day", "deploy deploy deploy tuesday", &client)?;
+    add("notes", "shipping", "deploy tuesday", &client)?;
+    add("storage", "backups", "backup backup cache", &client)?;
+    add("infra", "snapshots", "backup backup backup cache", &client)?;
+    add("infra", "caching", "cache", &client)?;
+
+    let groups = find_duplicates(0.95)?;
+    assert_eq!(groups.len(), 2);
+    assert_eq!(names(&groups[0].insights), vec!["ops/deploys", "release/deploy_day"]);
+    assert_eq!(names(&groups[1].insights), vec!["infra/snapshots", "storage/backups"]);
+    assert!(groups[0].similarity > 0.99);
+
+    // A lower threshold pulls the looser match into the deploy group
+    let groups = find_duplicates(0.85)?;
+    assert_eq!(
+      names(&groups[0].insights),
+      vec!["notes/shipping", "ops/deploys", "release/deploy_day"]
+    );
+
+    assert!(find_duplicates(1.5).is_err());
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_add_refuses_near_duplicates_without_force() -> Result<()> {
+    let _temp = setup_temp_insights_root();
+    let client = keyword_client("keywords-v1");
+    add("ops", "deploys", "deploy deploy tuesday", &client)?;
+    add("storage", "backups", "backup cache", &client)?;
+
+    let duplicate = NewInsight::new("release", "deploy_day", "deploy deploy tuesday", "Details");
+    let error = add_insight_with_client(&duplicate, &client).unwrap_err();
+    assert!(error.to_string().contains("ops/deploys"), "{error}");
+    assert!(error.to_string().contains("--force"), "{error}");
+    assert!(insight::load("release", "deploy_day").is_err());
+
+    let added = add_insight_with_client(&NewInsight { force: true, ..duplicate }, &client)?;
+    assert!(insight::load("release", "deploy_day").is_ok());
+    assert_eq!(added.similar.len(), 1);
+    assert_eq!(added.similar[0].insight.name, "deploys");
+    assert!(added.similar[0].similarity >= related::DEFAULT_DUPLICATE_THRESHOLD);
+
+    let added = add_insight_with_client(
+      &NewInsight::new("misc", "caching", "cache tuesday", "Details"),
+      &client,
+    )?;
+    assert!(added.similar.is_empty());
+    Ok(())
+  }
+}
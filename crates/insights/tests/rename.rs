@@ -0,0 +1,147 @@
+#[cfg(test)]
+mod rename_tests {
+  use anyhow::Result;
+  use insights::commands;
+  use insights::history;
+  use insights::insight::{self, Insight};
+  use serial_test::serial;
+  use std::env;
+  use tempfile::TempDir;
+
+  fn setup_temp_insights_root(_test_name: &str) -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    env::set_var("INSIGHTS_ROOT", temp_dir.path());
+    temp_dir
+  }
+
+  fn save(topic: &str, name: &str, details: &str) -> Result<Insight> {
+    let insight = Insight::new(
+      topic.to_string(),
+      name.to_string(),
+      format!("{name} overview"),
+      details.to_string(),
+    );
+    insight::save(&insight)?;
+    insight::load(topic, name)
+  }
+
+  #[test]
+  #[serial]
+  fn test_rename_moves_file_and_rewrites_frontmatter() -> Result<()> {
+    let temp = setup_temp_insights_root("rename_move");
+
+    let original = save("drafts", "caching", "Cache details")?;
+    let moved = insight::rename(&original, "Performance", "Cache-Strategy")?;
+
+    assert_eq!(moved.topic, "Performance");
+    assert_eq!(moved.name, "Cache-Strategy");
+    assert!(insight::load("drafts", "caching").is_err());
+    // Empty topic directories are cleaned up
+    assert!(!temp.path().join("drafts").exists());
+
+    let loaded = insight::load("performance", "cache-strategy")?;
+    assert_eq!(loaded.topic, "Performance");
+    assert_eq!(loaded.name, "Cache-Strategy");
+    assert_eq!(loaded.details, "Cache details");
+    assert_eq!(loaded.created, original.created);
+    assert!(loaded.updated >= original.updated);
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_rename_refuses_to_overwrite() -> Result<()> {
+    let _temp = setup_temp_insights_root("rename_conflict");
+
+    let original = save("topic", "first", "First details")?;
+    save("topic", "second", "Second details")?;
+
+    assert!(insight::rename(&original, "topic", "second").is_err());
+    assert_eq!(insight::load("topic", "first")?.details, "First details");
+    assert_eq!(insight::load("topic", "second")?.details, "Second details");
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_rename_changes_case_in_place() -> Result<()> {
+    let _temp = setup_temp_insights_root("rename_case");
+
+    let original = save("topic", "name", "Details")?;
+    let renamed = insight::rename(&original, "Topic", "Name")?;
+
+    assert_eq!(renamed.name, "Name");
+    assert_eq!(insight::load("topic", "name")?.name, "Name");
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_move_keeps_history() -> Result<()> {
+    let _temp = setup_temp_insights_root("rename_history");
+
+    let mut original = save("topic", "old", "First details")?;
+    insight::update(&mut original, None, Some("Second details"))?;
+
+    let moved = insight::rename(&original, "other", "new")?;
+    assert_eq!(moved.details, "Second details");
+
+    assert!(history::list("topic", "old")?.is_empty());
+    let revisions = history::list("other", "new")?;
+    assert_eq!(revisions.len(), 1);
+    assert_eq!(revisions[0].insight.details, "First details");
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_move_reports_stale_references() -> Result<()> {
+    let _temp = setup_temp_insights_root("rename_mentions");
+
+    let refresh = save("auth", "token-refresh", "Rotate tokens")?;
+    save("auth", "login", "Then see [[auth/token-refresh]]")?;
+    save("ops", "runbook", "Check Auth/Token-Refresh first")?;
+    save("ops", "checklist", "Run token-refresh, then log in")?;
+    save("ops", "unrelated", "Nothing to see")?;
+    save("ops", "successor", "Replaced by token-refresh-v2")?;
+
+    insight::rename(&refresh, "security", "token-refresh")?;
+
+    let mut stale: Vec<_> = commands::stale_references("auth", "token-refresh")?
+      .into_iter()
+      .map(|insight| insight.name)
+      .collect();
+    stale.sort();
+    assert_eq!(stale, vec!["checklist", "login", "runbook"]);
+    Ok(())
+  }
+
+  #[cfg(feature = "neural")]
+  #[test]
+  #[serial]
+  fn test_rename_clears_embedding_and_move_recomputes_it() -> Result<()> {
+    use insights::embedding_client::{self, MockEmbeddingService};
+
+    let _temp = setup_temp_insights_root("rename_embedding");
+
+    let client = embedding_client::with_service(Box::new(MockEmbeddingService));
+    let original = commands::add_insight_with_client(
+      &commands::NewInsight::new("topic", "embedded", "Overview", "Details"),
+      &client,
+    )?
+    .insight;
+    assert!(original.embedding.is_some());
+
+    // The topic is part of the embedded text, so moving invalidates it
+    let moved = insight::rename(&original, "elsewhere", "embedded")?;
+    assert!(moved.embedding.is_none());
+
+    // The command recomputes it straight away
+    let recomputed =
+      commands::move_insight_with_client("elsewhere", "embedded", "final", None, &client)?;
+    assert!(recomputed.embedding.is_some());
+    let loaded = insight::load("final", "embedded")?;
+    assert_eq!(loaded.embedding_hash, Some(insight::content_hash(&loaded)));
+    Ok(())
+  }
+}
@@ -0,0 +1,171 @@
+#[cfg(test)]
+mod scope_tests {
+  use anyhow::Result;
+  use insights::history;
+  use insights::insight::{self, Insight};
+  use insights::scope::{self, Scope};
+  use serial_test::serial;
+  use std::env;
+  use tempfile::TempDir;
+
+  /// Global and project stores in temporary directories. The project store
+  /// override is removed again on drop so other tests only see the global store.
+  struct Stores {
+    global: TempDir,
+    project: TempDir,
+  }
+
+  impl Drop for Stores {
+    fn drop(&mut self) {
+      env::remove_var("INSIGHTS_PROJECT_ROOT");
+    }
+  }
+
+  fn setup_stores() -> Stores {
+    let stores = Stores { global: TempDir::new().unwrap(), project: TempDir::new().unwrap() };
+    env::set_var("INSIGHTS_ROOT", stores.global.path());
+    env::set_var("INSIGHTS_PROJECT_ROOT", stores.project.path());
+    stores
+  }
+
+  fn save(topic: &str, name: &str, overview: &str, scope: Scope) -> Result<()> {
+    let mut insight = Insight::new(
+      topic.to_string(),
+      name.to_string(),
+      overview.to_string(),
+      "Details".to_string(),
+    );
+    insight.scope = scope;
+    insight::save(&insight)
+  }
+
+  #[test]
+  #[serial]
+  fn test_insights_are_saved_to_their_store() -> Result<()> {
+    let stores = setup_stores();
+
+    save("auth", "tokens", "Project tokens", Scope::Project)?;
+    save("ops", "deploys", "Global deploys", Scope::Global)?;
+
+    assert!(stores.project.path().join("auth/tokens.insight.md").exists());
+    assert!(stores.global.path().join("ops/deploys.insight.md").exists());
+
+    assert_eq!(insight::load("auth", "tokens")?.scope, Scope::Project);
+    assert_eq!(insight::load("ops", "deploys")?.scope, Scope::Global);
+    assert_eq!(insight::get_topics()?, vec!["auth", "ops"]);
+
+    let scopes: Vec<_> = insight::get_insights(None)?
+      .into_iter()
+      .map(|insight| (insight.name, insight.scope))
+      .collect();
+    assert_eq!(
+      scopes,
+      vec![("deploys".to_string(), Scope::Global), ("tokens".to_string(), Scope::Project)]
+    );
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_project_insight_takes_precedence() -> Result<()> {
+    let _stores = setup_stores();
+
+    save("auth", "tokens", "Global tokens", Scope::Global)?;
+    save("auth", "tokens", "Project tokens", Scope::Project)?;
+
+    let loaded = insight::load("auth", "tokens")?;
+    assert_eq!(loaded.overview, "Project tokens");
+    assert_eq!(loaded.scope, Scope::Project);
+
+    let visible = insight::get_insights(Some("auth"))?;
+    assert_eq!(visible.len(), 1);
+    assert_eq!(visible[0].overview, "Project tokens");
+
+    // Removing the project copy reveals the global one again
+    insight::delete(&loaded)?;
+    let loaded = insight::load("auth", "tokens")?;
+    assert_eq!(loaded.overview, "Global tokens");
+    assert_eq!(loaded.scope, Scope::Global);
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_updates_stay_in_their_store() -> Result<()> {
+    let stores = setup_stores();
+
+    save("auth", "tokens", "Project tokens", Scope::Project)?;
+
+    // A caller-built copy defaults to the global scope but still updates in place
+    let mut insight = Insight::new(
+      "auth".to_string(),
+      "tokens".to_string(),
+      "Project tokens".to_string(),
+      "Details".to_string(),
+    );
+    insight::update(&mut insight, Some("Rotated tokens"), None)?;
+
+    assert_eq!(insight.scope, Scope::Project);
+    assert!(!stores.global.path().join("auth").exists());
+    assert_eq!(insight::load("auth", "tokens")?.overview, "Rotated tokens");
+
+    let revisions = history::list("auth", "tokens")?;
+    assert_eq!(revisions.len(), 1);
+    assert!(revisions[0].path.starts_with(stores.project.path()));
+    Ok(())
+  }
+
+  #[cfg(feature = "neural")]
+  #[test]
+  #[serial]
+  fn test_global_add_refuses_to_be_shadowed() -> Result<()> {
+    use insights::commands::{self, NewInsight};
+    use insights::embedding_client::{self, MockEmbeddingService};
+
+    let stores = setup_stores();
+    save("auth", "tokens", "Project tokens", Scope::Project)?;
+
+    let client = embedding_client::with_service(Box::new(MockEmbeddingService));
+    let shadowed = NewInsight::new("Auth", "Tokens", "Global tokens", "Details");
+    assert!(commands::add_insight_with_client(&shadowed, &client).is_err());
+    assert!(!stores.global.path().join("auth").exists());
+
+    let project =
+      NewInsight { scope: Scope::Project, ..NewInsight::new("auth", "keys", "Keys", "") };
+    commands::add_insight_with_client(&project, &client)?;
+    commands::add_insight_with_client(
+      &NewInsight { force: true, ..NewInsight::new("auth", "other", "Other", "") },
+      &client,
+    )?;
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_project_store_requires_a_project() -> Result<()> {
+    let _stores = setup_stores();
+    env::set_var("INSIGHTS_PROJECT_ROOT", "");
+
+    assert!(scope::project_root().is_none());
+    assert!(save("auth", "tokens", "Project tokens", Scope::Project).is_err());
+    assert_eq!(scope::stores()?.len(), 1);
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_insights_root_keeps_project_discovery() -> Result<()> {
+    let _stores = setup_stores();
+    env::remove_var("INSIGHTS_PROJECT_ROOT");
+
+    let repository = TempDir::new()?;
+    std::fs::create_dir(repository.path().join(".git"))?;
+    let previous_dir = env::current_dir()?;
+    env::set_current_dir(repository.path())?;
+    let discovered = scope::project_root();
+    env::set_current_dir(previous_dir)?;
+
+    assert_eq!(discovered, Some(repository.path().join(".kernelle").join("insights")));
+    Ok(())
+  }
+}
@@ -0,0 +1,309 @@
+#[cfg(all(test, feature = "neural"))]
+mod vector_index_tests {
+  use anyhow::Result;
+  use insights::index;
+  use insights::insight::{self, Insight};
+  use insights::scope::Scope;
+  use serial_test::serial;
+  use std::env;
+  use tempfile::TempDir;
+
+  fn setup_temp_insights_root(_test_name: &str) -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    env::set_var("INSIGHTS_ROOT", temp_dir.path());
+    temp_dir
+  }
+
+  fn embedded_insight(topic: &str, name: &str, embedding: Vec<f32>) -> Insight {
+    let mut insight = Insight::new(
+      topic.to_string(),
+      name.to_string(),
+      format!("{name} overview"),
+      format!("{name} details"),
+    );
+    insight.embedding_version = Some("test-version".to_string());
+    insight.embedding = Some(embedding);
+    insight.embedding_hash = Some(insight::content_hash(&insight));
+    insight
+  }
+
+  #[test]
+  #[serial]
+  fn test_save_records_embedding_in_index() -> Result<()> {
+    let _temp = setup_temp_insights_root("index_save");
+
+    let insight = embedded_insight("Topic", "Indexed", vec![0.1, 0.2, 0.3]);
+    insight::save(&insight)?;
+
+    let vector_index = index::load(Scope::Global)?;
+    let entry = index::get(&vector_index, "topic", "indexed").expect("entry should be indexed");
+    assert_eq!(entry.topic, "Topic");
+    assert_eq!(entry.name, "Indexed");
+    assert_eq!(entry.embedding, vec![0.1, 0.2, 0.3]);
+    assert_eq!(entry.content_hash, insight::content_hash(&insight));
+
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_save_without_embedding_creates_no_index() -> Result<()> {
+    let _temp = setup_temp_insights_root("index_no_embedding");
+
+    let insight = Insight::new(
+      "topic".to_string(),
+      "plain".to_string(),
+      "Overview".to_string(),
+      "Details".to_string(),
+    );
+    insight::save(&insight)?;
+
+    assert!(!index::index_path(Scope::Global)?.exists());
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_update_and_delete_keep_index_current() -> Result<()> {
+    let _temp = setup_temp_insights_root("index_update_delete");
+
+    let mut insight = embedded_insight("topic", "changing", vec![0.5; 4]);
+    insight::save(&insight)?;
+    assert!(index::get(&index::load(Scope::Global)?, "topic", "changing").is_some());
+
+    // Updating content clears the embedding, so the stale vector must go too
+    insight::update(&mut insight, Some("New overview"), None)?;
+    assert!(index::get(&index::load(Scope::Global)?, "topic", "changing").is_none());
+
+    let removed = embedded_insight("topic", "removed", vec![0.7; 4]);
+    insight::save(&removed)?;
+    assert!(index::get(&index::load(Scope::Global)?, "topic", "removed").is_some());
+
+    insight::delete(&removed)?;
+    assert!(index::get(&index::load(Scope::Global)?, "topic", "removed").is_none());
+
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_concurrent_saves_keep_every_entry() -> Result<()> {
+    let _temp = setup_temp_insights_root("index_concurrent");
+
+    let writers: Vec<_> = (0..8)
+      .map(|i| {
+        std::thread::spawn(move || {
+          insight::save(&embedded_insight("topic", &format!("insight{i}"), vec![i as f32; 4]))
+        })
+      })
+      .collect();
+    for writer in writers {
+      writer.join().unwrap()?;
+    }
+
+    let vector_index = index::load(Scope::Global)?;
+    assert_eq!(vector_index.entries.len(), 8);
+    let leftovers = std::fs::read_dir(index::index_path(Scope::Global)?.parent().unwrap())?
+      .filter_map(|entry| entry.ok())
+      .filter(|entry| entry.file_name().to_string_lossy().starts_with(".tmp"))
+      .count();
+    assert_eq!(leftovers, 0);
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_index_directory_is_not_a_topic() -> Result<()> {
+    let _temp = setup_temp_insights_root("index_hidden");
+
+    insight::save(&embedded_insight("visible", "one", vec![1.0, 0.0]))?;
+    assert!(index::index_path(Scope::Global)?.exists());
+
+    assert_eq!(insight::get_topics()?, vec!["visible".to_string()]);
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_rebuild_restores_missing_index() -> Result<()> {
+    let _temp = setup_temp_insights_root("index_rebuild");
+
+    insight::save(&embedded_insight("a", "first", vec![1.0, 0.0]))?;
+    insight::save(&embedded_insight("b", "second", vec![0.0, 1.0]))?;
+    std::fs::remove_file(index::index_path(Scope::Global)?)?;
+
+    let rebuilt = index::rebuild()?;
+    assert_eq!(rebuilt.entries.len(), 2);
+    assert_eq!(index::load(Scope::Global)?.entries.len(), 2);
+
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_corrupt_index_is_moved_aside() -> Result<()> {
+    let _temp = setup_temp_insights_root("index_corrupt");
+
+    let path = index::index_path(Scope::Global)?;
+    std::fs::create_dir_all(path.parent().unwrap())?;
+    std::fs::write(&path, "not json")?;
+
+    assert!(index::load(Scope::Global)?.entries.is_empty());
+    assert!(!path.exists());
+    assert_eq!(std::fs::read_to_string(path.with_extension("json.corrupt"))?, "not json");
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_stale_embedding_is_not_indexed() -> Result<()> {
+    let _temp = setup_temp_insights_root("index_stale");
+
+    let mut insight = embedded_insight("topic", "stale", vec![0.5; 4]);
+    insight.embedding_hash = Some("outdated".to_string());
+    insight::save(&insight)?;
+
+    assert!(index::get(&index::load(Scope::Global)?, "topic", "stale").is_none());
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_neural_search_scores_against_index() -> Result<()> {
+    use insights::embedding_client::{self, MockEmbeddingService};
+    use insights::search::{self, SearchOptions};
+
+    let _temp = setup_temp_insights_root("index_search");
+
+    // Mock query embeddings are a constant vector, so only the aligned entry matches
+    insight::save(&embedded_insight("vectors", "aligned", vec![0.1; 384]))?;
+    let mut opposed = vec![0.0; 384];
+    opposed[0] = -1.0;
+    insight::save(&embedded_insight("vectors", "opposed", opposed))?;
+
+    let options = SearchOptions {
+      embedding_client: embedding_client::with_service(Box::new(MockEmbeddingService)),
+      ..Default::default()
+    };
+
+    let results = search::search(&["unrelated".to_string()], &options)?;
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].name, "aligned");
+    assert_eq!(results[0].overview, "aligned overview");
+    assert_eq!(results[0].details, "aligned details");
+
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_keyword_and_neural_matches_are_fused() -> Result<()> {
+    use insights::embedding_client::{self, MockEmbeddingService};
+    use insights::ranking::FusionWeights;
+    use insights::search::{self, SearchOptions};
+
+    let _temp = setup_temp_insights_root("index_fusion");
+
+    // Only the aligned entry is near the mock query embedding, and only the
+    // other one mentions the keyword
+    insight::save(&embedded_insight("fusion", "aligned", vec![0.1; 384]))?;
+    let mut opposed = vec![0.0; 384];
+    opposed[0] = -1.0;
+    let mut keyword = embedded_insight("fusion", "keyword", opposed);
+    keyword.details = "Pods are scheduled by kubernetes".to_string();
+    keyword.embedding_hash = Some(insight::content_hash(&keyword));
+    insight::save(&keyword)?;
+
+    let names = |lexical: f32| -> Result<Vec<String>> {
+      #[allow(unused_mut)]
+      let mut weights = FusionWeights { lexical, ..Default::default() };
+      // Word overlap would find the keyword too; leave BM25 to speak for it
+      #[cfg(feature = "semantic")]
+      {
+        weights.semantic = 0.0;
+      }
+      let options = SearchOptions {
+        weights,
+        embedding_client: embedding_client::with_service(Box::new(MockEmbeddingService)),
+        ..Default::default()
+      };
+      let results = search::search(&["kubernetes".to_string()], &options)?;
+      Ok(results.into_iter().map(|result| result.name).collect())
+    };
+
+    assert_eq!(names(2.0)?, vec!["keyword", "aligned"]);
+    assert_eq!(names(0.5)?, vec!["aligned", "keyword"]);
+    assert_eq!(names(0.0)?, vec!["aligned"]);
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_search_reembeds_insights_edited_since_indexing() -> Result<()> {
+    use insights::embedding_client::{self, MockEmbeddingService};
+    use insights::search::{self, SearchOptions};
+
+    let _temp = setup_temp_insights_root("index_edited");
+
+    let insight = embedded_insight("vectors", "edited", vec![0.1; 384]);
+    insight::save(&insight)?;
+
+    // Edit the file by hand, leaving the old vector and index entry in place
+    let path = insight::file_path(&insight)?;
+    let content = std::fs::read_to_string(&path)?;
+    std::fs::write(&path, content.replace("edited details", "rewritten details"))?;
+    let edited = insight::load("vectors", "edited")?;
+    assert_ne!(
+      index::get(&index::load(Scope::Global)?, "vectors", "edited").unwrap().content_hash,
+      insight::content_hash(&edited)
+    );
+
+    let options = SearchOptions {
+      embedding_client: embedding_client::with_service(Box::new(MockEmbeddingService)),
+      ..Default::default()
+    };
+    let results = search::search(&["unrelated".to_string()], &options)?;
+    assert_eq!(results[0].details, "rewritten details");
+
+    let entry = index::get(&index::load(Scope::Global)?, "vectors", "edited").unwrap().clone();
+    assert_eq!(entry.content_hash, insight::content_hash(&edited));
+    assert!(index::is_fresh(&entry, &path));
+    Ok(())
+  }
+
+  #[test]
+  #[serial]
+  fn test_index_command_rebuilds_index() -> Result<()> {
+    use insights::commands;
+    use insights::embedding_client::{self, MockEmbeddingService};
+
+    let _temp = setup_temp_insights_root("index_command_rebuild");
+    let client = embedding_client::with_service(Box::new(MockEmbeddingService));
+
+    commands::add_insight_with_client(
+      &commands::NewInsight {
+        force: true,
+        ..commands::NewInsight::new("topic", "one", "Overview", "Details")
+      },
+      &client,
+    )?;
+    commands::add_insight_with_client(
+      &commands::NewInsight {
+        force: true,
+        ..commands::NewInsight::new("topic", "two", "Overview", "Details")
+      },
+      &client,
+    )?;
+    std::fs::remove_file(index::index_path(Scope::Global)?)?;
+
+    commands::index_insights_with_client(
+      false,
+      commands::DEFAULT_INDEX_BATCH_SIZE,
+      &client,
+      |_, _| {},
+    )?;
+    assert_eq!(index::load(Scope::Global)?.entries.len(), 2);
+
+    Ok(())
+  }
+}